@@ -2,7 +2,7 @@ use simple_logger::SimpleLogger;
 use winit::{
     event::{DeviceEvent, ElementState, Event, KeyboardInput, ModifiersState, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    window::{CursorGrabMode, WindowBuilder},
 };
 
 fn main() {
@@ -34,7 +34,16 @@ fn main() {
                     use winit::event::VirtualKeyCode::*;
                     match key {
                         Escape => *control_flow = ControlFlow::Exit,
-                        G => window.set_cursor_grab(!modifiers.shift()).unwrap(),
+                        G | L => {
+                            let mode = match (key, modifiers.shift()) {
+                                (_, true) => CursorGrabMode::None,
+                                (G, false) => CursorGrabMode::Confined,
+                                _ => CursorGrabMode::Locked,
+                            };
+                            if let Err(err) = window.set_cursor_grab(mode) {
+                                println!("Couldn't grab the cursor: {}", err);
+                            }
+                        }
                         H => window.set_cursor_visible(modifiers.shift()),
                         _ => (),
                     }
@@ -0,0 +1,55 @@
+use simple_logger::SimpleLogger;
+use winit::{
+    event::{DeviceEvent, Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    gamepad::{GamepadAxis, GamepadButton},
+    window::WindowBuilder,
+};
+
+#[cfg(target_os = "linux")]
+use winit::platform::unix::EventLoopWindowTargetExtUnix;
+
+fn main() {
+    SimpleLogger::new().init().unwrap();
+    let event_loop = EventLoop::new();
+    #[cfg(target_os = "linux")]
+    event_loop.watch_gamepads();
+
+    let _window = WindowBuilder::new()
+        .with_title("Plug in a gamepad and press its buttons")
+        .build(&event_loop)
+        .unwrap();
+
+    event_loop.run(move |event, _event_loop, control_flow| {
+        *control_flow = ControlFlow::Wait;
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            Event::DeviceEvent { device_id, event } => match event {
+                DeviceEvent::Added =>
+                {
+                    #[cfg(target_os = "linux")]
+                    if let Some(info) = _event_loop.gamepad_info(device_id) {
+                        println!("{:?} added: {:?}", device_id, info);
+                    }
+                }
+                DeviceEvent::Removed => println!("{:?} removed", device_id),
+                DeviceEvent::Button { button, state } => {
+                    match GamepadButton::from_button_id(button) {
+                        Some(button) => println!("{:?} {:?}", button, state),
+                        None => println!("Button {} {:?}", button, state),
+                    }
+                }
+                DeviceEvent::Motion { axis, value } => match GamepadAxis::from_axis_id(axis) {
+                    Some(axis) => println!("{:?}: {:.2}", axis, value),
+                    None => println!("Axis {}: {:.2}", axis, value),
+                },
+                _ => (),
+            },
+            _ => (),
+        }
+    });
+}
@@ -7,7 +7,7 @@ fn main() {
         dpi::{PhysicalPosition, PhysicalSize, Position, Size},
         event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
         event_loop::{ControlFlow, EventLoop},
-        window::{CursorIcon, Fullscreen, WindowBuilder},
+        window::{CursorGrabMode, CursorIcon, Fullscreen, WindowBuilder},
     };
 
     const WINDOW_COUNT: usize = 3;
@@ -89,7 +89,16 @@ fn main() {
                                 )),
                                 (false, _) => None,
                             }),
-                            G => window.set_cursor_grab(state).unwrap(),
+                            G => {
+                                let mode = if state {
+                                    CursorGrabMode::Confined
+                                } else {
+                                    CursorGrabMode::None
+                                };
+                                if let Err(err) = window.set_cursor_grab(mode) {
+                                    println!("Couldn't grab the cursor: {}", err);
+                                }
+                            }
                             H => window.set_cursor_visible(!state),
                             I => {
                                 println!("Info:");
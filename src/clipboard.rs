@@ -0,0 +1,31 @@
+//! Types useful for interacting with the system clipboards.
+//!
+//! The clipboards are read with [`EventLoopWindowTarget::clipboard_text`][get] and written with
+//! [`EventLoopWindowTarget::set_clipboard_text`][set]. Other kinds of data, such as images or
+//! rich text, are exchanged as mime types with
+//! [`EventLoopWindowTarget::clipboard_data`][get_data] and
+//! [`EventLoopWindowTarget::set_clipboard_data`][set_data]. When another application changes a
+//! clipboard, an [`Event::ClipboardChanged`][changed] is emitted.
+//!
+//! [get]: crate::event_loop::EventLoopWindowTarget::clipboard_text
+//! [set]: crate::event_loop::EventLoopWindowTarget::set_clipboard_text
+//! [get_data]: crate::event_loop::EventLoopWindowTarget::clipboard_data
+//! [set_data]: crate::event_loop::EventLoopWindowTarget::set_clipboard_data
+//! [changed]: crate::event::Event::ClipboardChanged
+
+/// Identifies one of the system clipboards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ClipboardKind {
+    /// The regular clipboard, used by copy and paste.
+    Clipboard,
+
+    /// The primary selection, which holds the last selected text and is usually pasted with the
+    /// middle mouse button.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the compositor to support the `primary_selection` protocol.
+    /// - Only available on **X11** and **Wayland**.
+    Primary,
+}
@@ -0,0 +1,178 @@
+use crate::icon::{BadIcon, RgbaIcon};
+use crate::platform_impl::PlatformCustomCursor;
+use std::{fmt, time::Duration};
+
+/// An image of a custom cursor, drawn for displays with the given scale factor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CursorImage {
+    pub(crate) rgba: RgbaIcon,
+    pub(crate) hotspot_x: u32,
+    pub(crate) hotspot_y: u32,
+    pub(crate) scale_factor: u32,
+}
+
+impl CursorImage {
+    fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+        scale_factor: u32,
+    ) -> Result<Self, BadIcon> {
+        if hotspot_x >= width || hotspot_y >= height {
+            return Err(BadIcon::HotspotOutOfBounds {
+                width,
+                height,
+                hotspot_x,
+                hotspot_y,
+            });
+        }
+
+        Ok(CursorImage {
+            rgba: RgbaIcon::from_rgba(rgba, width, height)?,
+            hotspot_x,
+            hotspot_y,
+            scale_factor,
+        })
+    }
+}
+
+/// A frame of a custom cursor, with its image for each scale factor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CursorFrame {
+    /// Sorted by scale factor, starting with the base image.
+    pub(crate) images: Vec<CursorImage>,
+    /// How long the frame is shown, which is zero for cursors that aren't animated.
+    pub(crate) duration: Duration,
+}
+
+/// The images of a custom cursor, to be created with
+/// [`EventLoopWindowTarget::create_custom_cursor`].
+///
+/// [`EventLoopWindowTarget::create_custom_cursor`]: crate::event_loop::EventLoopWindowTarget::create_custom_cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomCursorSource {
+    /// The frames in the order they're shown, or the only frame of a cursor that isn't animated.
+    pub(crate) frames: Vec<CursorFrame>,
+}
+
+impl CustomCursorSource {
+    /// Creates a cursor from 32bpp RGBA data, with its hotspot at the given pixel.
+    ///
+    /// The image is drawn as is on displays with a scale factor of 1, and scaled up on others
+    /// unless a variant is added for them with [`with_hidpi_variant`](Self::with_hidpi_variant).
+    ///
+    /// The length of `rgba` must be divisible by 4, `width * height` must equal
+    /// `rgba.len() / 4`, and the hotspot must lie inside the image. Otherwise, this will return a
+    /// `BadIcon` error.
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Result<Self, BadIcon> {
+        Ok(CustomCursorSource {
+            frames: vec![CursorFrame {
+                images: vec![CursorImage::from_rgba(
+                    rgba, width, height, hotspot_x, hotspot_y, 1,
+                )?],
+                duration: Duration::from_secs(0),
+            }],
+        })
+    }
+
+    /// Creates an animated cursor, which shows each frame for its duration and then starts over.
+    ///
+    /// Each frame is a cursor created with [`from_rgba`](Self::from_rgba), along with its HiDPI
+    /// variants.
+    ///
+    /// Returns a `BadIcon` error if `frames` is empty, if one of the frames is animated itself,
+    /// or if one of the durations is zero.
+    pub fn from_animation(frames: Vec<(CustomCursorSource, Duration)>) -> Result<Self, BadIcon> {
+        if frames.is_empty() {
+            return Err(BadIcon::NoCursorFrames);
+        }
+
+        let frames = frames
+            .into_iter()
+            .map(|(source, duration)| {
+                if source.is_animated() {
+                    return Err(BadIcon::AnimatedCursorFrame);
+                }
+                if duration == Duration::from_secs(0) {
+                    return Err(BadIcon::ZeroFrameDuration);
+                }
+                let mut frame = source.frames.into_iter().next().unwrap();
+                frame.duration = duration;
+                Ok(frame)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(CustomCursorSource { frames })
+    }
+
+    /// Whether the cursor has several frames.
+    #[inline]
+    pub fn is_animated(&self) -> bool {
+        self.frames[0].duration > Duration::from_secs(0)
+    }
+
+    /// Adds a variant of the image, drawn on displays with the given scale factor.
+    ///
+    /// The size and hotspot are in physical pixels, so a variant for a scale factor of 2 is
+    /// usually twice as large as the base image. Displays with a fractional scale factor get the
+    /// variant of the next larger one. A variant replaces the previous one of the same scale
+    /// factor, including the base image for a scale factor of 1.
+    ///
+    /// The arguments are validated like in [`from_rgba`](Self::from_rgba). A `BadIcon` error is
+    /// also returned if `scale_factor` is 0, or if the cursor is animated: add the variants to its
+    /// frames instead.
+    pub fn with_hidpi_variant(
+        mut self,
+        scale_factor: u32,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Result<Self, BadIcon> {
+        if scale_factor == 0 {
+            return Err(BadIcon::ZeroScaleFactor);
+        }
+        if self.is_animated() {
+            return Err(BadIcon::AnimatedCursorVariant);
+        }
+
+        let image =
+            CursorImage::from_rgba(rgba, width, height, hotspot_x, hotspot_y, scale_factor)?;
+        let images = &mut self.frames[0].images;
+        match images.binary_search_by_key(&scale_factor, |image| image.scale_factor) {
+            Ok(index) => images[index] = image,
+            Err(index) => images.insert(index, image),
+        }
+        Ok(self)
+    }
+}
+
+/// A custom cursor created on the display, which windows show with
+/// [`Window::set_custom_cursor`].
+///
+/// Cloning it is cheap, and its resources are freed once every clone is dropped.
+///
+/// [`Window::set_custom_cursor`]: crate::window::Window::set_custom_cursor
+#[derive(Clone)]
+pub struct CustomCursor {
+    pub(crate) inner: PlatformCustomCursor,
+}
+
+impl fmt::Debug for CustomCursor {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        fmt::Debug::fmt(&self.inner, formatter)
+    }
+}
+
+/// For platforms which don't support custom cursors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(dead_code)] // Not used on every platform
+pub(crate) struct NoCustomCursor;
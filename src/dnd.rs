@@ -0,0 +1,80 @@
+//! Types useful for drag and drop between applications.
+//!
+//! A drag carrying data from one of the application's windows is started with
+//! [`Window::start_drag`][start], and its outcome is reported with a
+//! [`WindowEvent::DragEnded`][ended].
+//!
+//! Drags from other applications are reported to the windows they hover with a
+//! [`WindowEvent::DragEntered`][entered], followed by [`WindowEvent::DragMoved`][moved] events
+//! which let the window accept or reject the drop, and end with either a
+//! [`WindowEvent::DragDropped`][dropped] or a [`WindowEvent::DragLeft`][left].
+//!
+//! [start]: crate::window::Window::start_drag
+//! [ended]: crate::event::WindowEvent::DragEnded
+//! [entered]: crate::event::WindowEvent::DragEntered
+//! [moved]: crate::event::WindowEvent::DragMoved
+//! [dropped]: crate::event::WindowEvent::DragDropped
+//! [left]: crate::event::WindowEvent::DragLeft
+
+use std::path::PathBuf;
+
+/// What the application receiving a drop does with the dropped data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DndAction {
+    /// The data is copied, leaving the source untouched.
+    Copy,
+
+    /// The data is moved, so the source should delete it once the drag ended.
+    Move,
+
+    /// A link to the data is created.
+    Link,
+}
+
+bitflags! {
+    /// Set of actions a drag allows the receiving application to perform.
+    #[derive(Default)]
+    pub struct DndActions: u32 {
+        const COPY = 1 << 0;
+        const MOVE = 1 << 1;
+        const LINK = 1 << 2;
+    }
+}
+
+impl From<DndAction> for DndActions {
+    fn from(action: DndAction) -> Self {
+        match action {
+            DndAction::Copy => DndActions::COPY,
+            DndAction::Move => DndActions::MOVE,
+            DndAction::Link => DndActions::LINK,
+        }
+    }
+}
+
+/// Data dropped on a window.
+///
+/// Out of the MIME types the drag offers, files are preferred, then text, then the first one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DropData {
+    /// Files, dropped as a `text/uri-list`.
+    Files(Vec<PathBuf>),
+
+    /// Text, dropped as UTF-8.
+    Text(String),
+
+    /// Data of any other MIME type.
+    Data { mime_type: String, data: Vec<u8> },
+}
+
+/// How a drag started by the application ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DragOutcome {
+    /// The data was dropped on an application which performed the given action.
+    Dropped(DndAction),
+
+    /// The drag was cancelled, or the data was dropped where it couldn't be received.
+    Cancelled,
+}
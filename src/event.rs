@@ -37,6 +37,8 @@ use instant::Instant;
 use std::path::PathBuf;
 
 use crate::{
+    clipboard::ClipboardKind,
+    dnd::{DndAction, DragOutcome, DropData},
     dpi::{PhysicalPosition, PhysicalSize},
     platform_impl,
     window::{Theme, WindowId},
@@ -46,6 +48,7 @@ use crate::{
 ///
 /// See the module-level docs for more information on the event loop manages each event.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Event<'a, T: 'static> {
     /// Emitted when new events arrive from the OS to be processed.
     ///
@@ -57,12 +60,14 @@ pub enum Event<'a, T: 'static> {
 
     /// Emitted when the OS sends an event to a winit window.
     WindowEvent {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_window_id"))]
         window_id: WindowId,
         event: WindowEvent<'a>,
     },
 
     /// Emitted when the OS sends an event to a device.
     DeviceEvent {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
         device_id: DeviceId,
         event: DeviceEvent,
     },
@@ -70,6 +75,22 @@ pub enum Event<'a, T: 'static> {
     /// Emitted when an event is sent from [`EventLoopProxy::send_event`](crate::event_loop::EventLoopProxy::send_event)
     UserEvent(T),
 
+    /// Emitted when another application takes ownership of one of the system clipboards, or
+    /// changes what it holds.
+    ///
+    /// Changes made through [`EventLoopWindowTarget::set_clipboard_data`] and its siblings aren't
+    /// reported.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Only reported while one of the application's windows has keyboard focus,
+    ///   since the compositor only shares the clipboards with focused clients. It's also
+    ///   reported when keyboard focus is gained, as the clipboards are shared anew.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    ///
+    /// [`EventLoopWindowTarget::set_clipboard_data`]: crate::event_loop::EventLoopWindowTarget::set_clipboard_data
+    ClipboardChanged(ClipboardKind),
+
     /// Emitted when the application has been suspended.
     Suspended,
 
@@ -101,7 +122,9 @@ pub enum Event<'a, T: 'static> {
     ///
     /// Mainly of interest to applications with mostly-static graphics that avoid redrawing unless
     /// something changes, like most non-game GUIs.
-    RedrawRequested(WindowId),
+    RedrawRequested(
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_window_id"))] WindowId,
+    ),
 
     /// Emitted after all `RedrawRequested` events have been processed and control flow is about to
     /// be taken away from the program. If there are no `RedrawRequested` events, it is emitted
@@ -136,6 +159,7 @@ impl<T: Clone> Clone for Event<'static, T> {
             RedrawRequested(wid) => RedrawRequested(*wid),
             RedrawEventsCleared => RedrawEventsCleared,
             LoopDestroyed => LoopDestroyed,
+            ClipboardChanged(kind) => ClipboardChanged(*kind),
             Suspended => Suspended,
             Resumed => Resumed,
         }
@@ -154,6 +178,7 @@ impl<'a, T> Event<'a, T> {
             RedrawRequested(wid) => Ok(RedrawRequested(wid)),
             RedrawEventsCleared => Ok(RedrawEventsCleared),
             LoopDestroyed => Ok(LoopDestroyed),
+            ClipboardChanged(kind) => Ok(ClipboardChanged(kind)),
             Suspended => Ok(Suspended),
             Resumed => Ok(Resumed),
         }
@@ -174,6 +199,7 @@ impl<'a, T> Event<'a, T> {
             RedrawRequested(wid) => Some(RedrawRequested(wid)),
             RedrawEventsCleared => Some(RedrawEventsCleared),
             LoopDestroyed => Some(LoopDestroyed),
+            ClipboardChanged(kind) => Some(ClipboardChanged(kind)),
             Suspended => Some(Suspended),
             Resumed => Some(Resumed),
         }
@@ -181,20 +207,28 @@ impl<'a, T> Event<'a, T> {
 }
 
 /// Describes the reason the event loop is resuming.
+///
+/// When serialized, the `Instant`s are stored relative to the start of the recording, see the
+/// [`record`](crate::record) module.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum StartCause {
     /// Sent if the time specified by `ControlFlow::WaitUntil` has been reached. Contains the
     /// moment the timeout was requested and the requested resume time. The actual resume time is
     /// guaranteed to be equal to or after the requested resume time.
     ResumeTimeReached {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_instant"))]
         start: Instant,
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_instant"))]
         requested_resume: Instant,
     },
 
     /// Sent if the OS has new events to send to the window, after a wait was requested. Contains
     /// the moment the wait was requested and the resume time, if requested.
     WaitCancelled {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_instant"))]
         start: Instant,
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_option_instant"))]
         requested_resume: Option<Instant>,
     },
 
@@ -207,7 +241,11 @@ pub enum StartCause {
 }
 
 /// Describes an event from a `Window`.
+///
+/// `ScaleFactorChanged` and `DragMoved` can't be serialized, as they borrow the value the
+/// application answers with.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WindowEvent<'a> {
     /// The size of the window has changed. Contains the client area's new dimensions.
     Resized(PhysicalSize<u32>),
@@ -221,6 +259,13 @@ pub enum WindowEvent<'a> {
     /// The window has been destroyed.
     Destroyed,
 
+    /// The popup window should be closed, for instance because its parent was hidden or because
+    /// its input grab was broken by a click outside of the application's windows.
+    ///
+    /// See [`WindowBuilder::with_popup`](crate::window::WindowBuilder::with_popup) and
+    /// [`Window::set_popup_grab`](crate::window::Window::set_popup_grab).
+    PopupDismissed,
+
     /// A file has been dropped into the window.
     ///
     /// When the user drops multiple files at once, this event will be emitted for each file
@@ -239,6 +284,49 @@ pub enum WindowEvent<'a> {
     /// hovered.
     HoveredFileCancelled,
 
+    /// A drag started with [`Window::start_drag`] has ended.
+    ///
+    /// [`Window::start_drag`]: crate::window::Window::start_drag
+    DragEnded(DragOutcome),
+
+    /// A drag from another application entered the window, offering its data in the given MIME
+    /// types.
+    ///
+    /// It's followed by [`DragMoved`](Self::DragMoved) events, and ends with either a
+    /// [`DragDropped`](Self::DragDropped) or a [`DragLeft`](Self::DragLeft).
+    DragEntered { mime_types: Vec<String> },
+
+    /// A drag from another application moved over the window.
+    ///
+    /// `position` is the position of the cursor in physical pixels relative to the client area,
+    /// and `action` is the action proposed by the source of the drag. The drop is accepted at that
+    /// position with the action `accepted_action` points to once the event callback returns, or
+    /// rejected if it's `None`. By default, it's `action` if the drag offers `text/uri-list`, and
+    /// `None` otherwise.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** [`DndAction::Link`] isn't supported, and accepting the drop with it rejects
+    ///   it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    DragMoved {
+        position: PhysicalPosition<f64>,
+        action: DndAction,
+        accepted_action: &'a mut Option<DndAction>,
+    },
+
+    /// A drag from another application was dropped on the window, with the action it was
+    /// accepted with.
+    DragDropped {
+        position: PhysicalPosition<f64>,
+        action: DndAction,
+        data: DropData,
+    },
+
+    /// A drag from another application left the window, was dropped where it had been rejected,
+    /// or its data couldn't be received.
+    DragLeft,
+
     /// The window received a unicode character.
     ReceivedCharacter(char),
 
@@ -249,6 +337,7 @@ pub enum WindowEvent<'a> {
 
     /// An event from the keyboard has been received.
     KeyboardInput {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
         device_id: DeviceId,
         input: KeyboardInput,
         /// If `true`, the event was generated synthetically by winit
@@ -263,6 +352,39 @@ pub enum WindowEvent<'a> {
         is_synthetic: bool,
     },
 
+    /// A key was pressed or released.
+    ///
+    /// Unlike `KeyboardInput`, which is still sent alongside this event, it tells apart the
+    /// physical key from its meaning under the current layout, and carries the text produced by
+    /// the key press. `ReceivedCharacter` events are still sent for this text too.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only sent on **X11** and **Wayland** at the moment.
+    KeyEvent {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+        event: KeyEvent,
+        /// If `true`, the event was generated synthetically by winit, in the same circumstances as
+        /// for `WindowEvent::KeyboardInput`.
+        is_synthetic: bool,
+    },
+
+    /// An event from an input method.
+    ///
+    /// Only sent once the window allows it with [`Window::set_ime_allowed`], after which the
+    /// application has to draw the composition itself. Committed text is still sent as
+    /// `ReceivedCharacter` events too.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Only sent when the input method supports on-the-spot (`XIMPreeditCallbacks`)
+    ///   pre-editing.
+    /// - Only sent on **X11** and **Wayland** at the moment.
+    ///
+    /// [`Window::set_ime_allowed`]: crate::window::Window::set_ime_allowed
+    Ime(Ime),
+
     /// The keyboard modifiers have changed.
     ///
     /// Platform-specific behavior:
@@ -272,6 +394,7 @@ pub enum WindowEvent<'a> {
 
     /// The cursor has moved on the window.
     CursorMoved {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
         device_id: DeviceId,
 
         /// (x,y) coords in pixels relative to the top-left corner of the window. Because the range of this data is
@@ -283,13 +406,20 @@ pub enum WindowEvent<'a> {
     },
 
     /// The cursor has entered the window.
-    CursorEntered { device_id: DeviceId },
+    CursorEntered {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+    },
 
     /// The cursor has left the window.
-    CursorLeft { device_id: DeviceId },
+    CursorLeft {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+    },
 
     /// A mouse wheel movement or touchpad scroll occurred.
     MouseWheel {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
         device_id: DeviceId,
         delta: MouseScrollDelta,
         phase: TouchPhase,
@@ -299,6 +429,7 @@ pub enum WindowEvent<'a> {
 
     /// An mouse button press has been received.
     MouseInput {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
         device_id: DeviceId,
         state: ElementState,
         button: MouseButton,
@@ -312,13 +443,70 @@ pub enum WindowEvent<'a> {
     /// The parameters are: pressure level (value between 0 and 1 representing how hard the touchpad
     /// is being pressed) and stage (integer representing the click level).
     TouchpadPressure {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
         device_id: DeviceId,
         pressure: f32,
         stage: i64,
     },
 
+    /// Two fingers pinched on a touchpad, usually to zoom.
+    ///
+    /// The gesture is `Started` with a `delta` of 0.0, followed by `Moved` events until it's
+    /// either `Ended` or `Cancelled`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**, and on **X11** with XInput 2.4.
+    PinchGesture {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+
+        /// The change of the distance between the fingers since the previous event, relative to
+        /// their distance when the gesture started. Positive values mean that the fingers moved
+        /// apart, to zoom in.
+        delta: f64,
+        phase: TouchPhase,
+    },
+
+    /// Two fingers rotated on a touchpad.
+    ///
+    /// It's reported along with `PinchGesture`, with the same phases.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**, and on **X11** with XInput 2.4.
+    RotationGesture {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+
+        /// The rotation since the previous event in degrees, positive counterclockwise.
+        delta: f64,
+        phase: TouchPhase,
+    },
+
+    /// Three fingers or more swiped on a touchpad.
+    ///
+    /// The gesture is `Started` with a null `delta`, followed by `Moved` events until it's either
+    /// `Ended` or `Cancelled`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**, and on **X11** with XInput 2.4.
+    SwipeGesture {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+
+        /// The number of fingers swiping.
+        fingers: u32,
+
+        /// The motion of the center of the fingers since the previous event, in pixels.
+        delta: PhysicalPosition<f64>,
+        phase: TouchPhase,
+    },
+
     /// Motion on some analog axis. May report data redundant to other, more specific events.
     AxisMotion {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
         device_id: DeviceId,
         axis: AxisId,
         value: f64,
@@ -327,6 +515,16 @@ pub enum WindowEvent<'a> {
     /// Touch event has been received
     Touch(Touch),
 
+    /// A pen or another tool of a drawing tablet has been used over the window.
+    ///
+    /// The tool usually moves the cursor as well, so the matching `CursorMoved` and `MouseInput`
+    /// events are still delivered for applications which don't handle tablets.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    TabletTool(TabletToolEvent),
+
     /// The window's scale factor has changed.
     ///
     /// The following user actions can cause DPI changes:
@@ -340,6 +538,7 @@ pub enum WindowEvent<'a> {
     /// by the OS, but it can be changed to any value.
     ///
     /// For more information about DPI in general, see the [`dpi`](crate::dpi) module.
+    #[cfg_attr(feature = "serde", serde(skip))]
     ScaleFactorChanged {
         scale_factor: f64,
         new_inner_size: &'a mut PhysicalSize<u32>,
@@ -362,9 +561,24 @@ impl Clone for WindowEvent<'static> {
             Moved(pos) => Moved(pos.clone()),
             CloseRequested => CloseRequested,
             Destroyed => Destroyed,
+            PopupDismissed => PopupDismissed,
             DroppedFile(file) => DroppedFile(file.clone()),
             HoveredFile(file) => HoveredFile(file.clone()),
             HoveredFileCancelled => HoveredFileCancelled,
+            DragEnded(outcome) => DragEnded(*outcome),
+            DragEntered { mime_types } => DragEntered {
+                mime_types: mime_types.clone(),
+            },
+            DragDropped {
+                position,
+                action,
+                data,
+            } => DragDropped {
+                position: *position,
+                action: *action,
+                data: data.clone(),
+            },
+            DragLeft => DragLeft,
             ReceivedCharacter(c) => ReceivedCharacter(*c),
             Focused(f) => Focused(*f),
             KeyboardInput {
@@ -376,7 +590,17 @@ impl Clone for WindowEvent<'static> {
                 input: *input,
                 is_synthetic: *is_synthetic,
             },
+            KeyEvent {
+                device_id,
+                event,
+                is_synthetic,
+            } => KeyEvent {
+                device_id: *device_id,
+                event: event.clone(),
+                is_synthetic: *is_synthetic,
+            },
 
+            Ime(event) => Ime(event.clone()),
             ModifiersChanged(modifiers) => ModifiersChanged(modifiers.clone()),
             #[allow(deprecated)]
             CursorMoved {
@@ -427,6 +651,35 @@ impl Clone for WindowEvent<'static> {
                 pressure: *pressure,
                 stage: *stage,
             },
+            PinchGesture {
+                device_id,
+                delta,
+                phase,
+            } => PinchGesture {
+                device_id: *device_id,
+                delta: *delta,
+                phase: *phase,
+            },
+            RotationGesture {
+                device_id,
+                delta,
+                phase,
+            } => RotationGesture {
+                device_id: *device_id,
+                delta: *delta,
+                phase: *phase,
+            },
+            SwipeGesture {
+                device_id,
+                fingers,
+                delta,
+                phase,
+            } => SwipeGesture {
+                device_id: *device_id,
+                fingers: *fingers,
+                delta: *delta,
+                phase: *phase,
+            },
             AxisMotion {
                 device_id,
                 axis,
@@ -437,10 +690,12 @@ impl Clone for WindowEvent<'static> {
                 value: *value,
             },
             Touch(touch) => Touch(*touch),
+            TabletTool(event) => TabletTool(*event),
             ThemeChanged(theme) => ThemeChanged(theme.clone()),
             ScaleFactorChanged { .. } => {
                 unreachable!("Static event can't be about scale factor changing")
             }
+            DragMoved { .. } => unreachable!("Static event can't be about a drag moving"),
         };
     }
 }
@@ -453,9 +708,22 @@ impl<'a> WindowEvent<'a> {
             Moved(position) => Some(Moved(position)),
             CloseRequested => Some(CloseRequested),
             Destroyed => Some(Destroyed),
+            PopupDismissed => Some(PopupDismissed),
             DroppedFile(file) => Some(DroppedFile(file)),
             HoveredFile(file) => Some(HoveredFile(file)),
             HoveredFileCancelled => Some(HoveredFileCancelled),
+            DragEnded(outcome) => Some(DragEnded(outcome)),
+            DragEntered { mime_types } => Some(DragEntered { mime_types }),
+            DragDropped {
+                position,
+                action,
+                data,
+            } => Some(DragDropped {
+                position,
+                action,
+                data,
+            }),
+            DragLeft => Some(DragLeft),
             ReceivedCharacter(c) => Some(ReceivedCharacter(c)),
             Focused(focused) => Some(Focused(focused)),
             KeyboardInput {
@@ -467,6 +735,16 @@ impl<'a> WindowEvent<'a> {
                 input,
                 is_synthetic,
             }),
+            KeyEvent {
+                device_id,
+                event,
+                is_synthetic,
+            } => Some(KeyEvent {
+                device_id,
+                event,
+                is_synthetic,
+            }),
+            Ime(event) => Some(Ime(event)),
             ModifiersChanged(modifiers) => Some(ModifiersChanged(modifiers)),
             #[allow(deprecated)]
             CursorMoved {
@@ -513,6 +791,35 @@ impl<'a> WindowEvent<'a> {
                 pressure,
                 stage,
             }),
+            PinchGesture {
+                device_id,
+                delta,
+                phase,
+            } => Some(PinchGesture {
+                device_id,
+                delta,
+                phase,
+            }),
+            RotationGesture {
+                device_id,
+                delta,
+                phase,
+            } => Some(RotationGesture {
+                device_id,
+                delta,
+                phase,
+            }),
+            SwipeGesture {
+                device_id,
+                fingers,
+                delta,
+                phase,
+            } => Some(SwipeGesture {
+                device_id,
+                fingers,
+                delta,
+                phase,
+            }),
             AxisMotion {
                 device_id,
                 axis,
@@ -523,8 +830,10 @@ impl<'a> WindowEvent<'a> {
                 value,
             }),
             Touch(touch) => Some(Touch(touch)),
+            TabletTool(event) => Some(TabletTool(event)),
             ThemeChanged(theme) => Some(ThemeChanged(theme)),
             ScaleFactorChanged { .. } => None,
+            DragMoved { .. } => None,
         }
     }
 }
@@ -557,6 +866,7 @@ impl DeviceId {
 ///
 /// Note that these events are delivered regardless of input focus.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DeviceEvent {
     Added,
     Removed,
@@ -623,6 +933,403 @@ pub struct KeyboardInput {
     pub modifiers: ModifiersState,
 }
 
+/// Describes a key press or release, see `WindowEvent::KeyEvent`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyEvent {
+    /// Identifies the physical key, regardless of the keyboard layout.
+    ///
+    /// Use when the physical location of the key is more important than its meaning, such as for
+    /// movement controls in a first-person game.
+    pub physical_key: KeyCode,
+
+    /// The meaning of the key under the current keyboard layout and modifiers.
+    ///
+    /// Use when the meaning of the key is more important than its location, such as for keyboard
+    /// shortcuts. Releases report the same logical key as the matching press.
+    pub logical_key: Key,
+
+    /// The text produced by the key press.
+    ///
+    /// This is `None` for releases, for keys which don't produce any text such as `Shift`, and
+    /// for key presses which were consumed by a compose sequence or an IME.
+    pub text: Option<String>,
+
+    /// Tells apart keys which have the same meaning, like the left and right `Shift` keys.
+    pub location: KeyLocation,
+
+    pub state: ElementState,
+
+    /// `true` if the key is held down, and this press was generated by key repeat.
+    ///
+    /// Always `false` for releases.
+    pub is_repeat: bool,
+}
+
+/// The location of a key on the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyLocation {
+    /// The key is the only one with its meaning, or no more specific location is known.
+    Standard,
+    /// The key is on the left of the keyboard, like the left `Shift` key.
+    Left,
+    /// The key is on the right of the keyboard, like the right `Shift` key.
+    Right,
+    /// The key is on the numeric keypad.
+    Numpad,
+}
+
+/// The meaning of a key under the current keyboard layout and modifiers.
+///
+/// Named after the `key` values of the [W3C UI Events specification][spec].
+///
+/// [spec]: https://www.w3.org/TR/uievents-key/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Key {
+    /// A key producing a character, like `a`, `A` with `Shift` held or `1`.
+    Character(String),
+
+    /// A dead key, which modifies the character produced by the next key press. Contains the
+    /// character it's combined with, if known.
+    Dead(Option<char>),
+
+    /// The meaning of the key isn't known.
+    Unidentified,
+
+    Alt,
+    AltGraph,
+    CapsLock,
+    Control,
+    Fn,
+    NumLock,
+    ScrollLock,
+    Shift,
+    /// The "windows" key on PC and "command" key on Mac.
+    Super,
+
+    Enter,
+    Tab,
+    Space,
+
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    ArrowUp,
+    End,
+    Home,
+    PageDown,
+    PageUp,
+
+    Backspace,
+    Clear,
+    Copy,
+    Cut,
+    Delete,
+    Insert,
+    Paste,
+    Redo,
+    Undo,
+
+    Cancel,
+    ContextMenu,
+    Escape,
+    Execute,
+    Find,
+    Help,
+    Pause,
+    PrintScreen,
+    Select,
+
+    /// The "Compose" key on Linux.
+    Compose,
+    Convert,
+    NonConvert,
+    KanaMode,
+    KanjiMode,
+    Hiragana,
+    Katakana,
+    HiraganaKatakana,
+    Romaji,
+    Zenkaku,
+    Hankaku,
+    ZenkakuHankaku,
+    Eisu,
+    HangulMode,
+    HanjaMode,
+
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+
+    AudioVolumeDown,
+    AudioVolumeMute,
+    AudioVolumeUp,
+    MediaPlayPause,
+    MediaStop,
+    MediaTrackNext,
+    MediaTrackPrevious,
+
+    BrowserBack,
+    BrowserFavorites,
+    BrowserForward,
+    BrowserHome,
+    BrowserRefresh,
+    BrowserSearch,
+    BrowserStop,
+    LaunchApplication1,
+    LaunchApplication2,
+    LaunchMail,
+
+    Eject,
+    Power,
+    Sleep,
+    WakeUp,
+}
+
+/// A physical key, regardless of the keyboard layout.
+///
+/// Named after the `code` values of the [W3C UI Events specification][spec], which in turn are
+/// named after the characters produced by the key on a US keyboard.
+///
+/// [spec]: https://www.w3.org/TR/uievents-code/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyCode {
+    /// The key isn't known. Contains the platform-dependent scan code.
+    Unidentified(ScanCode),
+
+    Backquote,
+    Backslash,
+    BracketLeft,
+    BracketRight,
+    Comma,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    Equal,
+    /// The extra key next to the left `Shift` key on ISO keyboards.
+    IntlBackslash,
+    /// The key left of the right `Shift` key on Japanese keyboards.
+    IntlRo,
+    /// The key left of `Backspace` on Japanese keyboards.
+    IntlYen,
+    KeyA,
+    KeyB,
+    KeyC,
+    KeyD,
+    KeyE,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyI,
+    KeyJ,
+    KeyK,
+    KeyL,
+    KeyM,
+    KeyN,
+    KeyO,
+    KeyP,
+    KeyQ,
+    KeyR,
+    KeyS,
+    KeyT,
+    KeyU,
+    KeyV,
+    KeyW,
+    KeyX,
+    KeyY,
+    KeyZ,
+    Minus,
+    Period,
+    Quote,
+    Semicolon,
+    Slash,
+
+    AltLeft,
+    AltRight,
+    Backspace,
+    CapsLock,
+    ContextMenu,
+    ControlLeft,
+    ControlRight,
+    Enter,
+    SuperLeft,
+    SuperRight,
+    ShiftLeft,
+    ShiftRight,
+    Space,
+    Tab,
+
+    Convert,
+    KanaMode,
+    /// Hangul/English toggle on Korean keyboards.
+    Lang1,
+    /// Hanja conversion on Korean keyboards.
+    Lang2,
+    /// Katakana on Japanese keyboards.
+    Lang3,
+    /// Hiragana on Japanese keyboards.
+    Lang4,
+    /// Zenkaku/Hankaku on Japanese keyboards.
+    Lang5,
+    NonConvert,
+
+    Delete,
+    End,
+    Help,
+    Home,
+    Insert,
+    PageDown,
+    PageUp,
+
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    ArrowUp,
+
+    NumLock,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadComma,
+    NumpadDecimal,
+    NumpadDivide,
+    NumpadEnter,
+    NumpadEqual,
+    NumpadMultiply,
+    NumpadParenLeft,
+    NumpadParenRight,
+    NumpadSubtract,
+
+    Escape,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    PrintScreen,
+    ScrollLock,
+    Pause,
+
+    BrowserBack,
+    BrowserFavorites,
+    BrowserForward,
+    BrowserHome,
+    BrowserRefresh,
+    BrowserSearch,
+    BrowserStop,
+    Eject,
+    LaunchApp1,
+    LaunchApp2,
+    LaunchMail,
+    MediaPlayPause,
+    MediaSelect,
+    MediaStop,
+    MediaTrackNext,
+    MediaTrackPrevious,
+    Power,
+    Sleep,
+    AudioVolumeDown,
+    AudioVolumeMute,
+    AudioVolumeUp,
+    WakeUp,
+
+    Again,
+    Copy,
+    Cut,
+    Find,
+    Open,
+    Paste,
+    Props,
+    Select,
+    Undo,
+}
+
+/// Describes an event from an input method.
+///
+/// A composition starts with a `Preedit` event carrying the text being composed, and usually ends
+/// with a `Commit` followed by an empty `Preedit`. Applications should draw the pre-edit text in
+/// place of the cursor, without inserting it into the document until it's committed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Ime {
+    /// The input method was enabled for the window.
+    ///
+    /// Sent before any other `Ime` event.
+    Enabled,
+
+    /// The text being composed changed.
+    ///
+    /// The second field is the range of the cursor in the text, in bytes. `None` means the cursor
+    /// should be hidden. An empty string clears the composition.
+    Preedit(String, Option<(usize, usize)>),
+
+    /// The input method committed text, which should be inserted into the document.
+    Commit(String),
+
+    /// The input method was disabled for the window.
+    ///
+    /// Any composition in progress should be cleared.
+    Disabled,
+}
+
 /// Describes touch-screen input state.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -650,7 +1357,9 @@ pub enum TouchPhase {
 /// touch, such as when the window loses focus, or on iOS if the user moves the
 /// device against their face.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Touch {
+    #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
     pub device_id: DeviceId,
     pub phase: TouchPhase,
     pub location: PhysicalPosition<f64>,
@@ -659,14 +1368,113 @@ pub struct Touch {
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **iOS** 9.0+ and **Windows** 8+.
+    /// - Only available on **iOS** 9.0+, **Windows** 8+ and **X11**.
+    /// - **X11:** Only available on touchscreens reporting the pressure of each touch.
+    /// - **Wayland:** Unsupported, as `wl_touch` doesn't report the pressure of touches.
     pub force: Option<Force>,
     /// Unique identifier of a finger.
     pub id: u64,
 }
 
+/// Input from a pen, an eraser or another tool of a drawing tablet.
+///
+/// Each tool has its own `DeviceId`, so that several pens used on the same tablet can be told
+/// apart. A tool is `Entered` once it comes within range of the tablet over the window, and
+/// `Left` once it leaves the window or goes out of range, with any number of the other events in
+/// between.
+///
+/// ## Platform-specific
+///
+/// - **X11:** Each tool type of a tablet is a separate device, so pens of the same type share
+///   their `DeviceId`. As XInput2 has no proximity events, a tool is also `Left` once another
+///   device moves the cursor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TabletToolEvent {
+    /// The tool came within range of the tablet over the window.
+    Entered {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+        tool: TabletTool,
+    },
+
+    /// The tool left the window or went out of range of the tablet.
+    Left {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+    },
+
+    /// The tool moved, or the value of one of its axes changed.
+    Moved {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+
+        /// Position of the tool in pixels relative to the top-left corner of the window.
+        position: PhysicalPosition<f64>,
+        axes: TabletToolAxes,
+    },
+
+    /// The tip of the tool touched the surface of the tablet, or was lifted from it.
+    Tip {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+        state: ElementState,
+    },
+
+    /// A button on the barrel of the tool was pressed or released.
+    Button {
+        #[cfg_attr(feature = "serde", serde(with = "crate::record::serde_device_id"))]
+        device_id: DeviceId,
+
+        /// Index of the button, starting at 1 for the button closest to the tip.
+        button: u32,
+        state: ElementState,
+    },
+}
+
+/// The kind of a tool of a drawing tablet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TabletTool {
+    Pen,
+    Eraser,
+    Brush,
+    Pencil,
+    Airbrush,
+    Finger,
+    Mouse,
+    Lens,
+    Unknown,
+}
+
+/// The state of the axes of a tool of a drawing tablet.
+///
+/// An axis is `None` when the tool doesn't have it.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TabletToolAxes {
+    /// How hard the tip is pressed against the tablet, between 0.0 and 1.0.
+    pub pressure: Option<f32>,
+
+    /// The angle in degrees between the tool and the perpendicular of the tablet, along the X
+    /// axis. Positive values tilt the top of the tool towards the right.
+    pub tilt_x: Option<f32>,
+
+    /// The angle in degrees between the tool and the perpendicular of the tablet, along the Y
+    /// axis. Positive values tilt the top of the tool towards the bottom.
+    pub tilt_y: Option<f32>,
+
+    /// The distance between the tool and the tablet, between 0.0 when touching it and 1.0 at the
+    /// limit of its range.
+    pub distance: Option<f32>,
+
+    /// The clockwise rotation of the tool around its own axis in degrees, between 0.0 and 360.0.
+    pub rotation: Option<f32>,
+}
+
 /// Describes the force of a touch event
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Force {
     /// On iOS, the force is calibrated so that the same number corresponds to
     /// roughly the same amount of pressure on the screen regardless of the
@@ -13,7 +13,14 @@ use instant::Instant;
 use std::ops::Deref;
 use std::{error, fmt};
 
-use crate::{event::Event, monitor::MonitorHandle, platform_impl};
+use crate::{
+    clipboard::ClipboardKind,
+    error::ExternalError,
+    event::Event,
+    monitor::MonitorHandle,
+    platform_impl,
+    window::{CustomCursor, CustomCursorSource},
+};
 
 /// Provides a way to retrieve events from the system and from the windows that were registered to
 /// the events loop.
@@ -190,6 +197,119 @@ impl<T> EventLoopWindowTarget<T> {
     pub fn primary_monitor(&self) -> Option<MonitorHandle> {
         self.p.primary_monitor()
     }
+
+    /// Returns the text held by the given clipboard, or `None` if it doesn't hold any.
+    ///
+    /// When another application owns the clipboard, this blocks until it has sent the text.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Only works while one of the windows has the keyboard focus, as the
+    ///   compositor doesn't share the clipboard with other clients.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn clipboard_text(&self, kind: ClipboardKind) -> Result<Option<String>, ExternalError> {
+        self.p.clipboard_text(kind)
+    }
+
+    /// Puts `text` into the given clipboard.
+    ///
+    /// The text is served to other applications from the event loop, so it stays available
+    /// until another application takes ownership of the clipboard, or the event loop is dropped.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Only works while one of the windows has the keyboard focus.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_clipboard_text<S: Into<String>>(
+        &self,
+        kind: ClipboardKind,
+        text: S,
+    ) -> Result<(), ExternalError> {
+        self.p.set_clipboard_text(kind, text.into())
+    }
+
+    /// Returns the mime types the contents of the given clipboard are available as, from the
+    /// most to the least preferred by the application owning it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Legacy targets such as `UTF8_STRING` and `STRING` are listed alongside the mime
+    ///   types.
+    /// - **Wayland:** Only works while one of the windows has the keyboard focus.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Result<Vec<String>, ExternalError> {
+        self.p.clipboard_mime_types(kind)
+    }
+
+    /// Returns the contents of the given clipboard as `mime_type`, or `None` if they aren't
+    /// available as such.
+    ///
+    /// When another application owns the clipboard, this blocks until it has sent the data.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Only works while one of the windows has the keyboard focus.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.p.clipboard_data(kind, mime_type)
+    }
+
+    /// Puts data into the given clipboard, as pairs of a mime type and the data it's available
+    /// as, from the most to the least preferred.
+    ///
+    /// Like with [`set_clipboard_text`](Self::set_clipboard_text), the data stays available until
+    /// another application takes ownership of the clipboard, or the event loop is dropped.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Only works while one of the windows has the keyboard focus.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_clipboard_data<I, S>(
+        &self,
+        kind: ClipboardKind,
+        data: I,
+    ) -> Result<(), ExternalError>
+    where
+        I: IntoIterator<Item = (S, Vec<u8>)>,
+        S: Into<String>,
+    {
+        let data = data
+            .into_iter()
+            .map(|(mime_type, data)| (mime_type.into(), data))
+            .collect();
+        self.p.set_clipboard_data(kind, data)
+    }
+
+    /// Creates a custom cursor out of its images, to be shown over windows with
+    /// [`Window::set_custom_cursor`].
+    ///
+    /// The cursor is created once on the display and can then be set on any window of the event
+    /// loop, as often as needed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    ///
+    /// [`Window::set_custom_cursor`]: crate::window::Window::set_custom_cursor
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        source: CustomCursorSource,
+    ) -> Result<CustomCursor, ExternalError> {
+        Ok(CustomCursor {
+            inner: self.p.create_custom_cursor(source)?,
+        })
+    }
 }
 
 /// Used to send custom events to `EventLoop`.
@@ -0,0 +1,171 @@
+//! The standard layout of gamepads.
+//!
+//! Gamepads and joysticks are reported as devices, through `DeviceEvent::Added` and
+//! `DeviceEvent::Removed`, and their input through `DeviceEvent::Button` and
+//! `DeviceEvent::Motion`. Devices which are known to match the layout of a console controller
+//! report the ids of [`GamepadButton`] and [`GamepadAxis`], which can be converted with
+//! [`GamepadButton::from_button_id`] and [`GamepadAxis::from_axis_id`].
+//!
+//! The sticks of a mapped gamepad move from `-1.0` to `1.0`, with positive values to the right and
+//! to the bottom, and the triggers move from `0.0` to `1.0`. Other joysticks report their buttons
+//! and axes as the platform numbers them, with every axis moving from `-1.0` to `1.0`.
+//!
+//! ## Platform-specific
+//!
+//! Gamepads are only supported on Linux, which reads them from the evdev nodes in `/dev/input`.
+//! The user needs to be allowed to read those, which is usually the case for gamepads on
+//! desktop systems. Which devices are mapped can be extended through the
+//! `SDL_GAMECONTROLLERCONFIG` environment variable, in the format of SDL's game controller
+//! database. Their names and ids are given by `EventLoopWindowTargetExtUnix::gamepad_info`.
+
+use crate::event::{AxisId, ButtonId};
+
+/// A button of the standard gamepad layout.
+///
+/// The face buttons are named after their position, since consoles label them differently. On an
+/// Xbox controller, `South` is A and `East` is B, while on a PlayStation controller `South` is ×
+/// and `East` is ○.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftShoulder,
+    RightShoulder,
+    /// The digital state of the left trigger, for gamepads which have one.
+    LeftTrigger,
+    /// The digital state of the right trigger, for gamepads which have one.
+    RightTrigger,
+    /// The left center button, which is Back, Select or Share depending on the console.
+    Select,
+    /// The right center button, which is Start or Options depending on the console.
+    Start,
+    /// The button with the logo of the console.
+    Mode,
+    /// Pressing the left stick.
+    LeftThumb,
+    /// Pressing the right stick.
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    /// The button of the standard layout with the given id, if there's one.
+    pub fn from_button_id(button: ButtonId) -> Option<Self> {
+        let button = match button {
+            0x130 => GamepadButton::South,
+            0x131 => GamepadButton::East,
+            0x133 => GamepadButton::North,
+            0x134 => GamepadButton::West,
+            0x136 => GamepadButton::LeftShoulder,
+            0x137 => GamepadButton::RightShoulder,
+            0x138 => GamepadButton::LeftTrigger,
+            0x139 => GamepadButton::RightTrigger,
+            0x13a => GamepadButton::Select,
+            0x13b => GamepadButton::Start,
+            0x13c => GamepadButton::Mode,
+            0x13d => GamepadButton::LeftThumb,
+            0x13e => GamepadButton::RightThumb,
+            0x220 => GamepadButton::DPadUp,
+            0x221 => GamepadButton::DPadDown,
+            0x222 => GamepadButton::DPadLeft,
+            0x223 => GamepadButton::DPadRight,
+            _ => return None,
+        };
+        Some(button)
+    }
+
+    /// The id of the button in `DeviceEvent::Button`.
+    ///
+    /// It's the same as the evdev code of the button on Linux.
+    pub fn button_id(self) -> ButtonId {
+        match self {
+            GamepadButton::South => 0x130,
+            GamepadButton::East => 0x131,
+            GamepadButton::North => 0x133,
+            GamepadButton::West => 0x134,
+            GamepadButton::LeftShoulder => 0x136,
+            GamepadButton::RightShoulder => 0x137,
+            GamepadButton::LeftTrigger => 0x138,
+            GamepadButton::RightTrigger => 0x139,
+            GamepadButton::Select => 0x13a,
+            GamepadButton::Start => 0x13b,
+            GamepadButton::Mode => 0x13c,
+            GamepadButton::LeftThumb => 0x13d,
+            GamepadButton::RightThumb => 0x13e,
+            GamepadButton::DPadUp => 0x220,
+            GamepadButton::DPadDown => 0x221,
+            GamepadButton::DPadLeft => 0x222,
+            GamepadButton::DPadRight => 0x223,
+        }
+    }
+}
+
+/// An axis of the standard gamepad layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    /// The analog state of the left trigger, from `0.0` to `1.0`.
+    LeftTrigger,
+    RightStickX,
+    RightStickY,
+    /// The analog state of the right trigger, from `0.0` to `1.0`.
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    /// The axis of the standard layout with the given id, if there's one.
+    pub fn from_axis_id(axis: AxisId) -> Option<Self> {
+        let axis = match axis {
+            0 => GamepadAxis::LeftStickX,
+            1 => GamepadAxis::LeftStickY,
+            2 => GamepadAxis::LeftTrigger,
+            3 => GamepadAxis::RightStickX,
+            4 => GamepadAxis::RightStickY,
+            5 => GamepadAxis::RightTrigger,
+            _ => return None,
+        };
+        Some(axis)
+    }
+
+    /// The id of the axis in `DeviceEvent::Motion`.
+    ///
+    /// It's the same as the evdev code of the axis on Linux.
+    pub fn axis_id(self) -> AxisId {
+        match self {
+            GamepadAxis::LeftStickX => 0,
+            GamepadAxis::LeftStickY => 1,
+            GamepadAxis::LeftTrigger => 2,
+            GamepadAxis::RightStickX => 3,
+            GamepadAxis::RightStickY => 4,
+            GamepadAxis::RightTrigger => 5,
+        }
+    }
+
+    /// Whether the axis is a trigger, which moves from `0.0` to `1.0`.
+    #[inline]
+    pub fn is_trigger(self) -> bool {
+        matches!(self, GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger)
+    }
+}
+
+/// What a gamepad or joystick is, and whether it's mapped to the standard layout.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GamepadInfo {
+    /// The name the device gives itself.
+    pub name: String,
+    /// The USB vendor id, or 0 if the device doesn't have one.
+    pub vendor_id: u16,
+    /// The USB product id, or 0 if the device doesn't have one.
+    pub product_id: u16,
+    /// Whether the buttons and axes are reported as the ones of [`GamepadButton`] and
+    /// [`GamepadAxis`].
+    pub is_mapped: bool,
+}
@@ -13,7 +13,7 @@ pub(crate) struct Pixel {
 pub(crate) const PIXEL_SIZE: usize = mem::size_of::<Pixel>();
 
 #[derive(Debug)]
-/// An error produced when using `Icon::from_rgba` with invalid arguments.
+/// An error produced when creating an `Icon` or a `CustomCursorSource` with invalid arguments.
 pub enum BadIcon {
     /// Produced when the length of the `rgba` argument isn't divisible by 4, thus `rgba` can't be
     /// safely interpreted as 32bpp RGBA pixels.
@@ -26,6 +26,23 @@ pub enum BadIcon {
         width_x_height: usize,
         pixel_count: usize,
     },
+    /// Produced when the hotspot of a cursor lies outside of its image.
+    HotspotOutOfBounds {
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    },
+    /// Produced when a HiDPI variant of a cursor is given a scale factor of 0.
+    ZeroScaleFactor,
+    /// Produced when a HiDPI variant is added to an animated cursor, rather than to its frames.
+    AnimatedCursorVariant,
+    /// Produced when an animated cursor is created without frames.
+    NoCursorFrames,
+    /// Produced when a frame of an animated cursor is animated itself.
+    AnimatedCursorFrame,
+    /// Produced when a frame of an animated cursor has a duration of zero.
+    ZeroFrameDuration,
     /// Produced when underlying OS functionality failed to create the icon
     OsError(io::Error),
 }
@@ -46,6 +63,22 @@ impl fmt::Display for BadIcon {
                 "The specified dimensions ({:?}x{:?}) don't match the number of pixels supplied by the `rgba` argument ({:?}). For those dimensions, the expected pixel count is {:?}.",
                 width, height, pixel_count, width_x_height,
             ),
+            BadIcon::HotspotOutOfBounds {
+                width,
+                height,
+                hotspot_x,
+                hotspot_y,
+            } => write!(f,
+                "The hotspot ({:?}, {:?}) lies outside of the {:?}x{:?} cursor image.",
+                hotspot_x, hotspot_y, width, height,
+            ),
+            BadIcon::ZeroScaleFactor => write!(f, "Cursor variants need a positive scale factor."),
+            BadIcon::AnimatedCursorVariant => write!(f,
+                "HiDPI variants can't be added to animated cursors, only to their frames.",
+            ),
+            BadIcon::NoCursorFrames => write!(f, "Animated cursors need at least one frame."),
+            BadIcon::AnimatedCursorFrame => write!(f, "The frames of a cursor can't be animated."),
+            BadIcon::ZeroFrameDuration => write!(f, "Cursor frames need a positive duration."),
             BadIcon::OsError(e) => write!(f, "OS error when instantiating the icon: {:?}", e),
         }
     }
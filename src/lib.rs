@@ -147,14 +147,19 @@ extern crate bitflags;
 #[macro_use]
 extern crate objc;
 
+pub mod clipboard;
+mod cursor;
+pub mod dnd;
 pub mod dpi;
 #[macro_use]
 pub mod error;
 pub mod event;
 pub mod event_loop;
+pub mod gamepad;
 mod icon;
 pub mod monitor;
 mod platform_impl;
+pub mod record;
 pub mod window;
 
 pub mod platform;
@@ -0,0 +1,518 @@
+#![cfg(all(
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ),
+    feature = "mock"
+))]
+
+//! A headless backend which doesn't need a display server.
+//!
+//! Windows and monitors created by a mock `EventLoop` only exist in memory, and all input has to
+//! be scripted through a [`MockController`]. This makes it possible to test the input handling of
+//! an application deterministically, e.g. in CI:
+//!
+//! ```no_run
+//! use winit::{
+//!     event::{Event, WindowEvent},
+//!     event_loop::EventLoop,
+//!     platform::mock::{EventLoopExtMock, EventLoopWindowTargetExtMock},
+//!     window::Window,
+//! };
+//!
+//! let mut event_loop = EventLoop::<()>::new_mock();
+//! let window = Window::new(&event_loop).unwrap();
+//! let controller = event_loop.mock_controller().unwrap();
+//!
+//! controller.send_window_event(window.id(), WindowEvent::CloseRequested);
+//! event_loop.run_step(|event, _, _| {
+//!     if let Event::WindowEvent { event: WindowEvent::CloseRequested, .. } = event {
+//!         println!("The window was asked to close");
+//!     }
+//! });
+//! ```
+//!
+//! The backend can also be selected at runtime by setting the `WINIT_UNIX_BACKEND` environment
+//! variable to `mock`.
+//!
+//! Mock windows have nothing to render to: their [`raw_window_handle`] is a
+//! `RawWindowHandle::Wayland` whose surface and display pointers are null, which graphics APIs
+//! can't be given.
+//!
+//! [`raw_window_handle`]: raw_window_handle::HasRawWindowHandle::raw_window_handle
+
+use std::sync::Arc;
+#[cfg(target_os = "linux")]
+use std::{
+    fs::File,
+    os::unix::io::{FromRawFd, IntoRawFd},
+};
+
+#[cfg(target_os = "linux")]
+use crate::platform_impl::gamepad;
+use crate::{
+    clipboard::ClipboardKind,
+    dnd::{DndAction, DndActions, DragOutcome, DropData},
+    dpi::{PhysicalPosition, PhysicalSize, Position, Size},
+    event::{DeviceEvent, DeviceId, Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    monitor::MonitorHandle,
+    platform_impl::{
+        mock, DeviceId as LinuxDeviceId, EventLoop as LinuxEventLoop,
+        EventLoopWindowTarget as LinuxEventLoopWindowTarget, MonitorHandle as LinuxMonitorHandle,
+        WindowId as LinuxWindowId,
+    },
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursorSource, Fullscreen, PopupAttributes,
+        UserAttentionType, WindowId,
+    },
+};
+
+/// Additional methods on `EventLoop` to use the mock backend.
+pub trait EventLoopExtMock {
+    /// A type provided by the user that can be passed through `Event::UserEvent`.
+    type UserEvent;
+
+    /// Builds a new `EventLoop` that is forced to use the mock backend.
+    ///
+    /// Unlike the other backends, the mock event loop can be created on any thread.
+    fn new_mock() -> Self
+    where
+        Self: Sized;
+
+    /// Runs a single iteration of the event loop and returns the `ControlFlow` requested by the
+    /// event handler.
+    ///
+    /// One iteration consists of `NewEvents`, every event scripted before the call, user events,
+    /// `MainEventsCleared`, the requested redraws and `RedrawEventsCleared`. `LoopDestroyed` is
+    /// never sent, and this never blocks regardless of the `ControlFlow`.
+    ///
+    /// The first call sends `StartCause::Init`, subsequent calls send `StartCause::Poll`.
+    ///
+    /// # Panics
+    ///
+    /// If the `EventLoop` doesn't use the mock backend.
+    fn run_step<F>(&mut self, event_handler: F) -> ControlFlow
+    where
+        F: FnMut(
+            Event<'_, Self::UserEvent>,
+            &EventLoopWindowTarget<Self::UserEvent>,
+            &mut ControlFlow,
+        );
+}
+
+impl<T> EventLoopExtMock for EventLoop<T> {
+    type UserEvent = T;
+
+    #[inline]
+    fn new_mock() -> Self {
+        EventLoop {
+            event_loop: LinuxEventLoop::new_mock(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    fn run_step<F>(&mut self, event_handler: F) -> ControlFlow
+    where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        match self.event_loop {
+            LinuxEventLoop::Mock(ref mut event_loop) => event_loop.run_step(event_handler),
+            #[cfg(any(feature = "x11", feature = "wayland"))]
+            _ => panic!("`run_step` is only available on the mock backend"),
+        }
+    }
+}
+
+/// Additional methods on `EventLoopWindowTarget` to use the mock backend.
+pub trait EventLoopWindowTargetExtMock {
+    /// True if the `EventLoopWindowTarget` uses the mock backend.
+    fn is_mock(&self) -> bool;
+
+    /// Returns a controller to script input for this event loop.
+    ///
+    /// Returns `None` if the `EventLoop` doesn't use the mock backend.
+    fn mock_controller(&self) -> Option<MockController>;
+}
+
+impl<T> EventLoopWindowTargetExtMock for EventLoopWindowTarget<T> {
+    #[inline]
+    fn is_mock(&self) -> bool {
+        self.mock_controller().is_some()
+    }
+
+    #[inline]
+    fn mock_controller(&self) -> Option<MockController> {
+        match self.p {
+            LinuxEventLoopWindowTarget::Mock(ref p) => Some(MockController {
+                shared: p.shared().clone(),
+            }),
+            #[cfg(any(feature = "x11", feature = "wayland"))]
+            _ => None,
+        }
+    }
+}
+
+/// A handle to script input and inspect the state of a mock event loop.
+///
+/// Scripted events are queued, and get dispatched in order by the next iteration of the event
+/// loop. The controller can be sent to other threads, in which case queuing an event wakes up
+/// an event loop waiting in `ControlFlow::Wait`.
+#[derive(Clone)]
+pub struct MockController {
+    shared: Arc<mock::Shared>,
+}
+
+impl std::fmt::Debug for MockController {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad("MockController { .. }")
+    }
+}
+
+impl MockController {
+    /// Returns the `DeviceId` of the mock input device identified by `id`.
+    ///
+    /// Mock devices don't need to be registered: any `id` is valid, and two calls with the same
+    /// `id` return the same `DeviceId`.
+    #[inline]
+    pub fn device_id(id: u64) -> DeviceId {
+        DeviceId(LinuxDeviceId::Mock(mock::DeviceId(id)))
+    }
+
+    /// Queues a `WindowEvent` for `window_id`.
+    ///
+    /// When it gets dispatched, the event also updates the window state it describes: `Resized`
+    /// changes the inner size, `Moved` the outer position and `Focused` the focus.
+    ///
+    /// Events targeting a window which no longer exists are dropped.
+    ///
+    /// `WindowEvent::ScaleFactorChanged` can't be queued this way, use
+    /// [`set_scale_factor`](Self::set_scale_factor) instead.
+    pub fn send_window_event(&self, window_id: WindowId, event: WindowEvent<'static>) {
+        self.shared.push_event(mock::PendingEvent::Window {
+            window_id: mock_window_id(window_id),
+            event,
+        });
+    }
+
+    /// Queues a `DeviceEvent` for `device_id`.
+    pub fn send_device_event(&self, device_id: DeviceId, event: DeviceEvent) {
+        let device_id = match device_id.0 {
+            LinuxDeviceId::Mock(device_id) => device_id,
+            #[cfg(any(feature = "x11", feature = "wayland", target_os = "linux"))]
+            _ => panic!("`DeviceId` doesn't belong to the mock backend"),
+        };
+
+        self.shared
+            .push_event(mock::PendingEvent::Device { device_id, event });
+    }
+
+    /// Queues a scale factor change for `window_id`.
+    ///
+    /// When it gets dispatched, a `WindowEvent::ScaleFactorChanged` is sent with the inner size
+    /// scaled to keep the same logical size, followed by a `WindowEvent::Resized` if the size
+    /// picked by the event handler differs from the current one.
+    pub fn set_scale_factor(&self, window_id: WindowId, scale_factor: f64) {
+        self.shared
+            .push_event(mock::PendingEvent::ScaleFactorChanged {
+                window_id: mock_window_id(window_id),
+                scale_factor,
+            });
+    }
+
+    /// Connects a new monitor.
+    ///
+    /// The event loop starts with a single default monitor. Windows are created with the scale
+    /// factor of the monitor they're positioned on, which is the first monitor unless a position
+    /// was requested.
+    pub fn add_monitor(&self, monitor: MockMonitor) -> MonitorHandle {
+        MonitorHandle {
+            inner: LinuxMonitorHandle::Mock(self.shared.add_monitor(monitor)),
+        }
+    }
+
+    /// Disconnects a monitor.
+    pub fn remove_monitor(&self, monitor: &MonitorHandle) {
+        match monitor.inner {
+            LinuxMonitorHandle::Mock(ref monitor) => self.shared.remove_monitor(monitor),
+            #[cfg(any(feature = "x11", feature = "wayland"))]
+            _ => (),
+        }
+    }
+
+    /// Connects a gamepad, whose input is read from `events` like from an evdev device.
+    ///
+    /// `events` is a stream of `struct input_event` from `linux/input.h`, e.g. one end of a
+    /// `UnixStream` pair, which is read without blocking on each iteration of the event loop. The
+    /// gamepad is reported with `DeviceEvent::Added`, and mapped to the standard layout like a
+    /// real device would be. It's removed once the other end of the stream is closed.
+    #[cfg(target_os = "linux")]
+    pub fn connect_gamepad(&self, gamepad: MockGamepad, events: impl IntoRawFd) -> DeviceId {
+        let mut keys = gamepad.buttons;
+        keys.sort_unstable();
+        keys.dedup();
+        let mut axes: Vec<_> = gamepad
+            .axes
+            .into_iter()
+            .map(|(code, minimum, maximum)| (code, gamepad::AbsInfo { minimum, maximum }))
+            .collect();
+        axes.sort_by_key(|(code, _)| *code);
+        let capabilities = gamepad::Capabilities {
+            name: gamepad.name,
+            bus: gamepad.bus_type,
+            vendor: gamepad.vendor_id,
+            product: gamepad.product_id,
+            version: gamepad.version,
+            keys,
+            axes,
+        };
+
+        let file = unsafe { File::from_raw_fd(events.into_raw_fd()) };
+        let device_id = self
+            .shared
+            .lock()
+            .gamepads
+            .add_device(file, capabilities, None)
+            .expect("Failed to connect the gamepad");
+        self.shared.wake();
+        DeviceId(LinuxDeviceId::Gamepad(device_id))
+    }
+
+    /// Adds lines of SDL's game controller database, which map the gamepads connected afterwards
+    /// like the ones of the `SDL_GAMECONTROLLERCONFIG` environment variable would.
+    #[cfg(target_os = "linux")]
+    pub fn add_gamepad_mappings(&self, mappings: &str) {
+        self.shared.lock().gamepads.add_mappings(mappings);
+    }
+
+    /// Puts `text` into a clipboard, as if another application had copied it.
+    ///
+    /// This queues an [`Event::ClipboardChanged`].
+    pub fn set_clipboard_text(&self, kind: ClipboardKind, text: &str) {
+        let data = vec![(mock::TEXT_MIME_TYPE.to_owned(), text.as_bytes().to_vec())];
+        self.set_clipboard_data(kind, data);
+    }
+
+    /// Puts `data` into a clipboard, available as each of the given mime types, as if another
+    /// application had copied it.
+    ///
+    /// This queues an [`Event::ClipboardChanged`].
+    pub fn set_clipboard_data(&self, kind: ClipboardKind, data: Vec<(String, Vec<u8>)>) {
+        self.shared.lock().clipboards.insert(kind, data);
+        self.shared
+            .push_event(mock::PendingEvent::ClipboardChanged(kind));
+    }
+
+    /// Returns the text held by a clipboard.
+    pub fn clipboard_text(&self, kind: ClipboardKind) -> Option<String> {
+        let data = self.shared.clipboard_data(kind, mock::TEXT_MIME_TYPE)?;
+        Some(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    /// Returns the data a clipboard holds as `mime_type`.
+    pub fn clipboard_data(&self, kind: ClipboardKind, mime_type: &str) -> Option<Vec<u8>> {
+        self.shared.clipboard_data(kind, mime_type)
+    }
+
+    /// Ends the drag in progress from `window_id`, as if the user had dropped it or cancelled it.
+    ///
+    /// This queues a `WindowEvent::DragEnded`. Nothing happens if no drag is in progress.
+    pub fn end_drag(&self, window_id: WindowId, outcome: DragOutcome) {
+        let window_id = mock_window_id(window_id);
+        let drag = self
+            .shared
+            .lock()
+            .windows
+            .get_mut(&window_id)
+            .and_then(|state| state.drag.take());
+        if drag.is_some() {
+            self.shared.push_event(mock::PendingEvent::Window {
+                window_id,
+                event: WindowEvent::DragEnded(outcome),
+            });
+        }
+    }
+
+    /// Queues a move of the drag hovering `window_id`, as if the user had moved the cursor.
+    ///
+    /// Drags enter and leave a window with a `WindowEvent::DragEntered` and a
+    /// `WindowEvent::DragLeft` queued through [`send_window_event`](Self::send_window_event).
+    /// When the move gets dispatched, a `WindowEvent::DragMoved` is sent, and the action the
+    /// event handler accepted the drop with is stored in [`MockIncomingDrag::accepted_action`].
+    ///
+    /// Nothing happens if no drag hovers the window.
+    pub fn move_drag(
+        &self,
+        window_id: WindowId,
+        position: PhysicalPosition<f64>,
+        action: DndAction,
+    ) {
+        self.shared.push_event(mock::PendingEvent::DragMoved {
+            window_id: mock_window_id(window_id),
+            position,
+            action,
+        });
+    }
+
+    /// Queues a drop of the drag hovering `window_id`, as if the user had released it.
+    ///
+    /// When it gets dispatched, a `WindowEvent::DragDropped` carrying `data` is sent if the last
+    /// `WindowEvent::DragMoved` was accepted, and a `WindowEvent::DragLeft` otherwise.
+    ///
+    /// Nothing happens if no drag hovers the window.
+    pub fn drop_drag(&self, window_id: WindowId, position: PhysicalPosition<f64>, data: DropData) {
+        self.shared.push_event(mock::PendingEvent::Drop {
+            window_id: mock_window_id(window_id),
+            position,
+            data,
+        });
+    }
+
+    /// Returns the ids of all the windows which are still alive.
+    pub fn windows(&self) -> Vec<WindowId> {
+        let mut windows: Vec<_> = self
+            .shared
+            .lock()
+            .windows
+            .keys()
+            .map(|window_id| WindowId(LinuxWindowId::Mock(*window_id)))
+            .collect();
+        windows.sort();
+        windows
+    }
+
+    /// Returns a snapshot of the attributes of a window.
+    ///
+    /// Returns `None` if the window doesn't exist anymore.
+    pub fn window_state(&self, window_id: WindowId) -> Option<MockWindowState> {
+        self.shared
+            .lock()
+            .windows
+            .get(&mock_window_id(window_id))
+            .cloned()
+    }
+}
+
+fn mock_window_id(window_id: WindowId) -> mock::WindowId {
+    match window_id.0 {
+        LinuxWindowId::Mock(window_id) => window_id,
+        #[cfg(any(feature = "x11", feature = "wayland"))]
+        _ => panic!("`WindowId` doesn't belong to the mock backend"),
+    }
+}
+
+/// Description of a monitor connected to a mock event loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockMonitor {
+    pub name: String,
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    pub refresh_rate: u16,
+}
+
+impl Default for MockMonitor {
+    fn default() -> Self {
+        MockMonitor {
+            name: "mock".to_owned(),
+            position: PhysicalPosition::new(0, 0),
+            size: PhysicalSize::new(1920, 1080),
+            scale_factor: 1.0,
+            refresh_rate: 60,
+        }
+    }
+}
+
+/// Description of a gamepad connected to a mock event loop, as evdev reports it.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockGamepad {
+    pub name: String,
+    /// One of the `BUS_*` constants of `linux/input.h`, which is part of the id of the mapping.
+    pub bus_type: u16,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub version: u16,
+    /// The codes of the buttons.
+    pub buttons: Vec<u16>,
+    /// The codes of the absolute axes, with their minimum and maximum values.
+    pub axes: Vec<(u16, i32, i32)>,
+}
+
+#[cfg(target_os = "linux")]
+impl Default for MockGamepad {
+    fn default() -> Self {
+        MockGamepad {
+            name: "mock".to_owned(),
+            bus_type: 0x03,
+            vendor_id: 0,
+            product_id: 0,
+            version: 0,
+            buttons: Vec::new(),
+            axes: Vec::new(),
+        }
+    }
+}
+
+/// The attributes of a mock window, as last set by the application or by a dispatched event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockWindowState {
+    pub title: String,
+    pub visible: bool,
+    pub focused: bool,
+    pub inner_size: PhysicalSize<u32>,
+    pub outer_position: PhysicalPosition<i32>,
+    pub min_inner_size: Option<Size>,
+    pub max_inner_size: Option<Size>,
+    pub resize_increments: Option<Size>,
+    pub scale_factor: f64,
+    pub resizable: bool,
+    pub minimized: bool,
+    pub maximized: bool,
+    pub fullscreen: Option<Fullscreen>,
+    pub decorations: bool,
+    pub always_on_top: bool,
+    pub transparent: bool,
+    pub has_window_icon: bool,
+    /// Where the window is placed relative to its parent, if it's a popup.
+    pub popup: Option<PopupAttributes>,
+    /// Whether the popup grabs the input, see `Window::set_popup_grab`.
+    pub popup_grab: bool,
+    /// The window this one is a dialog of, see `WindowBuilderExtUnix::with_parent_window`.
+    pub parent_window: Option<WindowId>,
+    pub modal: bool,
+    /// The window this one is shown inside of, see `WindowBuilderExtUnix::with_child_of`.
+    pub child_of: Option<WindowId>,
+    pub cursor_icon: CursorIcon,
+    /// Images of the custom cursor shown instead of `cursor_icon`, if any.
+    pub custom_cursor: Option<CustomCursorSource>,
+    pub cursor_visible: bool,
+    pub cursor_grab: CursorGrabMode,
+    /// The part of the window `CursorGrabMode::Confined` is restricted to, if any.
+    pub cursor_grab_region: Option<(Position, Size)>,
+    pub cursor_position: Option<PhysicalPosition<f64>>,
+    pub ime_position: Option<PhysicalPosition<f64>>,
+    /// Whether the window receives `WindowEvent::Ime`, see `Window::set_ime_allowed`.
+    pub ime_allowed: bool,
+    pub user_attention: Option<UserAttentionType>,
+    pub drag: Option<MockDrag>,
+    pub incoming_drag: Option<MockIncomingDrag>,
+}
+
+/// A drag from another application hovering a mock window, from its
+/// `WindowEvent::DragEntered` until it's dropped or leaves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockIncomingDrag {
+    pub mime_types: Vec<String>,
+    pub accepted_action: Option<DndAction>,
+}
+
+/// A drag started from a mock window, which lasts until [`MockController::end_drag`] is called.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockDrag {
+    pub data: Vec<(String, Vec<u8>)>,
+    pub allowed_actions: DndActions,
+}
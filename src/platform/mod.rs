@@ -12,12 +12,14 @@
 //! And the following platform-specific module:
 //!
 //! - `run_return` (available on `windows`, `unix`, `macos`, and `android`)
+//! - `mock` (available on `unix` with the `mock` feature)
 //!
 //! However only the module corresponding to the platform you're compiling to will be available.
 
 pub mod android;
 pub mod ios;
 pub mod macos;
+pub mod mock;
 pub mod unix;
 pub mod windows;
 
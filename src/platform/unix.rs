@@ -6,23 +6,28 @@
     target_os = "openbsd"
 ))]
 
+#[cfg(any(feature = "x11", feature = "wayland"))]
 use std::os::raw;
 #[cfg(feature = "x11")]
 use std::{ptr, sync::Arc};
 
+#[cfg(feature = "x11")]
+use crate::error::OsError;
+#[cfg(target_os = "linux")]
+use crate::{event::DeviceId, gamepad::GamepadInfo, platform_impl::DeviceId as LinuxDeviceId};
 use crate::{
     event_loop::{EventLoop, EventLoopWindowTarget},
     monitor::MonitorHandle,
     window::{Window, WindowBuilder},
 };
 
-#[cfg(feature = "x11")]
 use crate::dpi::Size;
 #[cfg(feature = "x11")]
 use crate::platform_impl::x11::{ffi::XVisualInfo, XConnection};
+use crate::platform_impl::EventLoop as LinuxEventLoop;
+#[cfg(any(feature = "x11", feature = "wayland"))]
 use crate::platform_impl::{
-    EventLoop as LinuxEventLoop, EventLoopWindowTarget as LinuxEventLoopWindowTarget,
-    Window as LinuxWindow,
+    EventLoopWindowTarget as LinuxEventLoopWindowTarget, Window as LinuxWindow,
 };
 
 // TODO: stupid hack so that glutin can do its work
@@ -54,6 +59,22 @@ pub trait EventLoopWindowTargetExtUnix {
     /// The pointer will become invalid when the winit `EventLoop` is destroyed.
     #[cfg(feature = "wayland")]
     fn wayland_display(&self) -> Option<*mut raw::c_void>;
+
+    /// Returns the name and ids of the gamepad or joystick reporting input as `device_id`, and
+    /// whether it's mapped to the standard layout of [`crate::gamepad`].
+    ///
+    /// Returns `None` if the device isn't a gamepad, or if it was removed.
+    #[cfg(target_os = "linux")]
+    fn gamepad_info(&self, device_id: DeviceId) -> Option<GamepadInfo>;
+
+    /// Starts reading the gamepads and joysticks from `/dev/input`.
+    ///
+    /// The devices which are plugged in are reported with `DeviceEvent::Added` on the next
+    /// iteration of the event loop, and the ones plugged in later on once they're readable. No
+    /// device is opened, and no gamepad event is reported, before this is called. Calling it again
+    /// does nothing.
+    #[cfg(target_os = "linux")]
+    fn watch_gamepads(&self);
 }
 
 impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
@@ -66,7 +87,11 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
     #[inline]
     #[cfg(feature = "x11")]
     fn is_x11(&self) -> bool {
-        !self.p.is_wayland()
+        match self.p {
+            LinuxEventLoopWindowTarget::X(_) => true,
+            #[cfg(any(feature = "wayland", feature = "mock"))]
+            _ => false,
+        }
     }
 
     #[inline]
@@ -75,7 +100,7 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
     fn xlib_xconnection(&self) -> Option<Arc<XConnection>> {
         match self.p {
             LinuxEventLoopWindowTarget::X(ref e) => Some(e.x_connection().clone()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "mock"))]
             _ => None,
         }
     }
@@ -87,10 +112,25 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
             LinuxEventLoopWindowTarget::Wayland(ref p) => {
                 Some(p.display().get_display_ptr() as *mut _)
             }
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "mock"))]
             _ => None,
         }
     }
+
+    #[inline]
+    #[cfg(target_os = "linux")]
+    fn gamepad_info(&self, device_id: DeviceId) -> Option<GamepadInfo> {
+        match device_id.0 {
+            LinuxDeviceId::Gamepad(device_id) => self.p.gamepad_info(device_id),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    #[cfg(target_os = "linux")]
+    fn watch_gamepads(&self) {
+        self.p.watch_gamepads()
+    }
 }
 
 /// Additional methods on `EventLoop` that are specific to Unix.
@@ -142,6 +182,43 @@ pub trait EventLoopExtUnix {
     fn new_wayland_any_thread() -> Self
     where
         Self: Sized;
+
+    /// Builds a new X11 `EventLoop` on the Xlib `Display` of the application, such as the one of a
+    /// GUI toolkit or of a GL loader, instead of opening its own connection.
+    ///
+    /// The display isn't closed when the event loop is dropped. Winit sets its own Xlib error
+    /// handler, which only handles the errors of the requests winit makes on `display`, and
+    /// passes the other errors on to the handler that was set before, such as the one of the
+    /// application. The requests made in the callback of the event loop, outside of the calls to
+    /// winit, are the application's. Like [`new_x11_any_thread`](#tymethod.new_x11_any_thread),
+    /// this can be called from any thread.
+    ///
+    /// # Safety
+    ///
+    /// `display` must be a valid `Display`, opened after `XInitThreads` was called, which outlives
+    /// the event loop and its windows.
+    #[cfg(feature = "x11")]
+    unsafe fn new_x11_from_display(display: *mut raw::c_void) -> Result<Self, XNotSupported>
+    where
+        Self: Sized;
+
+    /// Builds a new Wayland `EventLoop` on the `wl_display` of the application, instead of
+    /// connecting to the compositor itself.
+    ///
+    /// The events of winit are dispatched on its own event queue, and the display isn't
+    /// disconnected when the event loop is dropped. Like
+    /// [`new_wayland_any_thread`](#tymethod.new_wayland_any_thread), this can be called from any
+    /// thread.
+    ///
+    /// # Safety
+    ///
+    /// `display` must be a valid `wl_display`, which outlives the event loop and its windows.
+    #[cfg(feature = "wayland")]
+    unsafe fn new_wayland_from_display(
+        display: *mut raw::c_void,
+    ) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        Self: Sized;
 }
 
 fn wrap_ev<T>(event_loop: LinuxEventLoop<T>) -> EventLoop<T> {
@@ -179,6 +256,20 @@ impl<T> EventLoopExtUnix for EventLoop<T> {
         LinuxEventLoop::new_x11().map(wrap_ev)
     }
 
+    #[inline]
+    #[cfg(feature = "x11")]
+    unsafe fn new_x11_from_display(display: *mut raw::c_void) -> Result<Self, XNotSupported> {
+        LinuxEventLoop::new_x11_from_display(display).map(wrap_ev)
+    }
+
+    #[inline]
+    #[cfg(feature = "wayland")]
+    unsafe fn new_wayland_from_display(
+        display: *mut raw::c_void,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        LinuxEventLoop::new_wayland_from_display(display).map(wrap_ev)
+    }
+
     #[inline]
     #[cfg(feature = "wayland")]
     fn new_wayland() -> Self {
@@ -198,6 +289,32 @@ pub trait WindowExtUnix {
     #[cfg(feature = "x11")]
     fn xlib_window(&self) -> Option<raw::c_ulong>;
 
+    /// Wraps the X11 window `xlib_window`, created by another library, so that `event_loop`
+    /// delivers its `WindowEvent`s.
+    ///
+    /// The input and structure events winit needs are selected on the window in addition to the
+    /// ones selected before, and the window gets a `WindowId` and an input context like the
+    /// windows winit creates. The window still belongs to its creator: its decorations, title and
+    /// size hints are only changed when asked to through the returned `Window`, and dropping it
+    /// destroys the input context and gives the window its previous event mask back instead of
+    /// destroying it.
+    ///
+    /// Returns an error if the event loop doesn't use X11, or if `xlib_window` isn't a window or
+    /// is already wrapped. Wayland surfaces can't be wrapped, since their events are dispatched on
+    /// the event queue of the library which created them.
+    ///
+    /// # Safety
+    ///
+    /// `xlib_window` must be a window of the X11 server the event loop is connected to, and mustn't
+    /// be destroyed before the returned `Window` is dropped.
+    #[cfg(feature = "x11")]
+    unsafe fn from_raw_x11<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        xlib_window: raw::c_ulong,
+    ) -> Result<Self, OsError>
+    where
+        Self: Sized;
+
     /// Returns a pointer to the `Display` object of xlib that is used by this window.
     ///
     /// Returns `None` if the window doesn't use xlib (if it uses wayland for example).
@@ -257,17 +374,26 @@ impl WindowExtUnix for Window {
     fn xlib_window(&self) -> Option<raw::c_ulong> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_window()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "mock"))]
             _ => None,
         }
     }
 
+    #[inline]
+    #[cfg(feature = "x11")]
+    unsafe fn from_raw_x11<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        xlib_window: raw::c_ulong,
+    ) -> Result<Self, OsError> {
+        LinuxWindow::from_raw_x11(&event_loop.p, xlib_window).map(|window| Window { window })
+    }
+
     #[inline]
     #[cfg(feature = "x11")]
     fn xlib_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_display()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "mock"))]
             _ => None,
         }
     }
@@ -277,7 +403,7 @@ impl WindowExtUnix for Window {
     fn xlib_screen_id(&self) -> Option<raw::c_int> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_screen_id()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "mock"))]
             _ => None,
         }
     }
@@ -288,7 +414,7 @@ impl WindowExtUnix for Window {
     fn xlib_xconnection(&self) -> Option<Arc<XConnection>> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_xconnection()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "mock"))]
             _ => None,
         }
     }
@@ -298,7 +424,7 @@ impl WindowExtUnix for Window {
     fn xcb_connection(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xcb_connection()),
-            #[cfg(feature = "wayland")]
+            #[cfg(any(feature = "wayland", feature = "mock"))]
             _ => None,
         }
     }
@@ -308,7 +434,7 @@ impl WindowExtUnix for Window {
     fn wayland_surface(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.surface().as_ref().c_ptr() as *mut _),
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "mock"))]
             _ => None,
         }
     }
@@ -318,7 +444,7 @@ impl WindowExtUnix for Window {
     fn wayland_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.display().get_display_ptr() as *mut _),
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "mock"))]
             _ => None,
         }
     }
@@ -328,7 +454,7 @@ impl WindowExtUnix for Window {
     fn set_wayland_theme<T: Theme>(&self, theme: T) {
         match self.window {
             LinuxWindow::Wayland(ref w) => w.set_theme(theme),
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "mock"))]
             _ => {}
         }
     }
@@ -358,8 +484,9 @@ pub trait WindowBuilderExtUnix {
     /// Build window with `_GTK_THEME_VARIANT` hint set to the specified value. Currently only relevant on X11.
     #[cfg(feature = "x11")]
     fn with_gtk_theme_variant(self, variant: String) -> Self;
-    /// Build window with resize increment hint. Only implemented on X11.
-    #[cfg(feature = "x11")]
+    /// Build window with resize increments. See [`Window::set_resize_increments`] for details.
+    ///
+    /// [`Window::set_resize_increments`]: crate::window::Window::set_resize_increments
     fn with_resize_increments<S: Into<Size>>(self, increments: S) -> Self;
     /// Build window with base size hint. Only implemented on X11.
     #[cfg(feature = "x11")]
@@ -372,6 +499,53 @@ pub trait WindowBuilderExtUnix {
     /// [Desktop Entry Spec](https://specifications.freedesktop.org/desktop-entry-spec/desktop-entry-spec-latest.html#desktop-file-id)
     #[cfg(feature = "wayland")]
     fn with_app_id(self, app_id: String) -> Self;
+
+    /// Build window as a dialog of `parent`, which it stays above of and is associated with.
+    ///
+    /// On X11, this sets the `WM_TRANSIENT_FOR` hint, and the window type defaults to `Dialog`
+    /// instead of `Normal`. On Wayland, this sets the parent of the `xdg_toplevel` of the window,
+    /// and building the window fails if `parent` isn't a toplevel window.
+    fn with_parent_window(self, parent: &Window) -> Self;
+
+    /// Build window as a modal dialog, which keeps the input from its parent while it's visible;
+    /// defaults to false. See [`with_parent_window`](Self::with_parent_window).
+    ///
+    /// On X11, this sets the `_NET_WM_STATE_MODAL` state, with which the window manager keeps the
+    /// window above its parent and focuses it instead. The mouse button and key presses of the
+    /// parent are also dropped while the modal window is visible. On Wayland, the parent of the
+    /// `xdg_toplevel` is set, and the mouse button and key presses of the parent are dropped while
+    /// the modal window exists, since the compositor doesn't know about modal windows.
+    fn with_modal(self, modal: bool) -> Self;
+
+    /// Build window as a child of `parent`, shown inside of its client area and moving along with
+    /// it, such as a video overlay or an embedded viewport.
+    ///
+    /// The position of the window given by [`WindowBuilder::with_position`] and
+    /// [`Window::set_outer_position`] is relative to the client area of the parent. Child windows
+    /// have their own `WindowId`, input events and raw window handle, but no decorations, and
+    /// the window manager doesn't give them the keyboard focus.
+    ///
+    /// On X11, the window is created as a subwindow of its parent. On Wayland, the surface of the
+    /// window is a desynchronized `wl_subsurface` of the parent's one, whose position applies
+    /// when the parent is drawn again, so a redraw of the parent is requested along with it.
+    ///
+    /// [`Window::set_outer_position`]: crate::window::Window::set_outer_position
+    fn with_child_of(self, parent: &Window) -> Self;
+
+    /// Build window embedded into the X11 window `parent` of another application, such as an
+    /// audio plugin host or an IDE, following the XEmbed protocol. Only relevant on X11.
+    ///
+    /// The window is created as a subwindow of `parent`, and its position is relative to it, as
+    /// for [`with_child_of`](Self::with_child_of). The embedder maps the window, so
+    /// [`Window::set_visible`] is forwarded to it through `_XEMBED_INFO`. The window is focused
+    /// when the embedder gives it its focus while its toplevel window is active, and it receives
+    /// the key events the embedder forwards to it. [`Window::focus_window`] and clicking the
+    /// window ask the embedder for the focus.
+    ///
+    /// [`Window::set_visible`]: crate::window::Window::set_visible
+    /// [`Window::focus_window`]: crate::window::Window::focus_window
+    #[cfg(feature = "x11")]
+    fn with_embed_parent(self, parent: raw::c_ulong) -> Self;
 }
 
 impl WindowBuilderExtUnix for WindowBuilder {
@@ -421,7 +595,6 @@ impl WindowBuilderExtUnix for WindowBuilder {
     }
 
     #[inline]
-    #[cfg(feature = "x11")]
     fn with_resize_increments<S: Into<Size>>(mut self, increments: S) -> Self {
         self.platform_specific.resize_increments = Some(increments.into());
         self
@@ -440,6 +613,31 @@ impl WindowBuilderExtUnix for WindowBuilder {
         self.platform_specific.app_id = Some(app_id);
         self
     }
+
+    #[inline]
+    fn with_parent_window(mut self, parent: &Window) -> Self {
+        self.platform_specific.parent_window = Some(parent.id());
+        self
+    }
+
+    #[inline]
+    fn with_modal(mut self, modal: bool) -> Self {
+        self.platform_specific.modal = modal;
+        self
+    }
+
+    #[inline]
+    fn with_child_of(mut self, parent: &Window) -> Self {
+        self.platform_specific.child_of = Some(parent.id());
+        self
+    }
+
+    #[inline]
+    #[cfg(feature = "x11")]
+    fn with_embed_parent(mut self, parent: raw::c_ulong) -> Self {
+        self.platform_specific.embed_parent = Some(parent);
+        self
+    }
 }
 
 /// Additional methods on `MonitorHandle` that are specific to Linux.
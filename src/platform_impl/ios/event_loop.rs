@@ -8,13 +8,16 @@ use std::{
 };
 
 use crate::{
+    clipboard::ClipboardKind,
     dpi::LogicalSize,
+    error::{ExternalError, NotSupportedError},
     event::Event,
     event_loop::{
         ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootEventLoopWindowTarget,
     },
     monitor::MonitorHandle as RootMonitorHandle,
     platform::ios::Idiom,
+    window::CustomCursorSource,
 };
 
 use crate::platform_impl::platform::{
@@ -28,7 +31,7 @@ use crate::platform_impl::platform::{
         CFRunLoopSourceSignal, CFRunLoopWakeUp, NSStringRust, UIApplicationMain,
         UIUserInterfaceIdiom,
     },
-    monitor, view, MonitorHandle,
+    monitor, view, MonitorHandle, PlatformCustomCursor,
 };
 
 #[derive(Debug)]
@@ -63,6 +66,46 @@ impl<T: 'static> EventLoopWindowTarget<T> {
 
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    pub fn clipboard_text(&self, _kind: ClipboardKind) -> Result<Option<String>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_clipboard_text(
+        &self,
+        _kind: ClipboardKind,
+        _text: String,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_mime_types(&self, _kind: ClipboardKind) -> Result<Vec<String>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_data(
+        &self,
+        _kind: ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_clipboard_data(
+        &self,
+        _kind: ClipboardKind,
+        _data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        _source: CustomCursorSource,
+    ) -> Result<PlatformCustomCursor, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
 }
 
 pub struct EventLoop<T: 'static> {
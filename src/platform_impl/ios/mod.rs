@@ -83,6 +83,7 @@ pub use self::{
     window::{PlatformSpecificWindowBuilderAttributes, Window, WindowId},
 };
 
+pub(crate) use crate::cursor::NoCustomCursor as PlatformCustomCursor;
 pub(crate) use crate::icon::NoIcon as PlatformIcon;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -96,6 +97,12 @@ impl DeviceId {
             uiscreen: std::ptr::null_mut(),
         }
     }
+
+    /// Devices can't be told apart on this platform.
+    #[cfg(feature = "serde")]
+    pub fn synthetic(_index: u32) -> Self {
+        unsafe { DeviceId::dummy() }
+    }
 }
 
 unsafe impl Send for DeviceId {}
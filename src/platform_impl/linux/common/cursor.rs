@@ -0,0 +1,61 @@
+//! Preparation of custom cursor images.
+
+use crate::cursor::CursorImage;
+#[cfg(feature = "x11")]
+use crate::cursor::{CursorFrame, CustomCursorSource};
+
+/// The variant of a custom cursor drawn for the given scale factor, out of variants sorted by
+/// their own scale factor: the smallest one at least as large, or else the largest one.
+pub fn pick_variant<T>(variants: &[(u32, T)], scale_factor: f64) -> &T {
+    let (_, variant) = pick(variants, scale_factor, |(variant_scale_factor, _)| {
+        *variant_scale_factor
+    });
+    variant
+}
+
+/// The image of a frame drawn for the given scale factor, picked like in `pick_variant`.
+#[cfg(feature = "x11")]
+pub fn pick_image(frame: &CursorFrame, scale_factor: f64) -> &CursorImage {
+    pick(&frame.images, scale_factor, |image| image.scale_factor)
+}
+
+fn pick<T, F>(variants: &[T], scale_factor: f64, variant_scale_factor: F) -> &T
+where
+    F: Fn(&T) -> u32,
+{
+    variants
+        .iter()
+        .find(|variant| variant_scale_factor(variant) as f64 >= scale_factor)
+        .or_else(|| variants.last())
+        .expect("custom cursors have at least one image")
+}
+
+/// The scale factors of the images across all frames of a cursor, in ascending order.
+#[cfg(feature = "x11")]
+pub fn scale_factors(source: &CustomCursorSource) -> Vec<u32> {
+    let mut scale_factors: Vec<u32> = source
+        .frames
+        .iter()
+        .flat_map(|frame| frame.images.iter().map(|image| image.scale_factor))
+        .collect();
+    scale_factors.sort_unstable();
+    scale_factors.dedup();
+    scale_factors
+}
+
+/// Converts the pixels of an image to premultiplied ARGB, as both X11 and Wayland expect them.
+pub fn argb_pixels(image: &CursorImage) -> Vec<u32> {
+    image
+        .rgba
+        .rgba
+        .chunks_exact(4)
+        .map(|pixel| {
+            let alpha = pixel[3] as u32;
+            let premultiply = |channel: u8| (channel as u32 * alpha + 127) / 255;
+            alpha << 24
+                | premultiply(pixel[0]) << 16
+                | premultiply(pixel[1]) << 8
+                | premultiply(pixel[2])
+        })
+        .collect()
+}
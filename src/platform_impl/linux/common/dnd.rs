@@ -0,0 +1,116 @@
+//! Decoding of the data dropped on our windows.
+
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    str::Utf8Error,
+};
+
+use percent_encoding::percent_decode;
+
+use crate::dnd::DropData;
+
+/// MIME type of dropped files.
+pub const URI_LIST_MIME_TYPE: &str = "text/uri-list";
+
+/// MIME types of dropped text, in order of preference.
+pub const TEXT_MIME_TYPES: [&str; 3] = ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"];
+
+#[derive(Debug)]
+pub enum DndDataParseError {
+    EmptyData,
+    InvalidUtf8(Utf8Error),
+    HostnameSpecified(String),
+    UnexpectedProtocol(String),
+    UnresolvablePath(io::Error),
+}
+
+impl fmt::Display for DndDataParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DndDataParseError::EmptyData => f.pad("the URI list is empty"),
+            DndDataParseError::InvalidUtf8(e) => write!(f, "the URI list is not UTF-8: {}", e),
+            DndDataParseError::HostnameSpecified(path) => {
+                write!(f, "the URI of `{}` names a host", path)
+            }
+            DndDataParseError::UnexpectedProtocol(uri) => {
+                write!(f, "`{}` is not a `file://` URI", uri)
+            }
+            DndDataParseError::UnresolvablePath(e) => write!(f, "a path can't be resolved: {}", e),
+        }
+    }
+}
+
+impl From<Utf8Error> for DndDataParseError {
+    fn from(e: Utf8Error) -> Self {
+        DndDataParseError::InvalidUtf8(e)
+    }
+}
+
+impl From<io::Error> for DndDataParseError {
+    fn from(e: io::Error) -> Self {
+        DndDataParseError::UnresolvablePath(e)
+    }
+}
+
+/// The MIME type in which dropped data is requested: files, then text, then the first type.
+#[cfg(feature = "wayland")]
+pub fn pick_drop_mime_type(mime_types: &[String]) -> Option<&str> {
+    let offers = |mime_type: &str| mime_types.iter().any(|offered| offered == mime_type);
+    if offers(URI_LIST_MIME_TYPE) {
+        return Some(URI_LIST_MIME_TYPE);
+    }
+
+    TEXT_MIME_TYPES
+        .iter()
+        .copied()
+        .find(|mime_type| offers(mime_type))
+        .or_else(|| mime_types.first().map(String::as_str))
+}
+
+/// Decodes the data of a drop, received as `mime_type`.
+pub fn drop_data(mime_type: String, data: Vec<u8>) -> Option<DropData> {
+    if mime_type == URI_LIST_MIME_TYPE {
+        match parse_uri_list(&data) {
+            Ok(paths) => Some(DropData::Files(paths)),
+            Err(err) => {
+                warn!("Failed to decode the dropped files: {}", err);
+                None
+            }
+        }
+    } else if TEXT_MIME_TYPES.contains(&mime_type.as_str()) {
+        Some(DropData::Text(String::from_utf8_lossy(&data).into_owned()))
+    } else {
+        Some(DropData::Data { mime_type, data })
+    }
+}
+
+/// Decodes the paths of a `text/uri-list`, which may only contain local `file://` URIs.
+pub fn parse_uri_list(data: &[u8]) -> Result<Vec<PathBuf>, DndDataParseError> {
+    if !data.is_empty() {
+        let mut path_list = Vec::new();
+        let decoded = percent_decode(data).decode_utf8()?.into_owned();
+        for uri in decoded.split("\r\n").filter(|u| !u.is_empty()) {
+            // The format is specified as protocol://host/path
+            // However, it's typically simply protocol:///path
+            let path_str = if uri.starts_with("file://") {
+                let path_str = uri.replace("file://", "");
+                if !path_str.starts_with('/') {
+                    // A hostname is specified
+                    // Supporting this case is beyond the scope of my mental health
+                    return Err(DndDataParseError::HostnameSpecified(path_str));
+                }
+                path_str
+            } else {
+                // Only the file protocol is supported
+                return Err(DndDataParseError::UnexpectedProtocol(uri.to_owned()));
+            };
+
+            let path = Path::new(&path_str).canonicalize()?;
+            path_list.push(path);
+        }
+        Ok(path_list)
+    } else {
+        Err(DndDataParseError::EmptyData)
+    }
+}
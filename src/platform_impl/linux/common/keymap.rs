@@ -0,0 +1,401 @@
+//! Conversion of evdev scancodes and XKB keysyms, which are shared by X11 and Wayland, to winit
+//! keys.
+
+use crate::event::{Key, KeyCode, KeyLocation, ScanCode};
+
+/// Converts an evdev scancode, i.e. an X11 keycode minus 8, to a `KeyCode`.
+pub fn scancode_to_keycode(scancode: ScanCode) -> KeyCode {
+    // See `linux/input-event-codes.h`.
+    match scancode {
+        1 => KeyCode::Escape,
+        2 => KeyCode::Digit1,
+        3 => KeyCode::Digit2,
+        4 => KeyCode::Digit3,
+        5 => KeyCode::Digit4,
+        6 => KeyCode::Digit5,
+        7 => KeyCode::Digit6,
+        8 => KeyCode::Digit7,
+        9 => KeyCode::Digit8,
+        10 => KeyCode::Digit9,
+        11 => KeyCode::Digit0,
+        12 => KeyCode::Minus,
+        13 => KeyCode::Equal,
+        14 => KeyCode::Backspace,
+        15 => KeyCode::Tab,
+        16 => KeyCode::KeyQ,
+        17 => KeyCode::KeyW,
+        18 => KeyCode::KeyE,
+        19 => KeyCode::KeyR,
+        20 => KeyCode::KeyT,
+        21 => KeyCode::KeyY,
+        22 => KeyCode::KeyU,
+        23 => KeyCode::KeyI,
+        24 => KeyCode::KeyO,
+        25 => KeyCode::KeyP,
+        26 => KeyCode::BracketLeft,
+        27 => KeyCode::BracketRight,
+        28 => KeyCode::Enter,
+        29 => KeyCode::ControlLeft,
+        30 => KeyCode::KeyA,
+        31 => KeyCode::KeyS,
+        32 => KeyCode::KeyD,
+        33 => KeyCode::KeyF,
+        34 => KeyCode::KeyG,
+        35 => KeyCode::KeyH,
+        36 => KeyCode::KeyJ,
+        37 => KeyCode::KeyK,
+        38 => KeyCode::KeyL,
+        39 => KeyCode::Semicolon,
+        40 => KeyCode::Quote,
+        41 => KeyCode::Backquote,
+        42 => KeyCode::ShiftLeft,
+        43 => KeyCode::Backslash,
+        44 => KeyCode::KeyZ,
+        45 => KeyCode::KeyX,
+        46 => KeyCode::KeyC,
+        47 => KeyCode::KeyV,
+        48 => KeyCode::KeyB,
+        49 => KeyCode::KeyN,
+        50 => KeyCode::KeyM,
+        51 => KeyCode::Comma,
+        52 => KeyCode::Period,
+        53 => KeyCode::Slash,
+        54 => KeyCode::ShiftRight,
+        55 => KeyCode::NumpadMultiply,
+        56 => KeyCode::AltLeft,
+        57 => KeyCode::Space,
+        58 => KeyCode::CapsLock,
+        59 => KeyCode::F1,
+        60 => KeyCode::F2,
+        61 => KeyCode::F3,
+        62 => KeyCode::F4,
+        63 => KeyCode::F5,
+        64 => KeyCode::F6,
+        65 => KeyCode::F7,
+        66 => KeyCode::F8,
+        67 => KeyCode::F9,
+        68 => KeyCode::F10,
+        69 => KeyCode::NumLock,
+        70 => KeyCode::ScrollLock,
+        71 => KeyCode::Numpad7,
+        72 => KeyCode::Numpad8,
+        73 => KeyCode::Numpad9,
+        74 => KeyCode::NumpadSubtract,
+        75 => KeyCode::Numpad4,
+        76 => KeyCode::Numpad5,
+        77 => KeyCode::Numpad6,
+        78 => KeyCode::NumpadAdd,
+        79 => KeyCode::Numpad1,
+        80 => KeyCode::Numpad2,
+        81 => KeyCode::Numpad3,
+        82 => KeyCode::Numpad0,
+        83 => KeyCode::NumpadDecimal,
+        85 => KeyCode::Lang5,
+        86 => KeyCode::IntlBackslash,
+        87 => KeyCode::F11,
+        88 => KeyCode::F12,
+        89 => KeyCode::IntlRo,
+        90 => KeyCode::Lang3,
+        91 => KeyCode::Lang4,
+        92 => KeyCode::Convert,
+        93 => KeyCode::KanaMode,
+        94 => KeyCode::NonConvert,
+        95 => KeyCode::NumpadComma,
+        96 => KeyCode::NumpadEnter,
+        97 => KeyCode::ControlRight,
+        98 => KeyCode::NumpadDivide,
+        99 => KeyCode::PrintScreen,
+        100 => KeyCode::AltRight,
+        102 => KeyCode::Home,
+        103 => KeyCode::ArrowUp,
+        104 => KeyCode::PageUp,
+        105 => KeyCode::ArrowLeft,
+        106 => KeyCode::ArrowRight,
+        107 => KeyCode::End,
+        108 => KeyCode::ArrowDown,
+        109 => KeyCode::PageDown,
+        110 => KeyCode::Insert,
+        111 => KeyCode::Delete,
+        113 => KeyCode::AudioVolumeMute,
+        114 => KeyCode::AudioVolumeDown,
+        115 => KeyCode::AudioVolumeUp,
+        116 => KeyCode::Power,
+        117 => KeyCode::NumpadEqual,
+        119 => KeyCode::Pause,
+        121 => KeyCode::NumpadComma,
+        122 => KeyCode::Lang1,
+        123 => KeyCode::Lang2,
+        124 => KeyCode::IntlYen,
+        125 => KeyCode::SuperLeft,
+        126 => KeyCode::SuperRight,
+        127 => KeyCode::ContextMenu,
+        128 => KeyCode::BrowserStop,
+        129 => KeyCode::Again,
+        130 => KeyCode::Props,
+        131 => KeyCode::Undo,
+        133 => KeyCode::Copy,
+        134 => KeyCode::Open,
+        135 => KeyCode::Paste,
+        136 => KeyCode::Find,
+        137 => KeyCode::Cut,
+        138 => KeyCode::Help,
+        140 => KeyCode::LaunchApp2,
+        142 => KeyCode::Sleep,
+        143 => KeyCode::WakeUp,
+        155 => KeyCode::LaunchMail,
+        156 => KeyCode::BrowserFavorites,
+        157 => KeyCode::LaunchApp1,
+        158 => KeyCode::BrowserBack,
+        159 => KeyCode::BrowserForward,
+        161 => KeyCode::Eject,
+        163 => KeyCode::MediaTrackNext,
+        164 => KeyCode::MediaPlayPause,
+        165 => KeyCode::MediaTrackPrevious,
+        166 => KeyCode::MediaStop,
+        172 => KeyCode::BrowserHome,
+        173 => KeyCode::BrowserRefresh,
+        179 => KeyCode::NumpadParenLeft,
+        180 => KeyCode::NumpadParenRight,
+        183 => KeyCode::F13,
+        184 => KeyCode::F14,
+        185 => KeyCode::F15,
+        186 => KeyCode::F16,
+        187 => KeyCode::F17,
+        188 => KeyCode::F18,
+        189 => KeyCode::F19,
+        190 => KeyCode::F20,
+        191 => KeyCode::F21,
+        192 => KeyCode::F22,
+        193 => KeyCode::F23,
+        194 => KeyCode::F24,
+        217 => KeyCode::BrowserSearch,
+        226 => KeyCode::MediaSelect,
+        353 => KeyCode::Select,
+        _ => KeyCode::Unidentified(scancode),
+    }
+}
+
+/// Returns the location of a physical key.
+pub fn keycode_location(keycode: KeyCode) -> KeyLocation {
+    match keycode {
+        KeyCode::AltLeft | KeyCode::ControlLeft | KeyCode::ShiftLeft | KeyCode::SuperLeft => {
+            KeyLocation::Left
+        }
+        KeyCode::AltRight | KeyCode::ControlRight | KeyCode::ShiftRight | KeyCode::SuperRight => {
+            KeyLocation::Right
+        }
+        KeyCode::Numpad0
+        | KeyCode::Numpad1
+        | KeyCode::Numpad2
+        | KeyCode::Numpad3
+        | KeyCode::Numpad4
+        | KeyCode::Numpad5
+        | KeyCode::Numpad6
+        | KeyCode::Numpad7
+        | KeyCode::Numpad8
+        | KeyCode::Numpad9
+        | KeyCode::NumpadAdd
+        | KeyCode::NumpadComma
+        | KeyCode::NumpadDecimal
+        | KeyCode::NumpadDivide
+        | KeyCode::NumpadEnter
+        | KeyCode::NumpadEqual
+        | KeyCode::NumpadMultiply
+        | KeyCode::NumpadParenLeft
+        | KeyCode::NumpadParenRight
+        | KeyCode::NumpadSubtract => KeyLocation::Numpad,
+        _ => KeyLocation::Standard,
+    }
+}
+
+/// Returns the logical key for a keysym, which already accounts for the active modifiers.
+///
+/// `text` is the text produced by the key press, if any. It's used for the keysyms which can't be
+/// converted to a character directly.
+pub fn keysym_to_key(keysym: u32, text: Option<&str>) -> Key {
+    if let Some(key) = keysym_to_named_key(keysym) {
+        return key;
+    }
+
+    if let Some(chr) = keysym_to_char(keysym) {
+        return Key::Character(chr.to_string());
+    }
+
+    match text {
+        Some(text) if !text.is_empty() && !text.chars().any(char::is_control) => {
+            Key::Character(text.to_owned())
+        }
+        _ => Key::Unidentified,
+    }
+}
+
+/// Converts the keysyms which directly encode a character.
+fn keysym_to_char(keysym: u32) -> Option<char> {
+    match keysym {
+        // Latin-1 keysyms are equal to their code point.
+        0x0020..=0x007e | 0x00a0..=0x00ff => std::char::from_u32(keysym),
+        // Unicode keysyms are the code point with the `0x01000000` bit set.
+        0x0100_0100..=0x0110_ffff => std::char::from_u32(keysym - 0x0100_0000),
+        // Keypad.
+        0xffaa => Some('*'),
+        0xffab => Some('+'),
+        0xffac => Some(','),
+        0xffad => Some('-'),
+        0xffae => Some('.'),
+        0xffaf => Some('/'),
+        0xffb0..=0xffb9 => std::char::from_u32(keysym - 0xffb0 + '0' as u32),
+        0xffbd => Some('='),
+        _ => None,
+    }
+}
+
+fn keysym_to_named_key(keysym: u32) -> Option<Key> {
+    // See `X11/keysymdef.h` and `X11/XF86keysym.h`.
+    Some(match keysym {
+        0x0020 => Key::Space,
+
+        // TTY function keys.
+        0xff08 => Key::Backspace,
+        0xff09 => Key::Tab,
+        0xff0b => Key::Clear,
+        0xff0d => Key::Enter,
+        0xff13 => Key::Pause,
+        0xff14 => Key::ScrollLock,
+        0xff15 => Key::PrintScreen,
+        0xff1b => Key::Escape,
+        0xffff => Key::Delete,
+
+        // International and multi-key character composition.
+        0xff20 => Key::Compose,
+        0xff21 => Key::KanjiMode,
+        0xff22 => Key::NonConvert,
+        0xff23 => Key::Convert,
+        0xff24 => Key::Romaji,
+        0xff25 => Key::Hiragana,
+        0xff26 => Key::Katakana,
+        0xff27 => Key::HiraganaKatakana,
+        0xff28 => Key::Zenkaku,
+        0xff29 => Key::Hankaku,
+        0xff2a => Key::ZenkakuHankaku,
+        0xff2d | 0xff2e => Key::KanaMode,
+        0xff30 => Key::Eisu,
+        0xff31 => Key::HangulMode,
+        0xff34 => Key::HanjaMode,
+
+        // Cursor control and motion.
+        0xff50 | 0xff95 => Key::Home,
+        0xff51 | 0xff96 => Key::ArrowLeft,
+        0xff52 | 0xff97 => Key::ArrowUp,
+        0xff53 | 0xff98 => Key::ArrowRight,
+        0xff54 | 0xff99 => Key::ArrowDown,
+        0xff55 | 0xff9a => Key::PageUp,
+        0xff56 | 0xff9b => Key::PageDown,
+        0xff57 | 0xff9c => Key::End,
+
+        // Misc functions.
+        0xff60 => Key::Select,
+        0xff61 => Key::PrintScreen,
+        0xff62 => Key::Execute,
+        0xff63 | 0xff9e => Key::Insert,
+        0xff65 => Key::Undo,
+        0xff66 => Key::Redo,
+        0xff67 => Key::ContextMenu,
+        0xff68 => Key::Find,
+        0xff69 => Key::Cancel,
+        0xff6a => Key::Help,
+        0xff6b => Key::Pause,
+        0xff7e => Key::AltGraph,
+        0xff7f => Key::NumLock,
+
+        // Keypad functions, the keypad characters are handled by `keysym_to_char`.
+        0xff80 => Key::Space,
+        0xff89 => Key::Tab,
+        0xff8d => Key::Enter,
+        0xff91 => Key::F1,
+        0xff92 => Key::F2,
+        0xff93 => Key::F3,
+        0xff94 => Key::F4,
+        0xff9d => Key::Clear,
+        0xff9f => Key::Delete,
+
+        // Function keys.
+        0xffbe => Key::F1,
+        0xffbf => Key::F2,
+        0xffc0 => Key::F3,
+        0xffc1 => Key::F4,
+        0xffc2 => Key::F5,
+        0xffc3 => Key::F6,
+        0xffc4 => Key::F7,
+        0xffc5 => Key::F8,
+        0xffc6 => Key::F9,
+        0xffc7 => Key::F10,
+        0xffc8 => Key::F11,
+        0xffc9 => Key::F12,
+        0xffca => Key::F13,
+        0xffcb => Key::F14,
+        0xffcc => Key::F15,
+        0xffcd => Key::F16,
+        0xffce => Key::F17,
+        0xffcf => Key::F18,
+        0xffd0 => Key::F19,
+        0xffd1 => Key::F20,
+        0xffd2 => Key::F21,
+        0xffd3 => Key::F22,
+        0xffd4 => Key::F23,
+        0xffd5 => Key::F24,
+
+        // Modifiers.
+        0xffe1 | 0xffe2 => Key::Shift,
+        0xffe3 | 0xffe4 => Key::Control,
+        0xffe5 | 0xffe6 => Key::CapsLock,
+        0xffe7 | 0xffe8 => Key::Super,
+        0xffe9 | 0xffea => Key::Alt,
+        0xffeb..=0xffee => Key::Super,
+        0xfe03 => Key::AltGraph,
+        0xfe20 => Key::Tab,
+
+        // Dead keys.
+        0xfe50 => Key::Dead(Some('`')),
+        0xfe51 => Key::Dead(Some('´')),
+        0xfe52 => Key::Dead(Some('^')),
+        0xfe53 => Key::Dead(Some('~')),
+        0xfe54 => Key::Dead(Some('¯')),
+        0xfe55 => Key::Dead(Some('˘')),
+        0xfe56 => Key::Dead(Some('˙')),
+        0xfe57 => Key::Dead(Some('¨')),
+        0xfe58 => Key::Dead(Some('˚')),
+        0xfe59 => Key::Dead(Some('˝')),
+        0xfe5a => Key::Dead(Some('ˇ')),
+        0xfe5b => Key::Dead(Some('¸')),
+        0xfe5c => Key::Dead(Some('˛')),
+        0xfe5d..=0xfe8f => Key::Dead(None),
+
+        // Vendor specific keys.
+        0x1008_ff11 => Key::AudioVolumeDown,
+        0x1008_ff12 => Key::AudioVolumeMute,
+        0x1008_ff13 => Key::AudioVolumeUp,
+        0x1008_ff14 | 0x1008_ff31 => Key::MediaPlayPause,
+        0x1008_ff15 => Key::MediaStop,
+        0x1008_ff16 => Key::MediaTrackPrevious,
+        0x1008_ff17 => Key::MediaTrackNext,
+        0x1008_ff18 => Key::BrowserHome,
+        0x1008_ff19 => Key::LaunchMail,
+        0x1008_ff1b => Key::BrowserSearch,
+        0x1008_ff1d => Key::LaunchApplication2,
+        0x1008_ff26 => Key::BrowserBack,
+        0x1008_ff27 => Key::BrowserForward,
+        0x1008_ff28 => Key::BrowserStop,
+        0x1008_ff29 => Key::BrowserRefresh,
+        0x1008_ff2a => Key::Power,
+        0x1008_ff2b => Key::WakeUp,
+        0x1008_ff2c => Key::Eject,
+        0x1008_ff2f => Key::Sleep,
+        0x1008_ff30 => Key::BrowserFavorites,
+        0x1008_ff33 => Key::LaunchApplication1,
+        0x1008_ff57 => Key::Copy,
+        0x1008_ff58 => Key::Cut,
+        0x1008_ff6d => Key::Paste,
+
+        _ => return None,
+    })
+}
@@ -0,0 +1,5 @@
+//! Code shared by the X11 and Wayland backends.
+
+pub mod cursor;
+pub mod dnd;
+pub mod keymap;
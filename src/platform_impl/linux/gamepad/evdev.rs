@@ -0,0 +1,290 @@
+//! Reading of evdev device nodes.
+//!
+//! See `linux/input.h` and `linux/input-event-codes.h` for the definitions.
+
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::{self, Read};
+use std::mem;
+use std::os::raw::{c_int, c_long, c_ulong};
+use std::os::unix::io::AsRawFd;
+
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_ABS: u16 = 0x03;
+
+pub const SYN_REPORT: u16 = 0;
+pub const SYN_DROPPED: u16 = 3;
+
+pub const KEY_MAX: u16 = 0x2ff;
+pub const BTN_MISC: u16 = 0x100;
+pub const BTN_JOYSTICK: u16 = 0x120;
+pub const BTN_SOUTH: u16 = 0x130;
+pub const BTN_THUMBR: u16 = 0x13e;
+pub const BTN_DPAD_UP: u16 = 0x220;
+pub const BTN_TRIGGER_HAPPY1: u16 = 0x2c0;
+pub const BTN_TRIGGER_HAPPY40: u16 = 0x2e7;
+
+pub const ABS_X: u16 = 0x00;
+pub const ABS_Y: u16 = 0x01;
+pub const ABS_Z: u16 = 0x02;
+pub const ABS_RX: u16 = 0x03;
+pub const ABS_RY: u16 = 0x04;
+pub const ABS_RZ: u16 = 0x05;
+pub const ABS_HAT0X: u16 = 0x10;
+pub const ABS_HAT3Y: u16 = 0x17;
+pub const ABS_MAX: u16 = 0x3f;
+
+/// `struct input_event`, whose time is made of two `long`s on every architecture.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RawInputEvent {
+    sec: c_long,
+    usec: c_long,
+    type_: u16,
+    code: u16,
+    value: i32,
+}
+
+/// `struct input_id`.
+#[repr(C)]
+#[derive(Default)]
+struct InputId {
+    bustype: u16,
+    vendor: u16,
+    product: u16,
+    version: u16,
+}
+
+/// `struct input_absinfo`.
+#[repr(C)]
+#[derive(Default)]
+struct InputAbsInfo {
+    value: i32,
+    minimum: i32,
+    maximum: i32,
+    fuzz: i32,
+    flat: i32,
+    resolution: i32,
+}
+
+/// An event read from a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub type_: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+/// The range of an absolute axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbsInfo {
+    pub minimum: i32,
+    pub maximum: i32,
+}
+
+impl AbsInfo {
+    /// Scales a value of the axis to `-1.0..=1.0`.
+    pub fn normalize(&self, value: i32) -> f64 {
+        if self.maximum <= self.minimum {
+            return 0.0;
+        }
+        let position =
+            (value as f64 - self.minimum as f64) / (self.maximum as f64 - self.minimum as f64);
+        if position <= 0.0 {
+            -1.0
+        } else if position >= 1.0 {
+            1.0
+        } else {
+            position * 2.0 - 1.0
+        }
+    }
+}
+
+/// What a device is, and which buttons and axes it has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    pub name: String,
+    pub bus: u16,
+    pub vendor: u16,
+    pub product: u16,
+    pub version: u16,
+
+    /// Codes of the buttons, in ascending order.
+    pub keys: Vec<u16>,
+
+    /// Codes of the absolute axes with their range, in ascending order.
+    pub axes: Vec<(u16, AbsInfo)>,
+}
+
+impl Capabilities {
+    /// Queries the capabilities of an opened device node.
+    pub fn query(device: &File) -> io::Result<Self> {
+        let fd = device.as_raw_fd();
+
+        let mut name = [0u8; 256];
+        ioctl(fd, ioc_read(0x06, name.len()), name.as_mut_ptr())?;
+        let name = CStr::from_bytes_with_nul(&name[..=name.iter().position(|&b| b == 0).unwrap()])
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+
+        let mut id = InputId::default();
+        ioctl(fd, ioc_read(0x02, mem::size_of::<InputId>()), &mut id)?;
+
+        let mut key_bits = [0u8; KEY_MAX as usize / 8 + 1];
+        ioctl(
+            fd,
+            ioc_read(0x20 + EV_KEY as c_ulong, key_bits.len()),
+            key_bits.as_mut_ptr(),
+        )?;
+        let mut abs_bits = [0u8; ABS_MAX as usize / 8 + 1];
+        ioctl(
+            fd,
+            ioc_read(0x20 + EV_ABS as c_ulong, abs_bits.len()),
+            abs_bits.as_mut_ptr(),
+        )?;
+
+        let keys = (0..=KEY_MAX)
+            .filter(|&code| test_bit(&key_bits, code))
+            .collect();
+        let mut axes = Vec::new();
+        for code in (0..=ABS_MAX).filter(|&code| test_bit(&abs_bits, code)) {
+            let mut info = InputAbsInfo::default();
+            ioctl(
+                fd,
+                ioc_read(0x40 + code as c_ulong, mem::size_of::<InputAbsInfo>()),
+                &mut info,
+            )?;
+            axes.push((
+                code,
+                AbsInfo {
+                    minimum: info.minimum,
+                    maximum: info.maximum,
+                },
+            ));
+        }
+
+        Ok(Capabilities {
+            name,
+            bus: id.bustype,
+            vendor: id.vendor,
+            product: id.product,
+            version: id.version,
+            keys,
+            axes,
+        })
+    }
+
+    /// Whether the device has the buttons of a joystick or a gamepad.
+    ///
+    /// Mice, touchpads and tablets use other buttons, so they're left out.
+    pub fn is_joystick(&self) -> bool {
+        self.keys.iter().any(|&code| {
+            (BTN_JOYSTICK..=BTN_THUMBR).contains(&code)
+                || (BTN_TRIGGER_HAPPY1..=BTN_TRIGGER_HAPPY40).contains(&code)
+        })
+    }
+
+    #[inline]
+    pub fn has_key(&self, code: u16) -> bool {
+        self.keys.binary_search(&code).is_ok()
+    }
+
+    pub fn axis(&self, code: u16) -> Option<AbsInfo> {
+        self.axes
+            .iter()
+            .find(|(axis, _)| *axis == code)
+            .map(|(_, info)| *info)
+    }
+}
+
+/// Reads the state of every button and axis of a device, as events setting them to their current
+/// value.
+///
+/// This is how the state is caught up with once the kernel dropped events.
+pub fn query_state(device: &File, capabilities: &Capabilities) -> io::Result<Vec<InputEvent>> {
+    let fd = device.as_raw_fd();
+
+    // `EVIOCGKEY`
+    let mut key_bits = [0u8; KEY_MAX as usize / 8 + 1];
+    ioctl(fd, ioc_read(0x18, key_bits.len()), key_bits.as_mut_ptr())?;
+    let mut events: Vec<InputEvent> = capabilities
+        .keys
+        .iter()
+        .map(|&code| InputEvent {
+            type_: EV_KEY,
+            code,
+            value: test_bit(&key_bits, code) as i32,
+        })
+        .collect();
+
+    // `EVIOCGABS`
+    for &(code, _) in &capabilities.axes {
+        let mut info = InputAbsInfo::default();
+        ioctl(
+            fd,
+            ioc_read(0x40 + code as c_ulong, mem::size_of::<InputAbsInfo>()),
+            &mut info,
+        )?;
+        events.push(InputEvent {
+            type_: EV_ABS,
+            code,
+            value: info.value,
+        });
+    }
+
+    Ok(events)
+}
+
+/// Reads the events available on a non-blocking device.
+///
+/// `buffer` keeps the bytes of an incomplete event between calls. Returns `false` once the
+/// device is gone.
+pub fn read_events(device: &mut File, buffer: &mut Vec<u8>, events: &mut Vec<InputEvent>) -> bool {
+    const EVENT_SIZE: usize = mem::size_of::<RawInputEvent>();
+
+    let mut chunk = [0u8; EVENT_SIZE * 64];
+    let alive = loop {
+        match device.read(&mut chunk) {
+            Ok(0) => break false,
+            Ok(len) => buffer.extend_from_slice(&chunk[..len]),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break true,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+            // `ENODEV` once the device is unplugged.
+            Err(_) => break false,
+        }
+    };
+
+    let complete = buffer.len() / EVENT_SIZE * EVENT_SIZE;
+    for bytes in buffer[..complete].chunks(EVENT_SIZE) {
+        let raw = unsafe { (bytes.as_ptr() as *const RawInputEvent).read_unaligned() };
+        events.push(InputEvent {
+            type_: raw.type_,
+            code: raw.code,
+            value: raw.value,
+        });
+    }
+    buffer.drain(..complete);
+
+    alive
+}
+
+#[inline]
+fn test_bit(bits: &[u8], code: u16) -> bool {
+    bits[code as usize / 8] & (1 << (code % 8)) != 0
+}
+
+/// `_IOC(_IOC_READ, 'E', number, size)`.
+#[inline]
+fn ioc_read(number: c_ulong, size: usize) -> c_ulong {
+    (2 << 30) | ((size as c_ulong) << 16) | ((b'E' as c_ulong) << 8) | number
+}
+
+fn ioctl<T>(fd: c_int, request: c_ulong, data: *mut T) -> io::Result<()> {
+    if unsafe { libc::ioctl(fd, request as _, data) } < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
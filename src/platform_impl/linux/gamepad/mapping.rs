@@ -0,0 +1,370 @@
+//! Mapping of joysticks to the standard gamepad layout.
+//!
+//! Mappings are read from SDL's game controller database format, in which a line looks like
+//! `GUID,name,a:b0,leftx:a0,dpup:h0.1,...,platform:Linux,`. The buttons, axes and hats on the
+//! right are numbered the way SDL numbers them on Linux, so that its database can be used as is.
+
+use super::evdev::{
+    AbsInfo, Capabilities, InputEvent, ABS_HAT0X, ABS_HAT3Y, ABS_MAX, ABS_RX, ABS_RY, ABS_RZ,
+    ABS_X, ABS_Y, ABS_Z, BTN_DPAD_UP, BTN_JOYSTICK, BTN_MISC, BTN_SOUTH, EV_ABS, EV_KEY, KEY_MAX,
+};
+use crate::gamepad::{GamepadAxis, GamepadButton};
+
+/// Environment variable holding extra mappings, one per line, as used by SDL.
+pub const MAPPINGS_ENV_VAR: &str = "SDL_GAMECONTROLLERCONFIG";
+
+/// Mappings of common gamepads whose kernel drivers don't follow the standard layout.
+const BUILTIN_MAPPINGS: &[&str] = &[
+    "030000005e0400008e02000010010000,Xbox 360 Controller,a:b0,b:b1,back:b6,dpdown:h0.4,\
+     dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b8,leftshoulder:b4,leftstick:b9,lefttrigger:a2,\
+     leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b10,righttrigger:a5,rightx:a3,righty:a4,\
+     start:b7,x:b2,y:b3,platform:Linux,",
+    "030000004c050000c405000011810000,PS4 Controller,a:b0,b:b1,back:b8,dpdown:h0.4,\
+     dpleft:h0.8,dpright:h0.2,dpup:h0.1,guide:b10,leftshoulder:b4,leftstick:b11,\
+     lefttrigger:a2,leftx:a0,lefty:a1,rightshoulder:b5,rightstick:b12,righttrigger:a5,\
+     rightx:a3,righty:a4,start:b9,x:b3,y:b2,platform:Linux,",
+];
+
+/// A button or axis of the standard layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Button(GamepadButton),
+    Axis(GamepadAxis),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AxisRange {
+    Full,
+    Positive,
+    Negative,
+}
+
+/// A button, axis or hat direction of the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Input {
+    Button(u16),
+    Axis {
+        code: u16,
+        range: AxisRange,
+        inverted: bool,
+    },
+    Hat {
+        code: u16,
+        direction: i32,
+    },
+}
+
+/// How the input of a device maps to the standard layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    bindings: Vec<(Input, Output)>,
+}
+
+impl Mapping {
+    /// Finds the mapping of a device, from the `extra` lines of SDL's database, the built-in
+    /// mappings, or the layout the kernel gives to gamepads following its conventions.
+    pub fn find(capabilities: &Capabilities, extra: &str) -> Option<Self> {
+        let guid = guid(capabilities);
+        // The same GUID may be listed once per platform, with different numbering.
+        let entries: Vec<&str> = extra
+            .lines()
+            .chain(BUILTIN_MAPPINGS.iter().copied())
+            .filter(|entry| is_linux_entry(entry))
+            .collect();
+
+        // The version of the device is often left out of the mappings, so look again without it.
+        let exact = entries.iter().find(|entry| entry_guid(entry) == Some(guid));
+        let loose = || {
+            entries
+                .iter()
+                .find(|entry| entry_guid(entry).map(without_version) == Some(without_version(guid)))
+        };
+        exact
+            .or_else(loose)
+            .and_then(|entry| Self::from_sdl(entry, capabilities))
+            .or_else(|| Self::standard(capabilities))
+    }
+
+    /// Parses a line of SDL's database.
+    fn from_sdl(entry: &str, capabilities: &Capabilities) -> Option<Self> {
+        let fields = entry.split(',').skip(2);
+
+        // SDL numbers the buttons from `BTN_JOYSTICK`, followed by the ones below it, and skips
+        // the hats when numbering the axes.
+        let buttons: Vec<u16> = (BTN_JOYSTICK..=KEY_MAX)
+            .chain(BTN_MISC..BTN_JOYSTICK)
+            .filter(|&code| capabilities.has_key(code))
+            .collect();
+        let axes: Vec<u16> = (0..=ABS_MAX)
+            .filter(|&code| !(ABS_HAT0X..=ABS_HAT3Y).contains(&code))
+            .filter(|&code| capabilities.axis(code).is_some())
+            .collect();
+
+        let mut bindings = Vec::new();
+        for field in fields {
+            let mut parts = field.trim().splitn(2, ':');
+            let (output, input) = match (parts.next(), parts.next()) {
+                (Some(output), Some(input)) => (output, input),
+                _ => continue,
+            };
+            let output = match sdl_output(output) {
+                Some(output) => output,
+                None => continue,
+            };
+            if let Some(input) = sdl_input(input, &buttons, &axes) {
+                bindings.push((input, output));
+            }
+        }
+
+        if bindings.is_empty() {
+            None
+        } else {
+            Some(Mapping { bindings })
+        }
+    }
+
+    /// The layout of drivers following the kernel's gamepad conventions, which report
+    /// `BTN_SOUTH`.
+    ///
+    /// See `Documentation/input/gamepad.rst` in the kernel.
+    fn standard(capabilities: &Capabilities) -> Option<Self> {
+        if !capabilities.has_key(BTN_SOUTH) {
+            return None;
+        }
+
+        let mut bindings = Vec::new();
+        for &code in &capabilities.keys {
+            if let Some(button) = GamepadButton::from_button_id(code as u32) {
+                let output = match button {
+                    GamepadButton::LeftTrigger => Output::Axis(GamepadAxis::LeftTrigger),
+                    GamepadButton::RightTrigger => Output::Axis(GamepadAxis::RightTrigger),
+                    _ => Output::Button(button),
+                };
+                bindings.push((Input::Button(code), output));
+            }
+        }
+
+        let axes = [
+            (ABS_X, GamepadAxis::LeftStickX),
+            (ABS_Y, GamepadAxis::LeftStickY),
+            (ABS_RX, GamepadAxis::RightStickX),
+            (ABS_RY, GamepadAxis::RightStickY),
+            (ABS_Z, GamepadAxis::LeftTrigger),
+            (ABS_RZ, GamepadAxis::RightTrigger),
+        ];
+        for &(code, axis) in &axes {
+            if capabilities.axis(code).is_some() {
+                // Analog triggers replace the digital ones.
+                bindings.retain(|(_, output)| *output != Output::Axis(axis));
+                bindings.push((
+                    Input::Axis {
+                        code,
+                        range: AxisRange::Full,
+                        inverted: false,
+                    },
+                    Output::Axis(axis),
+                ));
+            }
+        }
+
+        if !capabilities.has_key(BTN_DPAD_UP) && capabilities.axis(ABS_HAT0X).is_some() {
+            let hats = [
+                (ABS_HAT0X + 1, -1, GamepadButton::DPadUp),
+                (ABS_HAT0X + 1, 1, GamepadButton::DPadDown),
+                (ABS_HAT0X, -1, GamepadButton::DPadLeft),
+                (ABS_HAT0X, 1, GamepadButton::DPadRight),
+            ];
+            for &(code, direction, button) in &hats {
+                bindings.push((Input::Hat { code, direction }, Output::Button(button)));
+            }
+        }
+
+        Some(Mapping { bindings })
+    }
+
+    /// Calls `output` with the value of each output changed by the event.
+    ///
+    /// Buttons have a value of 1 while pressed and 0 otherwise, sticks move from -1 to 1, and
+    /// triggers from 0 to 1.
+    pub fn translate(
+        &self,
+        event: InputEvent,
+        capabilities: &Capabilities,
+        mut output: impl FnMut(Output, f64),
+    ) {
+        for &(input, bound_output) in &self.bindings {
+            let value = match input {
+                Input::Button(code) if event.type_ == EV_KEY && event.code == code => {
+                    if event.value != 0 {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                Input::Axis {
+                    code,
+                    range,
+                    inverted,
+                } if event.type_ == EV_ABS && event.code == code => {
+                    let info = match capabilities.axis(code) {
+                        Some(info) => info,
+                        None => continue,
+                    };
+                    axis_value(info, event.value, range, inverted, bound_output)
+                }
+                Input::Hat { code, direction } if event.type_ == EV_ABS && event.code == code => {
+                    if event.value.signum() == direction {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                _ => continue,
+            };
+            output(bound_output, value);
+        }
+    }
+}
+
+fn axis_value(info: AbsInfo, value: i32, range: AxisRange, inverted: bool, output: Output) -> f64 {
+    let mut value = info.normalize(value);
+    if inverted {
+        value = -value;
+    }
+    match (range, output) {
+        (AxisRange::Positive, _) => value.max(0.0),
+        (AxisRange::Negative, _) => (-value).max(0.0),
+        (AxisRange::Full, Output::Axis(axis)) if axis.is_trigger() => (value + 1.0) / 2.0,
+        (AxisRange::Full, Output::Axis(_)) => value,
+        (AxisRange::Full, Output::Button(_)) => {
+            if value > 0.5 {
+                1.0
+            } else {
+                0.0
+            }
+        }
+    }
+}
+
+fn sdl_output(name: &str) -> Option<Output> {
+    let output = match name {
+        "a" => Output::Button(GamepadButton::South),
+        "b" => Output::Button(GamepadButton::East),
+        "x" => Output::Button(GamepadButton::West),
+        "y" => Output::Button(GamepadButton::North),
+        "leftshoulder" => Output::Button(GamepadButton::LeftShoulder),
+        "rightshoulder" => Output::Button(GamepadButton::RightShoulder),
+        "back" => Output::Button(GamepadButton::Select),
+        "start" => Output::Button(GamepadButton::Start),
+        "guide" => Output::Button(GamepadButton::Mode),
+        "leftstick" => Output::Button(GamepadButton::LeftThumb),
+        "rightstick" => Output::Button(GamepadButton::RightThumb),
+        "dpup" => Output::Button(GamepadButton::DPadUp),
+        "dpdown" => Output::Button(GamepadButton::DPadDown),
+        "dpleft" => Output::Button(GamepadButton::DPadLeft),
+        "dpright" => Output::Button(GamepadButton::DPadRight),
+        "leftx" => Output::Axis(GamepadAxis::LeftStickX),
+        "lefty" => Output::Axis(GamepadAxis::LeftStickY),
+        "rightx" => Output::Axis(GamepadAxis::RightStickX),
+        "righty" => Output::Axis(GamepadAxis::RightStickY),
+        "lefttrigger" => Output::Axis(GamepadAxis::LeftTrigger),
+        "righttrigger" => Output::Axis(GamepadAxis::RightTrigger),
+        _ => return None,
+    };
+    Some(output)
+}
+
+/// Parses `bN`, `aN` with an optional `+` or `-` prefix and `~` suffix, or `hN.M`.
+fn sdl_input(input: &str, buttons: &[u16], axes: &[u16]) -> Option<Input> {
+    let (range, input) = match input.as_bytes().first()? {
+        b'+' => (AxisRange::Positive, &input[1..]),
+        b'-' => (AxisRange::Negative, &input[1..]),
+        _ => (AxisRange::Full, input),
+    };
+    let (inverted, input) = match input.strip_suffix('~') {
+        Some(input) => (true, input),
+        None => (false, input),
+    };
+
+    match input.as_bytes().first()? {
+        b'b' => {
+            let index: usize = input[1..].parse().ok()?;
+            buttons.get(index).map(|&code| Input::Button(code))
+        }
+        b'a' => {
+            let index: usize = input[1..].parse().ok()?;
+            axes.get(index).map(|&code| Input::Axis {
+                code,
+                range,
+                inverted,
+            })
+        }
+        b'h' => {
+            let mut parts = input[1..].splitn(2, '.');
+            let hat: u16 = parts.next()?.parse().ok()?;
+            let mask: u8 = parts.next()?.parse().ok()?;
+            if hat > 3 {
+                return None;
+            }
+            let x = ABS_HAT0X + hat * 2;
+            let (code, direction) = match mask {
+                1 => (x + 1, -1),
+                2 => (x, 1),
+                4 => (x + 1, 1),
+                8 => (x, -1),
+                _ => return None,
+            };
+            Some(Input::Hat { code, direction })
+        }
+        _ => None,
+    }
+}
+
+/// The GUID of the device in SDL's format.
+fn guid(capabilities: &Capabilities) -> [u8; 16] {
+    let mut guid = [0; 16];
+    for (index, &value) in [
+        capabilities.bus,
+        capabilities.vendor,
+        capabilities.product,
+        capabilities.version,
+    ]
+    .iter()
+    .enumerate()
+    {
+        guid[index * 4..index * 4 + 2].copy_from_slice(&value.to_le_bytes());
+    }
+    guid
+}
+
+/// Whether an entry is meant for Linux, which is assumed for entries without a platform.
+fn is_linux_entry(entry: &str) -> bool {
+    entry
+        .split(',')
+        .skip(2)
+        .map(str::trim)
+        .filter(|field| field.starts_with("platform:"))
+        .all(|field| field == "platform:Linux")
+}
+
+/// The GUID of an entry, without the checksum of newer versions of SDL.
+fn entry_guid(entry: &str) -> Option<[u8; 16]> {
+    let hex = entry.split(',').next()?.trim();
+    if hex.len() != 32 || hex.starts_with('#') {
+        return None;
+    }
+    let mut guid = [0; 16];
+    for (index, byte) in guid.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(index * 2..index * 2 + 2)?, 16).ok()?;
+    }
+    guid[2] = 0;
+    guid[3] = 0;
+    Some(guid)
+}
+
+#[inline]
+fn without_version(mut guid: [u8; 16]) -> [u8; 16] {
+    guid[12] = 0;
+    guid[13] = 0;
+    guid
+}
@@ -0,0 +1,429 @@
+//! Gamepads and joysticks, read from their evdev nodes in `/dev/input`.
+//!
+//! The devices and the hotplug notifications are polled through a single epoll file descriptor,
+//! which the event loops of every backend wait on.
+
+mod evdev;
+mod mapping;
+
+use std::collections::VecDeque;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::mem;
+use std::os::raw::c_int;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+#[cfg(any(feature = "x11", feature = "wayland"))]
+use std::{ffi::CString, fs, os::unix::io::FromRawFd};
+
+#[cfg(feature = "mock")]
+pub use self::evdev::AbsInfo;
+pub use self::evdev::Capabilities;
+
+use self::evdev::{InputEvent, EV_ABS, EV_KEY, EV_SYN, SYN_DROPPED, SYN_REPORT};
+use self::mapping::{Mapping, Output, MAPPINGS_ENV_VAR};
+use crate::event::{AxisId, ButtonId, DeviceEvent, ElementState};
+use crate::gamepad::{GamepadAxis, GamepadButton, GamepadInfo};
+
+const DEVICE_DIR: &str = "/dev/input";
+
+/// Epoll token of the hotplug notifications, the devices using their id.
+const INOTIFY_TOKEN: u64 = 0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeviceId(u32);
+
+struct Device {
+    id: DeviceId,
+    file: File,
+    path: Option<PathBuf>,
+    capabilities: Capabilities,
+    mapping: Option<Mapping>,
+
+    /// Bytes of an event which wasn't read completely.
+    buffer: Vec<u8>,
+
+    /// Whether the kernel dropped events, in which case we skip the rest of the report and read
+    /// the state of the device again.
+    dropped: bool,
+
+    pressed_buttons: Vec<ButtonId>,
+    axis_values: Vec<(AxisId, f64)>,
+}
+
+impl Device {
+    fn process_event(&mut self, event: InputEvent, events: &mut VecDeque<(DeviceId, DeviceEvent)>) {
+        if event.type_ == EV_SYN {
+            match event.code {
+                SYN_DROPPED => self.dropped = true,
+                SYN_REPORT if self.dropped => {
+                    self.dropped = false;
+                    self.resync(events);
+                }
+                _ => (),
+            }
+            return;
+        } else if self.dropped {
+            return;
+        }
+
+        let mut state = DeviceState {
+            id: self.id,
+            pressed_buttons: &mut self.pressed_buttons,
+            axis_values: &mut self.axis_values,
+            events,
+        };
+        match self.mapping {
+            Some(ref mapping) => {
+                mapping.translate(event, &self.capabilities, |output, value| match output {
+                    Output::Button(button) => state.set_button(button.button_id(), value > 0.5),
+                    Output::Axis(axis) => {
+                        state.set_axis(axis.axis_id(), value);
+                        // Triggers are buttons as well, pressed once they're pulled halfway.
+                        let button = match axis {
+                            GamepadAxis::LeftTrigger => GamepadButton::LeftTrigger,
+                            GamepadAxis::RightTrigger => GamepadButton::RightTrigger,
+                            _ => return,
+                        };
+                        state.set_button(button.button_id(), value > 0.5);
+                    }
+                })
+            }
+            None => match event.type_ {
+                EV_KEY => state.set_button(event.code as ButtonId, event.value != 0),
+                EV_ABS => {
+                    if let Some(info) = self.capabilities.axis(event.code) {
+                        state.set_axis(event.code as AxisId, info.normalize(event.value));
+                    }
+                }
+                _ => (),
+            },
+        }
+    }
+
+    /// Reports the changes the kernel dropped, by comparing the state of the device with ours.
+    fn resync(&mut self, events: &mut VecDeque<(DeviceId, DeviceEvent)>) {
+        match evdev::query_state(&self.file, &self.capabilities) {
+            Ok(state) => {
+                for event in state {
+                    self.process_event(event, events);
+                }
+            }
+            Err(err) => warn!("Failed to read the state of a gamepad: {}", err),
+        }
+    }
+}
+
+/// The state of a device's outputs, which only reports changes.
+struct DeviceState<'a> {
+    id: DeviceId,
+    pressed_buttons: &'a mut Vec<ButtonId>,
+    axis_values: &'a mut Vec<(AxisId, f64)>,
+    events: &'a mut VecDeque<(DeviceId, DeviceEvent)>,
+}
+
+impl DeviceState<'_> {
+    fn set_button(&mut self, button: ButtonId, pressed: bool) {
+        let index = self.pressed_buttons.iter().position(|&id| id == button);
+        let state = match (index, pressed) {
+            (None, true) => {
+                self.pressed_buttons.push(button);
+                ElementState::Pressed
+            }
+            (Some(index), false) => {
+                self.pressed_buttons.swap_remove(index);
+                ElementState::Released
+            }
+            _ => return,
+        };
+        self.events
+            .push_back((self.id, DeviceEvent::Button { button, state }));
+    }
+
+    fn set_axis(&mut self, axis: AxisId, value: f64) {
+        match self.axis_values.iter_mut().find(|(id, _)| *id == axis) {
+            Some((_, old_value)) if *old_value == value => return,
+            Some((_, old_value)) => *old_value = value,
+            None => self.axis_values.push((axis, value)),
+        }
+        self.events
+            .push_back((self.id, DeviceEvent::Motion { axis, value }));
+    }
+}
+
+/// The gamepads and joysticks of an event loop.
+pub struct Gamepads {
+    epoll: RawFd,
+    inotify: Option<File>,
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    watching: bool,
+    mappings: String,
+    devices: Vec<Device>,
+    next_id: u32,
+    events: VecDeque<(DeviceId, DeviceEvent)>,
+}
+
+impl Gamepads {
+    /// Creates an empty set of devices, to which they're added with `add_device` or
+    /// `watch_devices`.
+    pub fn new() -> io::Result<Self> {
+        let epoll = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+        if epoll < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Gamepads {
+            epoll,
+            inotify: None,
+            #[cfg(any(feature = "x11", feature = "wayland"))]
+            watching: false,
+            mappings: env::var(MAPPINGS_ENV_VAR).unwrap_or_default(),
+            devices: Vec::new(),
+            next_id: 0,
+            events: VecDeque::new(),
+        })
+    }
+
+    /// Opens the gamepads which are plugged in, and the ones plugged in later on.
+    ///
+    /// Does nothing if the devices are watched already.
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    pub fn watch_devices(&mut self) {
+        if self.watching {
+            return;
+        }
+        self.watching = true;
+
+        if let Err(err) = self.watch_device_dir() {
+            warn!("Failed to watch {} for gamepads: {}", DEVICE_DIR, err);
+        }
+
+        let mut paths: Vec<PathBuf> = match fs::read_dir(DEVICE_DIR) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .collect(),
+            Err(_) => return,
+        };
+        paths.sort();
+        for path in paths {
+            self.open_device(path);
+        }
+    }
+
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    fn watch_device_dir(&mut self) -> io::Result<()> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let inotify = unsafe { File::from_raw_fd(fd) };
+
+        // Device nodes are usually readable once udev changes their permissions.
+        let dir = CString::new(DEVICE_DIR).unwrap();
+        let mask = libc::IN_CREATE | libc::IN_ATTRIB;
+        if unsafe { libc::inotify_add_watch(fd, dir.as_ptr(), mask) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        self.register(fd, INOTIFY_TOKEN)?;
+        self.inotify = Some(inotify);
+        Ok(())
+    }
+
+    fn open_device(&mut self, path: PathBuf) {
+        let is_event_node = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|name| name.starts_with("event"))
+            .is_some();
+        if !is_event_node
+            || self
+                .devices
+                .iter()
+                .any(|device| device.path.as_ref() == Some(&path))
+        {
+            return;
+        }
+
+        // Nodes we can't read yet are tried again once their permissions change.
+        let file = match OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK | libc::O_CLOEXEC)
+            .open(&path)
+        {
+            Ok(file) => file,
+            Err(_) => return,
+        };
+        let capabilities = match Capabilities::query(&file) {
+            Ok(capabilities) if capabilities.is_joystick() => capabilities,
+            _ => return,
+        };
+        if let Err(err) = self.add_device(file, capabilities, Some(path)) {
+            warn!("Failed to add gamepad: {}", err);
+        }
+    }
+
+    /// Adds an opened device, reporting it with `DeviceEvent::Added`.
+    ///
+    /// `file` is read without blocking, as a stream of `struct input_event`.
+    pub fn add_device(
+        &mut self,
+        file: File,
+        capabilities: Capabilities,
+        path: Option<PathBuf>,
+    ) -> io::Result<DeviceId> {
+        let fd = file.as_raw_fd();
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let id = DeviceId(self.next_id + 1);
+        self.register(fd, id.0 as u64)?;
+        self.next_id += 1;
+
+        let mapping = Mapping::find(&capabilities, &self.mappings);
+        self.devices.push(Device {
+            id,
+            file,
+            path,
+            capabilities,
+            mapping,
+            buffer: Vec::new(),
+            dropped: false,
+            pressed_buttons: Vec::new(),
+            axis_values: Vec::new(),
+        });
+        self.events.push_back((id, DeviceEvent::Added));
+        Ok(id)
+    }
+
+    fn register(&self, fd: RawFd, token: u64) -> io::Result<()> {
+        let mut event = libc::epoll_event {
+            events: libc::EPOLLIN as u32,
+            u64: token,
+        };
+        if unsafe { libc::epoll_ctl(self.epoll, libc::EPOLL_CTL_ADD, fd, &mut event) } < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Adds lines of SDL's database, which are used to map the devices added afterwards.
+    #[cfg(feature = "mock")]
+    pub fn add_mappings(&mut self, mappings: &str) {
+        self.mappings.push('\n');
+        self.mappings.push_str(mappings);
+    }
+
+    /// The file descriptor which is readable when there are events to dispatch.
+    #[cfg(any(feature = "x11", feature = "wayland"))]
+    #[inline]
+    pub fn fd(&self) -> RawFd {
+        self.epoll
+    }
+
+    pub fn info(&self, id: DeviceId) -> Option<GamepadInfo> {
+        let device = self.devices.iter().find(|device| device.id == id)?;
+        Some(GamepadInfo {
+            name: device.capabilities.name.clone(),
+            vendor_id: device.capabilities.vendor,
+            product_id: device.capabilities.product,
+            is_mapped: device.mapping.is_some(),
+        })
+    }
+
+    /// Whether events were queued without the file descriptor becoming readable, as happens to
+    /// the devices opened by `watch_devices`.
+    #[cfg(feature = "x11")]
+    #[inline]
+    pub fn has_events(&self) -> bool {
+        !self.events.is_empty()
+    }
+
+    /// Reads the pending input and hotplug notifications, and calls `callback` with the events.
+    pub fn dispatch(&mut self, mut callback: impl FnMut(DeviceId, DeviceEvent)) {
+        let mut ready = [libc::epoll_event { events: 0, u64: 0 }; 16];
+        loop {
+            let count = unsafe {
+                libc::epoll_wait(self.epoll, ready.as_mut_ptr(), ready.len() as c_int, 0)
+            };
+            if count <= 0 {
+                break;
+            }
+
+            for event in &ready[..count as usize] {
+                match event.u64 {
+                    INOTIFY_TOKEN => self.read_device_dir(),
+                    token => self.read_device(DeviceId(token as u32)),
+                }
+            }
+            if (count as usize) < ready.len() {
+                break;
+            }
+        }
+
+        while let Some((id, event)) = self.events.pop_front() {
+            callback(id, event);
+        }
+    }
+
+    fn read_device(&mut self, id: DeviceId) {
+        let index = match self.devices.iter().position(|device| device.id == id) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let device = &mut self.devices[index];
+        let mut input_events = Vec::new();
+        let alive = evdev::read_events(&mut device.file, &mut device.buffer, &mut input_events);
+        for input_event in input_events {
+            device.process_event(input_event, &mut self.events);
+        }
+
+        // Closing the device also removes it from the epoll.
+        if !alive {
+            self.devices.remove(index);
+            self.events.push_back((id, DeviceEvent::Removed));
+        }
+    }
+
+    fn read_device_dir(&mut self) {
+        const HEADER_SIZE: usize = mem::size_of::<libc::inotify_event>();
+
+        let mut buffer = [0u8; 4096];
+        let mut names = Vec::new();
+        while let Some(Ok(len)) = self
+            .inotify
+            .as_mut()
+            .map(|inotify| inotify.read(&mut buffer))
+        {
+            // Each event is followed by its null-padded name.
+            let mut offset = 0;
+            while offset + HEADER_SIZE <= len {
+                let event = unsafe {
+                    (buffer[offset..].as_ptr() as *const libc::inotify_event).read_unaligned()
+                };
+                let name = &buffer[offset + HEADER_SIZE..offset + HEADER_SIZE + event.len as usize];
+                let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+                names.push(String::from_utf8_lossy(name).into_owned());
+                offset += HEADER_SIZE + event.len as usize;
+            }
+        }
+
+        for name in names {
+            self.open_device(Path::new(DEVICE_DIR).join(name));
+        }
+    }
+}
+
+impl Drop for Gamepads {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.epoll);
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::mem;
+use std::process;
+use std::sync::mpsc::{self, Receiver, SendError, Sender};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::clipboard::ClipboardKind;
+use crate::error::ExternalError;
+use crate::event::{Event, StartCause, WindowEvent};
+use crate::event_loop::{
+    ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootEventLoopWindowTarget,
+};
+#[cfg(target_os = "linux")]
+use crate::gamepad::GamepadInfo;
+use crate::monitor::MonitorHandle as RootMonitorHandle;
+#[cfg(target_os = "linux")]
+use crate::platform_impl::platform::gamepad;
+use crate::platform_impl::platform::sticky_exit_callback;
+use crate::platform_impl::MonitorHandle as PlatformMonitorHandle;
+use crate::window::CustomCursorSource;
+
+use super::state::{PendingEvent, Shared, TEXT_MIME_TYPE};
+use super::{make_wid, CustomCursor, MonitorHandle};
+
+pub struct EventLoopWindowTarget<T> {
+    /// State shared with windows, proxies and controllers.
+    pub(crate) shared: Arc<Shared>,
+
+    _marker: PhantomData<T>,
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    #[inline]
+    pub fn shared(&self) -> &Arc<Shared> {
+        &self.shared
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
+        self.shared.available_monitors()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[inline]
+    pub fn gamepad_info(&self, device_id: gamepad::DeviceId) -> Option<GamepadInfo> {
+        self.shared.lock().gamepads.info(device_id)
+    }
+
+    /// The gamepads of a mock event loop are only the ones of `MockController::connect_gamepad`.
+    #[cfg(target_os = "linux")]
+    #[inline]
+    pub fn watch_gamepads(&self) {}
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<RootMonitorHandle> {
+        self.shared
+            .primary_monitor()
+            .map(|monitor| RootMonitorHandle {
+                inner: PlatformMonitorHandle::Mock(monitor),
+            })
+    }
+
+    #[inline]
+    pub fn clipboard_text(&self, kind: ClipboardKind) -> Result<Option<String>, ExternalError> {
+        let data = self.shared.clipboard_data(kind, TEXT_MIME_TYPE);
+        Ok(data.map(|data| String::from_utf8_lossy(&data).into_owned()))
+    }
+
+    #[inline]
+    pub fn set_clipboard_text(
+        &self,
+        kind: ClipboardKind,
+        text: String,
+    ) -> Result<(), ExternalError> {
+        self.set_clipboard_data(kind, vec![(TEXT_MIME_TYPE.to_owned(), text.into_bytes())])
+    }
+
+    #[inline]
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Result<Vec<String>, ExternalError> {
+        let state = self.shared.lock();
+        let data = state.clipboards.get(&kind).into_iter().flatten();
+        Ok(data.map(|(mime_type, _)| mime_type.clone()).collect())
+    }
+
+    #[inline]
+    pub fn clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        Ok(self.shared.clipboard_data(kind, mime_type))
+    }
+
+    #[inline]
+    pub fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        self.shared.lock().clipboards.insert(kind, data);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        source: CustomCursorSource,
+    ) -> Result<CustomCursor, ExternalError> {
+        Ok(CustomCursor(Arc::new(source)))
+    }
+}
+
+pub struct EventLoop<T: 'static> {
+    /// State shared with windows, proxies and controllers.
+    shared: Arc<Shared>,
+
+    /// Sender of user events.
+    user_events_sender: Sender<T>,
+
+    /// Pending user events.
+    user_events_channel: Receiver<T>,
+
+    /// Whether `run_step` has already sent `StartCause::Init`.
+    stepped: bool,
+
+    /// Window target.
+    window_target: RootEventLoopWindowTarget<T>,
+}
+
+impl<T: 'static> EventLoop<T> {
+    pub fn new() -> EventLoop<T> {
+        let shared = Shared::new();
+        let (user_events_sender, user_events_channel) = mpsc::channel();
+
+        EventLoop {
+            shared: shared.clone(),
+            user_events_sender,
+            user_events_channel,
+            stepped: false,
+            window_target: RootEventLoopWindowTarget {
+                p: crate::platform_impl::EventLoopWindowTarget::Mock(EventLoopWindowTarget {
+                    shared,
+                    _marker: PhantomData,
+                }),
+                _marker: PhantomData,
+            },
+        }
+    }
+
+    pub fn run<F>(mut self, callback: F) -> !
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow) + 'static,
+    {
+        self.run_return(callback);
+        process::exit(0)
+    }
+
+    pub fn run_return<F>(&mut self, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let mut control_flow = ControlFlow::default();
+        let mut cause = StartCause::Init;
+
+        loop {
+            self.run_iteration(cause, &mut control_flow, &mut callback);
+
+            let start = Instant::now();
+            cause = match control_flow {
+                ControlFlow::Exit => break,
+                ControlFlow::Poll => StartCause::Poll,
+                ControlFlow::Wait => {
+                    self.shared.wait(None);
+                    StartCause::WaitCancelled {
+                        start,
+                        requested_resume: None,
+                    }
+                }
+                ControlFlow::WaitUntil(deadline) => {
+                    if self.shared.wait(Some(deadline)) {
+                        StartCause::WaitCancelled {
+                            start,
+                            requested_resume: Some(deadline),
+                        }
+                    } else {
+                        StartCause::ResumeTimeReached {
+                            start,
+                            requested_resume: deadline,
+                        }
+                    }
+                }
+            };
+        }
+
+        callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
+    }
+
+    /// Runs a single iteration of the event loop without ever blocking.
+    pub fn run_step<F>(&mut self, mut callback: F) -> ControlFlow
+    where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let cause = if self.stepped {
+            StartCause::Poll
+        } else {
+            StartCause::Init
+        };
+        self.stepped = true;
+
+        let mut control_flow = ControlFlow::default();
+        self.run_iteration(cause, &mut control_flow, &mut callback);
+        control_flow
+    }
+
+    fn run_iteration<F>(
+        &mut self,
+        cause: StartCause,
+        control_flow: &mut ControlFlow,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>, &RootEventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        sticky_exit_callback(
+            Event::NewEvents(cause),
+            &self.window_target,
+            control_flow,
+            callback,
+        );
+
+        // Only dispatch what was queued before this iteration started. Events queued by the
+        // callback (e.g. a `Resized` after `set_inner_size`) are handled by the next iteration.
+        let pending_events = {
+            let mut state = self.shared.lock();
+            state.woken = false;
+            mem::take(&mut state.pending)
+        };
+
+        for pending_event in pending_events {
+            match pending_event {
+                PendingEvent::Window { window_id, event } => {
+                    let alive = self.shared.lock().windows.contains_key(&window_id);
+                    if !alive && event != WindowEvent::Destroyed {
+                        continue;
+                    }
+
+                    self.shared.apply_window_event(window_id, &event);
+                    sticky_exit_callback(
+                        Event::WindowEvent {
+                            window_id: make_wid(window_id),
+                            event,
+                        },
+                        &self.window_target,
+                        control_flow,
+                        callback,
+                    );
+                }
+                PendingEvent::Device { device_id, event } => {
+                    sticky_exit_callback(
+                        Event::DeviceEvent {
+                            device_id: crate::event::DeviceId(
+                                crate::platform_impl::DeviceId::Mock(device_id),
+                            ),
+                            event,
+                        },
+                        &self.window_target,
+                        control_flow,
+                        callback,
+                    );
+                }
+                PendingEvent::DragMoved {
+                    window_id,
+                    position,
+                    action,
+                } => {
+                    let drag = match self.shared.incoming_drag(window_id) {
+                        Some(drag) => drag,
+                        None => continue,
+                    };
+                    let offers_files = drag.mime_types.iter().any(|m| m == "text/uri-list");
+                    let mut accepted_action = if offers_files { Some(action) } else { None };
+
+                    sticky_exit_callback(
+                        Event::WindowEvent {
+                            window_id: make_wid(window_id),
+                            event: WindowEvent::DragMoved {
+                                position,
+                                action,
+                                accepted_action: &mut accepted_action,
+                            },
+                        },
+                        &self.window_target,
+                        control_flow,
+                        callback,
+                    );
+                    self.shared
+                        .set_accepted_drop_action(window_id, accepted_action);
+                }
+                PendingEvent::Drop {
+                    window_id,
+                    position,
+                    data,
+                } => {
+                    let drag = match self.shared.incoming_drag(window_id) {
+                        Some(drag) => drag,
+                        None => continue,
+                    };
+                    let event = match drag.accepted_action {
+                        Some(action) => WindowEvent::DragDropped {
+                            position,
+                            action,
+                            data,
+                        },
+                        None => WindowEvent::DragLeft,
+                    };
+
+                    self.shared.apply_window_event(window_id, &event);
+                    sticky_exit_callback(
+                        Event::WindowEvent {
+                            window_id: make_wid(window_id),
+                            event,
+                        },
+                        &self.window_target,
+                        control_flow,
+                        callback,
+                    );
+                }
+                PendingEvent::ClipboardChanged(kind) => {
+                    sticky_exit_callback(
+                        Event::ClipboardChanged(kind),
+                        &self.window_target,
+                        control_flow,
+                        callback,
+                    );
+                }
+                PendingEvent::ScaleFactorChanged {
+                    window_id,
+                    scale_factor,
+                } => {
+                    let (current_size, mut new_inner_size) =
+                        match self.shared.update_scale_factor(window_id, scale_factor) {
+                            Some(sizes) => sizes,
+                            None => continue,
+                        };
+
+                    sticky_exit_callback(
+                        Event::WindowEvent {
+                            window_id: make_wid(window_id),
+                            event: WindowEvent::ScaleFactorChanged {
+                                scale_factor,
+                                new_inner_size: &mut new_inner_size,
+                            },
+                        },
+                        &self.window_target,
+                        control_flow,
+                        callback,
+                    );
+
+                    let event = WindowEvent::Resized(new_inner_size);
+                    self.shared.apply_window_event(window_id, &event);
+                    if new_inner_size != current_size {
+                        sticky_exit_callback(
+                            Event::WindowEvent {
+                                window_id: make_wid(window_id),
+                                event,
+                            },
+                            &self.window_target,
+                            control_flow,
+                            callback,
+                        );
+                    }
+                }
+            }
+        }
+
+        // Connected gamepads are read on each iteration, as they don't wake the loop up.
+        #[cfg(target_os = "linux")]
+        {
+            let mut gamepad_events = Vec::new();
+            self.shared
+                .lock()
+                .gamepads
+                .dispatch(|device_id, event| gamepad_events.push((device_id, event)));
+            for (device_id, event) in gamepad_events {
+                sticky_exit_callback(
+                    Event::DeviceEvent {
+                        device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Gamepad(
+                            device_id,
+                        )),
+                        event,
+                    },
+                    &self.window_target,
+                    control_flow,
+                    callback,
+                );
+            }
+        }
+
+        while let Ok(user_event) = self.user_events_channel.try_recv() {
+            sticky_exit_callback(
+                Event::UserEvent(user_event),
+                &self.window_target,
+                control_flow,
+                callback,
+            );
+        }
+
+        sticky_exit_callback(
+            Event::MainEventsCleared,
+            &self.window_target,
+            control_flow,
+            callback,
+        );
+
+        let redraw_requests = mem::take(&mut self.shared.lock().redraw_requests);
+        for window_id in redraw_requests {
+            if !self.shared.lock().windows.contains_key(&window_id) {
+                continue;
+            }
+
+            sticky_exit_callback(
+                Event::RedrawRequested(make_wid(window_id)),
+                &self.window_target,
+                control_flow,
+                callback,
+            );
+        }
+
+        sticky_exit_callback(
+            Event::RedrawEventsCleared,
+            &self.window_target,
+            control_flow,
+            callback,
+        );
+    }
+
+    #[inline]
+    pub fn create_proxy(&self) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            user_events_sender: self.user_events_sender.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+
+    #[inline]
+    pub fn window_target(&self) -> &RootEventLoopWindowTarget<T> {
+        &self.window_target
+    }
+}
+
+/// A handle that can be sent across the threads and used to wake up the `EventLoop`.
+pub struct EventLoopProxy<T: 'static> {
+    user_events_sender: Sender<T>,
+    shared: Arc<Shared>,
+}
+
+impl<T: 'static> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        EventLoopProxy {
+            user_events_sender: self.user_events_sender.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T: 'static> EventLoopProxy<T> {
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        self.user_events_sender
+            .send(event)
+            .map_err(|SendError(error)| EventLoopClosed(error))?;
+        self.shared.wake();
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+#![cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+
+//! A headless backend which keeps all of its state in memory.
+//!
+//! Nothing in here talks to a display server: windows and monitors are plain records stored in
+//! a `Shared` state, and input is whatever gets scripted through a `MockController`.
+
+use std::sync::Arc;
+
+use crate::window::CustomCursorSource;
+
+pub use self::event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
+pub use self::monitor::{MonitorHandle, VideoMode};
+pub use self::state::{PendingEvent, Shared, TEXT_MIME_TYPE};
+pub use self::window::Window;
+
+mod event_loop;
+mod monitor;
+mod state;
+mod window;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeviceId(pub(crate) u64);
+
+impl DeviceId {
+    // Only used when the mock backend is the only one enabled.
+    #[allow(dead_code)]
+    pub unsafe fn dummy() -> Self {
+        DeviceId(0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WindowId(pub(crate) u64);
+
+impl WindowId {
+    // Only used when the mock backend is the only one enabled.
+    #[allow(dead_code)]
+    pub unsafe fn dummy() -> Self {
+        WindowId(0)
+    }
+}
+
+/// A custom cursor, which only keeps its images around.
+#[derive(Debug, Clone)]
+pub struct CustomCursor(pub(crate) Arc<CustomCursorSource>);
+
+#[inline]
+fn make_wid(window_id: WindowId) -> crate::window::WindowId {
+    crate::window::WindowId(crate::platform_impl::WindowId::Mock(window_id))
+}
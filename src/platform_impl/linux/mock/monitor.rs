@@ -0,0 +1,150 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode};
+use crate::platform::mock::MockMonitor;
+use crate::platform_impl::{
+    MonitorHandle as PlatformMonitorHandle, VideoMode as PlatformVideoMode,
+};
+
+#[derive(Clone)]
+pub struct MonitorHandle {
+    id: u32,
+    info: Arc<Mutex<MockMonitor>>,
+}
+
+impl MonitorHandle {
+    pub(crate) fn new(id: u32, info: MockMonitor) -> Self {
+        MonitorHandle {
+            id,
+            info: Arc::new(Mutex::new(info)),
+        }
+    }
+
+    #[inline]
+    fn with_info<U, F: FnOnce(&MockMonitor) -> U>(&self, f: F) -> U {
+        f(&self.info.lock().unwrap())
+    }
+
+    /// Whether `position` lies within the bounds of this monitor.
+    pub(crate) fn contains(&self, position: PhysicalPosition<i32>) -> bool {
+        self.with_info(|info| {
+            let (x, y) = (position.x as i64, position.y as i64);
+            let (left, top) = (info.position.x as i64, info.position.y as i64);
+            x >= left
+                && y >= top
+                && x < left + info.size.width as i64
+                && y < top + info.size.height as i64
+        })
+    }
+
+    #[inline]
+    pub fn name(&self) -> Option<String> {
+        self.with_info(|info| Some(info.name.clone()))
+    }
+
+    #[inline]
+    pub fn native_identifier(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.with_info(|info| info.size)
+    }
+
+    #[inline]
+    pub fn position(&self) -> PhysicalPosition<i32> {
+        self.with_info(|info| info.position)
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.with_info(|info| info.scale_factor)
+    }
+
+    #[inline]
+    pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
+        let video_mode = self.with_info(|info| VideoMode {
+            size: info.size,
+            bit_depth: 32,
+            refresh_rate: info.refresh_rate,
+            monitor: self.clone(),
+        });
+
+        std::iter::once(RootVideoMode {
+            video_mode: PlatformVideoMode::Mock(video_mode),
+        })
+    }
+}
+
+impl fmt::Debug for MonitorHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_info(|info| {
+            f.debug_struct("MonitorHandle")
+                .field("id", &self.id)
+                .field("info", info)
+                .finish()
+        })
+    }
+}
+
+impl PartialEq for MonitorHandle {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for MonitorHandle {}
+
+impl PartialOrd for MonitorHandle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MonitorHandle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+}
+
+impl Hash for MonitorHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoMode {
+    pub(crate) size: PhysicalSize<u32>,
+    pub(crate) bit_depth: u16,
+    pub(crate) refresh_rate: u16,
+    pub(crate) monitor: MonitorHandle,
+}
+
+impl VideoMode {
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    #[inline]
+    pub fn bit_depth(&self) -> u16 {
+        self.bit_depth
+    }
+
+    #[inline]
+    pub fn refresh_rate(&self) -> u16 {
+        self.refresh_rate
+    }
+
+    pub fn monitor(&self) -> RootMonitorHandle {
+        RootMonitorHandle {
+            inner: PlatformMonitorHandle::Mock(self.monitor.clone()),
+        }
+    }
+}
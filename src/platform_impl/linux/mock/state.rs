@@ -0,0 +1,276 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Instant;
+
+use crate::clipboard::ClipboardKind;
+use crate::dnd::{DndAction, DropData};
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::event::{DeviceEvent, WindowEvent};
+use crate::platform::mock::{MockIncomingDrag, MockMonitor, MockWindowState};
+#[cfg(target_os = "linux")]
+use crate::platform_impl::platform::gamepad::Gamepads;
+
+use super::{DeviceId, MonitorHandle, WindowId};
+
+/// An event which was scripted through the controller, or generated by a window request, and is
+/// waiting to be dispatched by the event loop.
+#[derive(Debug)]
+pub enum PendingEvent {
+    Window {
+        window_id: WindowId,
+        event: WindowEvent<'static>,
+    },
+    Device {
+        device_id: DeviceId,
+        event: DeviceEvent,
+    },
+    /// `WindowEvent::ScaleFactorChanged` borrows the new size, so it can't be queued as a
+    /// `'static` window event and is resolved at dispatch time instead.
+    ScaleFactorChanged {
+        window_id: WindowId,
+        scale_factor: f64,
+    },
+    /// `WindowEvent::DragMoved` borrows the action the application answers with, so it's resolved
+    /// at dispatch time as well.
+    DragMoved {
+        window_id: WindowId,
+        position: PhysicalPosition<f64>,
+        action: DndAction,
+    },
+    /// A drop, which turns into a `WindowEvent::DragDropped` if the last move was accepted.
+    Drop {
+        window_id: WindowId,
+        position: PhysicalPosition<f64>,
+        data: DropData,
+    },
+    ClipboardChanged(ClipboardKind),
+}
+
+/// Mime type under which text is stored in the clipboards.
+pub const TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
+
+pub struct State {
+    /// Events waiting to be dispatched, in the order they were scripted.
+    pub pending: VecDeque<PendingEvent>,
+
+    /// Attributes of every window which is still alive.
+    pub windows: HashMap<WindowId, MockWindowState>,
+
+    /// The connected monitors. The first one is considered the primary monitor.
+    pub monitors: Vec<MonitorHandle>,
+
+    /// Windows for which a redraw was requested, in request order.
+    pub redraw_requests: Vec<WindowId>,
+
+    /// Data held by each clipboard, with the mime type it's available as.
+    pub clipboards: HashMap<ClipboardKind, Vec<(String, Vec<u8>)>>,
+
+    /// Set when something happened that should cancel a wait of the event loop.
+    pub woken: bool,
+
+    /// Gamepads connected through the controller.
+    #[cfg(target_os = "linux")]
+    pub gamepads: Gamepads,
+
+    next_window_id: u64,
+    next_monitor_id: u32,
+}
+
+/// The state shared between the event loop, its windows, its proxies and its controllers.
+pub struct Shared {
+    state: Mutex<State>,
+    wakeup: Condvar,
+}
+
+impl Shared {
+    pub fn new() -> Arc<Self> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                pending: VecDeque::new(),
+                windows: HashMap::new(),
+                monitors: Vec::new(),
+                redraw_requests: Vec::new(),
+                clipboards: HashMap::new(),
+                woken: false,
+                #[cfg(target_os = "linux")]
+                gamepads: Gamepads::new().expect("Failed to create the gamepad poll"),
+                next_window_id: 1,
+                next_monitor_id: 1,
+            }),
+            wakeup: Condvar::new(),
+        });
+
+        shared.add_monitor(MockMonitor::default());
+        shared
+    }
+
+    #[inline]
+    pub fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Returns the data a clipboard holds as `mime_type`.
+    pub fn clipboard_data(&self, kind: ClipboardKind, mime_type: &str) -> Option<Vec<u8>> {
+        self.lock()
+            .clipboards
+            .get(&kind)?
+            .iter()
+            .find(|(offered, _)| offered == mime_type)
+            .map(|(_, data)| data.clone())
+    }
+
+    /// Queues an event and wakes up the event loop.
+    pub fn push_event(&self, event: PendingEvent) {
+        let mut state = self.lock();
+        state.pending.push_back(event);
+        state.woken = true;
+        self.wakeup.notify_all();
+    }
+
+    /// Wakes up the event loop without queuing anything.
+    pub fn wake(&self) {
+        self.lock().woken = true;
+        self.wakeup.notify_all();
+    }
+
+    /// Blocks until something wakes the event loop up, or until `deadline` is reached.
+    ///
+    /// Returns `true` if the wait was cancelled by a wake up.
+    pub fn wait(&self, deadline: Option<Instant>) -> bool {
+        let mut state = self.lock();
+        loop {
+            if state.woken || !state.pending.is_empty() || !state.redraw_requests.is_empty() {
+                state.woken = false;
+                return true;
+            }
+
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    self.wakeup.wait_timeout(state, deadline - now).unwrap().0
+                }
+                None => self.wakeup.wait(state).unwrap(),
+            };
+        }
+    }
+
+    pub fn next_window_id(&self) -> WindowId {
+        let mut state = self.lock();
+        let window_id = WindowId(state.next_window_id);
+        state.next_window_id += 1;
+        window_id
+    }
+
+    pub fn add_monitor(&self, monitor: MockMonitor) -> MonitorHandle {
+        let mut state = self.lock();
+        let handle = MonitorHandle::new(state.next_monitor_id, monitor);
+        state.next_monitor_id += 1;
+        state.monitors.push(handle.clone());
+        handle
+    }
+
+    pub fn remove_monitor(&self, monitor: &MonitorHandle) {
+        self.lock().monitors.retain(|m| m != monitor);
+    }
+
+    pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
+        self.lock().monitors.iter().cloned().collect()
+    }
+
+    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
+        self.lock().monitors.first().cloned()
+    }
+
+    /// Returns the monitor containing `position`, falling back to the primary monitor.
+    pub fn monitor_at(&self, position: PhysicalPosition<i32>) -> Option<MonitorHandle> {
+        let state = self.lock();
+        state
+            .monitors
+            .iter()
+            .find(|monitor| monitor.contains(position))
+            .or_else(|| state.monitors.first())
+            .cloned()
+    }
+
+    pub fn request_redraw(&self, window_id: WindowId) {
+        let mut state = self.lock();
+        if !state.redraw_requests.contains(&window_id) {
+            state.redraw_requests.push(window_id);
+        }
+        self.wakeup.notify_all();
+    }
+
+    /// Applies the side effects an event delivered to the application has on the window state.
+    pub fn apply_window_event(&self, window_id: WindowId, event: &WindowEvent<'_>) {
+        let mut state = self.lock();
+        let focused_window = match *event {
+            WindowEvent::Focused(true) => Some(window_id),
+            _ => None,
+        };
+
+        if let Some(window) = state.windows.get_mut(&window_id) {
+            match *event {
+                WindowEvent::Resized(size) => window.inner_size = size,
+                WindowEvent::Moved(position) => window.outer_position = position,
+                WindowEvent::Focused(focused) => window.focused = focused,
+                WindowEvent::PopupDismissed => window.popup_grab = false,
+                WindowEvent::DragEntered { ref mime_types } => {
+                    window.incoming_drag = Some(MockIncomingDrag {
+                        mime_types: mime_types.clone(),
+                        accepted_action: None,
+                    })
+                }
+                WindowEvent::DragDropped { .. } | WindowEvent::DragLeft => {
+                    window.incoming_drag = None
+                }
+                _ => (),
+            }
+        }
+
+        // Only one window can have the keyboard focus at a time.
+        if let Some(focused_window) = focused_window {
+            for (window_id, window) in state.windows.iter_mut() {
+                if *window_id != focused_window {
+                    window.focused = false;
+                }
+            }
+        }
+    }
+
+    /// Returns the drag hovering a window, if any.
+    pub fn incoming_drag(&self, window_id: WindowId) -> Option<MockIncomingDrag> {
+        self.lock()
+            .windows
+            .get(&window_id)
+            .and_then(|window| window.incoming_drag.clone())
+    }
+
+    /// Stores the action the application accepted the drag hovering a window with.
+    pub fn set_accepted_drop_action(&self, window_id: WindowId, action: Option<DndAction>) {
+        let mut state = self.lock();
+        let drag = state
+            .windows
+            .get_mut(&window_id)
+            .and_then(|window| window.incoming_drag.as_mut());
+        if let Some(drag) = drag {
+            drag.accepted_action = action;
+        }
+    }
+
+    /// Updates the scale factor of a window and returns its current and suggested inner size.
+    pub fn update_scale_factor(
+        &self,
+        window_id: WindowId,
+        scale_factor: f64,
+    ) -> Option<(PhysicalSize<u32>, PhysicalSize<u32>)> {
+        let mut state = self.lock();
+        let window = state.windows.get_mut(&window_id)?;
+        let current_size = window.inner_size;
+        let logical_size = current_size.to_logical::<f64>(window.scale_factor);
+        window.scale_factor = scale_factor;
+        Some((current_size, logical_size.to_physical(scale_factor)))
+    }
+}
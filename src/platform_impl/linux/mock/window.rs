@@ -0,0 +1,467 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use raw_window_handle::unix::WaylandHandle;
+
+use crate::dnd::DndActions;
+use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
+use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
+use crate::event::WindowEvent;
+use crate::icon::Icon;
+use crate::monitor::MonitorHandle as RootMonitorHandle;
+use crate::platform::mock::{MockDrag, MockWindowState};
+use crate::platform_impl::platform::popup;
+use crate::platform_impl::{
+    MonitorHandle as PlatformMonitorHandle,
+    PlatformSpecificWindowBuilderAttributes as PlatformAttributes, WindowId as PlatformWindowId,
+};
+use crate::window::{CursorGrabMode, CursorIcon, Fullscreen, UserAttentionType, WindowAttributes};
+
+use super::state::{PendingEvent, Shared};
+use super::{CustomCursor, EventLoopWindowTarget, MonitorHandle, WindowId};
+
+pub struct Window {
+    /// Window id.
+    window_id: WindowId,
+
+    /// State shared with the event loop.
+    shared: Arc<Shared>,
+}
+
+impl Window {
+    pub fn new<T>(
+        event_loop_window_target: &EventLoopWindowTarget<T>,
+        attributes: WindowAttributes,
+        platform_attributes: PlatformAttributes,
+    ) -> Result<Self, RootOsError> {
+        let shared = event_loop_window_target.shared.clone();
+        let window_id = shared.next_window_id();
+
+        let popup_parent = attributes.popup.and_then(|popup| match popup.parent.0 {
+            PlatformWindowId::Mock(parent) => {
+                let state = shared.lock();
+                let parent = state.windows.get(&parent)?;
+                Some((popup, parent.outer_position, parent.scale_factor))
+            }
+            #[cfg(feature = "x11")]
+            PlatformWindowId::X(_) => None,
+            #[cfg(feature = "wayland")]
+            PlatformWindowId::Wayland(_) => None,
+        });
+
+        let (mut outer_position, scale_factor) = match popup_parent {
+            // Popups are shown next to their parent.
+            Some((_, parent_position, parent_scale_factor)) => {
+                (parent_position, parent_scale_factor)
+            }
+            // Place the window on the monitor it's requested to be on, or on the primary one.
+            None => {
+                let primary_monitor = shared.primary_monitor();
+                let primary_scale_factor = primary_monitor
+                    .as_ref()
+                    .map(|monitor| monitor.scale_factor())
+                    .unwrap_or(1.0);
+                let outer_position = match attributes.position {
+                    Some(position) => position.to_physical(primary_scale_factor),
+                    None => primary_monitor
+                        .map(|monitor| monitor.position())
+                        .unwrap_or_default(),
+                };
+                let scale_factor = shared
+                    .monitor_at(outer_position)
+                    .map(|monitor| monitor.scale_factor())
+                    .unwrap_or(1.0);
+                (outer_position, scale_factor)
+            }
+        };
+
+        let inner_size = attributes
+            .inner_size
+            .unwrap_or_else(|| LogicalSize::new(800., 600.).into())
+            .to_physical(scale_factor);
+
+        if let Some((popup, parent_position, _)) = popup_parent {
+            let (anchor_position, anchor_size) = popup.anchor_rect;
+            let anchor_position = anchor_position.to_physical::<i32>(scale_factor);
+            let anchor_position = PhysicalPosition::new(
+                parent_position.x + anchor_position.x,
+                parent_position.y + anchor_position.y,
+            );
+            let bounds = shared
+                .monitor_at(anchor_position)
+                .map(|monitor| (monitor.position(), monitor.size()));
+            outer_position = popup::place(
+                (anchor_position, anchor_size.to_physical(scale_factor)),
+                popup.gravity,
+                inner_size,
+                bounds,
+            );
+        }
+
+        let window_state = MockWindowState {
+            title: attributes.title,
+            visible: attributes.visible,
+            focused: false,
+            inner_size,
+            outer_position,
+            min_inner_size: attributes.min_inner_size,
+            max_inner_size: attributes.max_inner_size,
+            resize_increments: platform_attributes.resize_increments,
+            scale_factor,
+            resizable: attributes.resizable,
+            minimized: false,
+            maximized: attributes.maximized,
+            fullscreen: attributes.fullscreen,
+            decorations: attributes.decorations,
+            always_on_top: attributes.always_on_top,
+            transparent: attributes.transparent,
+            has_window_icon: attributes.window_icon.is_some(),
+            popup: popup_parent.map(|(popup, _, _)| popup),
+            popup_grab: false,
+            parent_window: platform_attributes.parent_window,
+            modal: platform_attributes.modal,
+            child_of: platform_attributes.child_of,
+            cursor_icon: CursorIcon::Default,
+            custom_cursor: None,
+            cursor_visible: true,
+            cursor_grab: CursorGrabMode::None,
+            cursor_grab_region: None,
+            cursor_position: None,
+            ime_position: None,
+            ime_allowed: false,
+            user_attention: None,
+            drag: None,
+            incoming_drag: None,
+        };
+
+        shared.lock().windows.insert(window_id, window_state);
+
+        Ok(Window { window_id, shared })
+    }
+
+    #[inline]
+    pub fn id(&self) -> WindowId {
+        self.window_id
+    }
+
+    #[inline]
+    fn with_state<U, F: FnOnce(&mut MockWindowState) -> U>(&self, f: F) -> U {
+        let mut state = self.shared.lock();
+        f(state.windows.get_mut(&self.window_id).unwrap())
+    }
+
+    #[inline]
+    fn push_event(&self, event: WindowEvent<'static>) {
+        self.shared.push_event(PendingEvent::Window {
+            window_id: self.window_id,
+            event,
+        });
+    }
+
+    #[inline]
+    pub fn set_title(&self, title: &str) {
+        self.with_state(|state| state.title = title.to_owned());
+    }
+
+    #[inline]
+    pub fn set_visible(&self, visible: bool) {
+        self.with_state(|state| {
+            state.visible = visible;
+            // Hiding a popup releases its grab.
+            state.popup_grab &= visible;
+        });
+    }
+
+    #[inline]
+    pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        Ok(self.with_state(|state| state.outer_position))
+    }
+
+    #[inline]
+    pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        // Mock windows don't have decorations, so both positions are the same.
+        self.outer_position()
+    }
+
+    #[inline]
+    pub fn set_outer_position(&self, position: Position) {
+        let position = {
+            let mut state = self.shared.lock();
+            let window = state.windows.get_mut(&self.window_id).unwrap();
+            let position = position.to_physical::<i32>(window.scale_factor);
+            let (delta_x, delta_y) = (
+                position.x - window.outer_position.x,
+                position.y - window.outer_position.y,
+            );
+            window.outer_position = position;
+
+            // Popups move along with their parent.
+            let parent = Some(PlatformWindowId::Mock(self.window_id));
+            for popup in state.windows.values_mut() {
+                if popup.popup.map(|popup| popup.parent.0) == parent {
+                    popup.outer_position.x += delta_x;
+                    popup.outer_position.y += delta_y;
+                }
+            }
+            position
+        };
+
+        self.push_event(WindowEvent::Moved(position));
+    }
+
+    #[inline]
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.with_state(|state| state.inner_size)
+    }
+
+    #[inline]
+    pub fn outer_size(&self) -> PhysicalSize<u32> {
+        self.inner_size()
+    }
+
+    #[inline]
+    pub fn set_inner_size(&self, size: Size) {
+        let size = self.with_state(|state| {
+            let size = size.to_physical(state.scale_factor);
+            state.inner_size = size;
+            size
+        });
+
+        self.push_event(WindowEvent::Resized(size));
+    }
+
+    #[inline]
+    pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
+        self.with_state(|state| state.min_inner_size = dimensions);
+    }
+
+    #[inline]
+    pub fn set_max_inner_size(&self, dimensions: Option<Size>) {
+        self.with_state(|state| state.max_inner_size = dimensions);
+    }
+
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        self.with_state(|state| state.resize_increments = increments);
+    }
+
+    #[inline]
+    pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
+        self.with_state(|state| {
+            state
+                .resize_increments
+                .map(|increments| increments.to_physical(state.scale_factor))
+        })
+    }
+
+    #[inline]
+    pub fn set_resizable(&self, resizable: bool) {
+        self.with_state(|state| state.resizable = resizable);
+    }
+
+    #[inline]
+    pub fn set_cursor_icon(&self, cursor: CursorIcon) {
+        self.with_state(|state| {
+            state.cursor_icon = cursor;
+            state.custom_cursor = None;
+        });
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) {
+        self.with_state(|state| state.custom_cursor = Some((*cursor.0).clone()));
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        self.with_state(|state| state.cursor_grab = mode);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_grab_region(
+        &self,
+        region: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        self.with_state(|state| state.cursor_grab_region = region);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_popup_grab(&self, grab: bool) -> Result<(), ExternalError> {
+        self.with_state(|state| {
+            if state.popup.is_none() {
+                return Err(ExternalError::NotSupported(NotSupportedError::new()));
+            }
+            state.popup_grab = grab;
+            Ok(())
+        })
+    }
+
+    #[inline]
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.with_state(|state| state.cursor_visible = visible);
+    }
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn start_drag(
+        &self,
+        data: Vec<(String, Vec<u8>)>,
+        allowed_actions: DndActions,
+    ) -> Result<(), ExternalError> {
+        self.with_state(|state| {
+            state.drag = Some(MockDrag {
+                data,
+                allowed_actions,
+            })
+        });
+        Ok(())
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.with_state(|state| state.scale_factor)
+    }
+
+    #[inline]
+    pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
+        self.with_state(|state| {
+            state.cursor_position = Some(position.to_physical(state.scale_factor));
+        });
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        self.with_state(|state| state.maximized = maximized);
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.with_state(|state| state.maximized)
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) {
+        self.with_state(|state| state.minimized = minimized);
+    }
+
+    #[inline]
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.with_state(|state| state.fullscreen.clone())
+    }
+
+    #[inline]
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        // Resolve the current monitor, like the other backends do.
+        let fullscreen = match fullscreen {
+            Some(Fullscreen::Borderless(None)) => {
+                Some(Fullscreen::Borderless(self.current_monitor()))
+            }
+            fullscreen => fullscreen,
+        };
+
+        self.with_state(|state| state.fullscreen = fullscreen);
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, decorations: bool) {
+        self.with_state(|state| state.decorations = decorations);
+    }
+
+    #[inline]
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        self.with_state(|state| state.always_on_top = always_on_top);
+    }
+
+    #[inline]
+    pub fn set_window_icon(&self, window_icon: Option<Icon>) {
+        self.with_state(|state| state.has_window_icon = window_icon.is_some());
+    }
+
+    #[inline]
+    pub fn set_ime_position(&self, position: Position) {
+        self.with_state(|state| {
+            state.ime_position = Some(position.to_physical(state.scale_factor))
+        });
+    }
+
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.with_state(|state| state.ime_allowed = allowed);
+    }
+
+    #[inline]
+    pub fn focus_window(&self) {
+        if !self.with_state(|state| state.focused) {
+            let previous = self
+                .shared
+                .lock()
+                .windows
+                .iter()
+                .find(|(_, state)| state.focused)
+                .map(|(window_id, _)| *window_id);
+
+            if let Some(window_id) = previous {
+                self.shared.push_event(PendingEvent::Window {
+                    window_id,
+                    event: WindowEvent::Focused(false),
+                });
+            }
+            self.push_event(WindowEvent::Focused(true));
+        }
+    }
+
+    #[inline]
+    pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
+        self.with_state(|state| state.user_attention = request_type);
+    }
+
+    #[inline]
+    pub fn request_redraw(&self) {
+        self.shared.request_redraw(self.window_id);
+    }
+
+    #[inline]
+    pub fn current_monitor(&self) -> Option<RootMonitorHandle> {
+        let position = self.with_state(|state| state.outer_position);
+        self.shared
+            .monitor_at(position)
+            .map(|monitor| RootMonitorHandle {
+                inner: PlatformMonitorHandle::Mock(monitor),
+            })
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
+        self.shared.available_monitors()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<RootMonitorHandle> {
+        self.shared
+            .primary_monitor()
+            .map(|monitor| RootMonitorHandle {
+                inner: PlatformMonitorHandle::Mock(monitor),
+            })
+    }
+
+    /// Mock windows have no native window, so a Wayland handle with null pointers is returned,
+    /// which can't be mistaken for a window of an X server.
+    #[inline]
+    pub fn raw_window_handle(&self) -> WaylandHandle {
+        WaylandHandle::empty()
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        self.shared.lock().windows.remove(&self.window_id);
+        self.push_event(WindowEvent::Destroyed);
+    }
+}
@@ -6,14 +6,21 @@
     target_os = "openbsd"
 ))]
 
-#[cfg(all(not(feature = "x11"), not(feature = "wayland")))]
-compile_error!("Please select a feature to build for unix: `x11`, `wayland`");
+#[cfg(all(not(feature = "x11"), not(feature = "wayland"), not(feature = "mock")))]
+compile_error!("Please select a feature to build for unix: `x11`, `wayland`, `mock`");
 
 #[cfg(feature = "wayland")]
 use std::error::Error;
+#[cfg(any(feature = "x11", feature = "wayland"))]
+use std::os::raw::c_void;
 use std::{collections::VecDeque, env, fmt};
 #[cfg(feature = "x11")]
-use std::{ffi::CStr, mem::MaybeUninit, os::raw::*, sync::Arc};
+use std::{
+    ffi::CStr,
+    mem::MaybeUninit,
+    os::raw::*,
+    sync::{Arc, Weak},
+};
 
 #[cfg(feature = "x11")]
 use parking_lot::Mutex;
@@ -24,17 +31,31 @@ pub use self::x11::XNotSupported;
 #[cfg(feature = "x11")]
 use self::x11::{ffi::XVisualInfo, util::WindowType as XWindowType, XConnection, XError};
 use crate::{
+    clipboard::ClipboardKind,
+    dnd::DndActions,
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     event::Event,
     event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
+    gamepad::GamepadInfo,
     icon::Icon,
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
-    window::{CursorIcon, Fullscreen, UserAttentionType, WindowAttributes},
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursorSource, Fullscreen, UserAttentionType,
+        WindowAttributes, WindowId as RootWindowId,
+    },
 };
 
 pub(crate) use crate::icon::RgbaIcon as PlatformIcon;
 
+#[cfg(any(feature = "x11", feature = "wayland"))]
+mod common;
+#[cfg(target_os = "linux")]
+pub mod gamepad;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(any(feature = "x11", feature = "mock"))]
+mod popup;
 #[cfg(feature = "wayland")]
 pub mod wayland;
 #[cfg(feature = "x11")]
@@ -42,9 +63,9 @@ pub mod x11;
 
 /// Environment variable specifying which backend should be used on unix platform.
 ///
-/// Legal values are x11 and wayland. If this variable is set only the named backend
+/// Legal values are x11, wayland and mock. If this variable is set only the named backend
 /// will be tried by winit. If it is not set, winit will try to connect to a wayland connection,
-/// and if it fails will fallback on x11.
+/// and if it fails will fallback on x11. The mock backend is never tried unless it's requested.
 ///
 /// If this variable is set with any other value, winit will panic.
 const BACKEND_PREFERENCE_ENV_VAR: &str = "WINIT_UNIX_BACKEND";
@@ -55,7 +76,6 @@ pub struct PlatformSpecificWindowBuilderAttributes {
     pub visual_infos: Option<XVisualInfo>,
     #[cfg(feature = "x11")]
     pub screen_id: Option<i32>,
-    #[cfg(feature = "x11")]
     pub resize_increments: Option<Size>,
     #[cfg(feature = "x11")]
     pub base_size: Option<Size>,
@@ -69,6 +89,11 @@ pub struct PlatformSpecificWindowBuilderAttributes {
     pub gtk_theme_variant: Option<String>,
     #[cfg(feature = "wayland")]
     pub app_id: Option<String>,
+    pub parent_window: Option<RootWindowId>,
+    pub modal: bool,
+    pub child_of: Option<RootWindowId>,
+    #[cfg(feature = "x11")]
+    pub embed_parent: Option<c_ulong>,
 }
 
 impl Default for PlatformSpecificWindowBuilderAttributes {
@@ -78,7 +103,6 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
             visual_infos: None,
             #[cfg(feature = "x11")]
             screen_id: None,
-            #[cfg(feature = "x11")]
             resize_increments: None,
             #[cfg(feature = "x11")]
             base_size: None,
@@ -92,6 +116,11 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
             gtk_theme_variant: None,
             #[cfg(feature = "wayland")]
             app_id: None,
+            parent_window: None,
+            modal: false,
+            child_of: None,
+            #[cfg(feature = "x11")]
+            embed_parent: None,
         }
     }
 }
@@ -99,7 +128,94 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
 #[cfg(feature = "x11")]
 lazy_static! {
     pub static ref X11_BACKEND: Mutex<Result<Arc<XConnection>, XNotSupported>> =
-        Mutex::new(XConnection::new(Some(x_error_callback)).map(Arc::new));
+        Mutex::new(XConnection::new(Some(x_error_callback)).map(register_x11_connection));
+
+    /// The connections `x_error_callback` reports the errors of.
+    static ref X11_CONNECTIONS: Mutex<Vec<Weak<XConnection>>> = Mutex::new(Vec::new());
+}
+
+#[cfg(feature = "x11")]
+fn register_x11_connection(xconn: XConnection) -> Arc<XConnection> {
+    let xconn = Arc::new(xconn);
+    let mut connections = X11_CONNECTIONS.lock();
+    connections.retain(|xconn| xconn.strong_count() > 0);
+    connections.push(Arc::downgrade(&xconn));
+    xconn
+}
+
+/// The connections we use on the displays of the application.
+#[cfg(feature = "x11")]
+fn shared_x11_connections() -> Vec<Arc<XConnection>> {
+    X11_CONNECTIONS
+        .lock()
+        .iter()
+        .filter_map(Weak::upgrade)
+        .filter(|xconn| !xconn.owns_display())
+        .collect()
+}
+
+/// Counts the requests made on the displays of the application while it's alive as ours, so
+/// that `x_error_callback` only reports their errors to us.
+#[cfg(feature = "x11")]
+struct OwnX11Requests(Vec<Arc<XConnection>>);
+
+#[cfg(feature = "x11")]
+impl OwnX11Requests {
+    fn begin() -> Self {
+        let connections = shared_x11_connections();
+        for xconn in &connections {
+            xconn.begin_own_requests();
+        }
+        OwnX11Requests(connections)
+    }
+}
+
+#[cfg(feature = "x11")]
+impl Drop for OwnX11Requests {
+    fn drop(&mut self) {
+        for xconn in &self.0 {
+            xconn.end_own_requests();
+        }
+    }
+}
+
+/// Counts the requests made on the displays of the application while it's alive as its own,
+/// which is the case in the callback of the event loop, outside of the calls it makes to us.
+#[cfg(feature = "x11")]
+struct ApplicationX11Requests(Vec<Arc<XConnection>>);
+
+#[cfg(feature = "x11")]
+impl ApplicationX11Requests {
+    fn begin() -> Self {
+        let connections = shared_x11_connections();
+        for xconn in &connections {
+            xconn.end_own_requests();
+        }
+        ApplicationX11Requests(connections)
+    }
+}
+
+#[cfg(feature = "x11")]
+impl Drop for ApplicationX11Requests {
+    fn drop(&mut self) {
+        for xconn in &self.0 {
+            xconn.begin_own_requests();
+        }
+    }
+}
+
+/// Wraps the callback of the event loop, in which the requests are the application's own.
+#[cfg(feature = "x11")]
+fn with_application_x11_requests<T, F>(
+    mut callback: F,
+) -> impl FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow)
+where
+    F: FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+{
+    move |event, target, control_flow| {
+        let _requests = ApplicationX11Requests::begin();
+        callback(event, target, control_flow)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -130,6 +246,8 @@ pub enum Window {
     X(x11::Window),
     #[cfg(feature = "wayland")]
     Wayland(wayland::Window),
+    #[cfg(feature = "mock")]
+    Mock(mock::Window),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -138,6 +256,8 @@ pub enum WindowId {
     X(x11::WindowId),
     #[cfg(feature = "wayland")]
     Wayland(wayland::WindowId),
+    #[cfg(feature = "mock")]
+    Mock(mock::WindowId),
 }
 
 impl WindowId {
@@ -146,6 +266,8 @@ impl WindowId {
         return WindowId::Wayland(wayland::WindowId::dummy());
         #[cfg(all(not(feature = "wayland"), feature = "x11"))]
         return WindowId::X(x11::WindowId::dummy());
+        #[cfg(all(not(feature = "wayland"), not(feature = "x11"), feature = "mock"))]
+        return WindowId::Mock(mock::WindowId::dummy());
     }
 }
 
@@ -155,6 +277,15 @@ pub enum DeviceId {
     X(x11::DeviceId),
     #[cfg(feature = "wayland")]
     Wayland(wayland::DeviceId),
+    #[cfg(feature = "wayland")]
+    WaylandTabletTool(wayland::TabletToolId),
+    #[cfg(feature = "mock")]
+    Mock(mock::DeviceId),
+    #[cfg(target_os = "linux")]
+    Gamepad(gamepad::DeviceId),
+    /// A device of an event recording, see `crate::record`.
+    #[cfg(feature = "serde")]
+    Replayed(u32),
 }
 
 impl DeviceId {
@@ -163,15 +294,34 @@ impl DeviceId {
         return DeviceId::Wayland(wayland::DeviceId::dummy());
         #[cfg(all(not(feature = "wayland"), feature = "x11"))]
         return DeviceId::X(x11::DeviceId::dummy());
+        #[cfg(all(not(feature = "wayland"), not(feature = "x11"), feature = "mock"))]
+        return DeviceId::Mock(mock::DeviceId::dummy());
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn synthetic(index: u32) -> Self {
+        DeviceId::Replayed(index)
     }
 }
 
+#[derive(Debug, Clone)]
+pub enum PlatformCustomCursor {
+    #[cfg(feature = "x11")]
+    X(x11::CustomCursor),
+    #[cfg(feature = "wayland")]
+    Wayland(wayland::CustomCursor),
+    #[cfg(feature = "mock")]
+    Mock(mock::CustomCursor),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum MonitorHandle {
     #[cfg(feature = "x11")]
     X(x11::MonitorHandle),
     #[cfg(feature = "wayland")]
     Wayland(wayland::MonitorHandle),
+    #[cfg(feature = "mock")]
+    Mock(mock::MonitorHandle),
 }
 
 /// `x11_or_wayland!(match expr; Enum(foo) => foo.something())`
@@ -180,24 +330,36 @@ pub enum MonitorHandle {
 /// match self {
 ///    Enum::X(foo) => foo.something(),
 ///    Enum::Wayland(foo) => foo.something(),
+///    Enum::Mock(foo) => foo.something(),
 /// }
 /// ```
-/// The result can be converted to another enum by adding `; as AnotherEnum`
+/// The result can be converted to another enum by adding `; as AnotherEnum`. The requests made
+/// in the X11 arm are counted as ours, see `OwnX11Requests`.
 macro_rules! x11_or_wayland {
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr; as $enum2:ident ) => {
         match $what {
             #[cfg(feature = "x11")]
-            $enum::X($($c1)*) => $enum2::X($x),
+            $enum::X($($c1)*) => {
+                let _requests = OwnX11Requests::begin();
+                $enum2::X($x)
+            }
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $enum2::Wayland($x),
+            #[cfg(feature = "mock")]
+            $enum::Mock($($c1)*) => $enum2::Mock($x),
         }
     };
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr) => {
         match $what {
             #[cfg(feature = "x11")]
-            $enum::X($($c1)*) => $x,
+            $enum::X($($c1)*) => {
+                let _requests = OwnX11Requests::begin();
+                $x
+            }
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $x,
+            #[cfg(feature = "mock")]
+            $enum::Mock($($c1)*) => $x,
         }
     };
 }
@@ -240,6 +402,8 @@ pub enum VideoMode {
     X(x11::VideoMode),
     #[cfg(feature = "wayland")]
     Wayland(wayland::VideoMode),
+    #[cfg(feature = "mock")]
+    Mock(mock::VideoMode),
 }
 
 impl VideoMode {
@@ -278,8 +442,28 @@ impl Window {
             }
             #[cfg(feature = "x11")]
             EventLoopWindowTarget::X(ref window_target) => {
+                let _requests = OwnX11Requests::begin();
                 x11::Window::new(window_target, attribs, pl_attribs).map(Window::X)
             }
+            #[cfg(feature = "mock")]
+            EventLoopWindowTarget::Mock(ref window_target) => {
+                mock::Window::new(window_target, attribs, pl_attribs).map(Window::Mock)
+            }
+        }
+    }
+
+    #[cfg(feature = "x11")]
+    pub unsafe fn from_raw_x11<T>(
+        window_target: &EventLoopWindowTarget<T>,
+        xwindow: c_ulong,
+    ) -> Result<Self, RootOsError> {
+        match *window_target {
+            EventLoopWindowTarget::X(ref window_target) => {
+                let _requests = OwnX11Requests::begin();
+                x11::Window::from_raw(window_target, xwindow).map(Window::X)
+            }
+            #[cfg(any(feature = "wayland", feature = "mock"))]
+            _ => Err(os_error!(OsError::XMisc("the event loop doesn't use X11"))),
         }
     }
 
@@ -338,6 +522,16 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_max_inner_size(dimensions))
     }
 
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        x11_or_wayland!(match self; Window(w) => w.set_resize_increments(increments))
+    }
+
+    #[inline]
+    pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
+        x11_or_wayland!(match self; Window(w) => w.resize_increments())
+    }
+
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
         x11_or_wayland!(match self; Window(w) => w.set_resizable(resizable))
@@ -349,8 +543,43 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        x11_or_wayland!(match self; Window(window) => window.set_cursor_grab(grab))
+    pub fn set_custom_cursor(&self, cursor: &PlatformCustomCursor) {
+        match (self, cursor) {
+            #[cfg(feature = "x11")]
+            (Window::X(window), PlatformCustomCursor::X(cursor)) => {
+                let _requests = OwnX11Requests::begin();
+                window.set_custom_cursor(cursor)
+            }
+            #[cfg(feature = "wayland")]
+            (Window::Wayland(window), PlatformCustomCursor::Wayland(cursor)) => {
+                window.set_custom_cursor(cursor)
+            }
+            #[cfg(feature = "mock")]
+            (Window::Mock(window), PlatformCustomCursor::Mock(cursor)) => {
+                window.set_custom_cursor(cursor)
+            }
+            // The cursor was created by the event loop of another backend.
+            #[allow(unreachable_patterns)]
+            _ => (),
+        }
+    }
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.set_cursor_grab(mode))
+    }
+
+    #[inline]
+    pub fn set_cursor_grab_region(
+        &self,
+        region: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.set_cursor_grab_region(region))
+    }
+
+    #[inline]
+    pub fn set_popup_grab(&self, grab: bool) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.set_popup_grab(grab))
     }
 
     #[inline]
@@ -363,6 +592,15 @@ impl Window {
         x11_or_wayland!(match self; Window(window) => window.drag_window())
     }
 
+    #[inline]
+    pub fn start_drag(
+        &self,
+        data: Vec<(String, Vec<u8>)>,
+        allowed_actions: DndActions,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.start_drag(data, allowed_actions))
+    }
+
     #[inline]
     pub fn scale_factor(&self) -> f64 {
         x11_or_wayland!(match self; Window(w) => w.scale_factor() as f64)
@@ -407,7 +645,12 @@ impl Window {
     pub fn set_always_on_top(&self, _always_on_top: bool) {
         match self {
             #[cfg(feature = "x11")]
-            &Window::X(ref w) => w.set_always_on_top(_always_on_top),
+            &Window::X(ref w) => {
+                let _requests = OwnX11Requests::begin();
+                w.set_always_on_top(_always_on_top)
+            }
+            #[cfg(feature = "mock")]
+            &Window::Mock(ref w) => w.set_always_on_top(_always_on_top),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -417,7 +660,12 @@ impl Window {
     pub fn set_window_icon(&self, _window_icon: Option<Icon>) {
         match self {
             #[cfg(feature = "x11")]
-            &Window::X(ref w) => w.set_window_icon(_window_icon),
+            &Window::X(ref w) => {
+                let _requests = OwnX11Requests::begin();
+                w.set_window_icon(_window_icon)
+            }
+            #[cfg(feature = "mock")]
+            &Window::Mock(ref w) => w.set_window_icon(_window_icon),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -428,11 +676,21 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_ime_position(position))
     }
 
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_allowed(allowed))
+    }
+
     #[inline]
     pub fn focus_window(&self) {
         match self {
             #[cfg(feature = "x11")]
-            &Window::X(ref w) => w.focus_window(),
+            &Window::X(ref w) => {
+                let _requests = OwnX11Requests::begin();
+                w.focus_window()
+            }
+            #[cfg(feature = "mock")]
+            &Window::Mock(ref w) => w.focus_window(),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -440,7 +698,12 @@ impl Window {
     pub fn request_user_attention(&self, _request_type: Option<UserAttentionType>) {
         match self {
             #[cfg(feature = "x11")]
-            &Window::X(ref w) => w.request_user_attention(_request_type),
+            &Window::X(ref w) => {
+                let _requests = OwnX11Requests::begin();
+                w.request_user_attention(_request_type)
+            }
+            #[cfg(feature = "mock")]
+            &Window::Mock(ref w) => w.request_user_attention(_request_type),
             #[cfg(feature = "wayland")]
             _ => (),
         }
@@ -456,6 +719,7 @@ impl Window {
         match self {
             #[cfg(feature = "x11")]
             &Window::X(ref window) => {
+                let _requests = OwnX11Requests::begin();
                 let current_monitor = MonitorHandle::X(window.current_monitor());
                 Some(RootMonitorHandle {
                     inner: current_monitor,
@@ -468,6 +732,8 @@ impl Window {
                     inner: current_monitor,
                 })
             }
+            #[cfg(feature = "mock")]
+            &Window::Mock(ref window) => window.current_monitor(),
         }
     }
 
@@ -475,17 +741,26 @@ impl Window {
     pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
         match self {
             #[cfg(feature = "x11")]
-            &Window::X(ref window) => window
-                .available_monitors()
-                .into_iter()
-                .map(MonitorHandle::X)
-                .collect(),
+            &Window::X(ref window) => {
+                let _requests = OwnX11Requests::begin();
+                window
+                    .available_monitors()
+                    .into_iter()
+                    .map(MonitorHandle::X)
+                    .collect()
+            }
             #[cfg(feature = "wayland")]
             &Window::Wayland(ref window) => window
                 .available_monitors()
                 .into_iter()
                 .map(MonitorHandle::Wayland)
                 .collect(),
+            #[cfg(feature = "mock")]
+            &Window::Mock(ref window) => window
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Mock)
+                .collect(),
         }
     }
 
@@ -494,6 +769,7 @@ impl Window {
         match self {
             #[cfg(feature = "x11")]
             &Window::X(ref window) => {
+                let _requests = OwnX11Requests::begin();
                 let primary_monitor = MonitorHandle::X(window.primary_monitor());
                 Some(RootMonitorHandle {
                     inner: primary_monitor,
@@ -501,6 +777,8 @@ impl Window {
             }
             #[cfg(feature = "wayland")]
             &Window::Wayland(ref window) => window.primary_monitor(),
+            #[cfg(feature = "mock")]
+            &Window::Mock(ref window) => window.primary_monitor(),
         }
     }
 
@@ -510,6 +788,8 @@ impl Window {
             &Window::X(ref window) => RawWindowHandle::Xlib(window.raw_window_handle()),
             #[cfg(feature = "wayland")]
             &Window::Wayland(ref window) => RawWindowHandle::Wayland(window.raw_window_handle()),
+            #[cfg(feature = "mock")]
+            &Window::Mock(ref window) => RawWindowHandle::Wayland(window.raw_window_handle()),
         }
     }
 }
@@ -519,8 +799,15 @@ unsafe extern "C" fn x_error_callback(
     display: *mut x11::ffi::Display,
     event: *mut x11::ffi::XErrorEvent,
 ) -> c_int {
-    let xconn_lock = X11_BACKEND.lock();
-    if let Ok(ref xconn) = *xconn_lock {
+    // On the displays of the application, only the errors of our own requests are ours. The
+    // other errors are passed on to the handler set before ours, as are those of other displays.
+    let xconn = X11_CONNECTIONS
+        .lock()
+        .iter()
+        .filter_map(Weak::upgrade)
+        .find(|xconn| xconn.display == display)
+        .filter(|xconn| xconn.is_own_request((*event).serial));
+    if let Some(xconn) = xconn {
         // `assume_init` is safe here because the array consists of `MaybeUninit` values,
         // which do not require initialization.
         let mut buf: [MaybeUninit<c_char>; 1024] = MaybeUninit::uninit().assume_init();
@@ -542,6 +829,8 @@ unsafe extern "C" fn x_error_callback(
         error!("X11 error: {:#?}", error);
 
         *xconn.latest_error.lock() = Some(error);
+    } else if let Some(previous_handler) = x11::previous_error_handler() {
+        return previous_handler(display, event);
     }
     // Fun fact: this return value is completely ignored.
     0
@@ -552,6 +841,8 @@ pub enum EventLoop<T: 'static> {
     Wayland(wayland::EventLoop<T>),
     #[cfg(feature = "x11")]
     X(x11::EventLoop<T>),
+    #[cfg(feature = "mock")]
+    Mock(mock::EventLoop<T>),
 }
 
 pub enum EventLoopProxy<T: 'static> {
@@ -559,6 +850,8 @@ pub enum EventLoopProxy<T: 'static> {
     X(x11::EventLoopProxy<T>),
     #[cfg(feature = "wayland")]
     Wayland(wayland::EventLoopProxy<T>),
+    #[cfg(feature = "mock")]
+    Mock(mock::EventLoopProxy<T>),
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
@@ -592,8 +885,14 @@ impl<T: 'static> EventLoop<T> {
                     #[cfg(not(feature = "wayland"))]
                     panic!("wayland feature is not enabled");
                 }
+                "mock" => {
+                    #[cfg(feature = "mock")]
+                    return EventLoop::new_mock();
+                    #[cfg(not(feature = "mock"))]
+                    panic!("mock feature is not enabled");
+                }
                 _ => panic!(
-                    "Unknown environment variable value for {}, try one of `x11`,`wayland`",
+                    "Unknown environment variable value for {}, try one of `x11`,`wayland`,`mock`",
                     BACKEND_PREFERENCE_ENV_VAR,
                 ),
             }
@@ -651,6 +950,28 @@ impl<T: 'static> EventLoop<T> {
         Ok(EventLoop::X(x11::EventLoop::new(xconn)))
     }
 
+    #[cfg(feature = "x11")]
+    pub unsafe fn new_x11_from_display(
+        display: *mut c_void,
+    ) -> Result<EventLoop<T>, XNotSupported> {
+        let xconn = XConnection::from_display(display as *mut _, Some(x_error_callback))?;
+        let xconn = register_x11_connection(xconn);
+        let _requests = OwnX11Requests::begin();
+        Ok(EventLoop::X(x11::EventLoop::new(xconn)))
+    }
+
+    #[cfg(feature = "wayland")]
+    pub unsafe fn new_wayland_from_display(
+        display: *mut c_void,
+    ) -> Result<EventLoop<T>, Box<dyn Error>> {
+        wayland::EventLoop::from_display(display).map(EventLoop::Wayland)
+    }
+
+    #[cfg(feature = "mock")]
+    pub fn new_mock() -> EventLoop<T> {
+        EventLoop::Mock(mock::EventLoop::new())
+    }
+
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.create_proxy(); as EventLoopProxy)
     }
@@ -659,6 +980,8 @@ impl<T: 'static> EventLoop<T> {
     where
         F: FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
+        #[cfg(feature = "x11")]
+        let callback = with_application_x11_requests(callback);
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.run_return(callback))
     }
 
@@ -666,6 +989,8 @@ impl<T: 'static> EventLoop<T> {
     where
         F: 'static + FnMut(crate::event::Event<'_, T>, &RootELW<T>, &mut ControlFlow),
     {
+        #[cfg(feature = "x11")]
+        let callback = with_application_x11_requests(callback);
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.run(callback))
     }
 
@@ -685,15 +1010,18 @@ pub enum EventLoopWindowTarget<T> {
     Wayland(wayland::EventLoopWindowTarget<T>),
     #[cfg(feature = "x11")]
     X(x11::EventLoopWindowTarget<T>),
+    #[cfg(feature = "mock")]
+    Mock(mock::EventLoopWindowTarget<T>),
 }
 
 impl<T> EventLoopWindowTarget<T> {
     #[inline]
+    #[cfg(feature = "wayland")]
     pub fn is_wayland(&self) -> bool {
         match *self {
             #[cfg(feature = "wayland")]
             EventLoopWindowTarget::Wayland(_) => true,
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "mock"))]
             _ => false,
         }
     }
@@ -708,11 +1036,19 @@ impl<T> EventLoopWindowTarget<T> {
                 .map(MonitorHandle::Wayland)
                 .collect(),
             #[cfg(feature = "x11")]
-            EventLoopWindowTarget::X(ref evlp) => evlp
-                .x_connection()
+            EventLoopWindowTarget::X(ref evlp) => {
+                let _requests = OwnX11Requests::begin();
+                evlp.x_connection()
+                    .available_monitors()
+                    .into_iter()
+                    .map(MonitorHandle::X)
+                    .collect()
+            }
+            #[cfg(feature = "mock")]
+            EventLoopWindowTarget::Mock(ref evlp) => evlp
                 .available_monitors()
                 .into_iter()
-                .map(MonitorHandle::X)
+                .map(MonitorHandle::Mock)
                 .collect(),
         }
     }
@@ -724,13 +1060,75 @@ impl<T> EventLoopWindowTarget<T> {
             EventLoopWindowTarget::Wayland(ref evlp) => evlp.primary_monitor(),
             #[cfg(feature = "x11")]
             EventLoopWindowTarget::X(ref evlp) => {
+                let _requests = OwnX11Requests::begin();
                 let primary_monitor = MonitorHandle::X(evlp.x_connection().primary_monitor());
                 Some(RootMonitorHandle {
                     inner: primary_monitor,
                 })
             }
+            #[cfg(feature = "mock")]
+            EventLoopWindowTarget::Mock(ref evlp) => evlp.primary_monitor(),
         }
     }
+
+    #[inline]
+    #[cfg(target_os = "linux")]
+    pub fn gamepad_info(&self, device_id: gamepad::DeviceId) -> Option<GamepadInfo> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.gamepad_info(device_id))
+    }
+
+    #[inline]
+    #[cfg(target_os = "linux")]
+    pub fn watch_gamepads(&self) {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.watch_gamepads())
+    }
+
+    #[inline]
+    pub fn clipboard_text(&self, kind: ClipboardKind) -> Result<Option<String>, ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.clipboard_text(kind))
+    }
+
+    #[inline]
+    pub fn set_clipboard_text(
+        &self,
+        kind: ClipboardKind,
+        text: String,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.set_clipboard_text(kind, text))
+    }
+
+    #[inline]
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Result<Vec<String>, ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.clipboard_mime_types(kind))
+    }
+
+    #[inline]
+    pub fn clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.clipboard_data(kind, mime_type))
+    }
+
+    #[inline]
+    pub fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.set_clipboard_data(kind, data))
+    }
+
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        source: CustomCursorSource,
+    ) -> Result<PlatformCustomCursor, ExternalError> {
+        Ok(
+            x11_or_wayland!(match self; EventLoopWindowTarget(evlp) => evlp.create_custom_cursor(source)?; as PlatformCustomCursor),
+        )
+    }
 }
 
 fn sticky_exit_callback<T, F>(
@@ -0,0 +1,76 @@
+//! Placement of popup windows, for the backends which position them themselves.
+
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::window::PopupGravity;
+
+/// Returns the position of a popup of `size`, placed next to `anchor_rect` as described by
+/// `gravity`, all in the same coordinate space.
+///
+/// A popup which doesn't fit inside of `bounds` is flipped to the other side of the anchor
+/// rectangle when it fits there, then slid back inside.
+pub fn place(
+    anchor_rect: (PhysicalPosition<i32>, PhysicalSize<u32>),
+    gravity: PopupGravity,
+    size: PhysicalSize<u32>,
+    bounds: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+) -> PhysicalPosition<i32> {
+    let (anchor_position, anchor_size) = anchor_rect;
+    let (direction_x, direction_y) = match gravity {
+        PopupGravity::Center => (0, 0),
+        PopupGravity::Top => (0, -1),
+        PopupGravity::Bottom => (0, 1),
+        PopupGravity::Left => (-1, 0),
+        PopupGravity::Right => (1, 0),
+        PopupGravity::TopLeft => (-1, -1),
+        PopupGravity::TopRight => (1, -1),
+        PopupGravity::BottomLeft => (-1, 1),
+        PopupGravity::BottomRight => (1, 1),
+    };
+
+    let x = place_on_axis(
+        (anchor_position.x, anchor_size.width as i32),
+        direction_x,
+        size.width as i32,
+        bounds.map(|(position, size)| (position.x, position.x + size.width as i32)),
+    );
+    let y = place_on_axis(
+        (anchor_position.y, anchor_size.height as i32),
+        direction_y,
+        size.height as i32,
+        bounds.map(|(position, size)| (position.y, position.y + size.height as i32)),
+    );
+    PhysicalPosition::new(x, y)
+}
+
+/// Places a popup of `length` along one axis. `direction` is `-1` when the popup extends towards
+/// the start of the axis, `1` when it extends towards the end and `0` when it's centered.
+fn place_on_axis(
+    (anchor_start, anchor_length): (i32, i32),
+    direction: i32,
+    length: i32,
+    bounds: Option<(i32, i32)>,
+) -> i32 {
+    let start_for = |direction: i32| {
+        let anchor = anchor_start + anchor_length * (direction + 1) / 2;
+        anchor - length * (1 - direction) / 2
+    };
+    let mut start = start_for(direction);
+
+    let (min, max) = match bounds {
+        Some(bounds) => bounds,
+        None => return start,
+    };
+    let fits = |start: i32| start >= min && start + length <= max;
+    if !fits(start) && fits(start_for(-direction)) {
+        start = start_for(-direction);
+    }
+
+    // Keep the start of the popup visible when it's larger than the bounds.
+    if start + length > max {
+        start = max - length;
+    }
+    if start < min {
+        start = min;
+    }
+    start
+}
@@ -0,0 +1,414 @@
+//! Clipboard and primary selection handling.
+
+use std::io::{self, Read, Write};
+use std::os::raw::c_int;
+use std::os::unix::io::AsRawFd;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use sctk::data_device::DataSourceEvent;
+use sctk::primary_selection::PrimarySelectionSourceEvent;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::DispatchData;
+
+use crate::clipboard::ClipboardKind;
+use crate::error::{ExternalError, NotSupportedError};
+use crate::platform_impl::OsError;
+
+use super::event_loop::WinitState;
+use super::EventLoopWindowTarget;
+
+/// How long we wait for the owner of a selection to send the next chunk of its contents.
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Mime types under which we offer text, and accept it in order of preference.
+const TEXT_MIME_TYPES: [&str; 4] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "TEXT",
+    "text/plain",
+];
+
+/// Data of a selection, along with the mime type of each representation.
+type Contents = Vec<(String, Vec<u8>)>;
+
+/// Data we're offering through the selections we currently own.
+#[derive(Default)]
+pub struct Clipboard {
+    /// Contents of the regular clipboard.
+    clipboard: Option<Rc<Contents>>,
+
+    /// Contents of the primary selection.
+    primary: Option<Rc<Contents>>,
+}
+
+impl Clipboard {
+    #[inline]
+    fn contents(&self, kind: ClipboardKind) -> Option<&Rc<Contents>> {
+        match kind {
+            ClipboardKind::Clipboard => self.clipboard.as_ref(),
+            ClipboardKind::Primary => self.primary.as_ref(),
+        }
+    }
+
+    #[inline]
+    fn contents_mut(&mut self, kind: ClipboardKind) -> &mut Option<Rc<Contents>> {
+        match kind {
+            ClipboardKind::Clipboard => &mut self.clipboard,
+            ClipboardKind::Primary => &mut self.primary,
+        }
+    }
+
+    /// Whether we currently own the given selection.
+    #[inline]
+    pub fn owns(&self, kind: ClipboardKind) -> bool {
+        self.contents(kind).is_some()
+    }
+
+    /// Forgets the contents of a cancelled source, unless they were replaced since.
+    fn forget(&mut self, kind: ClipboardKind, contents: &Rc<Contents>) {
+        let slot = self.contents_mut(kind);
+        if matches!(slot.as_ref(), Some(current) if Rc::ptr_eq(current, contents)) {
+            slot.take();
+        }
+    }
+}
+
+#[inline]
+fn wayland_error(message: &'static str) -> ExternalError {
+    ExternalError::Os(os_error!(OsError::WaylandMisc(message)))
+}
+
+#[inline]
+fn pick_text_mime_type(mime_types: &[String]) -> Option<String> {
+    TEXT_MIME_TYPES
+        .iter()
+        .find(|mime_type| mime_types.iter().any(|offered| offered == *mime_type))
+        .map(|mime_type| (*mime_type).to_owned())
+}
+
+/// Mime types under which we offer the given contents, including the aliases of text.
+pub(super) fn offered_mime_types(contents: &[(String, Vec<u8>)]) -> Vec<String> {
+    let mut mime_types: Vec<String> = contents
+        .iter()
+        .map(|(mime_type, _)| mime_type.clone())
+        .collect();
+    if mime_types
+        .iter()
+        .any(|mime_type| mime_type == TEXT_MIME_TYPES[0])
+    {
+        for alias in &TEXT_MIME_TYPES[1..] {
+            if !mime_types.iter().any(|mime_type| mime_type == alias) {
+                mime_types.push((*alias).to_owned());
+            }
+        }
+    }
+    mime_types
+}
+
+/// Data of the contents in the given mime type, falling back to text for its aliases.
+fn lookup<'a>(contents: &'a [(String, Vec<u8>)], mime_type: &str) -> Option<&'a [u8]> {
+    let find = |mime_type: &str| {
+        contents
+            .iter()
+            .find(|(offered, _)| offered == mime_type)
+            .map(|(_, data)| data.as_slice())
+    };
+
+    find(mime_type).or_else(|| {
+        if TEXT_MIME_TYPES[1..].contains(&mime_type) {
+            find(TEXT_MIME_TYPES[0])
+        } else {
+            None
+        }
+    })
+}
+
+/// Reads the contents of a selection until its owner closes the pipe, giving up if the owner
+/// doesn't send anything for `RECEIVE_TIMEOUT`.
+fn read_selection<P: Read + AsRawFd>(pipe: &mut P) -> Result<Vec<u8>, &'static str> {
+    let fd = pipe.as_raw_fd();
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            return Err("Failed to read the selection");
+        }
+    }
+
+    let mut data = Vec::new();
+    let mut chunk = [0; 4096];
+    let mut deadline = Instant::now() + RECEIVE_TIMEOUT;
+    loop {
+        match pipe.read(&mut chunk) {
+            Ok(0) => return Ok(data),
+            Ok(len) => {
+                data.extend_from_slice(&chunk[..len]);
+                deadline = Instant::now() + RECEIVE_TIMEOUT;
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err("Timed out waiting for the selection owner");
+                }
+
+                let mut fd = libc::pollfd {
+                    fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let timeout = (deadline - now).as_millis() as c_int;
+                unsafe { libc::poll(&mut fd, 1, timeout) };
+            }
+            Err(_) => return Err("Failed to read the selection"),
+        }
+    }
+}
+
+/// Sends our data in the requested mime type.
+#[inline]
+pub(super) fn send_data<W: Write>(contents: &[(String, Vec<u8>)], mime_type: &str, mut pipe: W) {
+    if let Some(data) = lookup(contents, mime_type) {
+        let _ = pipe.write_all(data);
+    }
+}
+
+/// Forgets the contents of our cancelled source.
+#[inline]
+fn source_cancelled(
+    kind: ClipboardKind,
+    contents: &Rc<Contents>,
+    mut dispatch_data: DispatchData<'_>,
+) {
+    let winit_state = dispatch_data.get::<WinitState>().unwrap();
+    winit_state.clipboard.forget(kind, contents);
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    /// Seat through which we talk to the selections.
+    fn clipboard_seat(&self) -> Option<WlSeat> {
+        if let Some((seat, _)) = self.state.borrow().latest_input_serial.as_ref() {
+            return Some(seat.clone());
+        }
+
+        self.env.get_all_seats().first().map(|seat| seat.detach())
+    }
+
+    /// Our own contents of the given selection, if we own it.
+    #[inline]
+    fn own_clipboard_contents(&self, kind: ClipboardKind) -> Option<Rc<Contents>> {
+        self.state.borrow().clipboard.contents(kind).cloned()
+    }
+
+    #[inline]
+    fn check_clipboard_supported(&self, kind: ClipboardKind) -> Result<(), ExternalError> {
+        if kind == ClipboardKind::Primary && self.env.get_primary_selection_manager().is_none() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+        Ok(())
+    }
+
+    /// Runs `f` on the mime types of the current selection offer, if any.
+    fn with_offered_mime_types<F, R>(&self, kind: ClipboardKind, f: F) -> Option<R>
+    where
+        F: FnOnce(&[String]) -> R,
+    {
+        let seat = self.clipboard_seat()?;
+
+        let mut result = None;
+        match kind {
+            ClipboardKind::Clipboard => {
+                let _ = self.env.with_data_device(&seat, |device| {
+                    device.with_selection(|offer| {
+                        result = offer.map(|offer| offer.with_mime_types(f));
+                    })
+                });
+            }
+            ClipboardKind::Primary => {
+                let _ = self.env.with_primary_selection(&seat, |device| {
+                    device.with_selection(|offer| {
+                        result = offer.map(|offer| offer.with_mime_types(f));
+                    })
+                });
+            }
+        }
+        result
+    }
+
+    /// Receives the data of the current selection offer in the mime type `pick` chooses.
+    fn receive_selection<F>(
+        &self,
+        kind: ClipboardKind,
+        pick: F,
+    ) -> Result<Option<Vec<u8>>, ExternalError>
+    where
+        F: FnOnce(&[String]) -> Option<String>,
+    {
+        let seat = match self.clipboard_seat() {
+            Some(seat) => seat,
+            None => return Ok(None),
+        };
+
+        let mut pipe = None;
+        let mut result = Ok(());
+        match kind {
+            ClipboardKind::Clipboard => {
+                let _ = self.env.with_data_device(&seat, |device| {
+                    device.with_selection(|offer| {
+                        let offer = match offer {
+                            Some(offer) => offer,
+                            None => return,
+                        };
+                        if let Some(mime_type) = offer.with_mime_types(pick) {
+                            result = offer
+                                .receive(mime_type)
+                                .map(|read_pipe| pipe = Some(read_pipe));
+                        }
+                    })
+                });
+            }
+            ClipboardKind::Primary => {
+                let _ = self.env.with_primary_selection(&seat, |device| {
+                    device.with_selection(|offer| {
+                        let offer = match offer {
+                            Some(offer) => offer,
+                            None => return,
+                        };
+                        if let Some(mime_type) = offer.with_mime_types(pick) {
+                            result = offer
+                                .receive(mime_type)
+                                .map(|read_pipe| pipe = Some(read_pipe));
+                        }
+                    })
+                });
+            }
+        }
+        result.map_err(|()| wayland_error("Failed to receive the selection"))?;
+
+        let mut pipe = match pipe {
+            Some(pipe) => pipe,
+            None => return Ok(None),
+        };
+
+        // The owner only gets our request once it's flushed.
+        let _ = self.display.flush();
+
+        read_selection(&mut pipe).map(Some).map_err(wayland_error)
+    }
+
+    pub fn clipboard_text(&self, kind: ClipboardKind) -> Result<Option<String>, ExternalError> {
+        self.check_clipboard_supported(kind)?;
+
+        let data = match self.own_clipboard_contents(kind) {
+            Some(contents) => lookup(&contents, TEXT_MIME_TYPES[0]).map(<[u8]>::to_vec),
+            None => self.receive_selection(kind, pick_text_mime_type)?,
+        };
+        Ok(data.map(|data| String::from_utf8_lossy(&data).into_owned()))
+    }
+
+    #[inline]
+    pub fn set_clipboard_text(
+        &self,
+        kind: ClipboardKind,
+        text: String,
+    ) -> Result<(), ExternalError> {
+        self.set_clipboard_data(
+            kind,
+            vec![(TEXT_MIME_TYPES[0].to_owned(), text.into_bytes())],
+        )
+    }
+
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Result<Vec<String>, ExternalError> {
+        self.check_clipboard_supported(kind)?;
+
+        if let Some(contents) = self.own_clipboard_contents(kind) {
+            return Ok(offered_mime_types(&contents));
+        }
+
+        let mime_types = self.with_offered_mime_types(kind, <[String]>::to_vec);
+        Ok(mime_types.unwrap_or_default())
+    }
+
+    pub fn clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.check_clipboard_supported(kind)?;
+
+        if let Some(contents) = self.own_clipboard_contents(kind) {
+            return Ok(lookup(&contents, mime_type).map(<[u8]>::to_vec));
+        }
+
+        self.receive_selection(kind, |mime_types| {
+            mime_types
+                .iter()
+                .find(|offered| *offered == mime_type)
+                .cloned()
+        })
+    }
+
+    pub fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        // The compositor only lets clients with input focus set the selections.
+        let (seat, serial) = self
+            .state
+            .borrow()
+            .latest_input_serial
+            .clone()
+            .ok_or_else(|| wayland_error("No input was received to set the selection with"))?;
+
+        self.check_clipboard_supported(kind)?;
+
+        let mime_types = offered_mime_types(&data);
+        let contents = Rc::new(data);
+        *self.state.borrow_mut().clipboard.contents_mut(kind) = Some(contents.clone());
+
+        let result = match kind {
+            ClipboardKind::Clipboard => {
+                let source = self
+                    .env
+                    .new_data_source(mime_types, move |event, dispatch_data| match event {
+                        DataSourceEvent::Send { mime_type, pipe } => {
+                            send_data(&contents, &mime_type, pipe)
+                        }
+                        DataSourceEvent::Cancelled => {
+                            source_cancelled(kind, &contents, dispatch_data)
+                        }
+                        _ => (),
+                    });
+                self.env
+                    .with_data_device(&seat, |device| device.set_selection(&Some(source), serial))
+            }
+            ClipboardKind::Primary => {
+                let source = self.env.new_primary_selection_source(
+                    mime_types,
+                    move |event, dispatch_data| match event {
+                        PrimarySelectionSourceEvent::Send { mime_type, pipe } => {
+                            send_data(&contents, &mime_type, pipe)
+                        }
+                        PrimarySelectionSourceEvent::Cancelled => {
+                            source_cancelled(kind, &contents, dispatch_data)
+                        }
+                    },
+                );
+                self.env.with_primary_selection(&seat, |device| {
+                    device.set_selection(&Some(source), serial)
+                })
+            }
+        };
+
+        if result.is_err() {
+            self.state.borrow_mut().clipboard.contents_mut(kind).take();
+            return Err(wayland_error(
+                "The seat has no device to set the selection on",
+            ));
+        }
+
+        let _ = self.display.flush();
+        Ok(())
+    }
+}
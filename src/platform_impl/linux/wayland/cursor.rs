@@ -0,0 +1,341 @@
+//! Custom and animated cursors, which we draw on the cursor surfaces of the windows.
+
+use std::io;
+use std::rc::Rc;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use sctk::reexports::calloop::timer::{Timeout, TimerHandle};
+use sctk::reexports::client::protocol::wl_buffer::WlBuffer;
+use sctk::reexports::client::protocol::wl_shm::{Format, WlShm};
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Attached;
+use sctk::shm::MemPool;
+
+use wayland_cursor::{Cursor, CursorTheme};
+
+use crate::cursor::CustomCursorSource;
+use crate::error::ExternalError;
+use crate::platform_impl::platform::common::cursor as common_cursor;
+use crate::platform_impl::OsError;
+use crate::window::CursorIcon;
+
+use super::{EventLoopWindowTarget, WindowId};
+
+/// A custom cursor, whose buffers live on the event loop's thread.
+///
+/// It only identifies the buffers in the `CursorManager`, so it can be sent to other threads.
+#[derive(Debug, Clone)]
+pub struct CustomCursor {
+    token: Arc<()>,
+}
+
+impl PartialEq for CustomCursor {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.token, &other.token)
+    }
+}
+
+/// A frame of a cursor drawn on a cursor surface.
+pub struct DrawnFrame {
+    /// The hotspot, in surface coordinates.
+    pub hotspot: (i32, i32),
+
+    /// How long the frame is shown, if the cursor is animated.
+    pub duration: Option<Duration>,
+}
+
+/// Placement of an image in the memory pool.
+#[derive(Debug, Clone, Copy)]
+struct ImageLayout {
+    scale_factor: i32,
+    offset: i32,
+    width: i32,
+    height: i32,
+    hotspot_x: i32,
+    hotspot_y: i32,
+}
+
+/// The images of a custom cursor, shared with the compositor.
+pub struct CursorBuffers {
+    pool: MemPool,
+
+    /// The duration of each frame, along with its layouts and the scale factor of their image.
+    frames: Vec<(Duration, Vec<(u32, ImageLayout)>)>,
+}
+
+impl CursorBuffers {
+    fn new(mut pool: MemPool, source: &CustomCursorSource) -> io::Result<Self> {
+        let mut frames = Vec::with_capacity(source.frames.len());
+        let mut data = Vec::new();
+        for frame in &source.frames {
+            let mut images = Vec::with_capacity(frame.images.len());
+            for image in &frame.images {
+                images.push((
+                    image.scale_factor,
+                    ImageLayout {
+                        scale_factor: image.scale_factor as i32,
+                        offset: data.len() as i32,
+                        width: image.rgba.width as i32,
+                        height: image.rgba.height as i32,
+                        hotspot_x: image.hotspot_x as i32,
+                        hotspot_y: image.hotspot_y as i32,
+                    },
+                ));
+                for pixel in common_cursor::argb_pixels(image) {
+                    data.extend_from_slice(&pixel.to_le_bytes());
+                }
+            }
+            frames.push((frame.duration, images));
+        }
+
+        pool.resize(data.len())?;
+        pool.mmap()[..data.len()].copy_from_slice(&data);
+        Ok(Self { pool, frames })
+    }
+
+    /// Draws the image of a frame for the given scale factor on the cursor surface.
+    ///
+    /// The frame index wraps around at the end of the animation.
+    pub fn draw(&self, surface: &WlSurface, scale_factor: i32, frame: usize) -> DrawnFrame {
+        let (duration, images) = &self.frames[frame % self.frames.len()];
+        let layout = common_cursor::pick_variant(images, scale_factor as f64);
+
+        // The compositor releases the buffer, and the pool destroys it, once it's replaced.
+        let buffer = self.pool.buffer(
+            layout.offset,
+            layout.width,
+            layout.height,
+            layout.width * 4,
+            Format::Argb8888,
+        );
+        attach_buffer(
+            surface,
+            &buffer,
+            layout.scale_factor,
+            (layout.width, layout.height),
+        );
+
+        DrawnFrame {
+            hotspot: (
+                layout.hotspot_x / layout.scale_factor,
+                layout.hotspot_y / layout.scale_factor,
+            ),
+            duration: Some(*duration).filter(|_| self.frames.len() > 1),
+        }
+    }
+}
+
+/// Cursor themes for each scale factor, to draw animated theme cursors with.
+///
+/// SCTK's themed pointers only show the first image of theme cursors, so we draw the animated
+/// ones ourselves.
+struct CursorThemes {
+    shm: Attached<WlShm>,
+    name: String,
+    size: u32,
+    themes: Vec<(u32, CursorTheme)>,
+}
+
+impl CursorThemes {
+    fn new(shm: Attached<WlShm>) -> Self {
+        // Same as `ThemeSpec::System`.
+        let name = std::env::var("XCURSOR_THEME").unwrap_or_else(|_| "default".into());
+        let size = std::env::var("XCURSOR_SIZE")
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(24);
+        Self {
+            shm,
+            name,
+            size,
+            themes: Vec::new(),
+        }
+    }
+
+    /// The first cursor of the theme out of `names`, unless it's a static one.
+    fn animated_cursor(&mut self, names: &[&str], scale_factor: u32) -> Option<&Cursor> {
+        let index = match self
+            .themes
+            .iter()
+            .position(|(theme_scale_factor, _)| *theme_scale_factor == scale_factor)
+        {
+            Some(index) => index,
+            None => {
+                let theme =
+                    CursorTheme::load_from_name(&self.name, self.size * scale_factor, &self.shm);
+                self.themes.push((scale_factor, theme));
+                self.themes.len() - 1
+            }
+        };
+
+        let theme = &mut self.themes[index].1;
+        let name = names.iter().find(|name| theme.get_cursor(name).is_some())?;
+        theme
+            .get_cursor(name)
+            .filter(|cursor| cursor.image_count() > 1)
+    }
+}
+
+/// Cursors the windows draw themselves, which can't leave the event loop's thread.
+pub struct CursorManager {
+    /// Buffers of the custom cursors created on the event loop.
+    custom_cursors: Vec<(Weak<()>, Rc<CursorBuffers>)>,
+
+    /// Themes to draw animated theme cursors with.
+    themes: CursorThemes,
+
+    /// Timer showing the next frames of animated cursors.
+    animation_timer: TimerHandle<WindowId>,
+}
+
+impl CursorManager {
+    pub fn new(shm: Attached<WlShm>, animation_timer: TimerHandle<WindowId>) -> Self {
+        Self {
+            custom_cursors: Vec::new(),
+            themes: CursorThemes::new(shm),
+            animation_timer,
+        }
+    }
+
+    /// The buffers of the cursor, unless it was created on another event loop.
+    pub fn custom_cursor(&self, cursor: &CustomCursor) -> Option<Rc<CursorBuffers>> {
+        let token = Arc::downgrade(&cursor.token);
+        self.custom_cursors
+            .iter()
+            .find(|(cached, _)| cached.ptr_eq(&token))
+            .map(|(_, buffers)| buffers.clone())
+    }
+
+    fn insert_custom_cursor(&mut self, cursor: &CustomCursor, buffers: CursorBuffers) {
+        // Windows still showing a dropped cursor keep its buffers around on their own.
+        self.custom_cursors
+            .retain(|(cached, _)| cached.upgrade().is_some());
+        self.custom_cursors
+            .push((Arc::downgrade(&cursor.token), Rc::new(buffers)));
+    }
+
+    /// Draws a frame of the theme cursor for the icon on the cursor surface, if it's animated.
+    ///
+    /// Static theme cursors are left to the themed pointers.
+    pub fn draw_theme_cursor(
+        &mut self,
+        cursor_icon: CursorIcon,
+        surface: &WlSurface,
+        scale_factor: i32,
+        frame: usize,
+    ) -> Option<DrawnFrame> {
+        let cursor = self
+            .themes
+            .animated_cursor(theme_cursor_names(cursor_icon), scale_factor as u32)?;
+        let image = &cursor[frame % cursor.image_count()];
+        let (width, height) = image.dimensions();
+        let (hotspot_x, hotspot_y) = image.hotspot();
+        attach_buffer(surface, image, scale_factor, (width as i32, height as i32));
+
+        Some(DrawnFrame {
+            hotspot: (
+                hotspot_x as i32 / scale_factor,
+                hotspot_y as i32 / scale_factor,
+            ),
+            // Guard against broken themes, which would have us redraw continuously.
+            duration: Some(Duration::from_millis(image.delay().max(1) as u64)),
+        })
+    }
+
+    /// Schedules the next frame of the window's cursor.
+    #[inline]
+    pub fn schedule_frame(&self, window_id: WindowId, duration: Duration) -> Timeout {
+        self.animation_timer.add_timeout(duration, window_id)
+    }
+
+    /// Cancels a scheduled frame.
+    #[inline]
+    pub fn cancel_frame(&self, timeout: &Timeout) {
+        self.animation_timer.cancel_timeout(timeout);
+    }
+}
+
+fn attach_buffer(surface: &WlSurface, buffer: &WlBuffer, scale_factor: i32, size: (i32, i32)) {
+    let (width, height) = size;
+    surface.set_buffer_scale(scale_factor);
+    surface.attach(Some(buffer), 0, 0);
+    if surface.as_ref().version() >= 4 {
+        surface.damage_buffer(0, 0, width, height);
+    } else {
+        surface.damage(0, 0, width / scale_factor, height / scale_factor);
+    }
+    surface.commit();
+}
+
+/// The names of the theme cursors for an icon, in order of preference.
+pub fn theme_cursor_names(cursor_icon: CursorIcon) -> &'static [&'static str] {
+    match cursor_icon {
+        CursorIcon::Alias => &["link"],
+        CursorIcon::Arrow => &["arrow"],
+        CursorIcon::Cell => &["plus"],
+        CursorIcon::Copy => &["copy"],
+        CursorIcon::Crosshair => &["crosshair"],
+        CursorIcon::Default => &["left_ptr"],
+        CursorIcon::Hand => &["hand"],
+        CursorIcon::Help => &["question_arrow"],
+        CursorIcon::Move => &["move"],
+        CursorIcon::Grab => &["openhand", "grab"],
+        CursorIcon::Grabbing => &["closedhand", "grabbing"],
+        CursorIcon::Progress => &["progress"],
+        CursorIcon::AllScroll => &["all-scroll"],
+        CursorIcon::ContextMenu => &["context-menu"],
+
+        CursorIcon::NoDrop => &["no-drop", "circle"],
+        CursorIcon::NotAllowed => &["crossed_circle"],
+
+        // Resize cursors
+        CursorIcon::EResize => &["right_side"],
+        CursorIcon::NResize => &["top_side"],
+        CursorIcon::NeResize => &["top_right_corner"],
+        CursorIcon::NwResize => &["top_left_corner"],
+        CursorIcon::SResize => &["bottom_side"],
+        CursorIcon::SeResize => &["bottom_right_corner"],
+        CursorIcon::SwResize => &["bottom_left_corner"],
+        CursorIcon::WResize => &["left_side"],
+        CursorIcon::EwResize => &["h_double_arrow"],
+        CursorIcon::NsResize => &["v_double_arrow"],
+        CursorIcon::NwseResize => &["bd_double_arrow", "size_bdiag"],
+        CursorIcon::NeswResize => &["fd_double_arrow", "size_fdiag"],
+        CursorIcon::ColResize => &["split_h", "h_double_arrow"],
+        CursorIcon::RowResize => &["split_v", "v_double_arrow"],
+        CursorIcon::Text => &["text", "xterm"],
+        CursorIcon::VerticalText => &["vertical-text"],
+
+        CursorIcon::Wait => &["watch"],
+
+        CursorIcon::ZoomIn => &["zoom-in"],
+        CursorIcon::ZoomOut => &["zoom-out"],
+    }
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    pub fn create_custom_cursor(
+        &self,
+        source: CustomCursorSource,
+    ) -> Result<CustomCursor, ExternalError> {
+        let buffers = self
+            .env
+            .create_simple_pool(|_| {})
+            .and_then(|pool| CursorBuffers::new(pool, &source))
+            .map_err(|_| {
+                ExternalError::Os(os_error!(OsError::WaylandMisc(
+                    "Failed to create the cursor buffers"
+                )))
+            })?;
+
+        let cursor = CustomCursor {
+            token: Arc::new(()),
+        };
+        self.state
+            .borrow_mut()
+            .cursor_manager
+            .insert_custom_cursor(&cursor, buffers);
+        Ok(cursor)
+    }
+}
@@ -9,13 +9,23 @@ use sctk::reexports::client::protocol::wl_shell::WlShell;
 use sctk::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
 use sctk::reexports::client::{Attached, DispatchData};
 use sctk::reexports::client::protocol::wl_shm::WlShm;
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
+use sctk::reexports::protocols::misc::gtk_primary_selection::client::gtk_primary_selection_device_manager::GtkPrimarySelectionDeviceManager;
 use sctk::reexports::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
 
+use sctk::data_device::{DataDevice, DataDeviceHandler, DataDeviceHandling, DndEvent};
 use sctk::environment::{Environment, SimpleGlobal};
 use sctk::output::{OutputHandler, OutputHandling, OutputInfo, OutputStatusListener};
+use sctk::primary_selection::{
+    PrimarySelectionDevice, PrimarySelectionDeviceManager, PrimarySelectionHandler,
+    PrimarySelectionHandling,
+};
 use sctk::seat::{SeatData, SeatHandler, SeatHandling, SeatListener};
 use sctk::shell::{Shell, ShellHandler, ShellHandling};
 use sctk::shm::ShmHandler;
@@ -49,7 +59,12 @@ sctk::environment!(WinitEnv,
         ZxdgDecorationManagerV1 => decoration_manager,
         ZwpRelativePointerManagerV1 => relative_pointer_manager,
         ZwpPointerConstraintsV1 => pointer_constraints,
+        ZwpPointerGesturesV1 => pointer_gestures,
         ZwpTextInputManagerV3 => text_input_manager,
+        ZwpTabletManagerV2 => tablet_manager,
+        WlDataDeviceManager => data_device_manager,
+        ZwpPrimarySelectionDeviceManagerV1 => primary_selection_manager,
+        GtkPrimarySelectionDeviceManager => primary_selection_manager,
     ],
     multis = [
         WlSeat => seats,
@@ -75,9 +90,17 @@ pub struct WinitEnv {
 
     pointer_constraints: SimpleGlobal<ZwpPointerConstraintsV1>,
 
+    pointer_gestures: SimpleGlobal<ZwpPointerGesturesV1>,
+
     text_input_manager: SimpleGlobal<ZwpTextInputManagerV3>,
 
+    tablet_manager: SimpleGlobal<ZwpTabletManagerV2>,
+
     decoration_manager: SimpleGlobal<ZxdgDecorationManagerV1>,
+
+    data_device_manager: DataDeviceHandler,
+
+    primary_selection_manager: PrimarySelectionHandler,
 }
 
 impl WinitEnv {
@@ -86,7 +109,7 @@ impl WinitEnv {
         let outputs = OutputHandler::new();
 
         // Keyboard/Pointer/Touch input.
-        let seats = SeatHandler::new();
+        let mut seats = SeatHandler::new();
 
         // Essential globals.
         let shm = ShmHandler::new();
@@ -106,9 +129,19 @@ impl WinitEnv {
         // Pointer grab functionality.
         let pointer_constraints = SimpleGlobal::new();
 
+        // Touchpad gestures.
+        let pointer_gestures = SimpleGlobal::new();
+
         // IME handling.
         let text_input_manager = SimpleGlobal::new();
 
+        // Drawing tablets.
+        let tablet_manager = SimpleGlobal::new();
+
+        // Clipboard and primary selection handling.
+        let data_device_manager = DataDeviceHandler::init(&mut seats);
+        let primary_selection_manager = PrimarySelectionHandler::init(&mut seats);
+
         Self {
             seats,
             outputs,
@@ -119,7 +152,11 @@ impl WinitEnv {
             decoration_manager,
             relative_pointer_manager,
             pointer_constraints,
+            pointer_gestures,
             text_input_manager,
+            tablet_manager,
+            data_device_manager,
+            primary_selection_manager,
         }
     }
 }
@@ -139,6 +176,35 @@ impl SeatHandling for WinitEnv {
     }
 }
 
+impl DataDeviceHandling for WinitEnv {
+    fn set_callback<F: FnMut(WlSeat, DndEvent<'_>, DispatchData<'_>) + 'static>(
+        &mut self,
+        callback: F,
+    ) -> Result<(), ()> {
+        self.data_device_manager.set_callback(callback)
+    }
+
+    fn with_device<F: FnOnce(&DataDevice)>(&self, seat: &WlSeat, f: F) -> Result<(), ()> {
+        self.data_device_manager.with_device(seat, f)
+    }
+}
+
+impl PrimarySelectionHandling for WinitEnv {
+    fn with_primary_selection<F: FnOnce(&PrimarySelectionDevice)>(
+        &self,
+        seat: &WlSeat,
+        f: F,
+    ) -> Result<(), ()> {
+        self.primary_selection_manager
+            .with_primary_selection(seat, f)
+    }
+
+    fn get_primary_selection_manager(&self) -> Option<PrimarySelectionDeviceManager> {
+        self.primary_selection_manager
+            .get_primary_selection_manager()
+    }
+}
+
 impl OutputHandling for WinitEnv {
     fn listen<F: FnMut(WlOutput, &OutputInfo, DispatchData<'_>) + 'static>(
         &mut self,
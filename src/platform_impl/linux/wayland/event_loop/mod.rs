@@ -1,6 +1,7 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::error::Error;
+use std::os::raw::c_void;
 use std::process;
 use std::rc::Rc;
 use std::time::{Duration, Instant};
@@ -17,8 +18,13 @@ use sctk::WaylandSource;
 
 use crate::event::{Event, StartCause, WindowEvent};
 use crate::event_loop::{ControlFlow, EventLoopWindowTarget as RootEventLoopWindowTarget};
+#[cfg(target_os = "linux")]
+use crate::gamepad::GamepadInfo;
+#[cfg(target_os = "linux")]
+use crate::platform_impl::platform::gamepad::{self, Gamepads};
 use crate::platform_impl::platform::sticky_exit_callback;
 
+use super::cursor::CursorManager;
 use super::env::{WindowingFeatures, WinitEnv};
 use super::output::OutputManager;
 use super::seat::SeatManager;
@@ -32,7 +38,8 @@ mod state;
 pub use proxy::EventLoopProxy;
 pub use state::WinitState;
 
-use sink::EventSink;
+use sink::SinkEvent;
+pub use sink::{DragMotion, EventSink};
 
 pub struct EventLoopWindowTarget<T> {
     /// Wayland display.
@@ -68,6 +75,29 @@ pub struct EventLoopWindowTarget<T> {
     _marker: std::marker::PhantomData<T>,
 }
 
+impl<T> EventLoopWindowTarget<T> {
+    #[cfg(target_os = "linux")]
+    pub fn gamepad_info(&self, device_id: gamepad::DeviceId) -> Option<GamepadInfo> {
+        self.state.borrow().gamepads.as_ref()?.info(device_id)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn watch_gamepads(&self) {
+        let mut state = self.state.borrow_mut();
+        let WinitState {
+            gamepads,
+            event_sink,
+            ..
+        } = &mut *state;
+        if let Some(gamepads) = gamepads {
+            // The gamepads which are plugged in already don't make the poll readable.
+            gamepads.watch_devices();
+            gamepads.dispatch(|device_id, event| event_sink.push_gamepad_event(event, device_id));
+            self.event_loop_awakener.ping();
+        }
+    }
+}
+
 pub struct EventLoop<T: 'static> {
     /// Event loop.
     event_loop: calloop::EventLoop<WinitState>,
@@ -93,8 +123,24 @@ pub struct EventLoop<T: 'static> {
 
 impl<T: 'static> EventLoop<T> {
     pub fn new() -> Result<EventLoop<T>, Box<dyn Error>> {
-        // Connect to wayland server and setup event queue.
+        // Connect to wayland server.
         let display = Display::connect_to_env()?;
+        Self::with_display(display)
+    }
+
+    /// Builds an event loop on the `wl_display` of the application, which stays connected when
+    /// the event loop is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `display_ptr` must be a valid `wl_display`, which outlives the event loop.
+    pub unsafe fn from_display(display_ptr: *mut c_void) -> Result<EventLoop<T>, Box<dyn Error>> {
+        let display = Display::from_external_display(display_ptr as *mut _);
+        Self::with_display(display)
+    }
+
+    fn with_display(display: Display) -> Result<EventLoop<T>, Box<dyn Error>> {
+        // Setup event queue, which is our own even when the connection is shared.
         let mut event_queue = display.create_event_queue();
         let display_proxy = display.attach(event_queue.token());
 
@@ -109,7 +155,7 @@ impl<T: 'static> EventLoop<T> {
         // Create a theme manager.
         let compositor = env.require_global::<WlCompositor>();
         let shm = env.require_global::<WlShm>();
-        let theme_manager = ThemeManager::init(ThemeSpec::System, compositor, shm);
+        let theme_manager = ThemeManager::init(ThemeSpec::System, compositor, shm.clone());
 
         // Setup theme seat and output managers.
         let seat_manager = SeatManager::new(&env, event_loop.handle(), theme_manager.clone());
@@ -144,6 +190,50 @@ impl<T: 'static> EventLoop<T> {
             },
         )?;
 
+        // A timer showing the next frames of animated cursors.
+        let cursor_animation_timer = calloop::timer::Timer::new()?;
+        let cursor_manager = CursorManager::new(shm, cursor_animation_timer.handle());
+        event_loop.handle().insert_source(
+            cursor_animation_timer,
+            move |window_id, _, winit_state| {
+                if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                    window_handle.next_cursor_frame(&mut winit_state.cursor_manager);
+                }
+            },
+        )?;
+
+        // Gamepads, which are read from their own file descriptor. The devices are only opened
+        // once the application asks for them.
+        #[cfg(target_os = "linux")]
+        let gamepads = Gamepads::new()
+            .map_err(|err| err.to_string())
+            .and_then(|gamepads| {
+                let source = calloop::generic::Generic::from_fd(
+                    gamepads.fd(),
+                    calloop::Interest::Readable,
+                    calloop::Mode::Level,
+                );
+                event_loop
+                    .handle()
+                    .insert_source(source, move |_, _, winit_state| {
+                        let WinitState {
+                            gamepads,
+                            event_sink,
+                            ..
+                        } = winit_state;
+                        if let Some(gamepads) = gamepads {
+                            gamepads.dispatch(|device_id, event| {
+                                event_sink.push_gamepad_event(event, device_id)
+                            });
+                        }
+                        Ok(())
+                    })
+                    .map_err(|err| err.to_string())?;
+                Ok(gamepads)
+            })
+            .map_err(|err| warn!("Failed to create the gamepad poll: {}", err))
+            .ok();
+
         let event_loop_handle = event_loop.handle();
         let window_map = HashMap::new();
         let event_sink = EventSink::new();
@@ -157,6 +247,11 @@ impl<T: 'static> EventLoop<T> {
                 window_map,
                 event_sink,
                 window_updates,
+                latest_input_serial: None,
+                clipboard: Default::default(),
+                cursor_manager,
+                #[cfg(target_os = "linux")]
+                gamepads,
             }),
             event_loop_handle,
             output_manager,
@@ -346,8 +441,37 @@ impl<T: 'static> EventLoop<T> {
 
             // Handle pending window events.
             for event in event_sink_back_buffer.drain(..) {
-                let event = event.map_nonuser_event().unwrap();
-                sticky_exit_callback(event, &self.window_target, &mut control_flow, &mut callback);
+                match event {
+                    SinkEvent::Event(event) => {
+                        let event = event.map_nonuser_event().unwrap();
+                        sticky_exit_callback(
+                            event,
+                            &self.window_target,
+                            &mut control_flow,
+                            &mut callback,
+                        );
+                    }
+                    SinkEvent::DragMoved(motion) => {
+                        let mut accepted_action = motion.accepted_action;
+                        let window_id = crate::window::WindowId(
+                            crate::platform_impl::WindowId::Wayland(motion.window_id),
+                        );
+                        sticky_exit_callback(
+                            Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::DragMoved {
+                                    position: motion.position,
+                                    action: motion.action,
+                                    accepted_action: &mut accepted_action,
+                                },
+                            },
+                            &self.window_target,
+                            &mut control_flow,
+                            &mut callback,
+                        );
+                        (motion.respond)(accepted_action);
+                    }
+                }
             }
 
             // Send events cleared.
@@ -373,6 +497,12 @@ impl<T: 'static> EventLoop<T> {
 
                 // Handle redraw request.
                 if window_update.redraw_requested {
+                    self.with_state(|state| {
+                        if let Some(window_handle) = state.window_map.get(window_id) {
+                            window_handle.window.set_drawn();
+                        }
+                    });
+
                     sticky_exit_callback(
                         Event::RedrawRequested(crate::window::WindowId(
                             crate::platform_impl::WindowId::Wayland(*window_id),
@@ -511,7 +641,7 @@ impl<T: 'static> EventLoop<T> {
             crate::platform_impl::EventLoopWindowTarget::Wayland(ref mut window_target) => {
                 window_target.state.get_mut()
             }
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "mock"))]
             _ => unreachable!(),
         };
 
@@ -526,7 +656,7 @@ impl<T: 'static> EventLoop<T> {
             crate::platform_impl::EventLoopWindowTarget::Wayland(ref mut window_target) => {
                 window_target.state.get_mut()
             }
-            #[cfg(feature = "x11")]
+            #[cfg(any(feature = "x11", feature = "mock"))]
             _ => unreachable!(),
         };
 
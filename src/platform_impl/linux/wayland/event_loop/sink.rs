@@ -1,6 +1,11 @@
 //! An event loop's sink to deliver events from the Wayland event callbacks.
 
+use crate::clipboard::ClipboardKind;
+use crate::dnd::DndAction;
+use crate::dpi::PhysicalPosition;
 use crate::event::{DeviceEvent, DeviceId as RootDeviceId, Event, WindowEvent};
+#[cfg(target_os = "linux")]
+use crate::platform_impl::platform::gamepad::DeviceId as GamepadDeviceId;
 use crate::platform_impl::platform::{DeviceId as PlatformDeviceId, WindowId as PlatformWindowId};
 use crate::window::WindowId as RootWindowId;
 
@@ -10,7 +15,28 @@ use super::{DeviceId, WindowId};
 /// to the winit's user.
 #[derive(Default)]
 pub struct EventSink {
-    pub window_events: Vec<Event<'static, ()>>,
+    pub window_events: Vec<SinkEvent>,
+}
+
+/// An event waiting in the sink.
+pub enum SinkEvent {
+    Event(Event<'static, ()>),
+
+    /// `WindowEvent::DragMoved` borrows the answer of the user, so it's only built when delivered.
+    DragMoved(DragMotion),
+}
+
+/// A drag moving over one of our windows, waiting for the user to accept or reject the drop.
+pub struct DragMotion {
+    pub window_id: WindowId,
+    pub position: PhysicalPosition<f64>,
+    pub action: DndAction,
+
+    /// Action the drop is accepted with unless the user changes it.
+    pub accepted_action: Option<DndAction>,
+
+    /// Tells the compositor about the answer of the user.
+    pub respond: Box<dyn FnOnce(Option<DndAction>)>,
 }
 
 impl EventSink {
@@ -20,17 +46,40 @@ impl EventSink {
 
     /// Add new device event to a queue.
     pub fn push_device_event(&mut self, event: DeviceEvent, device_id: DeviceId) {
-        self.window_events.push(Event::DeviceEvent {
-            event,
-            device_id: RootDeviceId(PlatformDeviceId::Wayland(device_id)),
-        });
+        self.window_events
+            .push(SinkEvent::Event(Event::DeviceEvent {
+                event,
+                device_id: RootDeviceId(PlatformDeviceId::Wayland(device_id)),
+            }));
+    }
+
+    /// Add new gamepad event to a queue.
+    #[cfg(target_os = "linux")]
+    pub fn push_gamepad_event(&mut self, event: DeviceEvent, device_id: GamepadDeviceId) {
+        self.window_events
+            .push(SinkEvent::Event(Event::DeviceEvent {
+                event,
+                device_id: RootDeviceId(PlatformDeviceId::Gamepad(device_id)),
+            }));
     }
 
     /// Add new window event to a queue.
     pub fn push_window_event(&mut self, event: WindowEvent<'static>, window_id: WindowId) {
-        self.window_events.push(Event::WindowEvent {
-            event,
-            window_id: RootWindowId(PlatformWindowId::Wayland(window_id)),
-        });
+        self.window_events
+            .push(SinkEvent::Event(Event::WindowEvent {
+                event,
+                window_id: RootWindowId(PlatformWindowId::Wayland(window_id)),
+            }));
+    }
+
+    /// Add new drag motion to a queue.
+    pub fn push_drag_motion(&mut self, motion: DragMotion) {
+        self.window_events.push(SinkEvent::DragMoved(motion));
+    }
+
+    /// Add new clipboard change to a queue.
+    pub fn push_clipboard_changed(&mut self, kind: ClipboardKind) {
+        self.window_events
+            .push(SinkEvent::Event(Event::ClipboardChanged(kind)));
     }
 }
@@ -2,7 +2,13 @@
 
 use std::collections::HashMap;
 
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+
 use super::EventSink;
+#[cfg(target_os = "linux")]
+use crate::platform_impl::platform::gamepad::Gamepads;
+use crate::platform_impl::wayland::clipboard::Clipboard;
+use crate::platform_impl::wayland::cursor::CursorManager;
 use crate::platform_impl::wayland::window::shim::{WindowHandle, WindowUpdate};
 use crate::platform_impl::wayland::WindowId;
 
@@ -22,4 +28,20 @@ pub struct WinitState {
     /// and requests from winit's windows are being forwarded to them either via
     /// `WindowUpdate` or buffer on the associated with it `WindowHandle`.
     pub window_map: HashMap<WindowId, WindowHandle>,
+
+    /// Seat and serial of the latest keyboard or pointer button input, which the
+    /// compositor requires to let us set the selections.
+    pub latest_input_serial: Option<(WlSeat, u32)>,
+
+    /// Data we're offering through the selections.
+    pub clipboard: Clipboard,
+
+    /// Cursors the windows draw themselves.
+    pub cursor_manager: CursorManager,
+
+    /// Gamepads and joysticks, whose events are delivered as device events.
+    ///
+    /// `None` if their poll couldn't be created.
+    #[cfg(target_os = "linux")]
+    pub gamepads: Option<Gamepads>,
 }
@@ -8,10 +8,13 @@
 
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 
+pub use cursor::CustomCursor;
 pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
 pub use output::{MonitorHandle, VideoMode};
 pub use window::Window;
 
+mod clipboard;
+mod cursor;
 mod env;
 mod event_loop;
 mod output;
@@ -27,6 +30,10 @@ impl DeviceId {
     }
 }
 
+/// A tool of a drawing tablet, identified by its `zwp_tablet_tool_v2`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TabletToolId(u32);
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct WindowId(usize);
 
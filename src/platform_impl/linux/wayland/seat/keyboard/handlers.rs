@@ -1,11 +1,14 @@
 //! Handling of various keyboard events.
 
 use sctk::reexports::client::protocol::wl_keyboard::KeyState;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
 
 use sctk::seat::keyboard::Event as KeyboardEvent;
 
-use crate::event::{ElementState, KeyboardInput, ModifiersState, WindowEvent};
+use crate::event::{ElementState, KeyEvent, KeyboardInput, ModifiersState, WindowEvent};
+use crate::platform_impl::platform::common::keymap as common_keymap;
 use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::window::shim;
 use crate::platform_impl::wayland::{self, DeviceId};
 
 use super::keymap;
@@ -16,11 +19,15 @@ pub(super) fn handle_keyboard(
     event: KeyboardEvent<'_>,
     inner: &mut KeyboardInner,
     winit_state: &mut WinitState,
+    seat: &WlSeat,
 ) {
     let event_sink = &mut winit_state.event_sink;
     match event {
-        KeyboardEvent::Enter { surface, .. } => {
+        KeyboardEvent::Enter {
+            surface, serial, ..
+        } => {
             let window_id = wayland::make_wid(&surface);
+            winit_state.latest_input_serial = Some((seat.clone(), serial));
 
             // Window gained focus.
             event_sink.push_window_event(WindowEvent::Focused(true), window_id);
@@ -47,6 +54,9 @@ pub(super) fn handle_keyboard(
             // Window lost focus.
             event_sink.push_window_event(WindowEvent::Focused(false), window_id);
 
+            // We won't get release events for keys that are still held down.
+            inner.pressed_keys.clear();
+
             // Reset the id.
             inner.target_window_id = None;
         }
@@ -55,8 +65,11 @@ pub(super) fn handle_keyboard(
             keysym,
             state,
             utf8,
+            serial,
             ..
         } => {
+            winit_state.latest_input_serial = Some((seat.clone(), serial));
+
             let window_id = match inner.target_window_id {
                 Some(window_id) => window_id,
                 None => return,
@@ -68,6 +81,13 @@ pub(super) fn handle_keyboard(
                 _ => unreachable!(),
             };
 
+            // Releases are still reported, so that no key stays pressed.
+            if state == ElementState::Pressed
+                && shim::is_blocked_by_modal(&winit_state.window_map, window_id)
+            {
+                return;
+            }
+
             let virtual_keycode = keymap::keysym_to_vkey(keysym);
 
             event_sink.push_window_event(
@@ -87,6 +107,38 @@ pub(super) fn handle_keyboard(
                 window_id,
             );
 
+            let text = utf8.clone().filter(|text| !text.is_empty());
+            let logical_key = match state {
+                ElementState::Pressed => {
+                    let logical_key = common_keymap::keysym_to_key(keysym, text.as_deref());
+                    inner.pressed_keys.insert(rawkey, logical_key.clone());
+                    logical_key
+                }
+                ElementState::Released => inner
+                    .pressed_keys
+                    .remove(&rawkey)
+                    .unwrap_or_else(|| common_keymap::keysym_to_key(keysym, None)),
+            };
+            let physical_key = common_keymap::scancode_to_keycode(rawkey);
+
+            event_sink.push_window_event(
+                WindowEvent::KeyEvent {
+                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                        DeviceId,
+                    )),
+                    event: KeyEvent {
+                        physical_key,
+                        logical_key,
+                        text,
+                        location: common_keymap::keycode_location(physical_key),
+                        state,
+                        is_repeat: false,
+                    },
+                    is_synthetic: false,
+                },
+                window_id,
+            );
+
             // Send ReceivedCharacter event only on ElementState::Pressed.
             if ElementState::Released == state {
                 return;
@@ -108,6 +160,9 @@ pub(super) fn handle_keyboard(
                 Some(window_id) => window_id,
                 None => return,
             };
+            if shim::is_blocked_by_modal(&winit_state.window_map, window_id) {
+                return;
+            }
 
             let virtual_keycode = keymap::keysym_to_vkey(keysym);
 
@@ -128,6 +183,32 @@ pub(super) fn handle_keyboard(
                 window_id,
             );
 
+            let text = utf8.clone().filter(|text| !text.is_empty());
+            let logical_key = inner
+                .pressed_keys
+                .get(&rawkey)
+                .cloned()
+                .unwrap_or_else(|| common_keymap::keysym_to_key(keysym, text.as_deref()));
+            let physical_key = common_keymap::scancode_to_keycode(rawkey);
+
+            event_sink.push_window_event(
+                WindowEvent::KeyEvent {
+                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                        DeviceId,
+                    )),
+                    event: KeyEvent {
+                        physical_key,
+                        logical_key,
+                        text,
+                        location: common_keymap::keycode_location(physical_key),
+                        state: ElementState::Pressed,
+                        is_repeat: true,
+                    },
+                    is_synthetic: false,
+                },
+                window_id,
+            );
+
             if let Some(txt) = utf8 {
                 for ch in txt.chars() {
                     event_sink.push_window_event(WindowEvent::ReceivedCharacter(ch), window_id);
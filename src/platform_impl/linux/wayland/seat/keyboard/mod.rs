@@ -1,6 +1,7 @@
 //! Wayland keyboard handling.
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use sctk::reexports::client::protocol::wl_keyboard::WlKeyboard;
@@ -11,7 +12,7 @@ use sctk::reexports::calloop::{LoopHandle, Source};
 
 use sctk::seat::keyboard::{self, RepeatSource};
 
-use crate::event::ModifiersState;
+use crate::event::{Key, ModifiersState};
 use crate::platform_impl::wayland::event_loop::WinitState;
 use crate::platform_impl::wayland::WindowId;
 
@@ -35,6 +36,7 @@ impl Keyboard {
         modifiers_state: Rc<RefCell<ModifiersState>>,
     ) -> Option<Self> {
         let mut inner = KeyboardInner::new(modifiers_state);
+        let keyboard_seat = seat.detach();
         let keyboard_data = keyboard::map_keyboard_repeat(
             loop_handle.clone(),
             &seat,
@@ -42,7 +44,7 @@ impl Keyboard {
             keyboard::RepeatKind::System,
             move |event, _, mut dispatch_data| {
                 let winit_state = dispatch_data.get::<WinitState>().unwrap();
-                handlers::handle_keyboard(event, &mut inner, winit_state);
+                handlers::handle_keyboard(event, &mut inner, winit_state, &keyboard_seat);
             },
         );
 
@@ -81,6 +83,9 @@ struct KeyboardInner {
 
     /// Current state of modifiers keys.
     modifiers_state: Rc<RefCell<ModifiersState>>,
+
+    /// Keys currently pressed, with the logical key they had when they were pressed.
+    pressed_keys: HashMap<u32, Key>,
 }
 
 impl KeyboardInner {
@@ -89,6 +94,7 @@ impl KeyboardInner {
             target_window_id: None,
             pending_modifers_state: None,
             modifiers_state,
+            pressed_keys: HashMap::new(),
         }
     }
 }
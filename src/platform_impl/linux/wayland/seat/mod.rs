@@ -5,12 +5,16 @@ use std::rc::Rc;
 
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
 
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::Attached;
 
 use sctk::environment::Environment;
+use sctk::primary_selection::PrimarySelectionDeviceManager;
 use sctk::reexports::calloop::LoopHandle;
 use sctk::seat::pointer::ThemeManager;
 use sctk::seat::{SeatData, SeatListener};
@@ -21,11 +25,15 @@ use crate::event::ModifiersState;
 
 mod keyboard;
 pub mod pointer;
+mod selection;
+mod tablet;
 pub mod text_input;
 mod touch;
 
 use keyboard::Keyboard;
 use pointer::Pointers;
+use selection::SelectionObserver;
+use tablet::TabletSeat;
 use text_input::TextInput;
 use touch::Touch;
 
@@ -42,13 +50,21 @@ impl SeatManager {
     ) -> Self {
         let relative_pointer_manager = env.get_global::<ZwpRelativePointerManagerV1>();
         let pointer_constraints = env.get_global::<ZwpPointerConstraintsV1>();
+        let pointer_gestures = env.get_global::<ZwpPointerGesturesV1>();
         let text_input_manager = env.get_global::<ZwpTextInputManagerV3>();
+        let tablet_manager = env.get_global::<ZwpTabletManagerV2>();
+        let data_device_manager = env.get_global::<WlDataDeviceManager>();
+        let primary_selection_manager = env.get_primary_selection_manager();
 
         let mut inner = SeatManagerInner::new(
             theme_manager,
             relative_pointer_manager,
             pointer_constraints,
+            pointer_gestures,
             text_input_manager,
+            tablet_manager,
+            data_device_manager,
+            primary_selection_manager,
             loop_handle,
         );
 
@@ -86,19 +102,36 @@ struct SeatManagerInner {
     /// Pointer constraints.
     pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
 
+    /// Pointer gestures.
+    pointer_gestures: Option<Attached<ZwpPointerGesturesV1>>,
+
     /// Text input manager.
     text_input_manager: Option<Attached<ZwpTextInputManagerV3>>,
 
+    /// Tablet manager.
+    tablet_manager: Option<Attached<ZwpTabletManagerV2>>,
+
+    /// Data device manager.
+    data_device_manager: Option<Attached<WlDataDeviceManager>>,
+
+    /// Primary selection manager.
+    primary_selection_manager: Option<PrimarySelectionDeviceManager>,
+
     /// A theme manager.
     theme_manager: ThemeManager,
 }
 
 impl SeatManagerInner {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         theme_manager: ThemeManager,
         relative_pointer_manager: Option<Attached<ZwpRelativePointerManagerV1>>,
         pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
+        pointer_gestures: Option<Attached<ZwpPointerGesturesV1>>,
         text_input_manager: Option<Attached<ZwpTextInputManagerV3>>,
+        tablet_manager: Option<Attached<ZwpTabletManagerV2>>,
+        data_device_manager: Option<Attached<WlDataDeviceManager>>,
+        primary_selection_manager: Option<PrimarySelectionDeviceManager>,
         loop_handle: LoopHandle<WinitState>,
     ) -> Self {
         Self {
@@ -106,7 +139,11 @@ impl SeatManagerInner {
             loop_handle,
             relative_pointer_manager,
             pointer_constraints,
+            pointer_gestures,
             text_input_manager,
+            tablet_manager,
+            data_device_manager,
+            primary_selection_manager,
             theme_manager,
         }
     }
@@ -123,6 +160,18 @@ impl SeatManagerInner {
 
         let seat_info = &mut self.seats[index];
 
+        // Handle selections.
+        if seat_data.defunct {
+            seat_info.selection = None;
+        } else if seat_info.selection.is_none() {
+            seat_info.selection = Some(SelectionObserver::new(
+                seat,
+                &self.loop_handle,
+                self.data_device_manager.as_ref(),
+                self.primary_selection_manager.as_ref(),
+            ));
+        }
+
         // Pointer handling.
         if seat_data.has_pointer && !seat_data.defunct {
             if seat_info.pointer.is_none() {
@@ -131,6 +180,11 @@ impl SeatManagerInner {
                     &self.theme_manager,
                     &self.relative_pointer_manager,
                     &self.pointer_constraints,
+                    &self.pointer_gestures,
+                    seat_info
+                        .selection
+                        .as_ref()
+                        .and_then(SelectionObserver::drag_device),
                     seat_info.modifiers_state.clone(),
                 ));
             }
@@ -168,6 +222,19 @@ impl SeatManagerInner {
                 seat_info.text_input = Some(TextInput::new(&seat, &text_input_manager));
             }
         }
+
+        // Handle drawing tablets.
+        if let Some(tablet_manager) = self.tablet_manager.as_ref() {
+            if seat_data.defunct {
+                seat_info.tablet = None;
+            } else if seat_info.tablet.is_none() {
+                seat_info.tablet = Some(TabletSeat::new(
+                    seat,
+                    tablet_manager,
+                    seat_info.modifiers_state.clone(),
+                ));
+            }
+        }
     }
 }
 
@@ -188,6 +255,12 @@ struct SeatInfo {
     /// Text input handling aka IME.
     text_input: Option<TextInput>,
 
+    /// Drawing tablets handling.
+    tablet: Option<TabletSeat>,
+
+    /// Selection observing, to report clipboard changes.
+    selection: Option<SelectionObserver>,
+
     /// The current state of modifiers observed in keyboard handler.
     ///
     /// We keep modifiers state on a seat, since it's being used by pointer events as well.
@@ -202,6 +275,8 @@ impl SeatInfo {
             pointer: None,
             touch: None,
             text_input: None,
+            tablet: None,
+            selection: None,
             modifiers_state: Rc::new(RefCell::new(ModifiersState::default())),
         }
     }
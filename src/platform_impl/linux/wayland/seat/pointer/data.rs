@@ -6,9 +6,11 @@ use std::rc::Rc;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Attached;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::{ZwpPointerConstraintsV1};
-use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
 
 use crate::event::{ModifiersState, TouchPhase};
+use crate::platform_impl::wayland::seat::selection::DragDevice;
+
+use super::PointerConstraint;
 
 /// A data being used by pointer handlers.
 pub(super) struct PointerData {
@@ -24,7 +26,10 @@ pub(super) struct PointerData {
     /// Pointer constraints.
     pub pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
 
-    pub confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+    pub pointer_constraint: Rc<RefCell<Option<PointerConstraint>>>,
+
+    /// Data device to start drags with.
+    pub drag_device: Option<DragDevice>,
 
     /// A latest event serial.
     pub latest_serial: Rc<Cell<u32>>,
@@ -35,16 +40,18 @@ pub(super) struct PointerData {
 
 impl PointerData {
     pub fn new(
-        confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+        pointer_constraint: Rc<RefCell<Option<PointerConstraint>>>,
         pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
+        drag_device: Option<DragDevice>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
     ) -> Self {
         Self {
             surface: None,
             latest_serial: Rc::new(Cell::new(0)),
-            confined_pointer,
+            pointer_constraint,
             modifiers_state,
             pointer_constraints,
+            drag_device,
             axis_data: AxisData::new(),
         }
     }
@@ -72,3 +79,16 @@ impl AxisData {
         }
     }
 }
+
+/// The data of a touchpad gesture in progress.
+#[derive(Default)]
+pub(super) struct GestureData {
+    /// Winit's surface the gesture started over.
+    pub surface: Option<WlSurface>,
+
+    /// The number of fingers of a swipe.
+    pub fingers: u32,
+
+    /// The last scale of a pinch, relative to the distance between the fingers when it started.
+    pub scale: f64,
+}
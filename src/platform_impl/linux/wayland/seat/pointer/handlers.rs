@@ -6,6 +6,8 @@ use std::rc::Rc;
 use sctk::reexports::client::protocol::wl_pointer::{self, Event as PointerEvent};
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_v1::Event as RelativePointerEvent;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_pinch_v1::Event as PinchGestureEvent;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_swipe_v1::Event as SwipeGestureEvent;
 
 use sctk::seat::pointer::ThemedPointer;
 
@@ -14,9 +16,10 @@ use crate::event::{
     DeviceEvent, ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent,
 };
 use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::window::shim;
 use crate::platform_impl::wayland::{self, DeviceId};
 
-use super::{PointerData, WinitPointer};
+use super::{GestureData, PointerData, WinitPointer};
 
 // These values are comming from <linux/input-event-codes.h>.
 const BTN_LEFT: u32 = 0x110;
@@ -58,12 +61,13 @@ pub(super) fn handle_pointer(
             // Notify window that pointer entered the surface.
             let winit_pointer = WinitPointer {
                 pointer,
-                confined_pointer: Rc::downgrade(&pointer_data.confined_pointer),
+                pointer_constraint: Rc::downgrade(&pointer_data.pointer_constraint),
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
                 seat,
+                drag_device: pointer_data.drag_device.clone(),
             };
-            window_handle.pointer_entered(winit_pointer);
+            window_handle.pointer_entered(winit_pointer, &mut winit_state.cursor_manager);
 
             event_sink.push_window_event(
                 WindowEvent::CursorEntered {
@@ -101,10 +105,11 @@ pub(super) fn handle_pointer(
             // Notify a window that pointer is no longer observing it.
             let winit_pointer = WinitPointer {
                 pointer,
-                confined_pointer: Rc::downgrade(&pointer_data.confined_pointer),
+                pointer_constraint: Rc::downgrade(&pointer_data.pointer_constraint),
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
                 seat,
+                drag_device: pointer_data.drag_device.clone(),
             };
             window_handle.pointer_left(winit_pointer);
 
@@ -150,6 +155,7 @@ pub(super) fn handle_pointer(
             ..
         } => {
             pointer_data.latest_serial.replace(serial);
+            winit_state.latest_input_serial = Some((seat.clone(), serial));
             let window_id = match pointer_data.surface.as_ref().map(wayland::make_wid) {
                 Some(window_id) => window_id,
                 None => return,
@@ -161,6 +167,13 @@ pub(super) fn handle_pointer(
                 _ => unreachable!(),
             };
 
+            // Releases are still reported, so that no button stays pressed.
+            if state == ElementState::Pressed
+                && shim::is_blocked_by_modal(&winit_state.window_map, window_id)
+            {
+                return;
+            }
+
             let button = match button {
                 BTN_LEFT => MouseButton::Left,
                 BTN_RIGHT => MouseButton::Right,
@@ -303,3 +316,117 @@ pub(super) fn handle_relative_pointer(event: RelativePointerEvent, winit_state:
             .push_device_event(DeviceEvent::MouseMotion { delta: (dx, dy) }, DeviceId)
     }
 }
+
+#[inline]
+pub(super) fn handle_swipe_gesture(
+    event: SwipeGestureEvent,
+    gesture_data: &mut GestureData,
+    winit_state: &mut WinitState,
+) {
+    let (delta, phase) = match event {
+        SwipeGestureEvent::Begin {
+            surface, fingers, ..
+        } => {
+            gesture_data.surface = Some(surface);
+            gesture_data.fingers = fingers;
+            ((0.0, 0.0), TouchPhase::Started)
+        }
+        SwipeGestureEvent::Update { dx, dy, .. } => ((dx, dy), TouchPhase::Moved),
+        SwipeGestureEvent::End { cancelled, .. } => {
+            let phase = if cancelled != 0 {
+                TouchPhase::Cancelled
+            } else {
+                TouchPhase::Ended
+            };
+            ((0.0, 0.0), phase)
+        }
+        _ => return,
+    };
+
+    let surface = match gesture_data.surface {
+        Some(ref surface) => surface,
+        None => return,
+    };
+    let window_id = wayland::make_wid(surface);
+    if !winit_state.window_map.contains_key(&window_id) {
+        return;
+    }
+
+    let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+    let delta = LogicalPosition::<f64>::from(delta).to_physical(scale_factor);
+    winit_state.event_sink.push_window_event(
+        WindowEvent::SwipeGesture {
+            device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId)),
+            fingers: gesture_data.fingers,
+            delta,
+            phase,
+        },
+        window_id,
+    );
+
+    if phase == TouchPhase::Ended || phase == TouchPhase::Cancelled {
+        gesture_data.surface = None;
+    }
+}
+
+#[inline]
+pub(super) fn handle_pinch_gesture(
+    event: PinchGestureEvent,
+    gesture_data: &mut GestureData,
+    winit_state: &mut WinitState,
+) {
+    let (scale_delta, rotation, phase) = match event {
+        PinchGestureEvent::Begin { surface, .. } => {
+            gesture_data.surface = Some(surface);
+            gesture_data.scale = 1.0;
+            (0.0, 0.0, TouchPhase::Started)
+        }
+        PinchGestureEvent::Update {
+            scale, rotation, ..
+        } => {
+            let scale_delta = scale - gesture_data.scale;
+            gesture_data.scale = scale;
+            (scale_delta, rotation, TouchPhase::Moved)
+        }
+        PinchGestureEvent::End { cancelled, .. } => {
+            let phase = if cancelled != 0 {
+                TouchPhase::Cancelled
+            } else {
+                TouchPhase::Ended
+            };
+            (0.0, 0.0, phase)
+        }
+        _ => return,
+    };
+
+    let window_id = match gesture_data.surface {
+        Some(ref surface) => wayland::make_wid(surface),
+        None => return,
+    };
+    if !winit_state.window_map.contains_key(&window_id) {
+        return;
+    }
+
+    let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+    winit_state.event_sink.push_window_event(
+        WindowEvent::PinchGesture {
+            device_id,
+            delta: scale_delta,
+            phase,
+        },
+        window_id,
+    );
+    // The rotation is reported clockwise.
+    winit_state.event_sink.push_window_event(
+        WindowEvent::RotationGesture {
+            device_id,
+            delta: -rotation,
+            phase,
+        },
+        window_id,
+    );
+
+    if phase == TouchPhase::Ended || phase == TouchPhase::Cancelled {
+        gesture_data.surface = None;
+    }
+}
@@ -4,6 +4,7 @@ use std::cell::{Cell, RefCell};
 use std::rc::{Rc, Weak};
 
 use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::protocol::wl_region::WlRegion;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Attached;
@@ -11,34 +12,46 @@ use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_rela
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::{ZwpPointerConstraintsV1, Lifetime};
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
+use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_locked_pointer_v1::ZwpLockedPointerV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1;
+use sctk::reexports::protocols::unstable::pointer_gestures::v1::client::zwp_pointer_gesture_swipe_v1::ZwpPointerGestureSwipeV1;
 
 use sctk::seat::pointer::{ThemeManager, ThemedPointer};
-use sctk::window::{ConceptFrame, Window};
 
+use crate::dnd::DndActions;
 use crate::event::ModifiersState;
+use crate::platform_impl::wayland::cursor;
 use crate::platform_impl::wayland::event_loop::WinitState;
-use crate::window::CursorIcon;
+use crate::platform_impl::wayland::window::shim::Toplevel;
+use crate::platform_impl::wayland::WindowId;
+use crate::window::{CursorGrabMode, CursorIcon};
+
+use super::selection::DragDevice;
 
 mod data;
 mod handlers;
 
-use data::PointerData;
+use data::{GestureData, PointerData};
 
 /// A proxy to Wayland pointer, which serves requests from a `WindowHandle`.
 pub struct WinitPointer {
     pointer: ThemedPointer,
 
-    /// Create confined pointers.
+    /// Create confined and locked pointers.
     pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
 
-    /// Cursor to handle confine requests.
-    confined_pointer: Weak<RefCell<Option<ZwpConfinedPointerV1>>>,
+    /// Constraint to handle grab requests.
+    pointer_constraint: Weak<RefCell<Option<PointerConstraint>>>,
 
     /// Latest observed serial in pointer events.
     latest_serial: Rc<Cell<u32>>,
 
     /// Seat.
     seat: WlSeat,
+
+    /// Data device to start drags with.
+    drag_device: Option<DragDevice>,
 }
 
 impl PartialEq for WinitPointer {
@@ -63,48 +76,7 @@ impl WinitPointer {
             }
         };
 
-        let cursors: &[&str] = match cursor_icon {
-            CursorIcon::Alias => &["link"],
-            CursorIcon::Arrow => &["arrow"],
-            CursorIcon::Cell => &["plus"],
-            CursorIcon::Copy => &["copy"],
-            CursorIcon::Crosshair => &["crosshair"],
-            CursorIcon::Default => &["left_ptr"],
-            CursorIcon::Hand => &["hand"],
-            CursorIcon::Help => &["question_arrow"],
-            CursorIcon::Move => &["move"],
-            CursorIcon::Grab => &["openhand", "grab"],
-            CursorIcon::Grabbing => &["closedhand", "grabbing"],
-            CursorIcon::Progress => &["progress"],
-            CursorIcon::AllScroll => &["all-scroll"],
-            CursorIcon::ContextMenu => &["context-menu"],
-
-            CursorIcon::NoDrop => &["no-drop", "circle"],
-            CursorIcon::NotAllowed => &["crossed_circle"],
-
-            // Resize cursors
-            CursorIcon::EResize => &["right_side"],
-            CursorIcon::NResize => &["top_side"],
-            CursorIcon::NeResize => &["top_right_corner"],
-            CursorIcon::NwResize => &["top_left_corner"],
-            CursorIcon::SResize => &["bottom_side"],
-            CursorIcon::SeResize => &["bottom_right_corner"],
-            CursorIcon::SwResize => &["bottom_left_corner"],
-            CursorIcon::WResize => &["left_side"],
-            CursorIcon::EwResize => &["h_double_arrow"],
-            CursorIcon::NsResize => &["v_double_arrow"],
-            CursorIcon::NwseResize => &["bd_double_arrow", "size_bdiag"],
-            CursorIcon::NeswResize => &["fd_double_arrow", "size_fdiag"],
-            CursorIcon::ColResize => &["split_h", "h_double_arrow"],
-            CursorIcon::RowResize => &["split_v", "v_double_arrow"],
-            CursorIcon::Text => &["text", "xterm"],
-            CursorIcon::VerticalText => &["vertical-text"],
-
-            CursorIcon::Wait => &["watch"],
-
-            CursorIcon::ZoomIn => &["zoom-in"],
-            CursorIcon::ZoomOut => &["zoom-out"],
-        };
+        let cursors = cursor::theme_cursor_names(cursor_icon);
 
         let serial = Some(self.latest_serial.get());
         for cursor in cursors {
@@ -114,44 +86,127 @@ impl WinitPointer {
         }
     }
 
-    /// Confine the pointer to a surface.
-    pub fn confine(&self, surface: &WlSurface) {
+    /// Show a cursor surface, whose hotspot is given in surface coordinates.
+    pub fn set_custom_cursor(&self, surface: &WlSurface, hotspot: (i32, i32)) {
+        let (hotspot_x, hotspot_y) = hotspot;
+        (*self.pointer).set_cursor(
+            self.latest_serial.get(),
+            Some(surface),
+            hotspot_x,
+            hotspot_y,
+        );
+    }
+
+    /// Confine or lock the pointer to a surface, replacing its current constraint.
+    ///
+    /// A confined pointer is restricted to `region` of the surface, if given.
+    pub fn constrain(&self, surface: &WlSurface, mode: CursorGrabMode, region: Option<&WlRegion>) {
         let pointer_constraints = match &self.pointer_constraints {
             Some(pointer_constraints) => pointer_constraints,
             None => return,
         };
 
-        let confined_pointer = match self.confined_pointer.upgrade() {
-            Some(confined_pointer) => confined_pointer,
+        let pointer_constraint = match self.pointer_constraint.upgrade() {
+            Some(pointer_constraint) => pointer_constraint,
+            // A pointer is gone.
+            None => return,
+        };
+
+        let mut pointer_constraint = pointer_constraint.borrow_mut();
+
+        // A pointer can't have several constraints on the same surface.
+        if let Some(pointer_constraint) = pointer_constraint.take() {
+            pointer_constraint.destroy();
+        }
+
+        let pointer = &self.pointer;
+        *pointer_constraint =
+            match mode {
+                CursorGrabMode::None => None,
+                CursorGrabMode::Confined => Some(PointerConstraint::Confined(
+                    init_confined_pointer(pointer_constraints, surface, pointer, region),
+                )),
+                CursorGrabMode::Locked => Some(PointerConstraint::Locked(init_locked_pointer(
+                    pointer_constraints,
+                    surface,
+                    pointer,
+                ))),
+            };
+    }
+
+    /// Restrict a confined pointer to `region` of its surface, or to the whole surface.
+    pub fn set_confine_region(&self, region: Option<&WlRegion>) {
+        let pointer_constraint = match self.pointer_constraint.upgrade() {
+            Some(pointer_constraint) => pointer_constraint,
             // A pointer is gone.
             None => return,
         };
 
-        *confined_pointer.borrow_mut() = Some(init_confined_pointer(
-            &pointer_constraints,
-            &surface,
-            &*self.pointer,
-        ));
+        let pointer_constraint = pointer_constraint.borrow();
+
+        if let Some(PointerConstraint::Confined(confined_pointer)) = &*pointer_constraint {
+            confined_pointer.set_region(region);
+        }
     }
 
-    /// Tries to unconfine the pointer if the current pointer is confined.
-    pub fn unconfine(&self) {
-        let confined_pointer = match self.confined_pointer.upgrade() {
-            Some(confined_pointer) => confined_pointer,
+    /// Tries to release the pointer if the current pointer is confined or locked.
+    pub fn unconstrain(&self) {
+        let pointer_constraint = match self.pointer_constraint.upgrade() {
+            Some(pointer_constraint) => pointer_constraint,
             // A pointer is gone.
             None => return,
         };
 
-        let mut confined_pointer = confined_pointer.borrow_mut();
+        let mut pointer_constraint = pointer_constraint.borrow_mut();
 
-        if let Some(confined_pointer) = confined_pointer.take() {
-            confined_pointer.destroy();
+        if let Some(pointer_constraint) = pointer_constraint.take() {
+            pointer_constraint.destroy();
         }
     }
 
-    pub fn drag_window(&self, window: &Window<ConceptFrame>) {
+    pub fn drag_window(&self, window: &dyn Toplevel) {
         window.start_interactive_move(&self.seat, self.latest_serial.get());
     }
+
+    /// Start dragging `data` out of the window.
+    ///
+    /// Returns `false` if the seat can't start drags.
+    pub fn start_drag(
+        &self,
+        surface: &WlSurface,
+        data: Vec<(String, Vec<u8>)>,
+        allowed_actions: DndActions,
+        window_id: WindowId,
+    ) -> bool {
+        let drag_device = match self.drag_device.as_ref() {
+            Some(drag_device) => drag_device,
+            None => return false,
+        };
+
+        drag_device.start_drag(
+            surface,
+            self.latest_serial.get(),
+            data,
+            allowed_actions,
+            window_id,
+        );
+        true
+    }
+}
+
+/// A constraint on the pointer, which applies while it's over the constraining surface.
+pub(super) enum PointerConstraint {
+    Confined(ZwpConfinedPointerV1),
+    Locked(ZwpLockedPointerV1),
+}
+
+impl PointerConstraint {
+    fn destroy(self) {
+        match self {
+            PointerConstraint::Confined(confined_pointer) => confined_pointer.destroy(),
+            PointerConstraint::Locked(locked_pointer) => locked_pointer.destroy(),
+        }
+    }
 }
 
 /// A pointer wrapper for easy releasing and managing pointers.
@@ -162,8 +217,11 @@ pub(super) struct Pointers {
     /// A relative pointer handler.
     relative_pointer: Option<ZwpRelativePointerV1>,
 
-    /// Confined pointer.
-    confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
+    /// Confined or locked pointer.
+    pointer_constraint: Rc<RefCell<Option<PointerConstraint>>>,
+
+    /// Touchpad gestures handlers.
+    gestures: Option<(ZwpPointerGestureSwipeV1, ZwpPointerGesturePinchV1)>,
 }
 
 impl Pointers {
@@ -172,12 +230,15 @@ impl Pointers {
         theme_manager: &ThemeManager,
         relative_pointer_manager: &Option<Attached<ZwpRelativePointerManagerV1>>,
         pointer_constraints: &Option<Attached<ZwpPointerConstraintsV1>>,
+        pointer_gestures: &Option<Attached<ZwpPointerGesturesV1>>,
+        drag_device: Option<DragDevice>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
     ) -> Self {
-        let confined_pointer = Rc::new(RefCell::new(None));
+        let pointer_constraint = Rc::new(RefCell::new(None));
         let pointer_data = Rc::new(RefCell::new(PointerData::new(
-            confined_pointer.clone(),
+            pointer_constraint.clone(),
             pointer_constraints.clone(),
+            drag_device,
             modifiers_state,
         )));
         let pointer_seat = seat.detach();
@@ -203,10 +264,16 @@ impl Pointers {
             None => None,
         };
 
+        // Setup touchpad gestures if they're available.
+        let gestures = pointer_gestures
+            .as_ref()
+            .map(|pointer_gestures| init_pointer_gestures(pointer_gestures, &pointer));
+
         Self {
             pointer,
             relative_pointer,
-            confined_pointer,
+            pointer_constraint,
+            gestures,
         }
     }
 }
@@ -218,9 +285,15 @@ impl Drop for Pointers {
             relative_pointer.destroy();
         }
 
-        // Drop confined pointer.
-        if let Some(confined_pointer) = self.confined_pointer.borrow_mut().take() {
-            confined_pointer.destroy();
+        // Drop touchpad gestures.
+        if let Some((swipe_gesture, pinch_gesture)) = self.gestures.take() {
+            swipe_gesture.destroy();
+            pinch_gesture.destroy();
+        }
+
+        // Drop confined or locked pointer.
+        if let Some(pointer_constraint) = self.pointer_constraint.borrow_mut().take() {
+            pointer_constraint.destroy();
         }
 
         // Drop the pointer itself in case it's possible.
@@ -243,15 +316,54 @@ pub(super) fn init_relative_pointer(
     relative_pointer.detach()
 }
 
+pub(super) fn init_pointer_gestures(
+    pointer_gestures: &ZwpPointerGesturesV1,
+    pointer: &WlPointer,
+) -> (ZwpPointerGestureSwipeV1, ZwpPointerGesturePinchV1) {
+    let swipe_gesture = pointer_gestures.get_swipe_gesture(pointer);
+    let mut swipe_data = GestureData::default();
+    swipe_gesture.quick_assign(move |_, event, mut dispatch_data| {
+        let winit_state = dispatch_data.get::<WinitState>().unwrap();
+        handlers::handle_swipe_gesture(event, &mut swipe_data, winit_state);
+    });
+
+    let pinch_gesture = pointer_gestures.get_pinch_gesture(pointer);
+    let mut pinch_data = GestureData::default();
+    pinch_gesture.quick_assign(move |_, event, mut dispatch_data| {
+        let winit_state = dispatch_data.get::<WinitState>().unwrap();
+        handlers::handle_pinch_gesture(event, &mut pinch_data, winit_state);
+    });
+
+    (swipe_gesture.detach(), pinch_gesture.detach())
+}
+
 pub(super) fn init_confined_pointer(
     pointer_constraints: &Attached<ZwpPointerConstraintsV1>,
     surface: &WlSurface,
     pointer: &WlPointer,
+    region: Option<&WlRegion>,
 ) -> ZwpConfinedPointerV1 {
-    let confined_pointer =
-        pointer_constraints.confine_pointer(surface, pointer, None, Lifetime::Persistent.to_raw());
+    let confined_pointer = pointer_constraints.confine_pointer(
+        surface,
+        pointer,
+        region,
+        Lifetime::Persistent.to_raw(),
+    );
 
     confined_pointer.quick_assign(move |_, _, _| {});
 
     confined_pointer.detach()
 }
+
+pub(super) fn init_locked_pointer(
+    pointer_constraints: &Attached<ZwpPointerConstraintsV1>,
+    surface: &WlSurface,
+    pointer: &WlPointer,
+) -> ZwpLockedPointerV1 {
+    let locked_pointer =
+        pointer_constraints.lock_pointer(surface, pointer, None, Lifetime::Persistent.to_raw());
+
+    locked_pointer.quick_assign(move |_, _, _| {});
+
+    locked_pointer.detach()
+}
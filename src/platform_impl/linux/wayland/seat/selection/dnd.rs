@@ -0,0 +1,390 @@
+//! Receiving of drags from other applications on our windows.
+
+use std::cell::{Cell, RefCell};
+use std::io::{self, ErrorKind, Read};
+use std::mem;
+use std::os::unix::io::FromRawFd;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use sctk::data_device::ReadPipe;
+use sctk::reexports::calloop::LoopHandle;
+use sctk::reexports::client::protocol::wl_data_device_manager::DndAction as WlDndAction;
+use sctk::reexports::client::protocol::wl_data_offer::{Event as DataOfferEvent, WlDataOffer};
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Main;
+
+use crate::dnd::{DndAction, DndActions, DropData};
+use crate::dpi::{LogicalPosition, PhysicalPosition};
+use crate::event::WindowEvent;
+use crate::platform_impl::platform::common::dnd::{self, URI_LIST_MIME_TYPE};
+use crate::platform_impl::wayland::event_loop::{DragMotion, WinitState};
+use crate::platform_impl::wayland::{self, WindowId};
+
+use super::drag::{wayland_actions, winit_action};
+
+/// What the source of an offer told us about it, stored in the offer's user data.
+struct OfferData {
+    mime_types: Vec<String>,
+    source_actions: WlDndAction,
+    action: WlDndAction,
+}
+
+/// Records what the source tells about a new offer.
+pub(super) fn track_offer(offer: &Main<WlDataOffer>) {
+    offer.as_ref().user_data().set(|| {
+        RefCell::new(OfferData {
+            mime_types: Vec::new(),
+            source_actions: WlDndAction::None,
+            action: WlDndAction::None,
+        })
+    });
+    offer.quick_assign(|offer, event, _| {
+        with_offer_data(&offer, |data| match event {
+            DataOfferEvent::Offer { mime_type } => data.mime_types.push(mime_type),
+            DataOfferEvent::SourceActions { source_actions } => {
+                data.source_actions = source_actions
+            }
+            DataOfferEvent::Action { dnd_action } => data.action = dnd_action,
+            _ => (),
+        });
+    });
+}
+
+#[inline]
+fn with_offer_data<F, R>(offer: &WlDataOffer, f: F) -> Option<R>
+where
+    F: FnOnce(&mut OfferData) -> R,
+{
+    let data = offer.as_ref().user_data().get::<RefCell<OfferData>>()?;
+    Some(f(&mut data.borrow_mut()))
+}
+
+/// The MIME type the data of an offer is received in once dropped.
+#[inline]
+fn drop_mime_type(offer: &WlDataOffer) -> Option<String> {
+    with_offer_data(offer, |data| {
+        dnd::pick_drop_mime_type(&data.mime_types).map(str::to_owned)
+    })
+    .flatten()
+}
+
+/// The action proposed to the user: the one the compositor picked, or the preferred one of the
+/// source.
+#[inline]
+fn proposed_action(offer: &WlDataOffer) -> DndAction {
+    with_offer_data(offer, |data| {
+        winit_action(data.action).or_else(|| winit_action(data.source_actions))
+    })
+    .flatten()
+    .unwrap_or(DndAction::Copy)
+}
+
+/// Drags of a seat hovering our windows.
+pub(super) struct DropTarget {
+    loop_handle: LoopHandle<WinitState>,
+    drag: Rc<RefCell<Option<HoveringDrag>>>,
+}
+
+struct HoveringDrag {
+    offer: WlDataOffer,
+    surface: WlSurface,
+    window_id: WindowId,
+
+    /// Serial of the enter event, which offers are accepted with.
+    serial: u32,
+
+    position: PhysicalPosition<f64>,
+
+    /// Action the user accepted the drop with at the latest position.
+    accepted_action: Option<DndAction>,
+
+    files: HoveredFiles,
+}
+
+/// Files of the drag, which are reported as hovered once the user accepts them.
+enum HoveredFiles {
+    Unrequested,
+    Requested,
+    Received(Vec<PathBuf>),
+}
+
+impl DropTarget {
+    pub(super) fn new(loop_handle: LoopHandle<WinitState>) -> Self {
+        Self {
+            loop_handle,
+            drag: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub(super) fn enter(
+        &self,
+        serial: u32,
+        surface: WlSurface,
+        x: f64,
+        y: f64,
+        offer: Option<WlDataOffer>,
+        winit_state: &mut WinitState,
+    ) {
+        // The offer of the previous drag is done with once a new one enters.
+        if let Some(drag) = self.drag.borrow_mut().take() {
+            drag.offer.destroy();
+        }
+
+        // Drags without data are only meant for the client that started them.
+        let offer = match offer {
+            Some(offer) => offer,
+            None => return,
+        };
+
+        let window_id = wayland::make_wid(&surface);
+        if !winit_state.window_map.contains_key(&window_id) {
+            offer.destroy();
+            return;
+        }
+
+        let mime_types =
+            with_offer_data(&offer, |data| data.mime_types.clone()).unwrap_or_default();
+        winit_state
+            .event_sink
+            .push_window_event(WindowEvent::DragEntered { mime_types }, window_id);
+
+        *self.drag.borrow_mut() = Some(HoveringDrag {
+            offer,
+            surface,
+            window_id,
+            serial,
+            position: PhysicalPosition::new(0., 0.),
+            accepted_action: None,
+            files: HoveredFiles::Unrequested,
+        });
+        self.motion(x, y, winit_state);
+    }
+
+    pub(super) fn motion(&self, x: f64, y: f64, winit_state: &mut WinitState) {
+        let mut drag = self.drag.borrow_mut();
+        let drag = match drag.as_mut() {
+            Some(drag) => drag,
+            None => return,
+        };
+
+        let scale_factor = sctk::get_surface_scale_factor(&drag.surface) as f64;
+        drag.position = LogicalPosition::new(x, y).to_physical(scale_factor);
+
+        let action = proposed_action(&drag.offer);
+        let offers_files = drop_mime_type(&drag.offer).as_deref() == Some(URI_LIST_MIME_TYPE);
+        let accepted_action = if offers_files { Some(action) } else { None };
+
+        let state = self.drag.clone();
+        let loop_handle = self.loop_handle.clone();
+        let offer = drag.offer.clone();
+        winit_state.event_sink.push_drag_motion(DragMotion {
+            window_id: drag.window_id,
+            position: drag.position,
+            action,
+            accepted_action,
+            respond: Box::new(move |accepted_action| {
+                respond(&state, &offer, &loop_handle, accepted_action)
+            }),
+        });
+    }
+
+    pub(super) fn leave(&self, winit_state: &mut WinitState) {
+        if let Some(drag) = self.drag.borrow_mut().take() {
+            drag_cancelled(&drag, winit_state);
+        }
+    }
+
+    pub(super) fn drop(&self, winit_state: &mut WinitState) {
+        let mut drag = match self.drag.borrow_mut().take() {
+            Some(drag) => drag,
+            None => return,
+        };
+
+        let accepted = drag
+            .accepted_action
+            .and_then(|action| drop_mime_type(&drag.offer).map(|mime_type| (action, mime_type)));
+        let (action, mime_type) = match accepted {
+            Some(accepted) => accepted,
+            None => return drag_cancelled(&drag, winit_state),
+        };
+
+        // Since version 3, the compositor tells which of the accepted actions was performed.
+        let action = if drag.offer.as_ref().version() >= 3 {
+            with_offer_data(&drag.offer, |data| winit_action(data.action))
+                .flatten()
+                .unwrap_or(action)
+        } else {
+            action
+        };
+
+        // The files were already received while hovering.
+        if mime_type == URI_LIST_MIME_TYPE {
+            if let HoveredFiles::Received(files) =
+                mem::replace(&mut drag.files, HoveredFiles::Unrequested)
+            {
+                return finish_drop(&drag, action, DropData::Files(files), winit_state);
+            }
+        }
+
+        let pipe = match receive(&drag.offer, mime_type.clone()) {
+            Ok(pipe) => pipe,
+            Err(_) => return drag_cancelled(&drag, winit_state),
+        };
+        read_pipe(&self.loop_handle, pipe, move |data, winit_state| match data
+            .and_then(|data| dnd::drop_data(mime_type, data))
+        {
+            Some(data) => finish_drop(&drag, action, data, winit_state),
+            None => drag_cancelled(&drag, winit_state),
+        });
+    }
+}
+
+/// Tells the compositor whether the user accepts the drop where the drag is.
+fn respond(
+    state: &Rc<RefCell<Option<HoveringDrag>>>,
+    offer: &WlDataOffer,
+    loop_handle: &LoopHandle<WinitState>,
+    accepted_action: Option<DndAction>,
+) {
+    let mut drag = state.borrow_mut();
+    let drag = match drag.as_mut() {
+        Some(drag) if drag.offer == *offer => drag,
+        _ => return,
+    };
+
+    drag.accepted_action = accepted_action;
+    let mime_type = accepted_action.and_then(|_| drop_mime_type(offer));
+    offer.accept(drag.serial, mime_type.clone());
+    if offer.as_ref().version() >= 3 {
+        let actions = wayland_actions(accepted_action.map(DndActions::from).unwrap_or_default());
+        offer.set_actions(actions, actions);
+    }
+
+    if mime_type.as_deref() != Some(URI_LIST_MIME_TYPE) {
+        return;
+    }
+    if let HoveredFiles::Unrequested = drag.files {
+        let pipe = match receive(offer, URI_LIST_MIME_TYPE.to_owned()) {
+            Ok(pipe) => pipe,
+            Err(_) => return,
+        };
+        drag.files = HoveredFiles::Requested;
+
+        let state = state.clone();
+        let offer = offer.clone();
+        read_pipe(loop_handle, pipe, move |data, winit_state| {
+            let mut drag = state.borrow_mut();
+            let drag = match drag.as_mut() {
+                Some(drag) if drag.offer == offer => drag,
+                _ => return,
+            };
+            let files = match data.map(|data| dnd::parse_uri_list(&data)) {
+                Some(Ok(files)) => files,
+                Some(Err(err)) => {
+                    warn!("Failed to decode the hovered files: {}", err);
+                    return;
+                }
+                None => return,
+            };
+
+            for path in &files {
+                winit_state
+                    .event_sink
+                    .push_window_event(WindowEvent::HoveredFile(path.clone()), drag.window_id);
+            }
+            drag.files = HoveredFiles::Received(files);
+        });
+    }
+}
+
+fn finish_drop(
+    drag: &HoveringDrag,
+    action: DndAction,
+    data: DropData,
+    winit_state: &mut WinitState,
+) {
+    if let DropData::Files(files) = &data {
+        for path in files {
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::DroppedFile(path.clone()), drag.window_id);
+        }
+    }
+    winit_state.event_sink.push_window_event(
+        WindowEvent::DragDropped {
+            position: drag.position,
+            action,
+            data,
+        },
+        drag.window_id,
+    );
+
+    if drag.offer.as_ref().version() >= 3 {
+        drag.offer.finish();
+    }
+    drag.offer.destroy();
+}
+
+fn drag_cancelled(drag: &HoveringDrag, winit_state: &mut WinitState) {
+    drag.offer.destroy();
+    winit_state
+        .event_sink
+        .push_window_event(WindowEvent::HoveredFileCancelled, drag.window_id);
+    winit_state
+        .event_sink
+        .push_window_event(WindowEvent::DragLeft, drag.window_id);
+}
+
+/// Asks the source of the offer to write its data in the given MIME type to a pipe.
+fn receive(offer: &WlDataOffer, mime_type: String) -> io::Result<ReadPipe> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    offer.receive(mime_type, fds[1]);
+    unsafe {
+        libc::close(fds[1]);
+        Ok(ReadPipe::from_raw_fd(fds[0]))
+    }
+}
+
+/// Reads the pipe from the event loop until the source closes it, then calls `callback` with
+/// the data, or with `None` if it couldn't be read.
+fn read_pipe<F>(loop_handle: &LoopHandle<WinitState>, pipe: ReadPipe, callback: F)
+where
+    F: FnOnce(Option<Vec<u8>>, &mut WinitState) + 'static,
+{
+    let source = Rc::new(Cell::new(None));
+    let mut callback = Some(callback);
+    let mut data = Vec::new();
+
+    let handle = loop_handle.clone();
+    let pipe_source = source.clone();
+    let inserted = loop_handle.insert_source(pipe, move |(), file, winit_state| {
+        let mut buffer = [0; 4096];
+        let result = match file.read(&mut buffer) {
+            Ok(0) => Some(mem::take(&mut data)),
+            Ok(len) => {
+                data.extend_from_slice(&buffer[..len]);
+                return;
+            }
+            Err(ref err) if err.kind() == ErrorKind::Interrupted => return,
+            Err(_) => None,
+        };
+
+        let callback = callback.take();
+        if let Some(source) = pipe_source.take() {
+            handle.kill(source);
+        }
+        if let Some(callback) = callback {
+            callback(result, winit_state);
+        }
+    });
+
+    match inserted {
+        Ok(inserted) => source.set(Some(inserted)),
+        Err(_) => warn!("Failed to read the data of a drag"),
+    }
+}
@@ -0,0 +1,109 @@
+//! Dragging of our own data out of the windows.
+
+use std::fs::File;
+use std::os::unix::io::FromRawFd;
+
+use sctk::reexports::client::protocol::wl_data_device::WlDataDevice;
+use sctk::reexports::client::protocol::wl_data_device_manager::{
+    DndAction as WlDndAction, WlDataDeviceManager,
+};
+use sctk::reexports::client::protocol::wl_data_source::Event as DataSourceEvent;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Attached;
+
+use crate::dnd::{DndAction, DndActions, DragOutcome};
+use crate::event::WindowEvent;
+use crate::platform_impl::wayland::clipboard;
+use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::WindowId;
+
+/// Data device of a seat, through which drags are started.
+#[derive(Clone)]
+pub struct DragDevice {
+    data_device: WlDataDevice,
+    data_device_manager: Attached<WlDataDeviceManager>,
+}
+
+impl DragDevice {
+    pub(super) fn new(
+        data_device: WlDataDevice,
+        data_device_manager: Attached<WlDataDeviceManager>,
+    ) -> Self {
+        Self {
+            data_device,
+            data_device_manager,
+        }
+    }
+
+    /// Starts dragging `data` from `origin`, in the implicit grab of `serial`.
+    ///
+    /// The outcome is reported as a `WindowEvent::DragEnded` of `window_id`.
+    pub fn start_drag(
+        &self,
+        origin: &WlSurface,
+        serial: u32,
+        data: Vec<(String, Vec<u8>)>,
+        allowed_actions: DndActions,
+        window_id: WindowId,
+    ) {
+        let source = self.data_device_manager.create_data_source();
+        for mime_type in clipboard::offered_mime_types(&data) {
+            source.offer(mime_type);
+        }
+        source.set_actions(wayland_actions(allowed_actions));
+
+        let mut action = None;
+        source.quick_assign(move |source, event, mut dispatch_data| {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            let outcome = match event {
+                DataSourceEvent::Send { mime_type, fd } => {
+                    let pipe = unsafe { File::from_raw_fd(fd) };
+                    clipboard::send_data(&data, &mime_type, pipe);
+                    return;
+                }
+                DataSourceEvent::Action { dnd_action } => {
+                    action = winit_action(dnd_action);
+                    return;
+                }
+                DataSourceEvent::DndFinished => match action {
+                    Some(action) => DragOutcome::Dropped(action),
+                    None => DragOutcome::Cancelled,
+                },
+                DataSourceEvent::Cancelled => DragOutcome::Cancelled,
+                _ => return,
+            };
+
+            source.destroy();
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::DragEnded(outcome), window_id);
+        });
+
+        self.data_device
+            .start_drag(Some(&*source), origin, None, serial);
+    }
+}
+
+/// Wayland has no counterpart of the link action, so it's left out.
+#[inline]
+pub(super) fn wayland_actions(actions: DndActions) -> WlDndAction {
+    let mut wayland_actions = WlDndAction::None;
+    if actions.contains(DndActions::COPY) {
+        wayland_actions |= WlDndAction::Copy;
+    }
+    if actions.contains(DndActions::MOVE) {
+        wayland_actions |= WlDndAction::Move;
+    }
+    wayland_actions
+}
+
+#[inline]
+pub(super) fn winit_action(action: WlDndAction) -> Option<DndAction> {
+    if action.contains(WlDndAction::Copy) {
+        Some(DndAction::Copy)
+    } else if action.contains(WlDndAction::Move) {
+        Some(DndAction::Move)
+    } else {
+        None
+    }
+}
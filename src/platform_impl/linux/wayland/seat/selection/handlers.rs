@@ -0,0 +1,87 @@
+//! Handling of the selection events.
+
+use sctk::reexports::client::protocol::wl_data_device::Event as DataDeviceEvent;
+use sctk::reexports::client::protocol::wl_data_offer::WlDataOffer;
+use sctk::reexports::protocols::misc::gtk_primary_selection::client::gtk_primary_selection_device::Event as GtkPrimarySelectionDeviceEvent;
+use sctk::reexports::protocols::misc::gtk_primary_selection::client::gtk_primary_selection_offer::GtkPrimarySelectionOffer;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_v1::Event as ZwpPrimarySelectionDeviceEvent;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_offer_v1::ZwpPrimarySelectionOfferV1;
+
+use crate::clipboard::ClipboardKind;
+use crate::platform_impl::wayland::event_loop::WinitState;
+
+use super::dnd::{self, DropTarget};
+use super::SelectionInner;
+
+#[inline]
+pub(super) fn handle_data_device(
+    event: DataDeviceEvent,
+    inner: &mut SelectionInner<WlDataOffer>,
+    drop_target: &DropTarget,
+    winit_state: &mut WinitState,
+) {
+    match event {
+        DataDeviceEvent::DataOffer { id } => dnd::track_offer(&id),
+        DataDeviceEvent::Enter {
+            serial,
+            surface,
+            x,
+            y,
+            id,
+        } => drop_target.enter(serial, surface, x, y, id, winit_state),
+        DataDeviceEvent::Motion { x, y, .. } => drop_target.motion(x, y, winit_state),
+        DataDeviceEvent::Leave => drop_target.leave(winit_state),
+        DataDeviceEvent::Drop => drop_target.drop(winit_state),
+        DataDeviceEvent::Selection { id } => {
+            if let Some(offer) = std::mem::replace(&mut inner.selection, id) {
+                offer.destroy();
+            }
+            selection_changed(ClipboardKind::Clipboard, winit_state);
+        }
+        _ => (),
+    }
+}
+
+#[inline]
+pub(super) fn handle_zwp_primary_selection_device(
+    event: ZwpPrimarySelectionDeviceEvent,
+    inner: &mut SelectionInner<ZwpPrimarySelectionOfferV1>,
+    winit_state: &mut WinitState,
+) {
+    match event {
+        ZwpPrimarySelectionDeviceEvent::DataOffer { offer } => offer.quick_assign(|_, _, _| {}),
+        ZwpPrimarySelectionDeviceEvent::Selection { id } => {
+            if let Some(offer) = std::mem::replace(&mut inner.selection, id) {
+                offer.destroy();
+            }
+            selection_changed(ClipboardKind::Primary, winit_state);
+        }
+        _ => (),
+    }
+}
+
+#[inline]
+pub(super) fn handle_gtk_primary_selection_device(
+    event: GtkPrimarySelectionDeviceEvent,
+    inner: &mut SelectionInner<GtkPrimarySelectionOffer>,
+    winit_state: &mut WinitState,
+) {
+    match event {
+        GtkPrimarySelectionDeviceEvent::DataOffer { offer } => offer.quick_assign(|_, _, _| {}),
+        GtkPrimarySelectionDeviceEvent::Selection { id } => {
+            if let Some(offer) = std::mem::replace(&mut inner.selection, id) {
+                offer.destroy();
+            }
+            selection_changed(ClipboardKind::Primary, winit_state);
+        }
+        _ => (),
+    }
+}
+
+/// Reports a selection change, unless the new selection is our own.
+#[inline]
+fn selection_changed(kind: ClipboardKind, winit_state: &mut WinitState) {
+    if !winit_state.clipboard.owns(kind) {
+        winit_state.event_sink.push_clipboard_changed(kind);
+    }
+}
@@ -0,0 +1,123 @@
+//! Observing of the selections to report clipboard changes.
+
+use sctk::reexports::client::protocol::wl_data_device::WlDataDevice;
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::Attached;
+use sctk::reexports::protocols::misc::gtk_primary_selection::client::gtk_primary_selection_device::GtkPrimarySelectionDevice;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1;
+
+use sctk::primary_selection::PrimarySelectionDeviceManager;
+use sctk::reexports::calloop::LoopHandle;
+
+use crate::platform_impl::wayland::event_loop::WinitState;
+
+mod dnd;
+mod drag;
+mod handlers;
+
+use dnd::DropTarget;
+pub use drag::DragDevice;
+
+/// Devices listening for the selection and drag and drop offers of a seat.
+///
+/// SCTK's devices don't tell when the selections change, so we keep our own
+/// devices around to receive the offers as well.
+pub struct SelectionObserver {
+    data_device: Option<WlDataDevice>,
+    data_device_manager: Option<Attached<WlDataDeviceManager>>,
+    primary_selection_device: Option<PrimarySelectionDevice>,
+}
+
+enum PrimarySelectionDevice {
+    Zwp(ZwpPrimarySelectionDeviceV1),
+    Gtk(GtkPrimarySelectionDevice),
+}
+
+impl SelectionObserver {
+    pub fn new(
+        seat: &Attached<WlSeat>,
+        loop_handle: &LoopHandle<WinitState>,
+        data_device_manager: Option<&Attached<WlDataDeviceManager>>,
+        primary_selection_manager: Option<&PrimarySelectionDeviceManager>,
+    ) -> Self {
+        let data_device = data_device_manager.map(|manager| {
+            let data_device = manager.get_data_device(seat);
+            let mut inner = SelectionInner::default();
+            let drop_target = DropTarget::new(loop_handle.clone());
+            data_device.quick_assign(move |_, event, mut dispatch_data| {
+                let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                handlers::handle_data_device(event, &mut inner, &drop_target, winit_state);
+            });
+            data_device.detach()
+        });
+
+        let primary_selection_device = primary_selection_manager.map(|manager| match manager {
+            PrimarySelectionDeviceManager::Zwp(manager) => {
+                let device = manager.get_device(seat);
+                let mut inner = SelectionInner::default();
+                device.quick_assign(move |_, event, mut dispatch_data| {
+                    let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                    handlers::handle_zwp_primary_selection_device(event, &mut inner, winit_state);
+                });
+                PrimarySelectionDevice::Zwp(device.detach())
+            }
+            PrimarySelectionDeviceManager::Gtk(manager) => {
+                let device = manager.get_device(seat);
+                let mut inner = SelectionInner::default();
+                device.quick_assign(move |_, event, mut dispatch_data| {
+                    let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                    handlers::handle_gtk_primary_selection_device(event, &mut inner, winit_state);
+                });
+                PrimarySelectionDevice::Gtk(device.detach())
+            }
+        });
+
+        Self {
+            data_device,
+            data_device_manager: data_device_manager.cloned(),
+            primary_selection_device,
+        }
+    }
+
+    /// Device to start drags with, if the compositor lets us know how they end.
+    pub fn drag_device(&self) -> Option<DragDevice> {
+        let data_device = self.data_device.as_ref()?;
+        let data_device_manager = self.data_device_manager.as_ref()?;
+        if data_device.as_ref().version() < 3 {
+            return None;
+        }
+        Some(DragDevice::new(
+            data_device.clone(),
+            data_device_manager.clone(),
+        ))
+    }
+}
+
+impl Drop for SelectionObserver {
+    fn drop(&mut self) {
+        if let Some(data_device) = self.data_device.as_ref() {
+            if data_device.as_ref().version() >= 2 {
+                data_device.release();
+            }
+        }
+
+        match self.primary_selection_device.as_ref() {
+            Some(PrimarySelectionDevice::Zwp(device)) => device.destroy(),
+            Some(PrimarySelectionDevice::Gtk(device)) => device.destroy(),
+            None => (),
+        }
+    }
+}
+
+/// Offers we've been handed by one of the devices.
+struct SelectionInner<O> {
+    /// Offer of the current selection.
+    selection: Option<O>,
+}
+
+impl<O> Default for SelectionInner<O> {
+    fn default() -> Self {
+        Self { selection: None }
+    }
+}
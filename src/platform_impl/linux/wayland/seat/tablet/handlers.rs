@@ -0,0 +1,205 @@
+//! Handlers for the tools of drawing tablets.
+
+use std::mem;
+
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_tool_v2::{
+    ButtonState, Capability, Event as ToolEvent, Type as ToolType, ZwpTabletToolV2,
+};
+
+use crate::dpi::LogicalPosition;
+use crate::event::{ElementState, MouseButton, TabletTool, TabletToolEvent, WindowEvent};
+use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::{self, WindowId};
+
+use super::{FrameEvent, TabletToolInner};
+
+// These values are comming from <linux/input-event-codes.h>.
+const BTN_STYLUS: u32 = 0x14b;
+const BTN_STYLUS2: u32 = 0x14c;
+const BTN_STYLUS3: u32 = 0x149;
+
+/// Handle tablet tool events.
+#[inline]
+pub(super) fn handle_tablet_tool(
+    tool: &ZwpTabletToolV2,
+    event: ToolEvent,
+    inner: &mut TabletToolInner,
+    winit_state: &mut WinitState,
+) {
+    match event {
+        ToolEvent::Type { tool_type } => {
+            inner.tool = match tool_type {
+                ToolType::Pen => TabletTool::Pen,
+                ToolType::Eraser => TabletTool::Eraser,
+                ToolType::Brush => TabletTool::Brush,
+                ToolType::Pencil => TabletTool::Pencil,
+                ToolType::Airbrush => TabletTool::Airbrush,
+                ToolType::Finger => TabletTool::Finger,
+                ToolType::Mouse => TabletTool::Mouse,
+                ToolType::Lens => TabletTool::Lens,
+                _ => TabletTool::Unknown,
+            };
+        }
+        ToolEvent::Capability { capability } => match capability {
+            Capability::Pressure => inner.axes.pressure = Some(0.0),
+            Capability::Tilt => {
+                inner.axes.tilt_x = Some(0.0);
+                inner.axes.tilt_y = Some(0.0);
+            }
+            Capability::Distance => inner.axes.distance = Some(0.0),
+            Capability::Rotation => inner.axes.rotation = Some(0.0),
+            _ => (),
+        },
+        ToolEvent::Removed => tool.destroy(),
+        ToolEvent::ProximityIn { surface, .. } => {
+            let window_id = wayland::make_wid(&surface);
+            if !winit_state.window_map.contains_key(&window_id) {
+                return;
+            }
+
+            inner.surface = Some(surface);
+            inner.pending_events.push((window_id, FrameEvent::Entered));
+        }
+        ToolEvent::ProximityOut => {
+            if let Some(surface) = inner.surface.take() {
+                let window_id = wayland::make_wid(&surface);
+                inner.pending_events.push((window_id, FrameEvent::Left));
+            }
+        }
+        ToolEvent::Down { .. } => push_event(inner, FrameEvent::Tip(ElementState::Pressed)),
+        ToolEvent::Up => push_event(inner, FrameEvent::Tip(ElementState::Released)),
+        ToolEvent::Button { button, state, .. } => {
+            let button = match button {
+                BTN_STYLUS => 1,
+                BTN_STYLUS2 => 2,
+                BTN_STYLUS3 => 3,
+                // Other buttons are reported with their code.
+                button => button,
+            };
+            let state = match state {
+                ButtonState::Pressed => ElementState::Pressed,
+                _ => ElementState::Released,
+            };
+            push_event(inner, FrameEvent::Button(button, state));
+        }
+        ToolEvent::Motion { x, y } => {
+            inner.position = LogicalPosition::new(x, y);
+            push_event(inner, FrameEvent::Moved);
+        }
+        ToolEvent::Pressure { pressure } => {
+            inner.axes.pressure = Some(pressure as f32 / 65535.0);
+            push_event(inner, FrameEvent::Moved);
+        }
+        ToolEvent::Distance { distance } => {
+            inner.axes.distance = Some(distance as f32 / 65535.0);
+            push_event(inner, FrameEvent::Moved);
+        }
+        ToolEvent::Tilt { tilt_x, tilt_y } => {
+            inner.axes.tilt_x = Some(tilt_x as f32);
+            inner.axes.tilt_y = Some(tilt_y as f32);
+            push_event(inner, FrameEvent::Moved);
+        }
+        ToolEvent::Rotation { degrees } => {
+            inner.axes.rotation = Some(degrees as f32);
+            push_event(inner, FrameEvent::Moved);
+        }
+        ToolEvent::Frame { .. } => deliver_frame(inner, winit_state),
+        _ => (),
+    }
+}
+
+/// Adds an event to the current frame of the tool, if it's over one of our windows.
+fn push_event(inner: &mut TabletToolInner, event: FrameEvent) {
+    let window_id = match inner.surface {
+        Some(ref surface) => wayland::make_wid(surface),
+        None => return,
+    };
+
+    let event = (window_id, event);
+    if event.1 != FrameEvent::Moved || !inner.pending_events.contains(&event) {
+        inner.pending_events.push(event);
+    }
+}
+
+/// Delivers the events of a frame, along with the pointer events they're emulating.
+fn deliver_frame(inner: &mut TabletToolInner, winit_state: &mut WinitState) {
+    let event_sink = &mut winit_state.event_sink;
+    let device_id =
+        crate::event::DeviceId(crate::platform_impl::DeviceId::WaylandTabletTool(inner.id));
+
+    let mut push_events = |pointer_event: WindowEvent<'static>,
+                           tablet_event: TabletToolEvent,
+                           window_id: WindowId| {
+        event_sink.push_window_event(pointer_event, window_id);
+        event_sink.push_window_event(WindowEvent::TabletTool(tablet_event), window_id);
+    };
+
+    for (window_id, event) in mem::take(&mut inner.pending_events) {
+        match event {
+            FrameEvent::Entered => push_events(
+                WindowEvent::CursorEntered { device_id },
+                TabletToolEvent::Entered {
+                    device_id,
+                    tool: inner.tool,
+                },
+                window_id,
+            ),
+            FrameEvent::Left => push_events(
+                WindowEvent::CursorLeft { device_id },
+                TabletToolEvent::Left { device_id },
+                window_id,
+            ),
+            FrameEvent::Moved => {
+                // The tool may have left the window since.
+                let surface = match inner.surface {
+                    Some(ref surface) if wayland::make_wid(surface) == window_id => surface,
+                    _ => continue,
+                };
+                let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+                let position = inner.position.to_physical(scale_factor);
+
+                push_events(
+                    WindowEvent::CursorMoved {
+                        device_id,
+                        position,
+                        modifiers: *inner.modifiers_state.borrow(),
+                    },
+                    TabletToolEvent::Moved {
+                        device_id,
+                        position,
+                        axes: inner.axes,
+                    },
+                    window_id,
+                );
+            }
+            FrameEvent::Tip(state) => push_events(
+                WindowEvent::MouseInput {
+                    device_id,
+                    state,
+                    button: MouseButton::Left,
+                    modifiers: *inner.modifiers_state.borrow(),
+                },
+                TabletToolEvent::Tip { device_id, state },
+                window_id,
+            ),
+            FrameEvent::Button(button, state) => push_events(
+                WindowEvent::MouseInput {
+                    device_id,
+                    state,
+                    button: match button {
+                        1 => MouseButton::Middle,
+                        2 => MouseButton::Right,
+                        button => MouseButton::Other(button as u16),
+                    },
+                    modifiers: *inner.modifiers_state.borrow(),
+                },
+                TabletToolEvent::Button {
+                    device_id,
+                    button,
+                    state,
+                },
+                window_id,
+            ),
+        }
+    }
+}
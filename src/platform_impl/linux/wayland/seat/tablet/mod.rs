@@ -0,0 +1,145 @@
+//! Drawing tablets handling.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{Attached, Main};
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_pad_group_v2::{
+    Event as PadGroupEvent, ZwpTabletPadGroupV2,
+};
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_pad_v2::{
+    Event as PadEvent, ZwpTabletPadV2,
+};
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_seat_v2::{
+    Event as TabletSeatEvent, ZwpTabletSeatV2,
+};
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_tool_v2::ZwpTabletToolV2;
+use sctk::reexports::protocols::unstable::tablet::v2::client::zwp_tablet_v2::{
+    Event as TabletEvent, ZwpTabletV2,
+};
+
+use crate::dpi::LogicalPosition;
+use crate::event::{ElementState, ModifiersState, TabletTool, TabletToolAxes};
+
+use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::{TabletToolId, WindowId};
+
+mod handlers;
+
+/// A wrapper around the tablet seat to automatically destroy the object on `Drop`.
+pub struct TabletSeat {
+    tablet_seat: ZwpTabletSeatV2,
+}
+
+impl TabletSeat {
+    pub fn new(
+        seat: &Attached<WlSeat>,
+        tablet_manager: &ZwpTabletManagerV2,
+        modifiers_state: Rc<RefCell<ModifiersState>>,
+    ) -> Self {
+        let tablet_seat = tablet_manager.get_tablet_seat(seat);
+        tablet_seat.quick_assign(move |_, event, _| match event {
+            TabletSeatEvent::TabletAdded { id } => assign_tablet(id),
+            TabletSeatEvent::ToolAdded { id } => assign_tool(id, modifiers_state.clone()),
+            TabletSeatEvent::PadAdded { id } => assign_pad(id),
+            _ => (),
+        });
+
+        Self {
+            tablet_seat: tablet_seat.detach(),
+        }
+    }
+}
+
+impl Drop for TabletSeat {
+    fn drop(&mut self) {
+        self.tablet_seat.destroy();
+    }
+}
+
+fn assign_tool(tool: Main<ZwpTabletToolV2>, modifiers_state: Rc<RefCell<ModifiersState>>) {
+    let mut inner = TabletToolInner::new(TabletToolId(tool.as_ref().id()), modifiers_state);
+    tool.quick_assign(move |tool, event, mut dispatch_data| {
+        let winit_state = dispatch_data.get::<WinitState>().unwrap();
+        handlers::handle_tablet_tool(&tool, event, &mut inner, winit_state);
+    });
+}
+
+// The tools are all we report, but the tablets and pads still have to be handled to destroy them
+// once they're removed.
+
+fn assign_tablet(tablet: Main<ZwpTabletV2>) {
+    tablet.quick_assign(|tablet, event, _| {
+        if let TabletEvent::Removed = event {
+            tablet.destroy();
+        }
+    });
+}
+
+fn assign_pad(pad: Main<ZwpTabletPadV2>) {
+    pad.quick_assign(|pad, event, _| match event {
+        PadEvent::Group { pad_group } => assign_pad_group(pad_group),
+        PadEvent::Removed => pad.destroy(),
+        _ => (),
+    });
+}
+
+fn assign_pad_group(pad_group: Main<ZwpTabletPadGroupV2>) {
+    pad_group.quick_assign(|_, event, _| match event {
+        PadGroupEvent::Ring { ring } => ring.quick_assign(|_, _, _| {}),
+        PadGroupEvent::Strip { strip } => strip.quick_assign(|_, _, _| {}),
+        _ => (),
+    });
+}
+
+/// The data used by tablet tool handlers.
+pub(super) struct TabletToolInner {
+    id: TabletToolId,
+
+    /// The kind of tool, sent once it's added.
+    tool: TabletTool,
+
+    /// The axes the tool has, with their last value.
+    axes: TabletToolAxes,
+
+    /// The surface the tool is over.
+    surface: Option<WlSurface>,
+
+    /// Position of the tool on the surface.
+    position: LogicalPosition<f64>,
+
+    /// Events received since the last frame, delivered together at the end of the frame.
+    pending_events: Vec<(WindowId, FrameEvent)>,
+
+    /// We keep modifiers state on a seat, since it's being used by the emulated pointer events.
+    modifiers_state: Rc<RefCell<ModifiersState>>,
+}
+
+impl TabletToolInner {
+    fn new(id: TabletToolId, modifiers_state: Rc<RefCell<ModifiersState>>) -> Self {
+        Self {
+            id,
+            tool: TabletTool::Unknown,
+            axes: Default::default(),
+            surface: None,
+            position: LogicalPosition::new(0.0, 0.0),
+            pending_events: Vec::new(),
+            modifiers_state,
+        }
+    }
+}
+
+/// An event of a tablet tool, waiting for the end of its frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FrameEvent {
+    Entered,
+    Left,
+
+    /// The tool moved or the value of its axes changed, which is only reported once per frame.
+    Moved,
+    Tip(ElementState),
+    Button(u32, ElementState),
+}
@@ -5,11 +5,11 @@ use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input
     Event as TextInputEvent, ZwpTextInputV3,
 };
 
-use crate::event::WindowEvent;
+use crate::event::{Ime, WindowEvent};
 use crate::platform_impl::wayland;
 use crate::platform_impl::wayland::event_loop::WinitState;
 
-use super::{TextInputHandler, TextInputInner};
+use super::{Preedit, TextInputHandler, TextInputInner};
 
 #[inline]
 pub(super) fn handle_text_input(
@@ -38,6 +38,10 @@ pub(super) fn handle_text_input(
                 text_input: text_input.detach(),
             };
             window_handle.text_input_entered(text_input_handler);
+
+            if window_handle.ime_allowed() {
+                event_sink.push_window_event(WindowEvent::Ime(Ime::Enabled), window_id);
+            }
         }
         TextInputEvent::Leave { surface } => {
             // Always issue a disable.
@@ -52,25 +56,87 @@ pub(super) fn handle_text_input(
             };
 
             inner.target_window_id = None;
+            inner.commit_string = None;
+            inner.pending_preedit = None;
+            inner.preedit_shown = false;
 
             // Remove text input handler from the window we're leaving.
             let text_input_handler = TextInputHandler {
                 text_input: text_input.detach(),
             };
             window_handle.text_input_left(text_input_handler);
+
+            if window_handle.ime_allowed() {
+                event_sink.push_window_event(WindowEvent::Ime(Ime::Disabled), window_id);
+            }
+        }
+        TextInputEvent::PreeditString {
+            text,
+            cursor_begin,
+            cursor_end,
+        } => {
+            // A negative cursor position means that the cursor should be hidden.
+            let cursor_range = if cursor_begin >= 0 && cursor_end >= 0 {
+                Some((cursor_begin as usize, cursor_end as usize))
+            } else {
+                None
+            };
+
+            inner.pending_preedit = Some(Preedit {
+                text: text.unwrap_or_default(),
+                cursor_range,
+            });
         }
         TextInputEvent::CommitString { text } => {
             // Update currenly commited string.
             inner.commit_string = text;
         }
         TextInputEvent::Done { .. } => {
-            let (window_id, text) = match (inner.target_window_id, inner.commit_string.take()) {
-                (Some(window_id), Some(text)) => (window_id, text),
-                _ => return,
+            let window_id = match inner.target_window_id {
+                Some(window_id) => window_id,
+                None => return,
             };
 
-            for ch in text.chars() {
-                event_sink.push_window_event(WindowEvent::ReceivedCharacter(ch), window_id);
+            // Windows which don't draw the composition only receive the committed characters.
+            let window_handle = winit_state.window_map.get(&window_id);
+            if !matches!(window_handle, Some(window_handle) if window_handle.ime_allowed()) {
+                inner.pending_preedit = None;
+                inner.preedit_shown = false;
+                if let Some(text) = inner.commit_string.take() {
+                    for ch in text.chars() {
+                        event_sink.push_window_event(WindowEvent::ReceivedCharacter(ch), window_id);
+                    }
+                }
+                return;
+            }
+
+            // The protocol requires to replace the pre-edit with the committed text, and then to
+            // show the new pre-edit, if any.
+            if let Some(text) = inner.commit_string.take() {
+                if inner.preedit_shown {
+                    event_sink.push_window_event(
+                        WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+                        window_id,
+                    );
+                    inner.preedit_shown = false;
+                }
+
+                event_sink
+                    .push_window_event(WindowEvent::Ime(Ime::Commit(text.clone())), window_id);
+
+                for ch in text.chars() {
+                    event_sink.push_window_event(WindowEvent::ReceivedCharacter(ch), window_id);
+                }
+            }
+
+            // The pre-edit is cleared when a `done` isn't preceded by a `preedit_string`.
+            let preedit = inner.pending_preedit.take().unwrap_or_default();
+            if !preedit.text.is_empty() || inner.preedit_shown {
+                inner.preedit_shown = !preedit.text.is_empty();
+                event_sink.push_window_event(
+                    WindowEvent::Ime(Ime::Preedit(preedit.text, preedit.cursor_range)),
+                    window_id,
+                );
             }
         }
         _ => (),
@@ -54,6 +54,12 @@ struct TextInputInner {
 
     /// Pending string to commit.
     commit_string: Option<String>,
+
+    /// Pending pre-edit, applied on the next `done` event.
+    pending_preedit: Option<Preedit>,
+
+    /// Whether the focused window is currently showing a non-empty pre-edit.
+    preedit_shown: bool,
 }
 
 impl TextInputInner {
@@ -61,6 +67,16 @@ impl TextInputInner {
         Self {
             target_window_id: None,
             commit_string: None,
+            pending_preedit: None,
+            preedit_shown: false,
         }
     }
 }
+
+#[derive(Default)]
+struct Preedit {
+    text: String,
+
+    /// Range of the cursor in the text, in bytes.
+    cursor_range: Option<(usize, usize)>,
+}
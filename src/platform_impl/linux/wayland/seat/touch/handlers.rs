@@ -38,7 +38,8 @@ pub(super) fn handle_touch(
                     )),
                     phase: TouchPhase::Started,
                     location: position.to_physical(scale_factor),
-                    force: None, // TODO
+                    // `wl_touch` doesn't report the pressure of touches.
+                    force: None,
                     id: id as u64,
                 }),
                 window_id,
@@ -65,7 +66,7 @@ pub(super) fn handle_touch(
                     )),
                     phase: TouchPhase::Ended,
                     location,
-                    force: None, // TODO
+                    force: None,
                     id: id as u64,
                 }),
                 window_id,
@@ -90,7 +91,7 @@ pub(super) fn handle_touch(
                     )),
                     phase: TouchPhase::Moved,
                     location,
-                    force: None, // TODO
+                    force: None,
                     id: id as u64,
                 }),
                 window_id,
@@ -110,7 +111,7 @@ pub(super) fn handle_touch(
                         )),
                         phase: TouchPhase::Cancelled,
                         location,
-                        force: None, // TODO
+                        force: None,
                         id: touch_point.id as u64,
                     }),
                     window_id,
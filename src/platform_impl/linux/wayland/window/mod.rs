@@ -2,8 +2,11 @@ use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
-use sctk::reexports::client::Display;
+use sctk::reexports::client::{DispatchData, Display};
+use sctk::reexports::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
 
 use sctk::reexports::calloop;
 
@@ -13,7 +16,8 @@ use sctk::window::{
 
 use raw_window_handle::unix::WaylandHandle;
 
-use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
+use crate::dnd::DndActions;
+use crate::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
 use crate::monitor::MonitorHandle as RootMonitorHandle;
 use crate::platform::unix::{ARGBColor as LocalARGBColor, Button, ButtonState, Element, Theme};
@@ -21,16 +25,18 @@ use crate::platform_impl::{
     MonitorHandle as PlatformMonitorHandle, OsError,
     PlatformSpecificWindowBuilderAttributes as PlatformAttributes,
 };
-use crate::window::{CursorIcon, Fullscreen, WindowAttributes};
+use crate::window::{CursorGrabMode, CursorIcon, Fullscreen, WindowAttributes};
 
 use super::env::WindowingFeatures;
 use super::event_loop::WinitState;
 use super::output::{MonitorHandle, OutputManagerHandle};
-use super::{EventLoopWindowTarget, WindowId};
+use super::{CustomCursor, EventLoopWindowTarget, WindowId};
 
 pub mod shim;
+mod xdg;
 
-use shim::{WindowHandle, WindowRequest, WindowUpdate};
+use shim::{WindowHandle, WindowRequest, WindowShell, WindowUpdate};
+use xdg::{PopupEvent, XdgPopupWindow, XdgWindow};
 
 pub struct Window {
     /// Window id.
@@ -62,6 +68,15 @@ pub struct Window {
 
     /// Requests that SCTK window should perform.
     window_requests: Arc<Mutex<Vec<WindowRequest>>>,
+
+    /// Constraints applied to the sizes the compositor configures the window with.
+    size_constraints: Arc<Mutex<SizeConstraints>>,
+
+    /// Position of a child window within its parent.
+    child_position: Option<Mutex<LogicalPosition<i32>>>,
+
+    /// Whether the window is a popup.
+    is_popup: bool,
 }
 
 impl Window {
@@ -84,6 +99,12 @@ impl Window {
                 window_update.redraw_requested = true;
 
                 surface.set_buffer_scale(scale);
+
+                // Redraw the cursor and convert the grab region for the new scale factor.
+                if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                    window_handle.draw_cursor(&mut winit_state.cursor_manager);
+                    window_handle.refresh_cursor_grab_region();
+                }
             })
             .detach();
 
@@ -94,101 +115,314 @@ impl Window {
         let maximzied_clone = maximized.clone();
         let fullscreen = Arc::new(AtomicBool::new(false));
         let fullscreen_clone = fullscreen.clone();
+        let size_constraints = Arc::new(Mutex::new(SizeConstraints {
+            min_size: attributes.min_inner_size,
+            resize_increments: platform_attributes.resize_increments,
+        }));
+        let size_constraints_clone = size_constraints.clone();
+        let configure_surface = surface.clone();
 
         let (width, height) = attributes
             .inner_size
             .map(|size| size.to_logical::<f64>(scale_factor as f64).into())
             .unwrap_or((800, 600));
 
-        let theme_manager = event_loop_window_target.theme_manager.clone();
-        let mut window = event_loop_window_target
-            .env
-            .create_window::<ConceptFrame, _>(
-                surface.clone(),
-                Some(theme_manager),
-                (width, height),
-                move |event, mut dispatch_data| {
-                    use sctk::window::{Event, State};
-
-                    let winit_state = dispatch_data.get::<WinitState>().unwrap();
-                    let mut window_update = winit_state.window_updates.get_mut(&window_id).unwrap();
-
-                    match event {
-                        Event::Refresh => {
-                            window_update.refresh_frame = true;
+        // Toplevel windows and popups are created from `xdg_wm_base` when it's supported.
+        let xdg_wm_base = event_loop_window_target.env.get_global::<XdgWmBase>();
+
+        let mut child_position = None;
+        let is_popup = platform_attributes.child_of.is_none() && attributes.popup.is_some();
+        let shell = match platform_attributes.child_of {
+            Some(parent) => {
+                let (parent, parent_requests) = match parent.0 {
+                    crate::platform_impl::WindowId::Wayland(parent) => event_loop_window_target
+                        .state
+                        .borrow()
+                        .window_map
+                        .get(&parent)
+                        .map(|window_handle| {
+                            (
+                                window_handle.window.surface().clone(),
+                                window_handle.pending_window_requests.clone(),
+                            )
+                        }),
+                    #[allow(unreachable_patterns)]
+                    _ => None,
+                }
+                .ok_or_else(|| {
+                    os_error!(OsError::WaylandMisc(
+                        "the parent of a child window isn't a window of this event loop"
+                    ))
+                })?;
+
+                let subsurface = event_loop_window_target
+                    .env
+                    .require_global::<WlSubcompositor>()
+                    .get_subsurface(&surface, &parent)
+                    .detach();
+
+                // The child is drawn whenever it commits, instead of along with its parent.
+                subsurface.set_desync();
+                let position = attributes
+                    .position
+                    .map(|position| position.to_logical::<i32>(scale_factor as f64))
+                    .unwrap_or_else(|| LogicalPosition::new(0, 0));
+                subsurface.set_position(position.x, position.y);
+
+                // The position applies on the next commit of the parent, so it's redrawn.
+                parent_requests.lock().unwrap().push(WindowRequest::Redraw);
+                event_loop_window_target.event_loop_awakener.ping();
+                child_position = Some(Mutex::new(position));
+
+                WindowShell::Subsurface {
+                    surface: surface.clone(),
+                    subsurface,
+                    parent,
+                }
+            }
+            None => match attributes.popup {
+                Some(popup) => {
+                    let xdg_wm_base = xdg_wm_base.ok_or_else(|| {
+                        os_error!(OsError::WaylandMisc(
+                            "popup windows need a compositor supporting xdg_wm_base"
+                        ))
+                    })?;
+
+                    let state = event_loop_window_target.state.borrow();
+                    let parent = match popup.parent.0 {
+                        crate::platform_impl::WindowId::Wayland(parent) => {
+                            state.window_map.get(&parent)
                         }
-                        Event::Configure { new_size, states } => {
-                            let is_maximized = states.contains(&State::Maximized);
-                            maximzied_clone.store(is_maximized, Ordering::Relaxed);
-                            let is_fullscreen = states.contains(&State::Fullscreen);
-                            fullscreen_clone.store(is_fullscreen, Ordering::Relaxed);
-
-                            window_update.refresh_frame = true;
-                            window_update.redraw_requested = true;
-                            if let Some((w, h)) = new_size {
-                                window_update.size = Some(LogicalSize::new(w, h));
-                            }
+                        #[allow(unreachable_patterns)]
+                        _ => None,
+                    };
+
+                    // The anchor rectangle is relative to the window geometry of the parent,
+                    // which starts at the frame of toplevel windows.
+                    let (parent_surface, content_offset) = match parent.map(|parent| &parent.window)
+                    {
+                        Some(WindowShell::Xdg(parent)) => {
+                            (parent.xdg_surface(), parent.content_offset())
                         }
-                        Event::Close => {
-                            window_update.close_window = true;
+                        Some(WindowShell::Popup(parent)) => (parent.xdg_surface(), (0, 0)),
+                        _ => {
+                            return Err(os_error!(OsError::WaylandMisc(
+                                "the parent of a popup window isn't a toplevel or popup window \
+                                 of this event loop"
+                            )))
+                        }
+                    };
+
+                    let parent_scale_factor = parent
+                        .map(|parent| sctk::get_surface_scale_factor(parent.window.surface()))
+                        .unwrap_or(scale_factor)
+                        as f64;
+                    let (anchor_position, anchor_size) = popup.anchor_rect;
+                    let anchor_position: LogicalPosition<i32> =
+                        anchor_position.to_logical(parent_scale_factor);
+                    let anchor_size: LogicalSize<u32> = anchor_size.to_logical(parent_scale_factor);
+                    let anchor_rect = (
+                        anchor_position.x + content_offset.0,
+                        anchor_position.y + content_offset.1,
+                        anchor_size.width,
+                        anchor_size.height,
+                    );
+
+                    let popup = XdgPopupWindow::new(
+                        &xdg_wm_base,
+                        surface.clone(),
+                        parent_surface,
+                        anchor_rect,
+                        popup.gravity,
+                        (width, height),
+                        move |event, mut dispatch_data| {
+                            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                            match event {
+                                PopupEvent::Configure { size: (w, h) } => {
+                                    let window_update =
+                                        winit_state.window_updates.get_mut(&window_id).unwrap();
+                                    window_update.size = Some(LogicalSize::new(w, h));
+                                    window_update.redraw_requested = true;
+                                }
+                                PopupEvent::Done => {
+                                    winit_state.event_sink.push_window_event(
+                                        crate::event::WindowEvent::PopupDismissed,
+                                        window_id,
+                                    );
+                                }
+                            }
+                        },
+                    );
+
+                    WindowShell::Popup(popup)
+                }
+                None => {
+                    let handler = move |event, mut dispatch_data: DispatchData<'_>| {
+                        use sctk::window::{Event, State};
+
+                        let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                        let window_update = winit_state.window_updates.get_mut(&window_id).unwrap();
+
+                        match event {
+                            Event::Refresh => {
+                                window_update.refresh_frame = true;
+                            }
+                            Event::Configure { new_size, states } => {
+                                let is_maximized = states.contains(&State::Maximized);
+                                maximzied_clone.store(is_maximized, Ordering::Relaxed);
+                                let is_fullscreen = states.contains(&State::Fullscreen);
+                                fullscreen_clone.store(is_fullscreen, Ordering::Relaxed);
+
+                                window_update.refresh_frame = true;
+                                window_update.redraw_requested = true;
+                                if let Some((w, h)) = new_size {
+                                    // The compositor picks the size itself in these states.
+                                    let is_constrained = states.iter().any(|state| {
+                                        matches!(
+                                            state,
+                                            State::Maximized
+                                                | State::Fullscreen
+                                                | State::TiledTop
+                                                | State::TiledRight
+                                                | State::TiledBottom
+                                                | State::TiledLeft
+                                        )
+                                    });
+                                    let size = LogicalSize::new(w, h);
+                                    window_update.size = Some(if is_constrained {
+                                        size
+                                    } else {
+                                        let scale_factor =
+                                            sctk::get_surface_scale_factor(&configure_surface);
+                                        size_constraints_clone
+                                            .lock()
+                                            .unwrap()
+                                            .snap(size, scale_factor as f64)
+                                    });
+                                }
+                            }
+                            Event::Close => {
+                                window_update.close_window = true;
+                            }
                         }
+                    };
+
+                    let theme_manager = event_loop_window_target.theme_manager.clone();
+                    let mut shell = match xdg_wm_base {
+                        Some(xdg_wm_base) => XdgWindow::new(
+                            &event_loop_window_target.env,
+                            &xdg_wm_base,
+                            surface.clone(),
+                            Some(theme_manager),
+                            (width, height),
+                            handler,
+                        )
+                        .map(WindowShell::Xdg),
+                        None => event_loop_window_target
+                            .env
+                            .create_window::<ConceptFrame, _>(
+                                surface.clone(),
+                                Some(theme_manager),
+                                (width, height),
+                                handler,
+                            )
+                            .map(WindowShell::Toplevel),
+                    }
+                    .map_err(|_| os_error!(OsError::WaylandMisc("failed to create window.")))?;
+                    let window = shell.toplevel().unwrap();
+
+                    // Set decorations.
+                    if attributes.decorations {
+                        window.set_decorate(Decorations::FollowServer);
+                    } else {
+                        window.set_decorate(Decorations::None);
                     }
-                },
-            )
-            .map_err(|_| os_error!(OsError::WaylandMisc("failed to create window.")))?;
 
-        // Set decorations.
-        if attributes.decorations {
-            window.set_decorate(Decorations::FollowServer);
-        } else {
-            window.set_decorate(Decorations::None);
-        }
+                    // Min dimensions.
+                    let min_size = attributes
+                        .min_inner_size
+                        .map(|size| size.to_logical::<f64>(scale_factor as f64).into());
+                    window.set_min_size(min_size);
+
+                    // Max dimensions.
+                    let max_size = attributes
+                        .max_inner_size
+                        .map(|size| size.to_logical::<f64>(scale_factor as f64).into());
+                    window.set_max_size(max_size);
+
+                    // Set Wayland specific window attributes.
+                    if let Some(app_id) = platform_attributes.app_id {
+                        window.set_app_id(app_id);
+                    }
 
-        // Min dimensions.
-        let min_size = attributes
-            .min_inner_size
-            .map(|size| size.to_logical::<f64>(scale_factor as f64).into());
-        window.set_min_size(min_size);
-
-        // Max dimensions.
-        let max_size = attributes
-            .max_inner_size
-            .map(|size| size.to_logical::<f64>(scale_factor as f64).into());
-        window.set_max_size(max_size);
-
-        // Set Wayland specific window attributes.
-        if let Some(app_id) = platform_attributes.app_id {
-            window.set_app_id(app_id);
-        }
+                    // Set common window attributes.
+                    //
+                    // We set resizable after other attributes, since it touches min and max size
+                    // under the hood.
+                    window.set_resizable(attributes.resizable);
+                    window.set_title(attributes.title);
+
+                    // Set fullscreen/maximized if so was requested.
+                    match attributes.fullscreen {
+                        Some(Fullscreen::Exclusive(_)) => {
+                            warn!("`Fullscreen::Exclusive` is ignored on Wayland")
+                        }
+                        Some(Fullscreen::Borderless(monitor)) => {
+                            let monitor =
+                                monitor.and_then(|RootMonitorHandle { inner: monitor }| {
+                                    match monitor {
+                                        PlatformMonitorHandle::Wayland(monitor) => {
+                                            Some(monitor.proxy)
+                                        }
+                                        #[cfg(feature = "x11")]
+                                        PlatformMonitorHandle::X(_) => None,
+                                        #[cfg(feature = "mock")]
+                                        PlatformMonitorHandle::Mock(_) => None,
+                                    }
+                                });
+
+                            window.set_fullscreen(monitor.as_ref());
+                        }
+                        None => {
+                            if attributes.maximized {
+                                window.set_maximized();
+                            }
+                        }
+                    }
 
-        // Set common window attributes.
-        //
-        // We set resizable after other attributes, since it touches min and max size under
-        // the hood.
-        window.set_resizable(attributes.resizable);
-        window.set_title(attributes.title);
+                    // Dialogs stay above of their parent.
+                    if let Some(parent) = platform_attributes.parent_window {
+                        let state = event_loop_window_target.state.borrow();
+                        let parent = match parent.0 {
+                            crate::platform_impl::WindowId::Wayland(parent) => {
+                                state.window_map.get(&parent)
+                            }
+                            #[allow(unreachable_patterns)]
+                            _ => None,
+                        };
 
-        // Set fullscreen/maximized if so was requested.
-        match attributes.fullscreen {
-            Some(Fullscreen::Exclusive(_)) => {
-                warn!("`Fullscreen::Exclusive` is ignored on Wayland")
-            }
-            Some(Fullscreen::Borderless(monitor)) => {
-                let monitor =
-                    monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
-                        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                        #[cfg(feature = "x11")]
-                        PlatformMonitorHandle::X(_) => None,
-                    });
+                        match (&shell, parent.map(|parent| &parent.window)) {
+                            (WindowShell::Xdg(window), Some(WindowShell::Xdg(parent))) => {
+                                window.set_parent(Some(parent.xdg_toplevel()));
+                            }
+                            // SCTK windows, used without `xdg_wm_base`, don't expose their
+                            // `xdg_toplevel`.
+                            (WindowShell::Toplevel(_), Some(WindowShell::Toplevel(_))) => {
+                                warn!("the parent of a dialog can't be set without xdg_wm_base")
+                            }
+                            _ => {
+                                return Err(os_error!(OsError::WaylandMisc(
+                                    "the parent of a dialog isn't a toplevel window of this \
+                                     event loop"
+                                )))
+                            }
+                        }
+                    }
 
-                window.set_fullscreen(monitor.as_ref());
-            }
-            None => {
-                if attributes.maximized {
-                    window.set_maximized();
+                    shell
                 }
-            }
-        }
+            },
+        };
 
         let size = Arc::new(Mutex::new(LogicalSize::new(width, height)));
 
@@ -201,7 +435,27 @@ impl Window {
         let window_requests = Arc::new(Mutex::new(Vec::with_capacity(64)));
 
         // Create a handle that performs all the requests on underlying sctk a window.
-        let window_handle = WindowHandle::new(window, size.clone(), window_requests.clone());
+        let cursor_surface = event_loop_window_target.env.create_surface().detach();
+        let compositor = event_loop_window_target
+            .env
+            .require_global::<WlCompositor>();
+        let mut window_handle = WindowHandle::new(
+            shell,
+            size.clone(),
+            window_requests.clone(),
+            cursor_surface,
+            compositor,
+        );
+
+        // Modal dialogs block the input of their parent, like on X11.
+        if platform_attributes.modal {
+            window_handle.modal_parent = match platform_attributes.parent_window {
+                Some(crate::window::WindowId(crate::platform_impl::WindowId::Wayland(parent))) => {
+                    Some(parent)
+                }
+                _ => None,
+            };
+        }
 
         let mut winit_state = event_loop_window_target.state.borrow_mut();
 
@@ -244,6 +498,9 @@ impl Window {
             fullscreen,
             maximized,
             windowing_features,
+            size_constraints,
+            child_position,
+            is_popup,
         };
 
         Ok(window)
@@ -270,17 +527,35 @@ impl Window {
 
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        Err(NotSupportedError::new())
+        // Only child windows know their position, relative to their parent.
+        match self.child_position {
+            Some(ref position) => {
+                let scale_factor = self.scale_factor() as f64;
+                Ok(position.lock().unwrap().to_physical(scale_factor))
+            }
+            None => Err(NotSupportedError::new()),
+        }
     }
 
     #[inline]
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        Err(NotSupportedError::new())
+        self.outer_position()
     }
 
     #[inline]
-    pub fn set_outer_position(&self, _: Position) {
-        // Not possible on Wayland.
+    pub fn set_outer_position(&self, position: Position) {
+        // Not possible on Wayland, except for child windows.
+        let child_position = match self.child_position {
+            Some(ref child_position) => child_position,
+            None => return,
+        };
+
+        let position = position.to_logical::<i32>(self.scale_factor() as f64);
+        *child_position.lock().unwrap() = position;
+
+        let position_request = WindowRequest::Position(position);
+        self.window_requests.lock().unwrap().push(position_request);
+        self.event_loop_awakener.ping();
     }
 
     pub fn inner_size(&self) -> PhysicalSize<u32> {
@@ -322,6 +597,7 @@ impl Window {
 
     #[inline]
     pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
+        self.size_constraints.lock().unwrap().min_size = dimensions;
         let scale_factor = self.scale_factor() as f64;
         let size = dimensions.map(|size| size.to_logical::<u32>(scale_factor));
 
@@ -340,6 +616,19 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        // The increments are applied on the next configure.
+        self.size_constraints.lock().unwrap().resize_increments = increments;
+    }
+
+    #[inline]
+    pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
+        let scale_factor = self.scale_factor() as f64;
+        let increments = self.size_constraints.lock().unwrap().resize_increments;
+        increments.map(|increments| increments.to_physical(scale_factor))
+    }
+
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
         let resizeable_request = WindowRequest::Resizeable(resizable);
@@ -414,6 +703,8 @@ impl Window {
                         PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
                         #[cfg(feature = "x11")]
                         PlatformMonitorHandle::X(_) => None,
+                        #[cfg(feature = "mock")]
+                        PlatformMonitorHandle::Mock(_) => None,
                     });
 
                 WindowRequest::Fullscreen(monitor)
@@ -561,6 +852,16 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) {
+        let custom_cursor_request = WindowRequest::NewCustomCursor(cursor.clone());
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(custom_cursor_request);
+        self.event_loop_awakener.ping();
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         let cursor_visible_request = WindowRequest::ShowCursor(visible);
@@ -572,12 +873,12 @@ impl Window {
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        if !self.windowing_features.cursor_grab() {
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        if mode != CursorGrabMode::None && !self.windowing_features.cursor_grab() {
             return Err(ExternalError::NotSupported(NotSupportedError::new()));
         }
 
-        let cursor_grab_request = WindowRequest::GrabCursor(grab);
+        let cursor_grab_request = WindowRequest::GrabCursor(mode);
         self.window_requests
             .lock()
             .unwrap()
@@ -587,6 +888,42 @@ impl Window {
         Ok(())
     }
 
+    #[inline]
+    pub fn set_cursor_grab_region(
+        &self,
+        region: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        if region.is_some() && !self.windowing_features.cursor_grab() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let cursor_grab_region_request = WindowRequest::CursorGrabRegion(region);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(cursor_grab_region_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_popup_grab(&self, grab: bool) -> Result<(), ExternalError> {
+        // The grab of an `xdg_popup` lasts until it's dismissed.
+        if !self.is_popup || !grab {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let popup_grab_request = WindowRequest::PopupGrab;
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(popup_grab_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
     #[inline]
     pub fn set_cursor_position(&self, _: Position) -> Result<(), ExternalError> {
         // XXX This is possible if the locked pointer is being used. We don't have any
@@ -611,6 +948,22 @@ impl Window {
         Ok(())
     }
 
+    #[inline]
+    pub fn start_drag(
+        &self,
+        data: Vec<(String, Vec<u8>)>,
+        allowed_actions: DndActions,
+    ) -> Result<(), ExternalError> {
+        let start_drag_request = WindowRequest::StartDrag(data, allowed_actions);
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(start_drag_request);
+        self.event_loop_awakener.ping();
+
+        Ok(())
+    }
+
     #[inline]
     pub fn set_ime_position(&self, position: Position) {
         let scale_factor = self.scale_factor() as f64;
@@ -623,6 +976,15 @@ impl Window {
         self.event_loop_awakener.ping();
     }
 
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.window_requests
+            .lock()
+            .unwrap()
+            .push(WindowRequest::AllowIme(allowed));
+        self.event_loop_awakener.ping();
+    }
+
     #[inline]
     pub fn display(&self) -> &Display {
         &self.display
@@ -679,3 +1041,38 @@ impl Drop for Window {
         self.event_loop_awakener.ping();
     }
 }
+
+/// The size constraints which Wayland leaves to the client.
+#[derive(Debug, Clone, Copy)]
+struct SizeConstraints {
+    /// The minimum size, which the resize increments are counted from.
+    min_size: Option<Size>,
+    resize_increments: Option<Size>,
+}
+
+impl SizeConstraints {
+    /// Snaps a size the compositor configured the window with down to the resize increments.
+    fn snap(&self, size: LogicalSize<u32>, scale_factor: f64) -> LogicalSize<u32> {
+        let increments = match self.resize_increments {
+            Some(increments) => increments.to_logical::<u32>(scale_factor),
+            None => return size,
+        };
+        let base_size = self
+            .min_size
+            .map(|min_size| min_size.to_logical::<u32>(scale_factor))
+            .unwrap_or_else(|| LogicalSize::new(0, 0));
+
+        LogicalSize::new(
+            snap_to_increment(size.width, base_size.width, increments.width),
+            snap_to_increment(size.height, base_size.height, increments.height),
+        )
+    }
+}
+
+fn snap_to_increment(size: u32, base: u32, increment: u32) -> u32 {
+    if increment == 0 || size <= base {
+        size
+    } else {
+        size - (size - base) % increment
+    }
+}
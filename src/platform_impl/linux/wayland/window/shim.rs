@@ -1,18 +1,31 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
+use sctk::reexports::calloop::timer::Timeout;
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
 use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_region::WlRegion;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_subsurface::WlSubsurface;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Attached;
 
 use sctk::window::{ConceptConfig, ConceptFrame, Decorations, Window};
 
-use crate::dpi::{LogicalPosition, LogicalSize};
+use crate::dnd::{DndActions, DragOutcome};
+use crate::dpi::{LogicalPosition, LogicalSize, Position, Size};
 
-use crate::event::WindowEvent;
-use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::event::{Ime, WindowEvent};
+use crate::platform_impl::wayland::cursor::{CursorBuffers, CursorManager, CustomCursor};
+use crate::platform_impl::wayland::event_loop::{EventSink, WinitState};
 use crate::platform_impl::wayland::seat::pointer::WinitPointer;
 use crate::platform_impl::wayland::seat::text_input::TextInputHandler;
-use crate::platform_impl::wayland::WindowId;
-use crate::window::CursorIcon;
+use crate::platform_impl::wayland::{self, WindowId};
+use crate::window::{CursorGrabMode, CursorIcon};
+
+use super::xdg::{XdgPopupWindow, XdgWindow};
 
 /// A request to SCTK window from Winit window.
 #[derive(Debug, Clone)]
@@ -31,12 +44,21 @@ pub enum WindowRequest {
     /// Change the cursor icon.
     NewCursorIcon(CursorIcon),
 
+    /// Show a custom cursor.
+    NewCustomCursor(CustomCursor),
+
     /// Grab cursor.
-    GrabCursor(bool),
+    GrabCursor(CursorGrabMode),
+
+    /// Restrict the confined cursor to a region of the window.
+    CursorGrabRegion(Option<(Position, Size)>),
 
     /// Drag window.
     DragWindow,
 
+    /// Start dragging data out of the window.
+    StartDrag(Vec<(String, Vec<u8>)>, DndActions),
+
     /// Maximize the window.
     Maximize(bool),
 
@@ -64,6 +86,15 @@ pub enum WindowRequest {
     /// Set IME window position.
     IMEPosition(LogicalPosition<u32>),
 
+    /// Whether the window receives `WindowEvent::Ime`.
+    AllowIme(bool),
+
+    /// Move a child window within its parent.
+    Position(LogicalPosition<i32>),
+
+    /// Grab the input for a popup.
+    PopupGrab,
+
     /// Redraw was requested.
     Redraw,
 
@@ -127,11 +158,190 @@ impl WindowUpdate {
     }
 }
 
+/// The shell requests of toplevel windows, whether their `xdg_toplevel` is ours or SCTK's.
+pub trait Toplevel {
+    /// Redraw the frame.
+    fn refresh(&mut self);
+
+    /// Resize the frame to the size of the content.
+    fn resize(&mut self, width: u32, height: u32);
+
+    fn set_title(&self, title: String);
+
+    fn set_app_id(&self, app_id: String);
+
+    fn set_decorate(&mut self, decorate: Decorations);
+
+    fn set_resizable(&self, resizable: bool);
+
+    fn set_min_size(&mut self, size: Option<(u32, u32)>);
+
+    fn set_max_size(&mut self, size: Option<(u32, u32)>);
+
+    fn set_maximized(&self);
+
+    fn unset_maximized(&self);
+
+    fn set_minimized(&self);
+
+    fn set_fullscreen(&self, output: Option<&WlOutput>);
+
+    fn unset_fullscreen(&self);
+
+    fn set_frame_config(&mut self, config: ConceptConfig);
+
+    fn start_interactive_move(&self, seat: &WlSeat, serial: u32);
+}
+
+impl Toplevel for Window<ConceptFrame> {
+    fn refresh(&mut self) {
+        Window::refresh(self);
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        Window::resize(self, width, height);
+    }
+
+    fn set_title(&self, title: String) {
+        Window::set_title(self, title);
+    }
+
+    fn set_app_id(&self, app_id: String) {
+        Window::set_app_id(self, app_id);
+    }
+
+    fn set_decorate(&mut self, decorate: Decorations) {
+        Window::set_decorate(self, decorate);
+    }
+
+    fn set_resizable(&self, resizable: bool) {
+        Window::set_resizable(self, resizable);
+    }
+
+    fn set_min_size(&mut self, size: Option<(u32, u32)>) {
+        Window::set_min_size(self, size);
+    }
+
+    fn set_max_size(&mut self, size: Option<(u32, u32)>) {
+        Window::set_max_size(self, size);
+    }
+
+    fn set_maximized(&self) {
+        Window::set_maximized(self);
+    }
+
+    fn unset_maximized(&self) {
+        Window::unset_maximized(self);
+    }
+
+    fn set_minimized(&self) {
+        Window::set_minimized(self);
+    }
+
+    fn set_fullscreen(&self, output: Option<&WlOutput>) {
+        Window::set_fullscreen(self, output);
+    }
+
+    fn unset_fullscreen(&self) {
+        Window::unset_fullscreen(self);
+    }
+
+    fn set_frame_config(&mut self, config: ConceptConfig) {
+        Window::set_frame_config(self, config);
+    }
+
+    fn start_interactive_move(&self, seat: &WlSeat, serial: u32) {
+        Window::start_interactive_move(self, seat, serial);
+    }
+}
+
+/// The role of the surface of a window.
+pub enum WindowShell {
+    /// A toplevel window, decorated by SCTK, for compositors without `xdg_wm_base`.
+    Toplevel(Window<ConceptFrame>),
+
+    /// A toplevel window, with the `xdg_toplevel` we created from `xdg_wm_base`.
+    Xdg(XdgWindow),
+
+    /// A popup window, placed by the compositor next to its parent.
+    Popup(XdgPopupWindow),
+
+    /// A child window, shown inside of the surface of its parent.
+    Subsurface {
+        surface: WlSurface,
+        subsurface: WlSubsurface,
+
+        /// The surface of the parent, whose commits apply the position of the subsurface.
+        parent: WlSurface,
+    },
+}
+
+impl WindowShell {
+    pub fn surface(&self) -> &WlSurface {
+        match self {
+            WindowShell::Toplevel(window) => window.surface(),
+            WindowShell::Xdg(window) => window.surface(),
+            WindowShell::Popup(popup) => popup.surface(),
+            WindowShell::Subsurface { surface, .. } => surface,
+        }
+    }
+
+    /// The toplevel windows, which the shell requests apply to.
+    pub fn toplevel(&mut self) -> Option<&mut dyn Toplevel> {
+        match self {
+            WindowShell::Toplevel(window) => Some(window),
+            WindowShell::Xdg(window) => Some(window),
+            WindowShell::Popup(_) | WindowShell::Subsurface { .. } => None,
+        }
+    }
+
+    /// Remember that popups were asked to draw themselves, after which they can't grab.
+    pub fn set_drawn(&self) {
+        if let WindowShell::Popup(popup) = self {
+            popup.set_drawn();
+        }
+    }
+
+    /// Redraw the frame of toplevel windows.
+    pub fn refresh(&mut self) {
+        if let Some(window) = self.toplevel() {
+            window.refresh();
+        }
+    }
+
+    /// Resize the frame of toplevel windows. Subsurfaces take the size of their buffer.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if let Some(window) = self.toplevel() {
+            window.resize(width, height);
+        }
+    }
+}
+
+impl Drop for WindowShell {
+    fn drop(&mut self) {
+        if let WindowShell::Subsurface {
+            surface,
+            subsurface,
+            ..
+        } = self
+        {
+            subsurface.destroy();
+            surface.destroy();
+        }
+    }
+}
+
+/// A cursor shown over the window.
+enum WindowCursor {
+    Icon(CursorIcon),
+    Custom(Rc<CursorBuffers>),
+}
+
 /// A handle to perform operations on SCTK window
 /// and react to events.
 pub struct WindowHandle {
     /// An actual window.
-    pub window: Window<ConceptFrame>,
+    pub window: WindowShell,
 
     /// The current size of the window.
     pub size: Arc<Mutex<LogicalSize<u32>>>,
@@ -139,72 +349,159 @@ pub struct WindowHandle {
     /// A pending requests to SCTK window.
     pub pending_window_requests: Arc<Mutex<Vec<WindowRequest>>>,
 
-    /// Current cursor icon.
-    pub cursor_icon: Cell<CursorIcon>,
+    /// Current cursor.
+    cursor: RefCell<WindowCursor>,
+
+    /// Surface we draw custom and animated cursors on.
+    cursor_surface: WlSurface,
+
+    /// Hotspot of the cursor in surface coordinates, if it's drawn on `cursor_surface`.
+    cursor_hotspot: Cell<Option<(i32, i32)>>,
+
+    /// Current frame of the cursor.
+    cursor_frame: Cell<usize>,
+
+    /// The scheduled next frame of an animated cursor.
+    cursor_animation: RefCell<Option<Timeout>>,
+
+    /// Whether the animation waits for a pointer to enter the window.
+    cursor_animation_paused: Cell<bool>,
 
     /// Visible cursor or not.
     cursor_visible: Cell<bool>,
 
-    /// Cursor confined to the surface.
-    confined: Cell<bool>,
+    /// How the cursor is grabbed by the surface.
+    cursor_grab_mode: Cell<CursorGrabMode>,
+
+    /// The part of the surface a confined cursor is restricted to, if not the whole surface.
+    cursor_grab_region: Cell<Option<(Position, Size)>>,
+
+    /// Compositor to create the cursor grab regions with.
+    compositor: Attached<WlCompositor>,
+
+    /// The parent whose input is blocked, if the window is a modal dialog.
+    pub modal_parent: Option<WindowId>,
 
     /// Pointers over the current surface.
     pointers: Vec<WinitPointer>,
 
     /// Text inputs on the current surface.
     text_inputs: Vec<TextInputHandler>,
+
+    /// Whether the window receives `WindowEvent::Ime`.
+    ime_allowed: Cell<bool>,
 }
 
 impl WindowHandle {
     pub fn new(
-        window: Window<ConceptFrame>,
+        window: WindowShell,
         size: Arc<Mutex<LogicalSize<u32>>>,
         pending_window_requests: Arc<Mutex<Vec<WindowRequest>>>,
+        cursor_surface: WlSurface,
+        compositor: Attached<WlCompositor>,
     ) -> Self {
         Self {
             window,
             size,
             pending_window_requests,
-            cursor_icon: Cell::new(CursorIcon::Default),
-            confined: Cell::new(false),
+            cursor: RefCell::new(WindowCursor::Icon(CursorIcon::Default)),
+            cursor_surface,
+            cursor_hotspot: Cell::new(None),
+            cursor_frame: Cell::new(0),
+            cursor_animation: RefCell::new(None),
+            cursor_animation_paused: Cell::new(false),
+            cursor_grab_mode: Cell::new(CursorGrabMode::None),
+            cursor_grab_region: Cell::new(None),
+            compositor,
             cursor_visible: Cell::new(true),
+            modal_parent: None,
             pointers: Vec::new(),
             text_inputs: Vec::new(),
+            ime_allowed: Cell::new(false),
         }
     }
 
-    pub fn set_cursor_grab(&self, grab: bool) {
-        // The new requested state matches the current confine status, return.
-        if self.confined.get() == grab {
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) {
+        // The new requested mode matches the current one, return.
+        if self.cursor_grab_mode.get() == mode {
             return;
         }
 
-        self.confined.replace(grab);
+        self.cursor_grab_mode.replace(mode);
 
         for pointer in self.pointers.iter() {
-            if self.confined.get() {
-                let surface = self.window.surface();
-                pointer.confine(&surface);
-            } else {
-                pointer.unconfine();
-            }
+            self.constrain_pointer(pointer);
+        }
+    }
+
+    pub fn set_cursor_grab_region(&self, region: Option<(Position, Size)>) {
+        self.cursor_grab_region.replace(region);
+        self.refresh_cursor_grab_region();
+    }
+
+    /// Update the region of the confined pointers, e.g. after the scale factor changed.
+    pub fn refresh_cursor_grab_region(&self) {
+        if self.cursor_grab_mode.get() != CursorGrabMode::Confined {
+            return;
+        }
+
+        let region = self.cursor_grab_region();
+        for pointer in self.pointers.iter() {
+            pointer.set_confine_region(region.as_ref());
+        }
+        if let Some(region) = region {
+            region.destroy();
         }
+
+        // The region is applied on the next commit of the surface.
+        self.window.surface().commit();
+    }
+
+    /// Confine or lock the pointer to the surface, according to the current grab.
+    fn constrain_pointer(&self, pointer: &WinitPointer) {
+        let mode = self.cursor_grab_mode.get();
+        let region = match mode {
+            CursorGrabMode::Confined => self.cursor_grab_region(),
+            _ => None,
+        };
+
+        pointer.constrain(self.window.surface(), mode, region.as_ref());
+
+        // The constraint keeps a copy of the region.
+        if let Some(region) = region {
+            region.destroy();
+        }
+    }
+
+    /// A region of the surface for the cursor grab region, if there's one.
+    fn cursor_grab_region(&self) -> Option<WlRegion> {
+        let (position, size) = self.cursor_grab_region.get()?;
+        let scale_factor = sctk::get_surface_scale_factor(self.window.surface()) as f64;
+        let position: LogicalPosition<i32> = position.to_logical(scale_factor);
+        let size: LogicalSize<i32> = size.to_logical(scale_factor);
+
+        let region = self.compositor.create_region();
+        region.add(position.x, position.y, size.width, size.height);
+        Some(region.detach())
     }
 
     /// Pointer appeared over the window.
-    pub fn pointer_entered(&mut self, pointer: WinitPointer) {
+    pub fn pointer_entered(&mut self, pointer: WinitPointer, cursor_manager: &mut CursorManager) {
         let position = self.pointers.iter().position(|p| *p == pointer);
 
         if position.is_none() {
-            if self.confined.get() {
-                let surface = self.window.surface();
-                pointer.confine(&surface);
+            if self.cursor_grab_mode.get() != CursorGrabMode::None {
+                self.constrain_pointer(&pointer);
             }
             self.pointers.push(pointer);
         }
 
         // Apply the current cursor style.
-        self.set_cursor_visible(self.cursor_visible.get());
+        if self.cursor_animation_paused.get() {
+            self.draw_cursor(cursor_manager);
+        } else {
+            self.set_cursor_visible(self.cursor_visible.get());
+        }
     }
 
     /// Pointer left the window.
@@ -214,9 +511,9 @@ impl WindowHandle {
         if let Some(position) = position {
             let pointer = self.pointers.remove(position);
 
-            // Drop the confined pointer.
-            if self.confined.get() {
-                pointer.unconfine();
+            // Drop the confined or locked pointer.
+            if self.cursor_grab_mode.get() != CursorGrabMode::None {
+                pointer.unconstrain();
             }
         }
     }
@@ -232,6 +529,21 @@ impl WindowHandle {
         }
     }
 
+    #[inline]
+    pub fn ime_allowed(&self) -> bool {
+        self.ime_allowed.get()
+    }
+
+    /// Starts or stops sending `WindowEvent::Ime`, as if the text input entered or left.
+    pub fn set_ime_allowed(&self, allowed: bool, window_id: WindowId, event_sink: &mut EventSink) {
+        if self.ime_allowed.replace(allowed) == allowed || self.text_inputs.is_empty() {
+            return;
+        }
+
+        let event = if allowed { Ime::Enabled } else { Ime::Disabled };
+        event_sink.push_window_event(WindowEvent::Ime(event), window_id);
+    }
+
     pub fn text_input_left(&mut self, text_input: TextInputHandler) {
         if let Some(position) = self.text_inputs.iter().position(|t| *t == text_input) {
             self.text_inputs.remove(position);
@@ -250,35 +562,138 @@ impl WindowHandle {
 
     pub fn set_cursor_visible(&self, visible: bool) {
         self.cursor_visible.replace(visible);
-        let cursor_icon = match visible {
-            true => Some(self.cursor_icon.get()),
-            false => None,
-        };
 
         for pointer in self.pointers.iter() {
-            pointer.set_cursor(cursor_icon)
+            self.show_cursor(pointer);
         }
     }
 
-    pub fn set_cursor_icon(&self, cursor_icon: CursorIcon) {
-        self.cursor_icon.replace(cursor_icon);
-
+    /// Show the current cursor, if visible, with the given pointer.
+    fn show_cursor(&self, pointer: &WinitPointer) {
         if !self.cursor_visible.get() {
+            pointer.set_cursor(None);
             return;
         }
 
+        match (&*self.cursor.borrow(), self.cursor_hotspot.get()) {
+            (_, Some(hotspot)) => pointer.set_custom_cursor(&self.cursor_surface, hotspot),
+            (WindowCursor::Icon(cursor_icon), None) => pointer.set_cursor(Some(*cursor_icon)),
+            // Custom cursors are always drawn.
+            (WindowCursor::Custom(_), None) => (),
+        }
+    }
+
+    pub fn set_cursor_icon(&self, cursor_icon: CursorIcon, cursor_manager: &mut CursorManager) {
+        self.cursor.replace(WindowCursor::Icon(cursor_icon));
+        self.cursor_frame.replace(0);
+        self.draw_cursor(cursor_manager);
+    }
+
+    pub fn set_custom_cursor(
+        &self,
+        buffers: Rc<CursorBuffers>,
+        cursor_manager: &mut CursorManager,
+    ) {
+        self.cursor.replace(WindowCursor::Custom(buffers));
+        self.cursor_frame.replace(0);
+        self.draw_cursor(cursor_manager);
+    }
+
+    /// Draw the current frame of the cursor for the scale factor of the window, unless it's a
+    /// static theme cursor, and schedule the next frame of animated ones.
+    pub fn draw_cursor(&self, cursor_manager: &mut CursorManager) {
+        if let Some(timeout) = self.cursor_animation.replace(None) {
+            cursor_manager.cancel_frame(&timeout);
+        }
+        self.cursor_animation_paused.replace(false);
+
+        let scale_factor = sctk::get_surface_scale_factor(self.window.surface());
+        let frame = self.cursor_frame.get();
+        let drawn_frame = match &*self.cursor.borrow() {
+            WindowCursor::Icon(cursor_icon) => cursor_manager.draw_theme_cursor(
+                *cursor_icon,
+                &self.cursor_surface,
+                scale_factor,
+                frame,
+            ),
+            WindowCursor::Custom(buffers) => {
+                Some(buffers.draw(&self.cursor_surface, scale_factor, frame))
+            }
+        };
+
+        if let Some(duration) = drawn_frame.as_ref().and_then(|frame| frame.duration) {
+            let window_id = wayland::make_wid(self.window.surface());
+            let timeout = cursor_manager.schedule_frame(window_id, duration);
+            self.cursor_animation.replace(Some(timeout));
+        }
+        self.cursor_hotspot
+            .replace(drawn_frame.map(|frame| frame.hotspot));
+
+        // The hotspot might have changed along with the image.
         for pointer in self.pointers.iter() {
-            pointer.set_cursor(Some(cursor_icon));
+            self.show_cursor(pointer);
         }
     }
 
+    /// Show the next frame of the animated cursor, or pause the animation until a pointer
+    /// enters the window.
+    pub fn next_cursor_frame(&self, cursor_manager: &mut CursorManager) {
+        self.cursor_animation.replace(None);
+        if self.pointers.is_empty() {
+            self.cursor_animation_paused.replace(true);
+            return;
+        }
+
+        self.cursor_frame
+            .replace(self.cursor_frame.get().wrapping_add(1));
+        self.draw_cursor(cursor_manager);
+    }
+
     pub fn drag_window(&self) {
+        let window: &dyn Toplevel = match self.window {
+            WindowShell::Toplevel(ref window) => window,
+            WindowShell::Xdg(ref window) => window,
+            WindowShell::Popup(_) | WindowShell::Subsurface { .. } => return,
+        };
         for pointer in self.pointers.iter() {
-            pointer.drag_window(&self.window);
+            pointer.drag_window(window);
+        }
+    }
+
+    /// Start dragging data with the first pointer over the window.
+    ///
+    /// Returns `false` if no pointer could start the drag.
+    pub fn start_drag(
+        &self,
+        data: Vec<(String, Vec<u8>)>,
+        allowed_actions: DndActions,
+        window_id: WindowId,
+    ) -> bool {
+        match self.pointers.first() {
+            Some(pointer) => {
+                pointer.start_drag(self.window.surface(), data, allowed_actions, window_id)
+            }
+            None => false,
         }
     }
 }
 
+impl Drop for WindowHandle {
+    fn drop(&mut self) {
+        self.cursor_surface.destroy();
+    }
+}
+
+/// Whether a modal dialog blocks the input of `window_id`.
+pub fn is_blocked_by_modal(
+    window_map: &HashMap<WindowId, WindowHandle>,
+    window_id: WindowId,
+) -> bool {
+    window_map
+        .values()
+        .any(|window_handle| window_handle.modal_parent == Some(window_id))
+}
+
 #[inline]
 pub fn handle_window_requests(winit_state: &mut WinitState) {
     let window_map = &mut winit_state.window_map;
@@ -291,35 +706,101 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
         for request in requests.drain(..) {
             match request {
                 WindowRequest::Fullscreen(fullscreen) => {
-                    window_handle.window.set_fullscreen(fullscreen.as_ref());
+                    if let Some(window) = window_handle.window.toplevel() {
+                        window.set_fullscreen(fullscreen.as_ref());
+                    }
                 }
                 WindowRequest::UnsetFullscreen => {
-                    window_handle.window.unset_fullscreen();
+                    if let Some(window) = window_handle.window.toplevel() {
+                        window.unset_fullscreen();
+                    }
                 }
                 WindowRequest::ShowCursor(show_cursor) => {
                     window_handle.set_cursor_visible(show_cursor);
                 }
                 WindowRequest::NewCursorIcon(cursor_icon) => {
-                    window_handle.set_cursor_icon(cursor_icon);
+                    window_handle.set_cursor_icon(cursor_icon, &mut winit_state.cursor_manager);
+                }
+                WindowRequest::NewCustomCursor(cursor) => {
+                    let cursor_manager = &mut winit_state.cursor_manager;
+                    if let Some(buffers) = cursor_manager.custom_cursor(&cursor) {
+                        window_handle.set_custom_cursor(buffers, cursor_manager);
+                    }
                 }
                 WindowRequest::IMEPosition(position) => {
                     window_handle.set_ime_position(position);
                 }
-                WindowRequest::GrabCursor(grab) => {
-                    window_handle.set_cursor_grab(grab);
+                WindowRequest::AllowIme(allowed) => {
+                    window_handle.set_ime_allowed(allowed, *window_id, &mut winit_state.event_sink);
+                }
+                WindowRequest::Position(position) => {
+                    if let WindowShell::Subsurface {
+                        surface,
+                        subsurface,
+                        parent,
+                    } = &window_handle.window
+                    {
+                        subsurface.set_position(position.x, position.y);
+
+                        // The position applies on the next commit of the parent, so it's redrawn.
+                        let parent_id = wayland::make_wid(parent);
+                        if let Some(window_update) = window_updates.get_mut(&parent_id) {
+                            window_update.redraw_requested = true;
+                        }
+
+                        let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+                        let event_sink = &mut winit_state.event_sink;
+                        event_sink.push_window_event(
+                            WindowEvent::Moved(position.to_physical(scale_factor)),
+                            *window_id,
+                        );
+                    }
+                }
+                WindowRequest::PopupGrab => {
+                    if let WindowShell::Popup(popup) = &window_handle.window {
+                        let grabbed = match winit_state.latest_input_serial.as_ref() {
+                            Some((seat, serial)) => popup.grab(seat, *serial),
+                            None => false,
+                        };
+
+                        // Like when the compositor denies the grab, the popup is dismissed.
+                        if !grabbed {
+                            let event_sink = &mut winit_state.event_sink;
+                            event_sink.push_window_event(WindowEvent::PopupDismissed, *window_id);
+                        }
+                    }
+                }
+                WindowRequest::GrabCursor(mode) => {
+                    window_handle.set_cursor_grab(mode);
+                }
+                WindowRequest::CursorGrabRegion(region) => {
+                    window_handle.set_cursor_grab_region(region);
                 }
                 WindowRequest::DragWindow => {
                     window_handle.drag_window();
                 }
+                WindowRequest::StartDrag(data, allowed_actions) => {
+                    if !window_handle.start_drag(data, allowed_actions, *window_id) {
+                        let event_sink = &mut winit_state.event_sink;
+                        event_sink.push_window_event(
+                            WindowEvent::DragEnded(DragOutcome::Cancelled),
+                            *window_id,
+                        );
+                    }
+                }
                 WindowRequest::Maximize(maximize) => {
-                    if maximize {
-                        window_handle.window.set_maximized();
-                    } else {
-                        window_handle.window.unset_maximized();
+                    if let Some(window) = window_handle.window.toplevel() {
+                        if maximize {
+                            window.set_maximized();
+                        } else {
+                            window.unset_maximized();
+                        }
                     }
                 }
                 WindowRequest::Minimize => {
-                    window_handle.window.set_minimized();
+                    if let Some(window) = window_handle.window.toplevel() {
+                        window.set_minimized();
+                    }
                 }
                 WindowRequest::Decorate(decorate) => {
                     let decorations = match decorate {
@@ -327,21 +808,27 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                         false => Decorations::None,
                     };
 
-                    window_handle.window.set_decorate(decorations);
+                    if let Some(window) = window_handle.window.toplevel() {
+                        window.set_decorate(decorations);
+                    }
 
                     // We should refresh the frame to apply decorations change.
                     let window_update = window_updates.get_mut(&window_id).unwrap();
                     window_update.refresh_frame = true;
                 }
                 WindowRequest::Resizeable(resizeable) => {
-                    window_handle.window.set_resizable(resizeable);
+                    if let Some(window) = window_handle.window.toplevel() {
+                        window.set_resizable(resizeable);
+                    }
 
                     // We should refresh the frame to update button state.
                     let window_update = window_updates.get_mut(&window_id).unwrap();
                     window_update.refresh_frame = true;
                 }
                 WindowRequest::Title(title) => {
-                    window_handle.window.set_title(title);
+                    if let Some(window) = window_handle.window.toplevel() {
+                        window.set_title(title);
+                    }
 
                     // We should refresh the frame to draw new title.
                     let window_update = window_updates.get_mut(&window_id).unwrap();
@@ -349,14 +836,18 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                 }
                 WindowRequest::MinSize(size) => {
                     let size = size.map(|size| (size.width, size.height));
-                    window_handle.window.set_min_size(size);
+                    if let Some(window) = window_handle.window.toplevel() {
+                        window.set_min_size(size);
+                    }
 
                     let window_update = window_updates.get_mut(&window_id).unwrap();
                     window_update.redraw_requested = true;
                 }
                 WindowRequest::MaxSize(size) => {
                     let size = size.map(|size| (size.width, size.height));
-                    window_handle.window.set_max_size(size);
+                    if let Some(window) = window_handle.window.toplevel() {
+                        window.set_max_size(size);
+                    }
 
                     let window_update = window_updates.get_mut(&window_id).unwrap();
                     window_update.redraw_requested = true;
@@ -374,7 +865,9 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                     window_update.redraw_requested = true;
                 }
                 WindowRequest::Theme(concept_config) => {
-                    window_handle.window.set_frame_config(concept_config);
+                    if let Some(window) = window_handle.window.toplevel() {
+                        window.set_frame_config(concept_config);
+                    }
 
                     // We should refresh the frame to apply new theme.
                     let window_update = window_updates.get_mut(&window_id).unwrap();
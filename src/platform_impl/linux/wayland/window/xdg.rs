@@ -0,0 +1,633 @@
+//! Shell surfaces winit creates from `xdg_wm_base` itself.
+//!
+//! SCTK keeps the `xdg_surface` and `xdg_toplevel` of its windows private, while popups are
+//! created from the `xdg_surface` of their parent, and dialogs are given the `xdg_toplevel` of
+//! theirs. So when the compositor supports `xdg_wm_base`, toplevel windows are created here,
+//! decorated by the same `ConceptFrame` SCTK would use.
+
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::convert::TryInto;
+use std::rc::Rc;
+
+use sctk::environment::Environment;
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_shm::WlShm;
+use sctk::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{Attached, DispatchData};
+use sctk::reexports::protocols::unstable::xdg_decoration::v1::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1;
+use sctk::reexports::protocols::unstable::xdg_decoration::v1::client::zxdg_toplevel_decoration_v1::{
+    self, ZxdgToplevelDecorationV1,
+};
+use sctk::reexports::protocols::xdg_shell::client::xdg_popup::{self, XdgPopup};
+use sctk::reexports::protocols::xdg_shell::client::xdg_positioner::{
+    Anchor, ConstraintAdjustment, Gravity,
+};
+use sctk::reexports::protocols::xdg_shell::client::xdg_surface::{self, XdgSurface};
+use sctk::reexports::protocols::xdg_shell::client::xdg_toplevel::{self, State, XdgToplevel};
+use sctk::reexports::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
+use sctk::seat::pointer::ThemeManager;
+use sctk::seat::{self, SeatListener};
+use sctk::window::{ConceptConfig, ConceptFrame, Decorations, Event, Frame, FrameRequest};
+
+use crate::platform_impl::wayland::env::WinitEnv;
+use crate::window::PopupGravity;
+
+use super::shim::Toplevel;
+
+// The minimum width works around https://gitlab.gnome.org/GNOME/mutter/issues/259, like SCTK.
+const MIN_WINDOW_SIZE: (u32, u32) = (2, 1);
+
+/// A toplevel window with an `xdg_toplevel` of our own.
+pub struct XdgWindow {
+    surface: WlSurface,
+    xdg_surface: XdgSurface,
+    toplevel: XdgToplevel,
+    frame: Rc<RefCell<ConceptFrame>>,
+
+    /// Server side decorations, if the compositor supports them.
+    decoration: Option<ZxdgToplevelDecorationV1>,
+
+    /// The state shared with the callbacks, dropped along with the window to break the cycles
+    /// between the frame and its callback.
+    inner: Rc<RefCell<Option<ToplevelState>>>,
+
+    _seat_listener: SeatListener,
+}
+
+struct ToplevelState {
+    toplevel: XdgToplevel,
+    frame: Rc<RefCell<ConceptFrame>>,
+    handler: Box<dyn FnMut(Event, DispatchData<'_>)>,
+
+    /// Sizes of the content, without the frame.
+    min_size: (u32, u32),
+    max_size: Option<(u32, u32)>,
+    current_size: (u32, u32),
+
+    /// The size to restore once the window is no longer maximized, fullscreen or tiled.
+    old_size: Option<(u32, u32)>,
+
+    /// Whether the frame is shown when the decorations are client side.
+    decorated: bool,
+}
+
+impl ToplevelState {
+    fn frame_request(
+        &mut self,
+        request: FrameRequest,
+        serial: u32,
+        dispatch_data: DispatchData<'_>,
+    ) {
+        match request {
+            FrameRequest::Minimize => self.toplevel.set_minimized(),
+            FrameRequest::Maximize => self.toplevel.set_maximized(),
+            FrameRequest::UnMaximize => self.toplevel.unset_maximized(),
+            FrameRequest::Move(seat) => self.toplevel._move(&seat, serial),
+            FrameRequest::Resize(seat, edges) => self.toplevel.resize(&seat, serial, edges),
+            FrameRequest::ShowMenu(seat, x, y) => {
+                self.toplevel.show_window_menu(&seat, serial, x, y)
+            }
+            FrameRequest::Close => (self.handler)(Event::Close, dispatch_data),
+            FrameRequest::Refresh => (self.handler)(Event::Refresh, dispatch_data),
+        }
+    }
+
+    fn configure(
+        &mut self,
+        new_size: Option<(u32, u32)>,
+        states: Vec<State>,
+        mut dispatch_data: DispatchData<'_>,
+    ) {
+        let mut frame = self.frame.borrow_mut();
+
+        // The states go first, since the frame is hidden when fullscreen.
+        let need_refresh = frame.set_states(&states);
+
+        let mut new_size = new_size.map(|(w, h)| {
+            let (mut w, mut h) = frame.subtract_borders(w as i32, h as i32);
+            w = cmp::max(w, self.min_size.0 as i32);
+            h = cmp::max(h, self.min_size.1 as i32);
+            if let Some((max_w, max_h)) = self.max_size {
+                w = cmp::min(w, max_w as i32);
+                h = cmp::min(h, max_h as i32);
+            }
+            (cmp::max(w, 1) as u32, cmp::max(h, 1) as u32)
+        });
+        drop(frame);
+
+        // The compositor picks the size itself in these states, and may forget the previous one.
+        let is_constrained = states.iter().any(|state| {
+            matches!(
+                state,
+                State::Maximized
+                    | State::Fullscreen
+                    | State::TiledTop
+                    | State::TiledRight
+                    | State::TiledBottom
+                    | State::TiledLeft
+            )
+        });
+        if is_constrained {
+            if self.old_size.is_none() {
+                self.old_size = Some(self.current_size);
+            }
+        } else if new_size.is_none() {
+            new_size = self.old_size.take();
+        } else {
+            self.old_size = None;
+        }
+
+        if need_refresh {
+            (self.handler)(Event::Refresh, dispatch_data.reborrow());
+        }
+        (self.handler)(Event::Configure { new_size, states }, dispatch_data);
+    }
+}
+
+impl XdgWindow {
+    /// Creates the `xdg_toplevel` of `surface`, and commits its initial state.
+    pub fn new<F>(
+        env: &Environment<WinitEnv>,
+        xdg_wm_base: &Attached<XdgWmBase>,
+        surface: WlSurface,
+        theme_manager: Option<ThemeManager>,
+        initial_size: (u32, u32),
+        handler: F,
+    ) -> Result<Self, std::io::Error>
+    where
+        F: FnMut(Event, DispatchData<'_>) + 'static,
+    {
+        let compositor = env.require_global::<WlCompositor>();
+        let subcompositor = env.require_global::<WlSubcompositor>();
+        let shm = env.require_global::<WlShm>();
+
+        let inner = Rc::new(RefCell::new(None::<ToplevelState>));
+
+        let frame_inner = inner.clone();
+        let mut frame = ConceptFrame::init(
+            &surface,
+            &compositor,
+            &subcompositor,
+            &shm,
+            theme_manager,
+            Box::new(move |request, serial, dispatch_data| {
+                if let Some(inner) = frame_inner.borrow_mut().as_mut() {
+                    inner.frame_request(request, serial, dispatch_data);
+                }
+            }),
+        )?;
+
+        // Without server side decorations, the frame is drawn from the start.
+        let decoration_manager = env.get_global::<ZxdgDecorationManagerV1>();
+        if decoration_manager.is_none() {
+            frame.set_hidden(false);
+        }
+        frame.resize(initial_size);
+        let frame = Rc::new(RefCell::new(frame));
+
+        // The toplevel configure is applied by the configure of the `xdg_surface` that follows.
+        let pending_configure = Rc::new(RefCell::new(None));
+        let xdg_surface = xdg_wm_base.get_xdg_surface(&surface);
+        let configure_inner = inner.clone();
+        let surface_pending_configure = pending_configure.clone();
+        xdg_surface.quick_assign(move |xdg_surface, event, dispatch_data| {
+            if let xdg_surface::Event::Configure { serial } = event {
+                xdg_surface.ack_configure(serial);
+                let configure = surface_pending_configure.borrow_mut().take();
+                if let (Some((new_size, states)), Some(inner)) =
+                    (configure, configure_inner.borrow_mut().as_mut())
+                {
+                    inner.configure(new_size, states, dispatch_data);
+                }
+            }
+        });
+
+        let toplevel = xdg_surface.get_toplevel();
+        let close_inner = inner.clone();
+        toplevel.quick_assign(move |_, event, dispatch_data| match event {
+            xdg_toplevel::Event::Configure {
+                width,
+                height,
+                states,
+            } => {
+                // A zero size lets us pick the size.
+                let new_size = if width == 0 || height == 0 {
+                    None
+                } else {
+                    Some((cmp::max(width, 1) as u32, cmp::max(height, 1) as u32))
+                };
+                let states = states
+                    .chunks_exact(4)
+                    .map(|state| u32::from_ne_bytes(state.try_into().unwrap()))
+                    .flat_map(State::from_raw)
+                    .collect::<Vec<_>>();
+                *pending_configure.borrow_mut() = Some((new_size, states));
+            }
+            xdg_toplevel::Event::Close => {
+                if let Some(inner) = close_inner.borrow_mut().as_mut() {
+                    (inner.handler)(Event::Close, dispatch_data);
+                }
+            }
+            _ => (),
+        });
+
+        {
+            let frame = frame.borrow();
+            let (min_w, min_h) =
+                frame.add_borders(MIN_WINDOW_SIZE.0 as i32, MIN_WINDOW_SIZE.1 as i32);
+            toplevel.set_min_size(min_w, min_h);
+            let (w, h) = frame.add_borders(initial_size.0 as i32, initial_size.1 as i32);
+            let (x, y) = frame.location();
+            xdg_surface.set_window_geometry(x, y, w, h);
+        }
+
+        // The frame reacts to the pointers of all the seats.
+        let mut seats = Vec::<WlSeat>::new();
+        for seat in env.get_all_seats() {
+            seat::with_seat_data(&seat, |seat_data| {
+                if seat_data.has_pointer && !seat_data.defunct {
+                    seats.push(seat.detach());
+                    frame.borrow_mut().new_seat(&seat);
+                }
+            });
+        }
+        let seat_frame = frame.clone();
+        let seat_listener = env.listen_for_seats(move |seat, seat_data, _| {
+            let is_known = seats.contains(&seat);
+            if !is_known && seat_data.has_pointer && !seat_data.defunct {
+                seat_frame.borrow_mut().new_seat(&seat);
+                seats.push(seat.detach());
+            } else if is_known && (!seat_data.has_pointer || seat_data.defunct) {
+                seat_frame.borrow_mut().remove_seat(&seat);
+                seats.retain(|known_seat| known_seat != &*seat);
+            }
+        });
+
+        *inner.borrow_mut() = Some(ToplevelState {
+            toplevel: toplevel.detach(),
+            frame: frame.clone(),
+            handler: Box::new(handler),
+            min_size: MIN_WINDOW_SIZE,
+            max_size: None,
+            current_size: initial_size,
+            old_size: None,
+            decorated: true,
+        });
+
+        let decoration = decoration_manager.map(|decoration_manager| {
+            let decoration = decoration_manager.get_toplevel_decoration(&toplevel);
+            let decoration_frame = frame.clone();
+            let decoration_inner = inner.clone();
+            decoration.quick_assign(move |_, event, _| {
+                use zxdg_toplevel_decoration_v1::{Event, Mode};
+
+                if let Event::Configure { mode } = event {
+                    let hidden = match mode {
+                        Mode::ServerSide => true,
+                        _ => !decoration_inner
+                            .borrow()
+                            .as_ref()
+                            .map(|inner| inner.decorated)
+                            .unwrap_or(false),
+                    };
+                    decoration_frame.borrow_mut().set_hidden(hidden);
+                }
+            });
+            decoration.detach()
+        });
+
+        // The initial commit, without a buffer, makes the compositor configure the window.
+        surface.commit();
+
+        Ok(Self {
+            surface,
+            xdg_surface: xdg_surface.detach(),
+            toplevel: toplevel.detach(),
+            frame,
+            decoration,
+            inner,
+            _seat_listener: seat_listener,
+        })
+    }
+
+    pub fn surface(&self) -> &WlSurface {
+        &self.surface
+    }
+
+    pub fn xdg_surface(&self) -> &XdgSurface {
+        &self.xdg_surface
+    }
+
+    pub fn xdg_toplevel(&self) -> &XdgToplevel {
+        &self.toplevel
+    }
+
+    /// Make the window a dialog of `parent`, or a standalone window again.
+    pub fn set_parent(&self, parent: Option<&XdgToplevel>) {
+        self.toplevel.set_parent(parent);
+    }
+
+    /// The offset of the content in the window geometry, which the positions of popups are
+    /// relative to.
+    pub fn content_offset(&self) -> (i32, i32) {
+        let (x, y) = self.frame.borrow().location();
+        (-x, -y)
+    }
+}
+
+impl Toplevel for XdgWindow {
+    fn refresh(&mut self) {
+        self.frame.borrow_mut().redraw();
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (cmp::max(width, 1), cmp::max(height, 1));
+        if let Some(inner) = self.inner.borrow_mut().as_mut() {
+            inner.current_size = (width, height);
+        }
+
+        let mut frame = self.frame.borrow_mut();
+        frame.resize((width, height));
+        let (w, h) = frame.add_borders(width as i32, height as i32);
+        let (x, y) = frame.location();
+        self.xdg_surface.set_window_geometry(x, y, w, h);
+    }
+
+    fn set_title(&self, mut title: String) {
+        // Longer titles would overflow the messages of the protocol.
+        if title.len() > 1024 {
+            let mut len = 1024;
+            while !title.is_char_boundary(len) {
+                len -= 1;
+            }
+            title.truncate(len);
+        }
+        self.frame.borrow_mut().set_title(title.clone());
+        self.toplevel.set_title(title);
+    }
+
+    fn set_app_id(&self, app_id: String) {
+        self.toplevel.set_app_id(app_id);
+    }
+
+    fn set_decorate(&mut self, decorate: Decorations) {
+        use zxdg_toplevel_decoration_v1::Mode;
+
+        if let Some(inner) = self.inner.borrow_mut().as_mut() {
+            inner.decorated = decorate != Decorations::None;
+        }
+
+        match (self.decoration.as_ref(), decorate) {
+            // Client side decorations are forced by giving up on server side ones for good.
+            (Some(decoration), Decorations::ClientSide) => {
+                decoration.destroy();
+                self.decoration = None;
+                self.frame.borrow_mut().set_hidden(false);
+            }
+            (Some(decoration), Decorations::ServerSide) => decoration.set_mode(Mode::ServerSide),
+            (Some(decoration), Decorations::FollowServer) => decoration.unset_mode(),
+            // The decoration can't be created again, so it's kept with a hidden frame.
+            (Some(decoration), Decorations::None) => {
+                decoration.set_mode(Mode::ClientSide);
+                self.frame.borrow_mut().set_hidden(true);
+            }
+            (None, Decorations::None) => self.frame.borrow_mut().set_hidden(true),
+            (None, _) => self.frame.borrow_mut().set_hidden(false),
+        }
+    }
+
+    fn set_resizable(&self, resizable: bool) {
+        let mut frame = self.frame.borrow_mut();
+        frame.set_resizable(resizable);
+
+        let inner = self.inner.borrow();
+        let inner = match inner.as_ref() {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        // Non resizable windows are locked to their current size.
+        let (min_size, max_size) = match resizable {
+            true => (inner.min_size, inner.max_size),
+            false => (inner.current_size, Some(inner.current_size)),
+        };
+        let (min_w, min_h) = frame.add_borders(min_size.0 as i32, min_size.1 as i32);
+        self.toplevel.set_min_size(min_w, min_h);
+        let (max_w, max_h) = max_size
+            .map(|(w, h)| frame.add_borders(w as i32, h as i32))
+            .unwrap_or((0, 0));
+        self.toplevel.set_max_size(max_w, max_h);
+    }
+
+    fn set_min_size(&mut self, size: Option<(u32, u32)>) {
+        let size = size.unwrap_or(MIN_WINDOW_SIZE);
+        let (w, h) = self
+            .frame
+            .borrow()
+            .add_borders(size.0 as i32, size.1 as i32);
+        self.toplevel.set_min_size(w, h);
+        if let Some(inner) = self.inner.borrow_mut().as_mut() {
+            inner.min_size = size;
+        }
+    }
+
+    fn set_max_size(&mut self, size: Option<(u32, u32)>) {
+        let (w, h) = size
+            .map(|(w, h)| self.frame.borrow().add_borders(w as i32, h as i32))
+            .unwrap_or((0, 0));
+        self.toplevel.set_max_size(w, h);
+        if let Some(inner) = self.inner.borrow_mut().as_mut() {
+            inner.max_size = size;
+        }
+    }
+
+    fn set_maximized(&self) {
+        self.toplevel.set_maximized();
+    }
+
+    fn unset_maximized(&self) {
+        self.toplevel.unset_maximized();
+    }
+
+    fn set_minimized(&self) {
+        self.toplevel.set_minimized();
+    }
+
+    fn set_fullscreen(&self, output: Option<&WlOutput>) {
+        self.toplevel.set_fullscreen(output);
+    }
+
+    fn unset_fullscreen(&self) {
+        self.toplevel.unset_fullscreen();
+    }
+
+    fn set_frame_config(&mut self, config: ConceptConfig) {
+        self.frame.borrow_mut().set_config(config);
+    }
+
+    fn start_interactive_move(&self, seat: &WlSeat, serial: u32) {
+        self.toplevel._move(seat, serial);
+    }
+}
+
+impl Drop for XdgWindow {
+    fn drop(&mut self) {
+        self.inner.borrow_mut().take();
+
+        // The decoration has to go before its toplevel.
+        if let Some(decoration) = self.decoration.take() {
+            decoration.destroy();
+        }
+        self.toplevel.destroy();
+        self.xdg_surface.destroy();
+    }
+}
+
+/// Events of a popup window.
+#[derive(Debug, Clone, Copy)]
+pub enum PopupEvent {
+    /// The compositor placed the popup, with the given size.
+    Configure { size: (u32, u32) },
+
+    /// The compositor dismissed the popup, which won't be shown again.
+    Done,
+}
+
+type PopupHandler = Box<dyn FnMut(PopupEvent, DispatchData<'_>)>;
+
+/// A popup window, placed by the compositor next to a rectangle of its parent.
+pub struct XdgPopupWindow {
+    surface: WlSurface,
+    xdg_surface: XdgSurface,
+    popup: XdgPopup,
+
+    /// Whether the popup may have been drawn, and so mapped, after which it can't grab.
+    drawn: Cell<bool>,
+
+    /// Whether the popup grabbed the input.
+    grabbed: Cell<bool>,
+}
+
+impl XdgPopupWindow {
+    /// Creates the `xdg_popup` of `surface`, and commits its initial state.
+    ///
+    /// The anchor rectangle is relative to the window geometry of the parent.
+    pub fn new<F>(
+        xdg_wm_base: &Attached<XdgWmBase>,
+        surface: WlSurface,
+        parent: &XdgSurface,
+        anchor_rect: (i32, i32, u32, u32),
+        gravity: PopupGravity,
+        size: (u32, u32),
+        handler: F,
+    ) -> Self
+    where
+        F: FnMut(PopupEvent, DispatchData<'_>) + 'static,
+    {
+        let (anchor, popup_gravity) = match gravity {
+            PopupGravity::Center => (Anchor::None, Gravity::None),
+            PopupGravity::Top => (Anchor::Top, Gravity::Top),
+            PopupGravity::Bottom => (Anchor::Bottom, Gravity::Bottom),
+            PopupGravity::Left => (Anchor::Left, Gravity::Left),
+            PopupGravity::Right => (Anchor::Right, Gravity::Right),
+            PopupGravity::TopLeft => (Anchor::TopLeft, Gravity::TopLeft),
+            PopupGravity::TopRight => (Anchor::TopRight, Gravity::TopRight),
+            PopupGravity::BottomLeft => (Anchor::BottomLeft, Gravity::BottomLeft),
+            PopupGravity::BottomRight => (Anchor::BottomRight, Gravity::BottomRight),
+        };
+
+        // A popup which doesn't fit is flipped to the other side of the rectangle, then slid.
+        let positioner = xdg_wm_base.create_positioner();
+        positioner.set_size(cmp::max(size.0, 1) as i32, cmp::max(size.1, 1) as i32);
+        let (x, y, width, height) = anchor_rect;
+        positioner.set_anchor_rect(x, y, width as i32, height as i32);
+        positioner.set_anchor(anchor);
+        positioner.set_gravity(popup_gravity);
+        let constraint_adjustment = ConstraintAdjustment::FlipX
+            | ConstraintAdjustment::FlipY
+            | ConstraintAdjustment::SlideX
+            | ConstraintAdjustment::SlideY;
+        positioner.set_constraint_adjustment(constraint_adjustment.bits());
+
+        let xdg_surface = xdg_wm_base.get_xdg_surface(&surface);
+        let popup = xdg_surface.get_popup(Some(parent), &positioner);
+        positioner.destroy();
+
+        let handler: Rc<RefCell<PopupHandler>> = Rc::new(RefCell::new(Box::new(handler)));
+
+        // The popup configure is applied by the configure of the `xdg_surface` that follows.
+        let pending_size = Rc::new(RefCell::new(None));
+        let surface_pending_size = pending_size.clone();
+        let surface_handler = handler.clone();
+        xdg_surface.quick_assign(move |xdg_surface, event, dispatch_data| {
+            if let xdg_surface::Event::Configure { serial } = event {
+                xdg_surface.ack_configure(serial);
+                if let Some(size) = surface_pending_size.borrow_mut().take() {
+                    (*surface_handler.borrow_mut())(PopupEvent::Configure { size }, dispatch_data);
+                }
+            }
+        });
+        popup.quick_assign(move |_, event, dispatch_data| match event {
+            xdg_popup::Event::Configure { width, height, .. } => {
+                let size = (cmp::max(width, 1) as u32, cmp::max(height, 1) as u32);
+                *pending_size.borrow_mut() = Some(size);
+            }
+            xdg_popup::Event::PopupDone => {
+                (*handler.borrow_mut())(PopupEvent::Done, dispatch_data);
+            }
+            _ => (),
+        });
+
+        // The initial commit, without a buffer, makes the compositor place the popup.
+        surface.commit();
+
+        Self {
+            surface,
+            xdg_surface: xdg_surface.detach(),
+            popup: popup.detach(),
+            drawn: Cell::new(false),
+            grabbed: Cell::new(false),
+        }
+    }
+
+    pub fn surface(&self) -> &WlSurface {
+        &self.surface
+    }
+
+    pub fn xdg_surface(&self) -> &XdgSurface {
+        &self.xdg_surface
+    }
+
+    /// Remember that the popup was asked to draw itself.
+    pub fn set_drawn(&self) {
+        self.drawn.replace(true);
+    }
+
+    /// Grab the input with the serial of the input event the popup was opened for.
+    ///
+    /// Returns `false` if the popup can't grab anymore, since grabbing after being mapped is a
+    /// protocol error.
+    pub fn grab(&self, seat: &WlSeat, serial: u32) -> bool {
+        if self.grabbed.get() {
+            return true;
+        }
+        if self.drawn.get() {
+            return false;
+        }
+
+        self.popup.grab(seat, serial);
+        self.grabbed.replace(true);
+        true
+    }
+}
+
+impl Drop for XdgPopupWindow {
+    fn drop(&mut self) {
+        self.popup.destroy();
+        self.xdg_surface.destroy();
+    }
+}
@@ -0,0 +1,656 @@
+use std::{
+    cell::RefCell,
+    ffi::CString,
+    mem::{self, MaybeUninit},
+    os::raw::*,
+    ptr, slice,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{ffi, util, XConnection, XError};
+use crate::clipboard::ClipboardKind;
+
+/// How long we wait for the owner of a selection to hand over its contents, or the next chunk
+/// of an incremental transfer.
+const CONVERT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Largest chunk we send at once, before falling back to incremental transfers.
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Mime type under which we store text.
+const TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
+
+/// Targets which describe the selection rather than its contents.
+const META_TARGETS: [&str; 4] = ["TARGETS", "MULTIPLE", "TIMESTAMP", "SAVE_TARGETS"];
+
+#[derive(Debug)]
+pub struct ClipboardAtoms {
+    pub clipboard: ffi::Atom,
+    pub targets: ffi::Atom,
+    pub incr: ffi::Atom,
+    pub utf8_string: ffi::Atom,
+    pub text: ffi::Atom,
+    pub text_plain: ffi::Atom,
+    pub text_plain_utf8: ffi::Atom,
+    // Property of our own window on which selection owners store the converted data
+    pub transfer: ffi::Atom,
+}
+
+impl ClipboardAtoms {
+    pub fn new(xconn: &Arc<XConnection>) -> Result<Self, XError> {
+        let names = [
+            b"CLIPBOARD\0".as_ptr() as *mut c_char,
+            b"TARGETS\0".as_ptr() as *mut c_char,
+            b"INCR\0".as_ptr() as *mut c_char,
+            b"UTF8_STRING\0".as_ptr() as *mut c_char,
+            b"TEXT\0".as_ptr() as *mut c_char,
+            b"text/plain\0".as_ptr() as *mut c_char,
+            b"text/plain;charset=utf-8\0".as_ptr() as *mut c_char,
+            b"_WINIT_CLIPBOARD\0".as_ptr() as *mut c_char,
+        ];
+        let atoms = unsafe { xconn.get_atoms(&names) }?;
+        Ok(ClipboardAtoms {
+            clipboard: atoms[0],
+            targets: atoms[1],
+            incr: atoms[2],
+            utf8_string: atoms[3],
+            text: atoms[4],
+            text_plain: atoms[5],
+            text_plain_utf8: atoms[6],
+            transfer: atoms[7],
+        })
+    }
+
+    /// Interns the mime types of `data` as the targets it's offered as.
+    pub fn contents(
+        &self,
+        xconn: &XConnection,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<Contents, &'static str> {
+        let mut contents = Contents::new();
+        for (mime_type, data) in data {
+            let mime_type = CString::new(mime_type).map_err(|_| "Invalid mime type")?;
+            let target = xconn.get_atom(&mime_type);
+            contents.push((target, data.into()));
+        }
+
+        // X11 applications mostly ask for text with the legacy targets.
+        let text = contents
+            .iter()
+            .find(|(target, _)| *target == self.text_plain_utf8)
+            .map(|(_, data)| data.clone());
+        if let Some(text) = text {
+            for &alias in &[self.utf8_string, self.text, self.text_plain] {
+                if contents.iter().all(|(target, _)| *target != alias) {
+                    contents.push((alias, text.clone()));
+                }
+            }
+        }
+        Ok(contents)
+    }
+}
+
+/// Data we're offering through a selection, with the target it's offered as.
+pub type Contents = Vec<(ffi::Atom, Arc<[u8]>)>;
+
+/// An incremental transfer of data too large to be sent at once.
+struct IncrTransfer {
+    requestor: ffi::Window,
+    property: ffi::Atom,
+    target: ffi::Atom,
+    data: Arc<[u8]>,
+    offset: usize,
+    // Events we had selected on the requestor before the first transfer to it started
+    saved_event_mask: c_long,
+}
+
+pub struct Clipboard {
+    xconn: Arc<XConnection>,
+    pub atoms: ClipboardAtoms,
+    // Hidden window which owns our selections and receives the contents of the others
+    pub window: ffi::Window,
+    // First XFixes event, when XFixes is available
+    xfixes_event_base: Option<c_int>,
+    // Data we're offering while we own `CLIPBOARD` and `PRIMARY`
+    clipboard: RefCell<Option<Contents>>,
+    primary: RefCell<Option<Contents>>,
+    // Incremental transfers in progress
+    transfers: RefCell<Vec<IncrTransfer>>,
+    chunk_size: usize,
+}
+
+impl Clipboard {
+    pub fn new(xconn: Arc<XConnection>, root: ffi::Window) -> Result<Self, XError> {
+        let atoms = ClipboardAtoms::new(&xconn)?;
+        let window =
+            unsafe { (xconn.xlib.XCreateSimpleWindow)(xconn.display, root, 0, 0, 1, 1, 0, 0, 0) };
+        // Incremental transfers are driven by property changes.
+        unsafe { (xconn.xlib.XSelectInput)(xconn.display, window, ffi::PropertyChangeMask) };
+
+        // Large requests are limited by the server, so is the size of a chunk.
+        let max_request_size = unsafe {
+            match (xconn.xlib.XExtendedMaxRequestSize)(xconn.display) {
+                0 => (xconn.xlib.XMaxRequestSize)(xconn.display),
+                size => size,
+            }
+        } as usize;
+        let chunk_size = (max_request_size * 4 / 2).min(MAX_CHUNK_SIZE);
+
+        let xfixes_event_base = xconn.xfixes.as_ref().and_then(|xfixes| unsafe {
+            let mut event_base = 0;
+            let mut error_base = 0;
+            if (xfixes.XFixesQueryExtension)(xconn.display, &mut event_base, &mut error_base)
+                == ffi::False
+            {
+                return None;
+            }
+
+            let mask = ffi::XFixesSetSelectionOwnerNotifyMask
+                | ffi::XFixesSelectionWindowDestroyNotifyMask
+                | ffi::XFixesSelectionClientCloseNotifyMask;
+            for &selection in &[atoms.clipboard, ffi::XA_PRIMARY] {
+                (xfixes.XFixesSelectSelectionInput)(xconn.display, window, selection, mask);
+            }
+            Some(event_base)
+        });
+
+        xconn.check_errors()?;
+        Ok(Clipboard {
+            xconn,
+            atoms,
+            window,
+            xfixes_event_base,
+            clipboard: Default::default(),
+            primary: Default::default(),
+            transfers: Default::default(),
+            chunk_size,
+        })
+    }
+
+    fn selection(&self, kind: ClipboardKind) -> ffi::Atom {
+        match kind {
+            ClipboardKind::Clipboard => self.atoms.clipboard,
+            ClipboardKind::Primary => ffi::XA_PRIMARY,
+        }
+    }
+
+    fn kind(&self, selection: ffi::Atom) -> Option<ClipboardKind> {
+        if selection == self.atoms.clipboard {
+            Some(ClipboardKind::Clipboard)
+        } else if selection == ffi::XA_PRIMARY {
+            Some(ClipboardKind::Primary)
+        } else {
+            None
+        }
+    }
+
+    fn contents(&self, kind: ClipboardKind) -> &RefCell<Option<Contents>> {
+        match kind {
+            ClipboardKind::Clipboard => &self.clipboard,
+            ClipboardKind::Primary => &self.primary,
+        }
+    }
+
+    /// Returns our own data for `target`, as long as we own the selection.
+    fn own_data(&self, kind: ClipboardKind, target: ffi::Atom) -> Option<Arc<[u8]>> {
+        self.contents(kind)
+            .borrow()
+            .as_ref()?
+            .iter()
+            .find(|(offered, _)| *offered == target)
+            .map(|(_, data)| data.clone())
+    }
+
+    fn owner(&self, kind: ClipboardKind) -> ffi::Window {
+        unsafe { (self.xconn.xlib.XGetSelectionOwner)(self.xconn.display, self.selection(kind)) }
+    }
+
+    pub fn set_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), &'static str> {
+        let contents = self.atoms.contents(&self.xconn, data)?;
+
+        let selection = self.selection(kind);
+        unsafe {
+            (self.xconn.xlib.XSetSelectionOwner)(
+                self.xconn.display,
+                selection,
+                self.window,
+                ffi::CurrentTime,
+            );
+        }
+        if self.owner(kind) != self.window {
+            return Err("Failed to acquire ownership of the selection");
+        }
+        *self.contents(kind).borrow_mut() = Some(contents);
+        Ok(())
+    }
+
+    pub fn set_text(&self, kind: ClipboardKind, text: String) -> Result<(), &'static str> {
+        self.set_data(kind, vec![(TEXT_MIME_TYPE.to_owned(), text.into_bytes())])
+    }
+
+    pub fn mime_types(&self, kind: ClipboardKind) -> Result<Vec<String>, &'static str> {
+        let targets = match self.owner(kind) {
+            0 => return Ok(Vec::new()),
+            owner if owner == self.window => self
+                .contents(kind)
+                .borrow()
+                .iter()
+                .flatten()
+                .map(|(target, _)| *target)
+                .collect(),
+            _ => match self.convert(self.selection(kind), self.atoms.targets)? {
+                Some((ffi::XA_ATOM, data)) => data
+                    .chunks_exact(mem::size_of::<ffi::Atom>())
+                    .map(|atom| {
+                        let mut bytes = [0; mem::size_of::<ffi::Atom>()];
+                        bytes.copy_from_slice(atom);
+                        ffi::Atom::from_ne_bytes(bytes)
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            },
+        };
+
+        Ok(targets
+            .into_iter()
+            .filter_map(|target| self.xconn.get_atom_name(target))
+            .filter(|name| !META_TARGETS.contains(&name.as_str()))
+            .collect())
+    }
+
+    pub fn data(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, &'static str> {
+        let mime_type = CString::new(mime_type).map_err(|_| "Invalid mime type")?;
+        let target = self.xconn.get_atom(&mime_type);
+        match self.owner(kind) {
+            0 => Ok(None),
+            owner if owner == self.window => {
+                Ok(self.own_data(kind, target).map(|data| data.to_vec()))
+            }
+            _ => Ok(self
+                .convert(self.selection(kind), target)?
+                .map(|(_, data)| data)),
+        }
+    }
+
+    pub fn text(&self, kind: ClipboardKind) -> Result<Option<String>, &'static str> {
+        let selection = self.selection(kind);
+        match self.owner(kind) {
+            0 => return Ok(None),
+            owner if owner == self.window => {
+                let data = self.own_data(kind, self.atoms.text_plain_utf8);
+                return Ok(data.map(|data| String::from_utf8_lossy(&data).into_owned()));
+            }
+            _ => (),
+        }
+
+        // Owners that don't know about UTF-8 can still give us Latin-1.
+        if let Some((_, data)) = self.convert(selection, self.atoms.utf8_string)? {
+            Ok(Some(String::from_utf8_lossy(&data).into_owned()))
+        } else if let Some((_, data)) = self.convert(selection, ffi::XA_STRING)? {
+            Ok(Some(data.into_iter().map(char::from).collect()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Asks the owner of `selection` to convert it to `target`, and blocks until it did.
+    fn convert(
+        &self,
+        selection: ffi::Atom,
+        target: ffi::Atom,
+    ) -> Result<Option<(ffi::Atom, Vec<u8>)>, &'static str> {
+        unsafe {
+            (self.xconn.xlib.XConvertSelection)(
+                self.xconn.display,
+                selection,
+                target,
+                self.atoms.transfer,
+                self.window,
+                ffi::CurrentTime,
+            );
+        }
+        self.xconn
+            .flush_requests()
+            .map_err(|_| "Failed to request the selection")?;
+
+        let notify = self.wait_for_event(ffi::SelectionNotify, |event| {
+            let notify: &ffi::XSelectionEvent = event.as_ref();
+            // Replies to requests that timed out earlier are skipped.
+            notify.selection == selection && notify.target == target
+        })?;
+        let notify: &ffi::XSelectionEvent = notify.as_ref();
+        if notify.property == 0 {
+            // The owner refused the conversion.
+            return Ok(None);
+        }
+
+        let (property_type, data) = self.take_transfer_property()?;
+        if property_type != self.atoms.incr {
+            return Ok(Some((property_type, data)));
+        }
+
+        // Deleting the property above asked for the first chunk; an empty one ends the transfer.
+        let mut property_type = 0;
+        let mut data = Vec::new();
+        loop {
+            self.wait_for_event(ffi::PropertyNotify, |event| {
+                let property: &ffi::XPropertyEvent = event.as_ref();
+                property.atom == self.atoms.transfer && property.state == ffi::PropertyNewValue
+            })?;
+
+            let (chunk_type, chunk) = self.take_transfer_property()?;
+            if chunk.is_empty() {
+                return Ok(Some((property_type, data)));
+            }
+            property_type = chunk_type;
+            data.extend_from_slice(&chunk);
+        }
+    }
+
+    /// Reads and deletes the property on which selection owners store converted data.
+    fn take_transfer_property(&self) -> Result<(ffi::Atom, Vec<u8>), &'static str> {
+        let mut property_type = 0;
+        let mut format = 0;
+        let mut items = 0;
+        let mut bytes_after = 0;
+        let mut buffer = ptr::null_mut();
+        unsafe {
+            (self.xconn.xlib.XGetWindowProperty)(
+                self.xconn.display,
+                self.window,
+                self.atoms.transfer,
+                0,
+                // This is a quantity of 32-bit chunks, and large enough to read everything at once.
+                c_long::from(i32::MAX) / 4,
+                ffi::True,
+                ffi::AnyPropertyType as ffi::Atom,
+                &mut property_type,
+                &mut format,
+                &mut items,
+                &mut bytes_after,
+                &mut buffer,
+            );
+        }
+        self.xconn
+            .check_errors()
+            .map_err(|_| "Failed to read the selection")?;
+        if buffer.is_null() {
+            return Ok((property_type, Vec::new()));
+        }
+
+        // Xlib hands out 32-bit items as `c_long`s.
+        let item_size = match format {
+            16 => mem::size_of::<c_short>(),
+            32 => mem::size_of::<c_long>(),
+            _ => 1,
+        };
+        let data = unsafe { slice::from_raw_parts(buffer, items as usize * item_size) }.to_vec();
+        unsafe { (self.xconn.xlib.XFree)(buffer as *mut _) };
+        Ok((property_type, data))
+    }
+
+    /// Blocks until our window receives an event of `event_type` matching `predicate`.
+    fn wait_for_event<F>(
+        &self,
+        event_type: c_int,
+        mut predicate: F,
+    ) -> Result<ffi::XEvent, &'static str>
+    where
+        F: FnMut(&ffi::XEvent) -> bool,
+    {
+        let deadline = Instant::now() + CONVERT_TIMEOUT;
+        loop {
+            let mut event = MaybeUninit::uninit();
+            let found = unsafe {
+                (self.xconn.xlib.XCheckTypedWindowEvent)(
+                    self.xconn.display,
+                    self.window,
+                    event_type,
+                    event.as_mut_ptr(),
+                )
+            };
+            if found == ffi::True {
+                let event = unsafe { event.assume_init() };
+                if predicate(&event) {
+                    return Ok(event);
+                }
+                continue;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err("Timed out waiting for the selection owner");
+            }
+
+            let mut fd = libc::pollfd {
+                fd: self.xconn.x11_fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout = (deadline - now).as_millis() as c_int;
+            unsafe { libc::poll(&mut fd, 1, timeout) };
+        }
+    }
+
+    /// Answers another client asking for the contents of a selection we own.
+    pub fn handle_request(&self, request: &ffi::XSelectionRequestEvent) -> Result<(), XError> {
+        match self.kind(request.selection) {
+            Some(kind) => self.answer_request(request, self.contents(kind).borrow().as_ref()),
+            None => self.answer_request(request, None),
+        }
+    }
+
+    /// Converts `contents` as requested, or refuses the request when there's nothing to offer.
+    pub fn answer_request(
+        &self,
+        request: &ffi::XSelectionRequestEvent,
+        contents: Option<&Contents>,
+    ) -> Result<(), XError> {
+        // Obsolete clients leave the property unset and expect us to use the target.
+        let property = if request.property == 0 {
+            request.target
+        } else {
+            request.property
+        };
+
+        let converted = match contents {
+            Some(contents) if request.target == self.atoms.targets => {
+                let mut targets = vec![self.atoms.targets];
+                targets.extend(contents.iter().map(|(target, _)| *target));
+                self.xconn
+                    .change_property(
+                        request.requestor,
+                        property,
+                        ffi::XA_ATOM,
+                        util::PropMode::Replace,
+                        &targets,
+                    )
+                    .queue();
+                true
+            }
+            Some(contents) => match contents
+                .iter()
+                .find(|(offered, _)| *offered == request.target)
+            {
+                Some((_, data)) => {
+                    // `TEXT` lets us pick the encoding, so we always answer with UTF-8.
+                    let target = if request.target == self.atoms.text {
+                        self.atoms.utf8_string
+                    } else {
+                        request.target
+                    };
+                    self.send_data(request.requestor, property, target, data.clone());
+                    true
+                }
+                None => false,
+            },
+            None => false,
+        };
+
+        let notify = ffi::XSelectionEvent {
+            type_: ffi::SelectionNotify,
+            serial: 0,
+            send_event: ffi::True,
+            display: self.xconn.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property: if converted { property } else { 0 },
+            time: request.time,
+        };
+        self.xconn
+            .send_event(request.requestor, None, notify)
+            .flush()
+    }
+
+    fn send_data(
+        &self,
+        requestor: ffi::Window,
+        property: ffi::Atom,
+        target: ffi::Atom,
+        data: Arc<[u8]>,
+    ) {
+        if data.len() <= self.chunk_size {
+            self.xconn
+                .change_property(requestor, property, target, util::PropMode::Replace, &data)
+                .queue();
+            return;
+        }
+
+        // The requestor deletes the property whenever it's ready for the next chunk. It may be one
+        // of our windows, so we add to the events we select on it, until its last transfer ends.
+        let saved_event_mask = match self
+            .transfers
+            .borrow()
+            .iter()
+            .find(|t| t.requestor == requestor)
+        {
+            Some(transfer) => transfer.saved_event_mask,
+            None => unsafe {
+                let mut attributes: ffi::XWindowAttributes = mem::zeroed();
+                let event_mask = if (self.xconn.xlib.XGetWindowAttributes)(
+                    self.xconn.display,
+                    requestor,
+                    &mut attributes,
+                ) != 0
+                {
+                    attributes.your_event_mask
+                } else {
+                    ffi::NoEventMask
+                };
+                (self.xconn.xlib.XSelectInput)(
+                    self.xconn.display,
+                    requestor,
+                    event_mask | ffi::PropertyChangeMask,
+                );
+                event_mask
+            },
+        };
+        self.xconn
+            .change_property(
+                requestor,
+                property,
+                self.atoms.incr,
+                util::PropMode::Replace,
+                &[data.len() as c_ulong],
+            )
+            .queue();
+        self.transfers.borrow_mut().push(IncrTransfer {
+            requestor,
+            property,
+            target,
+            data,
+            offset: 0,
+            saved_event_mask,
+        });
+    }
+
+    /// Sends the next chunk of an incremental transfer once its requestor consumed the last one.
+    pub fn handle_property_notify(&self, event: &ffi::XPropertyEvent) {
+        if event.state != ffi::PropertyDelete {
+            return;
+        }
+
+        let mut transfers = self.transfers.borrow_mut();
+        let index = match transfers
+            .iter()
+            .position(|t| t.requestor == event.window && t.property == event.atom)
+        {
+            Some(index) => index,
+            None => return,
+        };
+
+        let transfer = &mut transfers[index];
+        let end = (transfer.offset + self.chunk_size).min(transfer.data.len());
+        let chunk = &transfer.data[transfer.offset..end];
+        self.xconn
+            .change_property(
+                transfer.requestor,
+                transfer.property,
+                transfer.target,
+                util::PropMode::Replace,
+                chunk,
+            )
+            .queue();
+
+        // The empty chunk we've just sent ends the transfer.
+        if chunk.is_empty() {
+            let transfer = transfers.swap_remove(index);
+            if transfers.iter().all(|t| t.requestor != transfer.requestor) {
+                unsafe {
+                    (self.xconn.xlib.XSelectInput)(
+                        self.xconn.display,
+                        transfer.requestor,
+                        transfer.saved_event_mask,
+                    );
+                }
+            }
+        } else {
+            transfer.offset = end;
+        }
+
+        if let Err(err) = self.xconn.flush_requests() {
+            warn!("Failed to send a clipboard chunk: {:?}", err);
+        }
+    }
+
+    /// Drops the data we were offering after another client took over the selection.
+    pub fn handle_clear(&self, clear: &ffi::XSelectionClearEvent) {
+        if let Some(kind) = self.kind(clear.selection) {
+            self.contents(kind).borrow_mut().take();
+        }
+    }
+
+    /// Returns the clipboard whose owner changed, when `event` notifies about it.
+    pub fn handle_xfixes_event(&self, event: &ffi::XEvent) -> Option<ClipboardKind> {
+        let event_base = self.xfixes_event_base?;
+        if event.get_type() != event_base + ffi::XFixesSelectionNotify {
+            return None;
+        }
+
+        let event =
+            unsafe { &*(event as *const ffi::XEvent as *const ffi::XFixesSelectionNotifyEvent) };
+        // We know about our own changes.
+        if event.owner == self.window {
+            return None;
+        }
+        self.kind(event.selection)
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xconn.xlib.XDestroyWindow)(self.xconn.display, self.window);
+        }
+    }
+}
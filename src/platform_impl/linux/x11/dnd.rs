@@ -1,14 +1,11 @@
-use std::{
-    io,
-    os::raw::*,
-    path::{Path, PathBuf},
-    str::Utf8Error,
-    sync::Arc,
-};
-
-use percent_encoding::percent_decode;
+use std::{os::raw::*, path::PathBuf, sync::Arc};
 
 use super::{ffi, util, XConnection, XError};
+use crate::{
+    dnd::{DndAction, DropData},
+    dpi::PhysicalPosition,
+    platform_impl::platform::common::dnd::{self, DndDataParseError, TEXT_MIME_TYPES},
+};
 
 #[derive(Debug)]
 pub struct DndAtoms {
@@ -18,7 +15,9 @@ pub struct DndAtoms {
     pub drop: ffi::Atom,
     pub position: ffi::Atom,
     pub status: ffi::Atom,
-    pub action_private: ffi::Atom,
+    pub action_copy: ffi::Atom,
+    pub action_move: ffi::Atom,
+    pub action_link: ffi::Atom,
     pub selection: ffi::Atom,
     pub finished: ffi::Atom,
     pub type_list: ffi::Atom,
@@ -35,12 +34,14 @@ impl DndAtoms {
             b"XdndDrop\0".as_ptr() as *mut c_char,
             b"XdndPosition\0".as_ptr() as *mut c_char,
             b"XdndStatus\0".as_ptr() as *mut c_char,
-            b"XdndActionPrivate\0".as_ptr() as *mut c_char,
             b"XdndSelection\0".as_ptr() as *mut c_char,
             b"XdndFinished\0".as_ptr() as *mut c_char,
             b"XdndTypeList\0".as_ptr() as *mut c_char,
             b"text/uri-list\0".as_ptr() as *mut c_char,
             b"None\0".as_ptr() as *mut c_char,
+            b"XdndActionCopy\0".as_ptr() as *mut c_char,
+            b"XdndActionMove\0".as_ptr() as *mut c_char,
+            b"XdndActionLink\0".as_ptr() as *mut c_char,
         ];
         let atoms = unsafe { xconn.get_atoms(&names) }?;
         Ok(DndAtoms {
@@ -50,41 +51,46 @@ impl DndAtoms {
             drop: atoms[3],
             position: atoms[4],
             status: atoms[5],
-            action_private: atoms[6],
-            selection: atoms[7],
-            finished: atoms[8],
-            type_list: atoms[9],
-            uri_list: atoms[10],
-            none: atoms[11],
+            selection: atoms[6],
+            finished: atoms[7],
+            type_list: atoms[8],
+            uri_list: atoms[9],
+            none: atoms[10],
+            action_copy: atoms[11],
+            action_move: atoms[12],
+            action_link: atoms[13],
         })
     }
-}
-
-#[derive(Debug, Clone, Copy)]
-pub enum DndState {
-    Accepted,
-    Rejected,
-}
 
-#[derive(Debug)]
-pub enum DndDataParseError {
-    EmptyData,
-    InvalidUtf8(Utf8Error),
-    HostnameSpecified(String),
-    UnexpectedProtocol(String),
-    UnresolvablePath(io::Error),
-}
+    pub fn action(&self, atom: ffi::Atom) -> Option<DndAction> {
+        if atom == self.action_copy {
+            Some(DndAction::Copy)
+        } else if atom == self.action_move {
+            Some(DndAction::Move)
+        } else if atom == self.action_link {
+            Some(DndAction::Link)
+        } else {
+            None
+        }
+    }
 
-impl From<Utf8Error> for DndDataParseError {
-    fn from(e: Utf8Error) -> Self {
-        DndDataParseError::InvalidUtf8(e)
+    pub fn action_atom(&self, action: Option<DndAction>) -> ffi::Atom {
+        match action {
+            Some(DndAction::Copy) => self.action_copy,
+            Some(DndAction::Move) => self.action_move,
+            Some(DndAction::Link) => self.action_link,
+            None => self.none,
+        }
     }
 }
 
-impl From<io::Error> for DndDataParseError {
-    fn from(e: io::Error) -> Self {
-        DndDataParseError::UnresolvablePath(e)
-    }
+/// A drop waiting for its data to be converted.
+#[derive(Debug, Clone, Copy)]
+pub struct PendingDrop {
+    pub source_window: c_ulong,
+    pub target: ffi::Atom,
+    pub position: PhysicalPosition<f64>,
+    pub action: DndAction,
 }
 
 pub struct Dnd {
@@ -95,6 +101,10 @@ pub struct Dnd {
     pub type_list: Option<Vec<c_ulong>>,
     // Populated by XdndPosition event handler
     pub source_window: Option<c_ulong>,
+    pub position: Option<PhysicalPosition<f64>>,
+    pub accepted_action: Option<DndAction>,
+    // Populated by XdndDrop event handler, while the data is being converted
+    pub pending_drop: Option<PendingDrop>,
     // Populated by SelectionNotify event handler (triggered by XdndPosition event handler)
     pub result: Option<Result<Vec<PathBuf>, DndDataParseError>>,
 }
@@ -108,6 +118,9 @@ impl Dnd {
             version: None,
             type_list: None,
             source_window: None,
+            position: None,
+            accepted_action: None,
+            pending_drop: None,
             result: None,
         })
     }
@@ -116,19 +129,58 @@ impl Dnd {
         self.version = None;
         self.type_list = None;
         self.source_window = None;
+        self.position = None;
+        self.accepted_action = None;
+        self.pending_drop = None;
         self.result = None;
     }
 
+    pub fn offers(&self, atom: ffi::Atom) -> bool {
+        self.type_list
+            .iter()
+            .flatten()
+            .any(|&offered| offered == atom)
+    }
+
+    pub fn mime_types(&self) -> Vec<String> {
+        self.type_list
+            .iter()
+            .flatten()
+            .filter(|&&atom| atom != 0)
+            .filter_map(|&atom| self.xconn.get_atom_name(atom))
+            .collect()
+    }
+
+    /// The type in which the dropped data is requested: files, then text, then the first type.
+    pub fn drop_target(&self) -> Option<ffi::Atom> {
+        if self.offers(self.atoms.uri_list) {
+            return Some(self.atoms.uri_list);
+        }
+
+        let type_list = self.type_list.as_ref()?;
+        let names: Vec<_> = type_list
+            .iter()
+            .map(|&atom| (atom, self.xconn.get_atom_name(atom)))
+            .collect();
+        TEXT_MIME_TYPES
+            .iter()
+            .find_map(|mime_type| {
+                names
+                    .iter()
+                    .find(|(_, name)| name.as_deref() == Some(*mime_type))
+                    .map(|(atom, _)| *atom)
+            })
+            .or_else(|| type_list.iter().copied().find(|&atom| atom != 0))
+    }
+
     pub unsafe fn send_status(
         &self,
         this_window: c_ulong,
         target_window: c_ulong,
-        state: DndState,
+        action: Option<DndAction>,
     ) -> Result<(), XError> {
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, self.atoms.action_private as c_long),
-            DndState::Rejected => (0, self.atoms.none as c_long),
-        };
+        let accepted = action.is_some() as c_long;
+        let action = self.atoms.action_atom(action) as c_long;
         self.xconn
             .send_client_msg(
                 target_window,
@@ -144,12 +196,10 @@ impl Dnd {
         &self,
         this_window: c_ulong,
         target_window: c_ulong,
-        state: DndState,
+        action: Option<DndAction>,
     ) -> Result<(), XError> {
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, self.atoms.action_private as c_long),
-            DndState::Rejected => (0, self.atoms.none as c_long),
-        };
+        let accepted = action.is_some() as c_long;
+        let action = self.atoms.action_atom(action) as c_long;
         self.xconn
             .send_client_msg(
                 target_window,
@@ -169,11 +219,11 @@ impl Dnd {
             .get_property(source_window, self.atoms.type_list, ffi::XA_ATOM)
     }
 
-    pub unsafe fn convert_selection(&self, window: c_ulong, time: c_ulong) {
+    pub unsafe fn convert_selection(&self, window: c_ulong, target: ffi::Atom, time: c_ulong) {
         (self.xconn.xlib.XConvertSelection)(
             self.xconn.display,
             self.atoms.selection,
-            self.atoms.uri_list,
+            target,
             self.atoms.selection,
             window,
             time,
@@ -183,37 +233,15 @@ impl Dnd {
     pub unsafe fn read_data(
         &self,
         window: c_ulong,
+        target: ffi::Atom,
     ) -> Result<Vec<c_uchar>, util::GetPropertyError> {
         self.xconn
-            .get_property(window, self.atoms.selection, self.atoms.uri_list)
+            .get_property(window, self.atoms.selection, target)
     }
 
-    pub fn parse_data(&self, data: &mut Vec<c_uchar>) -> Result<Vec<PathBuf>, DndDataParseError> {
-        if !data.is_empty() {
-            let mut path_list = Vec::new();
-            let decoded = percent_decode(data).decode_utf8()?.into_owned();
-            for uri in decoded.split("\r\n").filter(|u| !u.is_empty()) {
-                // The format is specified as protocol://host/path
-                // However, it's typically simply protocol:///path
-                let path_str = if uri.starts_with("file://") {
-                    let path_str = uri.replace("file://", "");
-                    if !path_str.starts_with('/') {
-                        // A hostname is specified
-                        // Supporting this case is beyond the scope of my mental health
-                        return Err(DndDataParseError::HostnameSpecified(path_str));
-                    }
-                    path_str
-                } else {
-                    // Only the file protocol is supported
-                    return Err(DndDataParseError::UnexpectedProtocol(uri.to_owned()));
-                };
-
-                let path = Path::new(&path_str).canonicalize()?;
-                path_list.push(path);
-            }
-            Ok(path_list)
-        } else {
-            Err(DndDataParseError::EmptyData)
-        }
+    /// Decodes the data of a drop, received as `target`.
+    pub fn drop_data(&self, target: ffi::Atom, data: Vec<c_uchar>) -> Option<DropData> {
+        let mime_type = self.xconn.get_atom_name(target)?;
+        dnd::drop_data(mime_type, data)
     }
 }
@@ -0,0 +1,495 @@
+use std::{
+    os::raw::*,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::{
+    clipboard::{ClipboardAtoms, Contents},
+    dnd::DndAtoms,
+    ffi, util, XConnection, XError,
+};
+use crate::dnd::{DndAction, DndActions, DragOutcome};
+
+/// Highest version of the XDND protocol we speak.
+const XDND_VERSION: c_long = 5;
+
+/// Oldest version of the XDND protocol we can talk to.
+const XDND_MIN_VERSION: c_long = 3;
+
+/// How long we wait for the target to answer once the button is released, and then to finish the
+/// drop, before cancelling the drag.
+const DROP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The source side of XDND, for drags started from one of our windows.
+///
+/// The window the drag starts from grabs the pointer, so that the event processor receives the
+/// motion and the release wherever they happen.
+pub struct DragSource {
+    xconn: Arc<XConnection>,
+    atoms: DndAtoms,
+    clipboard_atoms: ClipboardAtoms,
+    root: ffi::Window,
+    session: Mutex<Option<DragSession>>,
+}
+
+struct DragSession {
+    // Window the drag started from, and which grabs the pointer
+    window: ffi::Window,
+    contents: Contents,
+    actions: DndActions,
+    target: Option<DragTarget>,
+    // Time of the button release, while we still have to drop
+    release_time: Option<ffi::Time>,
+    // Whether the drop was sent, and we're waiting for the target to finish
+    dropped: bool,
+    // When we give up on the target, once the button is released
+    deadline: Option<Instant>,
+}
+
+struct DragTarget {
+    window: ffi::Window,
+    version: c_long,
+    // Action the target accepted with its latest status
+    accepted: Option<DndAction>,
+    // Targets may only be sent a position once they answered the previous one
+    awaiting_status: bool,
+    pending_position: Option<(c_int, c_int, ffi::Time)>,
+}
+
+impl DragSource {
+    pub fn new(xconn: Arc<XConnection>, root: ffi::Window) -> Result<Self, XError> {
+        let atoms = DndAtoms::new(&xconn)?;
+        let clipboard_atoms = ClipboardAtoms::new(&xconn)?;
+        Ok(DragSource {
+            xconn,
+            atoms,
+            clipboard_atoms,
+            root,
+            session: Default::default(),
+        })
+    }
+
+    pub fn start(
+        &self,
+        window: ffi::Window,
+        data: Vec<(String, Vec<u8>)>,
+        actions: DndActions,
+    ) -> Result<(), &'static str> {
+        let contents = self.clipboard_atoms.contents(&self.xconn, data)?;
+
+        let mut session = self.session.lock().unwrap();
+        // A drop that was never finished doesn't prevent new drags.
+        match session.as_ref() {
+            Some(old_session) if old_session.dropped || old_session.timed_out() => {
+                if let Some(target) = old_session.target.as_ref() {
+                    self.send_leave(old_session.window, target);
+                }
+                *session = None;
+            }
+            Some(_) => return Err("A drag is already in progress"),
+            None => (),
+        }
+
+        let types: Vec<ffi::Atom> = contents.iter().map(|(target, _)| *target).collect();
+        self.xconn
+            .change_property(
+                window,
+                self.atoms.type_list,
+                ffi::XA_ATOM,
+                util::PropMode::Replace,
+                &types,
+            )
+            .queue();
+
+        unsafe {
+            let display = self.xconn.display;
+            (self.xconn.xlib.XSetSelectionOwner)(
+                display,
+                self.atoms.selection,
+                window,
+                ffi::CurrentTime,
+            );
+            if (self.xconn.xlib.XGetSelectionOwner)(display, self.atoms.selection) != window {
+                return Err("Failed to acquire ownership of the drag selection");
+            }
+
+            let grab = (self.xconn.xlib.XGrabPointer)(
+                display,
+                window,
+                ffi::False,
+                (ffi::ButtonReleaseMask | ffi::PointerMotionMask) as c_uint,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                0,
+                0,
+                ffi::CurrentTime,
+            );
+            if grab != ffi::GrabSuccess {
+                return Err("Failed to grab the pointer");
+            }
+            // Only needed to cancel the drag with escape, so it doesn't matter if it fails.
+            (self.xconn.xlib.XGrabKeyboard)(
+                display,
+                window,
+                ffi::False,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                ffi::CurrentTime,
+            );
+        }
+        self.xconn
+            .flush_requests()
+            .map_err(|_| "Failed to start the drag")?;
+
+        *session = Some(DragSession {
+            window,
+            contents,
+            actions,
+            target: None,
+            release_time: None,
+            dropped: false,
+            deadline: None,
+        });
+        Ok(())
+    }
+
+    /// Runs `f` on the data being dragged, if any.
+    pub fn with_contents<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(Option<&Contents>) -> R,
+    {
+        let session = self.session.lock().unwrap();
+        f(session.as_ref().map(|session| &session.contents))
+    }
+
+    #[inline]
+    pub fn is_drag_selection(&self, selection: ffi::Atom) -> bool {
+        selection == self.atoms.selection
+    }
+
+    #[inline]
+    pub fn is_source_message(&self, message_type: ffi::Atom) -> bool {
+        message_type == self.atoms.status || message_type == self.atoms.finished
+    }
+
+    /// Follows the pointer with the drag.
+    pub fn handle_motion(&self, event: &ffi::XMotionEvent) {
+        let mut session = self.session.lock().unwrap();
+        let session = match session.as_mut() {
+            Some(session) if session.window == event.window && session.release_time.is_none() => {
+                session
+            }
+            _ => return,
+        };
+
+        let target = self.find_target(event.x_root, event.y_root);
+        if target.map(|(window, _)| window) != session.target.as_ref().map(|t| t.window) {
+            if let Some(old_target) = session.target.take() {
+                self.send_leave(session.window, &old_target);
+            }
+            if let Some((window, version)) = target {
+                session.target = Some(DragTarget {
+                    window,
+                    version,
+                    accepted: None,
+                    awaiting_status: false,
+                    pending_position: None,
+                });
+                self.send_enter(session);
+            }
+        }
+
+        let (source, actions) = (session.window, session.actions);
+        if let Some(target) = session.target.as_mut() {
+            let position = (event.x_root, event.y_root, event.time);
+            if target.awaiting_status {
+                target.pending_position = Some(position);
+            } else {
+                self.send_position(source, actions, target, position);
+            }
+        }
+        self.flush();
+    }
+
+    /// Drops the data, and returns how the drag ended if the release ended it right away.
+    pub fn handle_button_release(
+        &self,
+        event: &ffi::XButtonEvent,
+    ) -> Option<(ffi::Window, DragOutcome)> {
+        let mut session = self.session.lock().unwrap();
+        match session.as_mut() {
+            Some(session) if session.window == event.window && session.release_time.is_none() => {
+                session.release_time = Some(event.time);
+                session.deadline = Some(Instant::now() + DROP_TIMEOUT);
+            }
+            _ => return None,
+        }
+
+        self.ungrab();
+        let ended = self.try_drop(&mut session);
+        self.flush();
+        ended
+    }
+
+    /// Cancels the drag when escape is pressed.
+    pub fn handle_key_press(
+        &self,
+        event: &mut ffi::XKeyEvent,
+    ) -> Option<(ffi::Window, DragOutcome)> {
+        let mut session = self.session.lock().unwrap();
+        match session.as_ref() {
+            Some(session) if session.window == event.window && session.release_time.is_none() => (),
+            _ => return None,
+        }
+
+        let keysym = unsafe { (self.xconn.xlib.XLookupKeysym)(event, 0) };
+        if keysym != ffi::XK_Escape as ffi::KeySym {
+            return None;
+        }
+
+        self.ungrab();
+        let session = session.take().unwrap();
+        if let Some(target) = session.target.as_ref() {
+            self.send_leave(session.window, target);
+        }
+        self.flush();
+        Some((session.window, DragOutcome::Cancelled))
+    }
+
+    /// When the event loop has to wake up to cancel a drop the target doesn't answer.
+    pub fn deadline(&self) -> Option<Instant> {
+        let session = self.session.lock().unwrap();
+        session.as_ref().and_then(|session| session.deadline)
+    }
+
+    /// Cancels the drop if the target didn't answer in time, and returns how the drag ended if
+    /// it did.
+    pub fn handle_timeout(&self) -> Option<(ffi::Window, DragOutcome)> {
+        let mut session = self.session.lock().unwrap();
+        if !session.as_ref()?.timed_out() {
+            return None;
+        }
+
+        let session = session.take().unwrap();
+        if let Some(target) = session.target.as_ref() {
+            self.send_leave(session.window, target);
+        }
+        self.flush();
+        Some((session.window, DragOutcome::Cancelled))
+    }
+
+    /// Handles `XdndStatus` and `XdndFinished`, and returns how the drag ended if it did.
+    pub fn handle_client_message(
+        &self,
+        message: &ffi::XClientMessageEvent,
+    ) -> Option<(ffi::Window, DragOutcome)> {
+        let mut guard = self.session.lock().unwrap();
+        let session = guard.as_mut()?;
+        let (source, actions) = (session.window, session.actions);
+        let target = match session.target.as_mut() {
+            Some(target) if target.window == message.data.get_long(0) as ffi::Window => target,
+            _ => return None,
+        };
+
+        if message.message_type == self.atoms.status && !session.dropped {
+            target.awaiting_status = false;
+            target.accepted = if message.data.get_long(1) & 1 != 0 {
+                self.atoms
+                    .action(message.data.get_long(4) as ffi::Atom)
+                    .filter(|action| actions.contains((*action).into()))
+                    .or_else(|| preferred_action(actions))
+            } else {
+                None
+            };
+
+            if session.release_time.is_some() {
+                let ended = self.try_drop(&mut guard);
+                self.flush();
+                return ended;
+            }
+
+            if let Some(position) = target.pending_position.take() {
+                self.send_position(source, actions, target, position);
+                self.flush();
+            }
+            None
+        } else if message.message_type == self.atoms.finished && session.dropped {
+            // Only version 5 reports the outcome, older targets could only accept or refuse.
+            let succeeded = target.version < 5 || message.data.get_long(1) & 1 != 0;
+            let action = if target.version < 5 {
+                target.accepted
+            } else {
+                self.atoms
+                    .action(message.data.get_long(2) as ffi::Atom)
+                    .or(target.accepted)
+            };
+            let outcome = match action {
+                Some(action) if succeeded => DragOutcome::Dropped(action),
+                _ => DragOutcome::Cancelled,
+            };
+            guard.take();
+            Some((source, outcome))
+        } else {
+            None
+        }
+    }
+
+    /// Drops on the target if it accepted the data, unless we're still waiting for its answer.
+    fn try_drop(&self, slot: &mut Option<DragSession>) -> Option<(ffi::Window, DragOutcome)> {
+        let session = slot.as_mut()?;
+        let time = session.release_time?;
+        match session.target.as_ref() {
+            Some(target) if target.awaiting_status => return None,
+            Some(target) if target.accepted.is_some() => {
+                self.send(
+                    target.window,
+                    self.atoms.drop,
+                    [session.window as c_long, 0, time as c_long, 0, 0],
+                );
+                session.dropped = true;
+                session.deadline = Some(Instant::now() + DROP_TIMEOUT);
+                return None;
+            }
+            Some(target) => self.send_leave(session.window, target),
+            None => (),
+        }
+
+        let session = slot.take().unwrap();
+        Some((session.window, DragOutcome::Cancelled))
+    }
+
+    /// Finds the XDND aware window under the given root coordinates, along with the version of
+    /// the protocol we'll use with it.
+    fn find_target(&self, x: c_int, y: c_int) -> Option<(ffi::Window, c_long)> {
+        let mut window = self.root;
+        loop {
+            let mut child = 0;
+            let found = unsafe {
+                let (mut child_x, mut child_y) = (0, 0);
+                (self.xconn.xlib.XTranslateCoordinates)(
+                    self.xconn.display,
+                    self.root,
+                    window,
+                    x,
+                    y,
+                    &mut child_x,
+                    &mut child_y,
+                    &mut child,
+                )
+            };
+            if found == ffi::False || child == 0 {
+                return None;
+            }
+            window = child;
+
+            let version = self
+                .xconn
+                .get_property::<c_ulong>(window, self.atoms.aware, ffi::XA_ATOM)
+                .ok()
+                .and_then(|version| version.first().copied());
+            if let Some(version) = version {
+                let version = (version as c_long).min(XDND_VERSION);
+                return if version >= XDND_MIN_VERSION {
+                    Some((window, version))
+                } else {
+                    None
+                };
+            }
+        }
+    }
+
+    fn send(&self, target: ffi::Window, message_type: ffi::Atom, data: [c_long; 5]) {
+        self.xconn
+            .send_client_msg(target, target, message_type, None, data)
+            .queue();
+    }
+
+    fn send_enter(&self, session: &DragSession) {
+        let target = match session.target.as_ref() {
+            Some(target) => target,
+            None => return,
+        };
+
+        // The first three types are sent right away, the others have to be read from the
+        // `XdndTypeList` property.
+        let mut types = session.contents.iter().map(|(target, _)| *target as c_long);
+        let more_types = (session.contents.len() > 3) as c_long;
+        self.send(
+            target.window,
+            self.atoms.enter,
+            [
+                session.window as c_long,
+                (target.version << 24) | more_types,
+                types.next().unwrap_or(0),
+                types.next().unwrap_or(0),
+                types.next().unwrap_or(0),
+            ],
+        );
+    }
+
+    fn send_position(
+        &self,
+        source: ffi::Window,
+        actions: DndActions,
+        target: &mut DragTarget,
+        (x, y, time): (c_int, c_int, ffi::Time),
+    ) {
+        let action = self.atoms.action_atom(preferred_action(actions));
+        self.send(
+            target.window,
+            self.atoms.position,
+            [
+                source as c_long,
+                0,
+                ((x as c_long) << 16) | (y as c_long & 0xffff),
+                time as c_long,
+                action as c_long,
+            ],
+        );
+        target.awaiting_status = true;
+    }
+
+    fn send_leave(&self, source: ffi::Window, target: &DragTarget) {
+        self.send(
+            target.window,
+            self.atoms.leave,
+            [source as c_long, 0, 0, 0, 0],
+        );
+    }
+
+    fn ungrab(&self) {
+        unsafe {
+            (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
+            (self.xconn.xlib.XUngrabKeyboard)(self.xconn.display, ffi::CurrentTime);
+        }
+    }
+
+    fn flush(&self) {
+        if let Err(err) = self.xconn.flush_requests() {
+            warn!("Failed to send a drag and drop message: {:?}", err);
+        }
+    }
+}
+
+impl DragSession {
+    fn timed_out(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => Instant::now() >= deadline,
+            None => false,
+        }
+    }
+}
+
+/// The action proposed to the target, in order of preference.
+fn preferred_action(actions: DndActions) -> Option<DndAction> {
+    if actions.contains(DndActions::COPY) {
+        Some(DndAction::Copy)
+    } else if actions.contains(DndActions::MOVE) {
+        Some(DndAction::Move)
+    } else if actions.contains(DndActions::LINK) {
+        Some(DndAction::Link)
+    } else {
+        None
+    }
+}
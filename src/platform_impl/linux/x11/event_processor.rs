@@ -1,23 +1,32 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, slice, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    slice,
+    sync::{Arc, Weak},
+};
 
 use libc::{c_char, c_int, c_long, c_uint, c_ulong};
 
 use parking_lot::MutexGuard;
 
 use super::{
-    events, ffi, get_xtarget, mkdid, mkwid, monitor, util, Device, DeviceId, DeviceInfo, Dnd,
-    DndState, GenericEventCookie, ImeReceiver, ScrollOrientation, UnownedWindow, WindowId,
-    XExtension,
+    dnd::PendingDrop, events, ffi, get_xtarget, ime::ImeContextCreationError, mkdid, mkwid,
+    monitor, util, Device, DeviceId, DeviceInfo, Dnd, GenericEventCookie, ImeEvent, ImeReceiver,
+    ImeRequest, ScrollOrientation, UnownedWindow, ValuatorAxis, WindowId, XExtension,
 };
 
 use util::modifiers::{ModifierKeyState, ModifierKeymap};
 
 use crate::{
+    dnd::{DndAction, DropData},
     dpi::{PhysicalPosition, PhysicalSize},
     event::{
-        DeviceEvent, ElementState, Event, KeyboardInput, ModifiersState, TouchPhase, WindowEvent,
+        DeviceEvent, ElementState, Event, Force, Ime, Key, KeyEvent, KeyboardInput, ModifiersState,
+        TabletToolAxes, TabletToolEvent, TouchPhase, WindowEvent,
     },
     event_loop::EventLoopWindowTarget as RootELW,
+    platform_impl::platform::common::{dnd as common_dnd, keymap},
 };
 
 /// The X11 documentation states: "Keycodes lie in the inclusive range [8,255]".
@@ -32,9 +41,17 @@ pub(super) struct EventProcessor<T: 'static> {
     pub(super) target: Rc<RootELW<T>>,
     pub(super) mod_keymap: ModifierKeymap,
     pub(super) device_mod_state: ModifierKeyState,
+    // Keys currently pressed, with the logical key they had when they were pressed
+    pub(super) pressed_keys: HashMap<ffi::KeyCode, Key>,
     // Number of touch events currently in progress
     pub(super) num_touch: u32,
     pub(super) first_touch: Option<u64>,
+    // Last pressure of the touches in progress, as updates only carry the valuators that changed
+    pub(super) touch_forces: HashMap<u64, f64>,
+    // Tools of drawing tablets over one of our windows, with the last state of their axes
+    pub(super) tablet_tools: HashMap<DeviceId, (ffi::Window, TabletToolAxes)>,
+    // Scale of the pinch gesture in progress, relative to the distance of the fingers at its start
+    pub(super) pinch_scale: f64,
     // Currently focused window belonging to this process
     pub(super) active_window: Option<ffi::Window>,
 }
@@ -78,6 +95,44 @@ impl<T: 'static> EventProcessor<T> {
         self.with_window(window_id, |_| ()).is_some()
     }
 
+    /// The popups whose parent is `parent`.
+    fn popups_of(&self, parent: ffi::Window) -> Vec<Arc<UnownedWindow>> {
+        let wt = get_xtarget(&self.target);
+        let windows = wt.windows.borrow();
+        windows
+            .values()
+            .filter_map(Weak::upgrade)
+            .filter(|window| window.popup_parent() == Some(parent))
+            .collect()
+    }
+
+    /// Whether a visible modal window blocks the input of `window`.
+    fn is_blocked_by_modal(&self, window: ffi::Window) -> bool {
+        let wt = get_xtarget(&self.target);
+        let windows = wt.windows.borrow();
+        windows
+            .values()
+            .filter_map(Weak::upgrade)
+            .any(|modal| modal.blocks_input_of(window))
+    }
+
+    /// Moves the popups of `parent` along with it, as well as their own popups.
+    fn move_popups(&self, parent: ffi::Window, parent_position: (i32, i32)) {
+        for popup in self.popups_of(parent) {
+            if let Some(position) = popup.follow_popup_parent(parent_position) {
+                self.move_popups(popup.xlib_window(), position);
+            }
+        }
+    }
+
+    /// Puts the popups of `parent` back above it, as well as their own popups.
+    fn raise_popups(&self, parent: ffi::Window) {
+        for popup in self.popups_of(parent) {
+            popup.raise_popup();
+            self.raise_popups(popup.xlib_window());
+        }
+    }
+
     pub(super) fn poll(&self) -> bool {
         let wt = get_xtarget(&self.target);
         let result = unsafe { (wt.xconn.xlib.XPending)(wt.xconn.display) };
@@ -129,6 +184,8 @@ impl<T: 'static> EventProcessor<T> {
                 })
             }
         {
+            // The pre-edit callbacks are run by `XFilterEvent`.
+            self.process_ime_events(&mut callback);
             return;
         }
 
@@ -194,6 +251,83 @@ impl<T: 'static> EventProcessor<T> {
                             *response_msg,
                         )
                         .queue();
+                } else if client_msg.message_type
+                    == unsafe { wt.xconn.get_atom_unchecked(b"_XEMBED\0") }
+                {
+                    // The embedder of an embedded window tells it whether it has the focus, and
+                    // forwards the key events to it.
+                    let focused = self
+                        .with_window(window, |window| window.handle_xembed_message(client_msg))
+                        .flatten();
+                    match focused {
+                        Some(true) if self.active_window != Some(window) => {
+                            wt.ime
+                                .borrow_mut()
+                                .focus(window)
+                                .expect("Failed to focus input context");
+                            self.active_window = Some(window);
+
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::Focused(true),
+                            });
+
+                            let modifiers = self.device_mod_state.modifiers();
+                            if !modifiers.is_empty() {
+                                callback(Event::WindowEvent {
+                                    window_id,
+                                    event: WindowEvent::ModifiersChanged(modifiers),
+                                });
+                            }
+
+                            Self::handle_pressed_keys(
+                                wt,
+                                window_id,
+                                ElementState::Pressed,
+                                &self.mod_keymap,
+                                &mut self.device_mod_state,
+                                &mut self.pressed_keys,
+                                &mut callback,
+                            );
+                        }
+                        Some(false) if self.active_window == Some(window) => {
+                            wt.ime
+                                .borrow_mut()
+                                .unfocus(window)
+                                .expect("Failed to unfocus input context");
+                            self.active_window = None;
+
+                            Self::handle_pressed_keys(
+                                wt,
+                                window_id,
+                                ElementState::Released,
+                                &self.mod_keymap,
+                                &mut self.device_mod_state,
+                                &mut self.pressed_keys,
+                                &mut callback,
+                            );
+
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::ModifiersChanged(ModifiersState::empty()),
+                            });
+
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::Focused(false),
+                            });
+                        }
+                        _ => (),
+                    }
+                } else if wt.drag_source.is_source_message(client_msg.message_type) {
+                    if let Some((window, outcome)) =
+                        wt.drag_source.handle_client_message(client_msg)
+                    {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(window),
+                            event: WindowEvent::DragEnded(outcome),
+                        });
+                    }
                 } else if client_msg.message_type == self.dnd.atoms.enter {
                     let source_window = client_msg.data.get_long(0) as c_ulong;
                     let flags = client_msg.data.get_long(1);
@@ -211,14 +345,20 @@ impl<T: 'static> EventProcessor<T> {
                     {
                         self.dnd.type_list = Some(more_types);
                     }
+
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::DragEntered {
+                            mime_types: self.dnd.mime_types(),
+                        },
+                    });
                 } else if client_msg.message_type == self.dnd.atoms.position {
-                    // This event occurs every time the mouse moves while a file's being dragged
-                    // over our window. We emit HoveredFile in response; while the macOS backend
-                    // does that upon a drag entering, XDND doesn't have access to the actual drop
-                    // data until this event. For parity with other platforms, we only emit
-                    // `HoveredFile` the first time, though if winit's API is later extended to
-                    // supply position updates with `HoveredFile` or another event, implementing
-                    // that here would be trivial.
+                    // This event occurs every time the mouse moves while something's being
+                    // dragged over our window. We emit `DragMoved` in response, which decides
+                    // whether the drop is accepted at this position. For `HoveredFile`, XDND
+                    // doesn't have access to the actual drop data until this event, so we request
+                    // the file list the first time the drop is accepted. For parity with other
+                    // platforms, `HoveredFile` is only emitted once per file.
 
                     let source_window = client_msg.data.get_long(0) as c_ulong;
 
@@ -226,78 +366,145 @@ impl<T: 'static> EventProcessor<T> {
                     // where `shift = mem::size_of::<c_short>() * 8`
                     // Note that coordinates are in "desktop space", not "window space"
                     // (in X11 parlance, they're root window coordinates)
-                    //let packed_coordinates = client_msg.data.get_long(2);
-                    //let shift = mem::size_of::<libc::c_short>() * 8;
-                    //let x = packed_coordinates >> shift;
-                    //let y = packed_coordinates & !(x << shift);
+                    let packed_coordinates = client_msg.data.get_long(2);
+                    let x_root = (packed_coordinates >> 16) & 0xffff;
+                    let y_root = packed_coordinates & 0xffff;
+                    let origin = wt
+                        .xconn
+                        .translate_coords(window, wt.root)
+                        .unwrap_or_default();
+                    let position = PhysicalPosition::new(
+                        (x_root - origin.x_rel_root as c_long) as f64,
+                        (y_root - origin.y_rel_root as c_long) as f64,
+                    );
 
                     // By our own state flow, `version` should never be `None` at this point.
                     let version = self.dnd.version.unwrap_or(5);
 
-                    // Action is specified in versions 2 and up, though we don't need it anyway.
-                    //let action = client_msg.data.get_long(4);
-
-                    let accepted = if let Some(ref type_list) = self.dnd.type_list {
-                        type_list.contains(&self.dnd.atoms.uri_list)
+                    // Action is specified in versions 2 and up, and copying is the default.
+                    let action = if version >= 2 {
+                        self.dnd
+                            .atoms
+                            .action(client_msg.data.get_long(4) as ffi::Atom)
                     } else {
-                        false
-                    };
+                        None
+                    }
+                    .unwrap_or(DndAction::Copy);
 
-                    if accepted {
-                        self.dnd.source_window = Some(source_window);
-                        unsafe {
-                            if self.dnd.result.is_none() {
-                                let time = if version >= 1 {
-                                    client_msg.data.get_long(3) as c_ulong
-                                } else {
-                                    // In version 0, time isn't specified
-                                    ffi::CurrentTime
-                                };
-                                // This results in the `SelectionNotify` event below
-                                self.dnd.convert_selection(window, time);
-                            }
-                            self.dnd
-                                .send_status(window, source_window, DndState::Accepted)
-                                .expect("Failed to send `XdndStatus` message.");
-                        }
-                    } else {
-                        unsafe {
+                    let offers_files = self.dnd.offers(self.dnd.atoms.uri_list);
+                    let mut accepted_action = if offers_files { Some(action) } else { None };
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::DragMoved {
+                            position,
+                            action,
+                            accepted_action: &mut accepted_action,
+                        },
+                    });
+
+                    self.dnd.source_window = Some(source_window);
+                    self.dnd.position = Some(position);
+                    self.dnd.accepted_action = accepted_action;
+
+                    unsafe {
+                        if accepted_action.is_some() && offers_files && self.dnd.result.is_none() {
+                            let time = if version >= 1 {
+                                client_msg.data.get_long(3) as c_ulong
+                            } else {
+                                // In version 0, time isn't specified
+                                ffi::CurrentTime
+                            };
+                            // This results in the `SelectionNotify` event below
                             self.dnd
-                                .send_status(window, source_window, DndState::Rejected)
-                                .expect("Failed to send `XdndStatus` message.");
+                                .convert_selection(window, self.dnd.atoms.uri_list, time);
                         }
-                        self.dnd.reset();
+                        self.dnd
+                            .send_status(window, source_window, accepted_action)
+                            .expect("Failed to send `XdndStatus` message.");
                     }
                 } else if client_msg.message_type == self.dnd.atoms.drop {
-                    let (source_window, state) = if let Some(source_window) = self.dnd.source_window
-                    {
+                    let drop = match (
+                        self.dnd.source_window,
+                        self.dnd.position,
+                        self.dnd.accepted_action,
+                        self.dnd.drop_target(),
+                    ) {
+                        (Some(source_window), Some(position), Some(action), Some(target)) => {
+                            Some(PendingDrop {
+                                source_window,
+                                target,
+                                position,
+                                action,
+                            })
+                        }
+                        _ => None,
+                    };
+
+                    if let Some(drop) = drop {
                         if let Some(Ok(ref path_list)) = self.dnd.result {
-                            for path in path_list {
+                            // The file list was already received while hovering.
+                            let path_list = path_list.clone();
+                            for path in &path_list {
                                 callback(Event::WindowEvent {
                                     window_id,
                                     event: WindowEvent::DroppedFile(path.clone()),
                                 });
                             }
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::DragDropped {
+                                    position: drop.position,
+                                    action: drop.action,
+                                    data: DropData::Files(path_list),
+                                },
+                            });
+                            unsafe {
+                                self.dnd
+                                    .send_finished(window, drop.source_window, Some(drop.action))
+                                    .expect("Failed to send `XdndFinished` message.");
+                            }
+                            self.dnd.reset();
+                        } else {
+                            let version = self.dnd.version.unwrap_or(5);
+                            let time = if version >= 1 {
+                                client_msg.data.get_long(2) as c_ulong
+                            } else {
+                                ffi::CurrentTime
+                            };
+                            // This results in the `SelectionNotify` event below, which finishes
+                            // the drop.
+                            unsafe { self.dnd.convert_selection(window, drop.target, time) };
+                            self.dnd.pending_drop = Some(drop);
                         }
-                        (source_window, DndState::Accepted)
                     } else {
-                        // `source_window` won't be part of our DND state if we already rejected the drop in our
-                        // `XdndPosition` handler.
+                        // `source_window` won't be part of our DND state if we never got an
+                        // `XdndPosition`, and the drop is rejected if we rejected the last one.
                         let source_window = client_msg.data.get_long(0) as c_ulong;
-                        (source_window, DndState::Rejected)
-                    };
-                    unsafe {
-                        self.dnd
-                            .send_finished(window, source_window, state)
-                            .expect("Failed to send `XdndFinished` message.");
+                        unsafe {
+                            self.dnd
+                                .send_finished(window, source_window, None)
+                                .expect("Failed to send `XdndFinished` message.");
+                        }
+                        self.dnd.reset();
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::HoveredFileCancelled,
+                        });
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::DragLeft,
+                        });
                     }
-                    self.dnd.reset();
                 } else if client_msg.message_type == self.dnd.atoms.leave {
                     self.dnd.reset();
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::HoveredFileCancelled,
                     });
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::DragLeft,
+                    });
                 }
             }
 
@@ -307,19 +514,83 @@ impl<T: 'static> EventProcessor<T> {
                 let window = xsel.requestor;
                 let window_id = mkwid(window);
 
-                if xsel.property == self.dnd.atoms.selection {
+                let pending_drop = match self.dnd.pending_drop {
+                    Some(drop)
+                        if xsel.selection == self.dnd.atoms.selection
+                            && xsel.target == drop.target =>
+                    {
+                        self.dnd.pending_drop.take()
+                    }
+                    _ => None,
+                };
+
+                if let Some(drop) = pending_drop {
+                    // This is where we receive the data of a drop.
+                    let data = if xsel.property == self.dnd.atoms.selection {
+                        unsafe { self.dnd.read_data(window, drop.target) }
+                            .ok()
+                            .and_then(|data| self.dnd.drop_data(drop.target, data))
+                    } else {
+                        None
+                    };
+
+                    let action = data.as_ref().map(|_| drop.action);
+                    unsafe {
+                        self.dnd
+                            .send_finished(window, drop.source_window, action)
+                            .expect("Failed to send `XdndFinished` message.");
+                    }
+                    self.dnd.reset();
+
+                    match data {
+                        Some(data) => {
+                            if let DropData::Files(ref path_list) = data {
+                                for path in path_list {
+                                    callback(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::DroppedFile(path.clone()),
+                                    });
+                                }
+                            }
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::DragDropped {
+                                    position: drop.position,
+                                    action: drop.action,
+                                    data,
+                                },
+                            });
+                        }
+                        None => {
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::HoveredFileCancelled,
+                            });
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::DragLeft,
+                            });
+                        }
+                    }
+                } else if xsel.property == self.dnd.atoms.selection
+                    && self.dnd.source_window.is_some()
+                {
                     let mut result = None;
 
-                    // This is where we receive data from drag and drop
-                    if let Ok(mut data) = unsafe { self.dnd.read_data(window) } {
-                        let parse_result = self.dnd.parse_data(&mut data);
-                        if let Ok(ref path_list) = parse_result {
-                            for path in path_list {
-                                callback(Event::WindowEvent {
-                                    window_id,
-                                    event: WindowEvent::HoveredFile(path.clone()),
-                                });
+                    // This is where we receive the file list of a hovering drag.
+                    if let Ok(data) = unsafe { self.dnd.read_data(window, self.dnd.atoms.uri_list) }
+                    {
+                        let parse_result = common_dnd::parse_uri_list(&data);
+                        match parse_result {
+                            Ok(ref path_list) => {
+                                for path in path_list {
+                                    callback(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::HoveredFile(path.clone()),
+                                    });
+                                }
                             }
+                            Err(ref err) => warn!("Failed to decode the hovered files: {}", err),
                         }
                         result = Some(parse_result);
                     }
@@ -328,6 +599,30 @@ impl<T: 'static> EventProcessor<T> {
                 }
             }
 
+            ffi::SelectionRequest => {
+                let xsel: &ffi::XSelectionRequestEvent = xev.as_ref();
+
+                let result = if wt.drag_source.is_drag_selection(xsel.selection) {
+                    wt.drag_source
+                        .with_contents(|contents| wt.clipboard.answer_request(xsel, contents))
+                } else {
+                    wt.clipboard.handle_request(xsel)
+                };
+                if let Err(err) = result {
+                    warn!("Failed to answer a selection request: {:?}", err);
+                }
+            }
+
+            ffi::PropertyNotify => {
+                let xev: &ffi::XPropertyEvent = xev.as_ref();
+                wt.clipboard.handle_property_notify(xev);
+            }
+
+            ffi::SelectionClear => {
+                let xsel: &ffi::XSelectionClearEvent = xev.as_ref();
+                wt.clipboard.handle_clear(xsel);
+            }
+
             ffi::ConfigureNotify => {
                 let xev: &ffi::XConfigureEvent = xev.as_ref();
                 let xwindow = xev.window;
@@ -342,6 +637,9 @@ impl<T: 'static> EventProcessor<T> {
                     // (whether the window moved or not) is accompanied by an extraneous `Moved` event
                     // that has a position relative to the parent window.
                     let is_synthetic = xev.send_event == ffi::True;
+                    // Child windows aren't managed by the window manager, so their position is
+                    // always relative to their parent.
+                    let is_child = window.child_of().is_some();
 
                     // These are both in physical space.
                     let new_inner_size = (xev.width as u32, xev.height as u32);
@@ -352,7 +650,7 @@ impl<T: 'static> EventProcessor<T> {
                     let (mut resized, moved) = {
                         let resized =
                             util::maybe_change(&mut shared_state_lock.size, new_inner_size);
-                        let moved = if is_synthetic {
+                        let moved = if is_synthetic || is_child {
                             util::maybe_change(
                                 &mut shared_state_lock.inner_position,
                                 new_inner_position,
@@ -383,8 +681,7 @@ impl<T: 'static> EventProcessor<T> {
                             .as_ref()
                             .cloned()
                             .unwrap_or_else(|| {
-                                let frame_extents =
-                                    wt.xconn.get_frame_extents_heuristic(xwindow, wt.root);
+                                let frame_extents = window.frame_extents_heuristic();
                                 shared_state_lock.frame_extents = Some(frame_extents.clone());
                                 frame_extents
                             });
@@ -398,6 +695,13 @@ impl<T: 'static> EventProcessor<T> {
                                     window_id,
                                     event: WindowEvent::Moved(outer.into()),
                                 });
+                                window.refresh_cursor_grab_region();
+                                let root_position = if is_child {
+                                    window.inner_position_physical()
+                                } else {
+                                    new_inner_position
+                                };
+                                self.move_popups(xwindow, root_position);
                             });
                         }
                         outer
@@ -446,6 +750,8 @@ impl<T: 'static> EventProcessor<T> {
                                         new_inner_size: &mut new_inner_size,
                                     },
                                 });
+                                window.refresh_custom_cursor();
+                                window.refresh_cursor_grab_region();
                             });
 
                             if new_inner_size != old_inner_size {
@@ -501,6 +807,19 @@ impl<T: 'static> EventProcessor<T> {
                 });
             }
 
+            ffi::UnmapNotify => {
+                let xev: &ffi::XUnmapEvent = xev.as_ref();
+
+                // Popups are dismissed along with their parent.
+                for popup in self.popups_of(xev.window) {
+                    popup.set_visible(false);
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(popup.xlib_window()),
+                        event: WindowEvent::PopupDismissed,
+                    });
+                }
+            }
+
             ffi::DestroyNotify => {
                 let xev: &ffi::XDestroyWindowEvent = xev.as_ref();
 
@@ -544,9 +863,54 @@ impl<T: 'static> EventProcessor<T> {
                 }
             }
 
+            // Core pointer events are only selected by the grab of an outgoing drag, and by the
+            // grab of a popup, which only gets the presses outside of our windows.
+            ffi::ButtonPress => {
+                let xbutton: &ffi::XButtonEvent = xev.as_ref();
+                let released = self
+                    .with_window(xbutton.window, |window| window.release_popup_grab())
+                    .unwrap_or(false);
+                if released {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(xbutton.window),
+                        event: WindowEvent::PopupDismissed,
+                    });
+                }
+            }
+
+            ffi::MotionNotify => {
+                let xmotion: &ffi::XMotionEvent = xev.as_ref();
+                wt.drag_source.handle_motion(xmotion);
+            }
+
+            ffi::ButtonRelease => {
+                let xbutton: &ffi::XButtonEvent = xev.as_ref();
+                if let Some((window, outcome)) = wt.drag_source.handle_button_release(xbutton) {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(window),
+                        event: WindowEvent::DragEnded(outcome),
+                    });
+                }
+            }
+
             ffi::KeyPress | ffi::KeyRelease => {
                 use crate::event::ElementState::{Pressed, Released};
 
+                if xev.get_type() == ffi::KeyPress {
+                    let xkev: &mut ffi::XKeyEvent = xev.as_mut();
+                    if let Some((window, outcome)) = wt.drag_source.handle_key_press(xkev) {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(window),
+                            event: WindowEvent::DragEnded(outcome),
+                        });
+                        return;
+                    }
+                    // Releases are still reported, so that no key stays pressed.
+                    if self.is_blocked_by_modal(xkev.window) {
+                        return;
+                    }
+                }
+
                 // Note that in compose/pre-edit sequences, this will always be Released.
                 let state = if xev.get_type() == ffi::KeyPress {
                     Pressed
@@ -567,9 +931,23 @@ impl<T: 'static> EventProcessor<T> {
 
                 // When a compose sequence or IME pre-edit is finished, it ends in a KeyPress with
                 // a keycode of 0.
-                if keycode != 0 {
+                let keysym = if keycode != 0 {
+                    Some(wt.xconn.lookup_keysym(xkev))
+                } else {
+                    None
+                };
+
+                let written = if state == Pressed {
+                    wt.ime
+                        .borrow()
+                        .get_context(window)
+                        .map(|ic| wt.xconn.lookup_utf8(ic, xkev))
+                } else {
+                    None
+                };
+
+                if let Some(keysym) = keysym {
                     let scancode = keycode - KEYCODE_OFFSET as u32;
-                    let keysym = wt.xconn.lookup_keysym(xkev);
                     let virtual_keycode = events::keysym_to_element(keysym as c_uint);
 
                     update_modifiers!(
@@ -593,14 +971,54 @@ impl<T: 'static> EventProcessor<T> {
                             is_synthetic: false,
                         },
                     });
-                }
 
-                if state == Pressed {
-                    let written = if let Some(ic) = wt.ime.borrow().get_context(window) {
-                        wt.xconn.lookup_utf8(ic, xkev)
+                    let text = written.clone().filter(|written| !written.is_empty());
+                    let keycode = keycode as ffi::KeyCode;
+                    let (logical_key, is_repeat) = if state == Pressed {
+                        let logical_key = keymap::keysym_to_key(keysym as u32, text.as_deref());
+                        // Auto-repeat is detectable, so repeated presses aren't preceded by a
+                        // release.
+                        let is_repeat = self
+                            .pressed_keys
+                            .insert(keycode, logical_key.clone())
+                            .is_some();
+                        (logical_key, is_repeat)
                     } else {
-                        return;
+                        let logical_key = self
+                            .pressed_keys
+                            .remove(&keycode)
+                            .unwrap_or_else(|| keymap::keysym_to_key(keysym as u32, None));
+                        (logical_key, false)
                     };
+                    let physical_key = keymap::scancode_to_keycode(scancode);
+
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::KeyEvent {
+                            device_id,
+                            event: KeyEvent {
+                                physical_key,
+                                logical_key,
+                                text,
+                                location: keymap::keycode_location(physical_key),
+                                state,
+                                is_repeat,
+                            },
+                            is_synthetic: false,
+                        },
+                    });
+                }
+
+                if let Some(written) = written {
+                    // Text that doesn't come from a plain key press was composed by the input
+                    // method, either on-the-spot or through a compose sequence.
+                    let is_composing = wt.ime.borrow().is_composing(window);
+                    if (keysym.is_none() || is_composing) && !written.is_empty() {
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::Ime(Ime::Commit(written.clone())),
+                        });
+                    }
 
                     for chr in written.chars() {
                         let event = Event::WindowEvent {
@@ -643,6 +1061,14 @@ impl<T: 'static> EventProcessor<T> {
                             // Deliver multi-touch events instead of emulated mouse events.
                             return;
                         }
+                        if xev.evtype == ffi::XI_ButtonPress && self.is_blocked_by_modal(xev.event)
+                        {
+                            return;
+                        }
+                        if xev.evtype == ffi::XI_ButtonPress {
+                            // Clicking an embedded window activates it.
+                            self.with_window(xev.event, |window| window.request_embedder_focus());
+                        }
 
                         let modifiers = ModifiersState::from_x11(&xev.mods);
                         update_modifiers!(modifiers, None);
@@ -714,6 +1140,15 @@ impl<T: 'static> EventProcessor<T> {
                                 },
                             }),
                         }
+
+                        let tablet_events =
+                            tablet_tool_events(&self.devices.borrow(), &mut self.tablet_tools, xev);
+                        for (window, event) in tablet_events {
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(window),
+                                event: WindowEvent::TabletTool(event),
+                            });
+                        }
                     }
                     ffi::XI_Motion => {
                         let xev: &ffi::XIDeviceEvent = unsafe { &*(xev.data as *const _) };
@@ -725,6 +1160,9 @@ impl<T: 'static> EventProcessor<T> {
                         update_modifiers!(modifiers, None);
 
                         let cursor_moved = self.with_window(xev.event, |window| {
+                            if window.warp_locked_cursor(new_cursor_pos) {
+                                return false;
+                            }
                             let mut shared_state_lock = window.shared_state.lock();
                             util::maybe_change(&mut shared_state_lock.cursor_pos, new_cursor_pos)
                         });
@@ -743,6 +1181,15 @@ impl<T: 'static> EventProcessor<T> {
                             return;
                         }
 
+                        let tablet_events =
+                            tablet_tool_events(&self.devices.borrow(), &mut self.tablet_tools, xev);
+                        for (window, event) in tablet_events {
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(window),
+                                event: WindowEvent::TabletTool(event),
+                            });
+                        }
+
                         // More gymnastics, for self.devices
                         let mut events = Vec::new();
                         {
@@ -866,6 +1313,23 @@ impl<T: 'static> EventProcessor<T> {
                         // Leave, FocusIn, and FocusOut can be received by a window that's already
                         // been destroyed, which the user presumably doesn't want to deal with.
                         let window_closed = !self.window_exists(xev.event);
+                        let left_tools: Vec<_> = self
+                            .tablet_tools
+                            .iter()
+                            .filter(|&(_, &(window, _))| window == xev.event)
+                            .map(|(&device_id, _)| device_id)
+                            .collect();
+                        for device_id in left_tools {
+                            self.tablet_tools.remove(&device_id);
+                            if !window_closed {
+                                callback(Event::WindowEvent {
+                                    window_id: mkwid(xev.event),
+                                    event: WindowEvent::TabletTool(TabletToolEvent::Left {
+                                        device_id: mkdid(device_id.0),
+                                    }),
+                                });
+                            }
+                        }
                         if !window_closed {
                             callback(Event::WindowEvent {
                                 window_id: mkwid(xev.event),
@@ -883,6 +1347,9 @@ impl<T: 'static> EventProcessor<T> {
                             .focus(xev.event)
                             .expect("Failed to focus input context");
 
+                        // The window manager may have raised the window above its popups.
+                        self.raise_popups(xev.event);
+
                         let modifiers = ModifiersState::from_x11(&xev.mods);
 
                         self.device_mod_state.update_state(&modifiers, None);
@@ -930,6 +1397,7 @@ impl<T: 'static> EventProcessor<T> {
                                 ElementState::Pressed,
                                 &self.mod_keymap,
                                 &mut self.device_mod_state,
+                                &mut self.pressed_keys,
                                 &mut callback,
                             );
                         }
@@ -939,6 +1407,18 @@ impl<T: 'static> EventProcessor<T> {
                         if !self.window_exists(xev.event) {
                             return;
                         }
+
+                        // The grab of a popup was broken by someone else.
+                        if xev.mode == ffi::XINotifyUngrab
+                            && self
+                                .with_window(xev.event, |window| window.release_popup_grab())
+                                .unwrap_or(false)
+                        {
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(xev.event),
+                                event: WindowEvent::PopupDismissed,
+                            });
+                        }
                         wt.ime
                             .borrow_mut()
                             .unfocus(xev.event)
@@ -954,6 +1434,7 @@ impl<T: 'static> EventProcessor<T> {
                                 ElementState::Released,
                                 &self.mod_keymap,
                                 &mut self.device_mod_state,
+                                &mut self.pressed_keys,
                                 &mut callback,
                             );
 
@@ -998,19 +1479,102 @@ impl<T: 'static> EventProcessor<T> {
                                 });
                             }
 
+                            let force = touch_force(
+                                &self.devices.borrow(),
+                                &mut self.touch_forces,
+                                xev,
+                                id,
+                                phase,
+                            );
                             callback(Event::WindowEvent {
                                 window_id,
                                 event: WindowEvent::Touch(Touch {
                                     device_id: mkdid(xev.deviceid),
                                     phase,
                                     location,
-                                    force: None, // TODO
+                                    force,
                                     id,
                                 }),
                             })
                         }
                     }
 
+                    ffi::XI_GesturePinchBegin
+                    | ffi::XI_GesturePinchUpdate
+                    | ffi::XI_GesturePinchEnd => {
+                        let xev: &ffi::XIGesturePinchEvent = unsafe { &*(xev.data as *const _) };
+                        if !self.window_exists(xev.event) {
+                            return;
+                        }
+                        let window_id = mkwid(xev.event);
+                        let device_id = mkdid(xev.deviceid);
+
+                        let phase = match xev.evtype {
+                            ffi::XI_GesturePinchBegin => TouchPhase::Started,
+                            ffi::XI_GesturePinchUpdate => TouchPhase::Moved,
+                            _ if xev.flags & ffi::XIGesturePinchEventCancelled != 0 => {
+                                TouchPhase::Cancelled
+                            }
+                            _ => TouchPhase::Ended,
+                        };
+                        let (scale_delta, angle_delta) = if phase == TouchPhase::Moved {
+                            (xev.scale - self.pinch_scale, xev.delta_angle)
+                        } else {
+                            (0.0, 0.0)
+                        };
+                        self.pinch_scale = xev.scale;
+
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::PinchGesture {
+                                device_id,
+                                delta: scale_delta,
+                                phase,
+                            },
+                        });
+                        // The angle is reported clockwise.
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::RotationGesture {
+                                device_id,
+                                delta: -angle_delta,
+                                phase,
+                            },
+                        });
+                    }
+                    ffi::XI_GestureSwipeBegin
+                    | ffi::XI_GestureSwipeUpdate
+                    | ffi::XI_GestureSwipeEnd => {
+                        let xev: &ffi::XIGestureSwipeEvent = unsafe { &*(xev.data as *const _) };
+                        if !self.window_exists(xev.event) {
+                            return;
+                        }
+
+                        let phase = match xev.evtype {
+                            ffi::XI_GestureSwipeBegin => TouchPhase::Started,
+                            ffi::XI_GestureSwipeUpdate => TouchPhase::Moved,
+                            _ if xev.flags & ffi::XIGestureSwipeEventCancelled != 0 => {
+                                TouchPhase::Cancelled
+                            }
+                            _ => TouchPhase::Ended,
+                        };
+                        let delta = if phase == TouchPhase::Moved {
+                            PhysicalPosition::new(xev.delta_x, xev.delta_y)
+                        } else {
+                            PhysicalPosition::new(0.0, 0.0)
+                        };
+
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(xev.event),
+                            event: WindowEvent::SwipeGesture {
+                                device_id: mkdid(xev.deviceid),
+                                fingers: xev.detail as u32,
+                                delta,
+                                phase,
+                            },
+                        });
+                    }
+
                     ffi::XI_RawButtonPress | ffi::XI_RawButtonRelease => {
                         let xev: &ffi::XIRawEvent = unsafe { &*(xev.data as *const _) };
                         if xev.flags & ffi::XIPointerEmulated == 0 {
@@ -1202,6 +1766,8 @@ impl<T: 'static> EventProcessor<T> {
                                                             new_inner_size: &mut new_inner_size,
                                                         },
                                                     });
+                                                    window.refresh_custom_cursor();
+                                                    window.refresh_cursor_grab_region();
 
                                                     if new_inner_size != old_inner_size {
                                                         let (new_width, new_height) =
@@ -1217,15 +1783,66 @@ impl<T: 'static> EventProcessor<T> {
                                 });
                         }
                     }
+                } else if let Some(kind) = wt.clipboard.handle_xfixes_event(xev) {
+                    callback(Event::ClipboardChanged(kind));
                 }
             }
         }
 
-        match self.ime_receiver.try_recv() {
-            Ok((window_id, x, y)) => {
-                wt.ime.borrow_mut().send_xim_spot(window_id, x, y);
+        self.process_ime_requests();
+        self.process_ime_events(&mut callback);
+    }
+
+    /// Handles the requests windows made to the input method.
+    pub(super) fn process_ime_requests(&self) {
+        let wt = get_xtarget(&self.target);
+        while let Ok(request) = self.ime_receiver.try_recv() {
+            match request {
+                ImeRequest::Position(window, x, y) => {
+                    wt.ime.borrow_mut().send_xim_spot(window, x, y);
+                }
+                ImeRequest::Allowed(window, allowed) => {
+                    let mut ime = wt.ime.borrow_mut();
+                    let result = ime.set_allowed(window, allowed).and_then(|replaced| {
+                        if replaced && self.active_window == Some(window) {
+                            ime.focus(window).map_err(ImeContextCreationError::XError)?;
+                        }
+                        Ok(())
+                    });
+                    if let Err(err) = result {
+                        warn!("Failed to replace input context: {:?}", err);
+                    }
+                }
+                ImeRequest::DestroyContext(window) => {
+                    if let Err(err) = wt.ime.borrow_mut().remove_context(window) {
+                        warn!("Failed to destroy input context: {:?}", err);
+                    }
+                }
             }
-            Err(_) => (),
+        }
+    }
+
+    fn process_ime_events<F>(&self, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        // The input method must not stay borrowed while running the callback.
+        let next_event = || wt.ime.borrow().next_event();
+        while let Some((window, event)) = next_event() {
+            let event = match event {
+                ImeEvent::Enabled => Ime::Enabled,
+                ImeEvent::Start | ImeEvent::End => Ime::Preedit(String::new(), None),
+                ImeEvent::Update(text, cursor_pos) => {
+                    Ime::Preedit(text, Some((cursor_pos, cursor_pos)))
+                }
+                ImeEvent::Disabled => Ime::Disabled,
+            };
+
+            callback(Event::WindowEvent {
+                window_id: mkwid(window),
+                event: WindowEvent::Ime(event),
+            });
         }
     }
 
@@ -1235,6 +1852,7 @@ impl<T: 'static> EventProcessor<T> {
         state: ElementState,
         mod_keymap: &ModifierKeymap,
         device_mod_state: &mut ModifierKeyState,
+        pressed_keys: &mut HashMap<ffi::KeyCode, Key>,
         callback: &mut F,
     ) where
         F: FnMut(Event<'_, T>),
@@ -1275,8 +1893,175 @@ impl<T: 'static> EventProcessor<T> {
                     is_synthetic: true,
                 },
             });
+
+            let physical_key = keymap::scancode_to_keycode(scancode);
+            let logical_key = match state {
+                ElementState::Pressed => {
+                    let logical_key = keymap::keysym_to_key(keysym as u32, None);
+                    pressed_keys.insert(keycode, logical_key.clone());
+                    logical_key
+                }
+                ElementState::Released => pressed_keys
+                    .remove(&keycode)
+                    .unwrap_or_else(|| keymap::keysym_to_key(keysym as u32, None)),
+            };
+
+            callback(Event::WindowEvent {
+                window_id,
+                event: WindowEvent::KeyEvent {
+                    device_id,
+                    event: KeyEvent {
+                        physical_key,
+                        logical_key,
+                        text: None,
+                        location: keymap::keycode_location(physical_key),
+                        state,
+                        is_repeat: false,
+                    },
+                    is_synthetic: true,
+                },
+            });
+        }
+
+        // Keys released while the window wasn't focused never get a release event.
+        if state == ElementState::Released {
+            pressed_keys.clear();
+        }
+    }
+}
+
+/// Reads the pressure of a touch from the pressure valuator of its device, if it has one.
+fn touch_force(
+    devices: &HashMap<DeviceId, Device>,
+    forces: &mut HashMap<u64, f64>,
+    xev: &ffi::XIDeviceEvent,
+    id: u64,
+    phase: TouchPhase,
+) -> Option<Force> {
+    let pressure_axis = devices.get(&DeviceId(xev.sourceid))?.pressure_axis?;
+    let pressure = valuators(xev)
+        .into_iter()
+        .find(|&(number, _)| number == pressure_axis.number);
+
+    // Updates only carry the valuators which changed.
+    let force = match pressure.map(|(_, pressure)| pressure) {
+        Some(pressure) => {
+            let force = pressure_axis.normalize(pressure);
+            forces.insert(id, force);
+            Some(force)
+        }
+        None => forces.get(&id).copied(),
+    };
+    if let TouchPhase::Ended | TouchPhase::Cancelled = phase {
+        forces.remove(&id);
+    }
+    force.map(Force::Normalized)
+}
+
+/// Translates the motion and buttons of a tool of a drawing tablet, along with the window it's
+/// over.
+fn tablet_tool_events(
+    devices: &HashMap<DeviceId, Device>,
+    tools: &mut HashMap<DeviceId, (ffi::Window, TabletToolAxes)>,
+    xev: &ffi::XIDeviceEvent,
+) -> Vec<(ffi::Window, TabletToolEvent)> {
+    let source = DeviceId(xev.sourceid);
+    let device_id = mkdid(xev.sourceid);
+    let mut events = Vec::new();
+
+    let tool = match devices.get(&source).and_then(|device| device.tablet_tool) {
+        Some(tool) => tool,
+        None => {
+            // Another device moving the cursor means the tools went out of range, as there are
+            // no proximity events in XInput2.
+            if xev.evtype == ffi::XI_Motion {
+                for (tool_id, (window, _)) in tools.drain() {
+                    let device_id = mkdid(tool_id.0);
+                    events.push((window, TabletToolEvent::Left { device_id }));
+                }
+            }
+            return events;
+        }
+    };
+
+    match tools.get(&source) {
+        Some(&(window, _)) if window == xev.event => (),
+        previous => {
+            if let Some(&(window, _)) = previous {
+                events.push((window, TabletToolEvent::Left { device_id }));
+            }
+            tools.insert(source, (xev.event, TabletToolAxes::default()));
+            events.push((
+                xev.event,
+                TabletToolEvent::Entered {
+                    device_id,
+                    tool: tool.tool,
+                },
+            ));
+        }
+    }
+    let axes = &mut tools.get_mut(&source).unwrap().1;
+
+    if xev.evtype == ffi::XI_Motion {
+        // Motion only carries the valuators which changed.
+        for (number, value) in valuators(xev) {
+            let is_axis = |axis: Option<ValuatorAxis>| axis.map(|axis| axis.number) == Some(number);
+            if is_axis(tool.pressure) {
+                axes.pressure = tool.pressure.map(|axis| axis.normalize(value) as f32);
+            } else if is_axis(tool.tilt_x) {
+                axes.tilt_x = tool.tilt_x.map(|axis| axis.degrees(value) as f32);
+            } else if is_axis(tool.tilt_y) {
+                axes.tilt_y = tool.tilt_y.map(|axis| axis.degrees(value) as f32);
+            } else if is_axis(tool.distance) {
+                axes.distance = tool.distance.map(|axis| axis.normalize(value) as f32);
+            } else if is_axis(tool.rotation) {
+                axes.rotation = tool
+                    .rotation
+                    .map(|axis| axis.normalize(value) as f32 * 360.0);
+            }
+        }
+        events.push((
+            xev.event,
+            TabletToolEvent::Moved {
+                device_id,
+                position: PhysicalPosition::new(xev.event_x, xev.event_y),
+                axes: *axes,
+            },
+        ));
+    } else {
+        let state = if xev.evtype == ffi::XI_ButtonPress {
+            ElementState::Pressed
+        } else {
+            ElementState::Released
+        };
+        // The tip is the first button, then come the barrel buttons, skipping the scroll wheel.
+        let event = match xev.detail {
+            1 => TabletToolEvent::Tip { device_id, state },
+            4..=7 => return events,
+            detail => TabletToolEvent::Button {
+                device_id,
+                button: (if detail > 7 { detail - 5 } else { detail - 1 }) as u32,
+                state,
+            },
+        };
+        events.push((xev.event, event));
+    }
+    events
+}
+
+/// The values of the valuators carried by a device event, along with their number.
+fn valuators(xev: &ffi::XIDeviceEvent) -> Vec<(i32, f64)> {
+    let mask =
+        unsafe { slice::from_raw_parts(xev.valuators.mask, xev.valuators.mask_len as usize) };
+    let mut value = xev.valuators.values;
+    let mut valuators = Vec::new();
+    for i in 0..xev.valuators.mask_len * 8 {
+        if ffi::XIMaskIsSet(mask, i) {
+            valuators.push((i, unsafe { *value }));
+            value = unsafe { value.offset(1) };
         }
     }
+    valuators
 }
 
 fn is_first_touch(first: &mut Option<u64>, num: &mut u32, id: u64, phase: TouchPhase) -> bool {
@@ -1,3 +1,6 @@
+use std::os::raw::{c_double, c_int, c_ulong};
+
+pub use x11_dl::xfixes::{PointerBarrier, XFixesSelectionNotifyEvent, Xlib as XFixes};
 use x11_dl::xmd::CARD32;
 pub use x11_dl::{
     error::OpenError, keysym::*, xcursor::*, xinput::*, xinput2::*, xlib::*, xlib_xcb::*,
@@ -7,3 +10,103 @@ pub use x11_dl::{
 // Isn't defined by x11_dl
 #[allow(non_upper_case_globals)]
 pub const IconicState: CARD32 = 3;
+
+// Nor are the XFixes constants
+#[allow(non_upper_case_globals)]
+pub const XFixesSelectionNotify: c_int = 0;
+#[allow(non_upper_case_globals)]
+pub const XFixesSetSelectionOwnerNotifyMask: c_ulong = 1 << 0;
+#[allow(non_upper_case_globals)]
+pub const XFixesSelectionWindowDestroyNotifyMask: c_ulong = 1 << 1;
+#[allow(non_upper_case_globals)]
+pub const XFixesSelectionClientCloseNotifyMask: c_ulong = 1 << 2;
+
+// Nor are the touchpad gestures of XInput 2.4
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchBegin: c_int = 27;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchUpdate: c_int = 28;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchEnd: c_int = 29;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeBegin: c_int = 30;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeUpdate: c_int = 31;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeEnd: c_int = 32;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchBeginMask: i64 = 1 << XI_GesturePinchBegin;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchUpdateMask: i64 = 1 << XI_GesturePinchUpdate;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchEndMask: i64 = 1 << XI_GesturePinchEnd;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeBeginMask: i64 = 1 << XI_GestureSwipeBegin;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeUpdateMask: i64 = 1 << XI_GestureSwipeUpdate;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeEndMask: i64 = 1 << XI_GestureSwipeEnd;
+#[allow(non_upper_case_globals)]
+pub const XIGesturePinchEventCancelled: c_int = 1 << 0;
+#[allow(non_upper_case_globals)]
+pub const XIGestureSwipeEventCancelled: c_int = 1 << 0;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct XIGesturePinchEvent {
+    pub _type: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub extension: c_int,
+    pub evtype: c_int,
+    pub time: Time,
+    pub deviceid: c_int,
+    pub sourceid: c_int,
+    pub detail: c_int,
+    pub root: Window,
+    pub event: Window,
+    pub child: Window,
+    pub root_x: c_double,
+    pub root_y: c_double,
+    pub event_x: c_double,
+    pub event_y: c_double,
+    pub delta_x: c_double,
+    pub delta_y: c_double,
+    pub delta_unaccel_x: c_double,
+    pub delta_unaccel_y: c_double,
+    pub scale: c_double,
+    pub delta_angle: c_double,
+    pub flags: c_int,
+    pub mods: XIModifierState,
+    pub group: XIGroupState,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct XIGestureSwipeEvent {
+    pub _type: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub extension: c_int,
+    pub evtype: c_int,
+    pub time: Time,
+    pub deviceid: c_int,
+    pub sourceid: c_int,
+    pub detail: c_int,
+    pub root: Window,
+    pub event: Window,
+    pub child: Window,
+    pub root_x: c_double,
+    pub root_y: c_double,
+    pub event_x: c_double,
+    pub event_y: c_double,
+    pub delta_x: c_double,
+    pub delta_y: c_double,
+    pub delta_unaccel_x: c_double,
+    pub delta_unaccel_y: c_double,
+    pub flags: c_int,
+    pub mods: XIModifierState,
+    pub group: XIGroupState,
+}
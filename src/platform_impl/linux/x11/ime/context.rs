@@ -1,10 +1,11 @@
 use std::{
-    os::raw::{c_short, c_void},
+    ffi::CStr,
+    os::raw::{c_int, c_short, c_ulong, c_ushort, c_void},
     ptr,
     sync::Arc,
 };
 
-use super::{ffi, util, XConnection, XError};
+use super::{ffi, util, ImeEvent, ImeEventSender, XConnection, XError};
 
 #[derive(Debug)]
 pub enum ImeContextCreationError {
@@ -28,14 +29,194 @@ unsafe fn create_pre_edit_attr<'a>(
     .expect("XVaCreateNestedList returned NULL")
 }
 
+// Not provided by x11-dl.
+#[repr(C)]
+struct XIMStyles {
+    count_styles: c_ushort,
+    supported_styles: *const XIMStyle,
+}
+
+type XIMStyle = c_ulong;
+
+const PREEDIT_CALLBACKS_STYLE: XIMStyle = (ffi::XIMPreeditCallbacks | ffi::XIMStatusNothing) as _;
+
+// Whether the input method supports on-the-spot pre-editing, in which it leaves drawing the
+// pre-edit text to us.
+unsafe fn supports_preedit_callbacks(xconn: &Arc<XConnection>, im: ffi::XIM) -> bool {
+    let mut styles: *mut XIMStyles = ptr::null_mut();
+    (xconn.xlib.XGetIMValues)(
+        im,
+        ffi::XNQueryInputStyle_0.as_ptr() as *const _,
+        &mut styles,
+        ptr::null_mut::<()>(),
+    );
+    if styles.is_null() {
+        return false;
+    }
+
+    let supported = (0..(*styles).count_styles as usize)
+        .any(|i| *(*styles).supported_styles.add(i) == PREEDIT_CALLBACKS_STYLE);
+    (xconn.xlib.XFree)(styles as *mut _);
+    supported
+}
+
+/// The pre-edit state of a context, shared with its XIM callbacks.
+struct ImeContextClientData {
+    window: ffi::Window,
+    event_sender: ImeEventSender,
+    is_composing: bool,
+    text: Vec<char>,
+    cursor_pos: usize,
+}
+
+impl ImeContextClientData {
+    fn send_update(&self) {
+        if !self.is_composing {
+            return;
+        }
+
+        let text: String = self.text.iter().collect();
+        // The cursor position is given in characters, but we report it in bytes.
+        let cursor_byte_pos = self.text[..self.cursor_pos]
+            .iter()
+            .map(|c| c.len_utf8())
+            .sum();
+        let _ = self
+            .event_sender
+            .send((self.window, ImeEvent::Update(text, cursor_byte_pos)));
+    }
+}
+
+// The start callback is an `XICProc`, since its return value is the maximum length of the
+// pre-edit text.
+unsafe extern "C" fn preedit_start_callback(
+    _ic: ffi::XIC,
+    client_data: ffi::XPointer,
+    _call_data: ffi::XPointer,
+) -> c_int {
+    let client_data = &mut *(client_data as *mut ImeContextClientData);
+    client_data.is_composing = true;
+    client_data.text.clear();
+    client_data.cursor_pos = 0;
+    let _ = client_data
+        .event_sender
+        .send((client_data.window, ImeEvent::Start));
+    // No length limit.
+    -1
+}
+
+unsafe extern "C" fn preedit_done_callback(
+    _ic: ffi::XIM,
+    client_data: ffi::XPointer,
+    _call_data: ffi::XPointer,
+) {
+    let client_data = &mut *(client_data as *mut ImeContextClientData);
+    client_data.is_composing = false;
+    client_data.text.clear();
+    client_data.cursor_pos = 0;
+    let _ = client_data
+        .event_sender
+        .send((client_data.window, ImeEvent::End));
+}
+
+unsafe extern "C" fn preedit_draw_callback(
+    _ic: ffi::XIM,
+    client_data: ffi::XPointer,
+    call_data: ffi::XPointer,
+) {
+    let client_data = &mut *(client_data as *mut ImeContextClientData);
+    let call_data = &*(call_data as *const ffi::XIMPreeditDrawCallbackStruct);
+
+    // The changed range is replaced with the new text, which is NULL for a deletion.
+    let new_chars: Vec<char> = if call_data.text.is_null() {
+        Vec::new()
+    } else {
+        let xim_text = &*call_data.text;
+        // Wide character text is never sent, since we never ask for it.
+        if xim_text.encoding_is_wchar != 0 || xim_text.string.multi_byte.is_null() {
+            return;
+        }
+        CStr::from_ptr(xim_text.string.multi_byte)
+            .to_string_lossy()
+            .chars()
+            .collect()
+    };
+
+    let len = client_data.text.len();
+    let chg_first = (call_data.chg_first.max(0) as usize).min(len);
+    let chg_last = (chg_first + call_data.chg_length.max(0) as usize).min(len);
+    client_data.text.splice(chg_first..chg_last, new_chars);
+    client_data.cursor_pos = (call_data.caret.max(0) as usize).min(client_data.text.len());
+    client_data.send_update();
+}
+
+unsafe extern "C" fn preedit_caret_callback(
+    _ic: ffi::XIM,
+    client_data: ffi::XPointer,
+    call_data: ffi::XPointer,
+) {
+    let client_data = &mut *(client_data as *mut ImeContextClientData);
+    let call_data = &mut *(call_data as *mut ffi::XIMPreeditCaretCallbackStruct);
+
+    let len = client_data.text.len();
+    client_data.cursor_pos = match call_data.direction {
+        ffi::XIMCaretDirection::XIMForwardChar => (client_data.cursor_pos + 1).min(len),
+        ffi::XIMCaretDirection::XIMBackwardChar => client_data.cursor_pos.saturating_sub(1),
+        ffi::XIMCaretDirection::XIMLineStart => 0,
+        ffi::XIMCaretDirection::XIMLineEnd => len,
+        ffi::XIMCaretDirection::XIMAbsolutePosition => {
+            (call_data.position.max(0) as usize).min(len)
+        }
+        _ => client_data.cursor_pos,
+    };
+    // The caret callback has to report the new position back.
+    call_data.position = client_data.cursor_pos as c_int;
+    client_data.send_update();
+}
+
+/// The callbacks of a context using on-the-spot pre-editing.
+struct PreeditCallbacks {
+    start_callback: ffi::XICCallback,
+    done_callback: ffi::XIMCallback,
+    draw_callback: ffi::XIMCallback,
+    caret_callback: ffi::XIMCallback,
+}
+
+impl PreeditCallbacks {
+    fn new(client_data: ffi::XPointer) -> Self {
+        PreeditCallbacks {
+            start_callback: ffi::XICCallback {
+                client_data,
+                callback: Some(preedit_start_callback),
+            },
+            done_callback: ffi::XIMCallback {
+                client_data,
+                callback: Some(preedit_done_callback),
+            },
+            draw_callback: ffi::XIMCallback {
+                client_data,
+                callback: Some(preedit_draw_callback),
+            },
+            caret_callback: ffi::XIMCallback {
+                client_data,
+                callback: Some(preedit_caret_callback),
+            },
+        }
+    }
+}
+
 // WARNING: this struct doesn't destroy its XIC resource when dropped.
 // This is intentional, as it doesn't have enough information to know whether or not the context
 // still exists on the server. Since `ImeInner` has that awareness, destruction must be handled
 // through `ImeInner`.
-#[derive(Debug)]
 pub struct ImeContext {
     pub ic: ffi::XIC,
     pub ic_spot: ffi::XPoint,
+    // Whether the window asked for on-the-spot pre-editing, see `Window::set_ime_allowed`.
+    pub allowed: bool,
+    // Both are boxed, since the input method holds pointers to them.
+    client_data: Box<ImeContextClientData>,
+    callbacks: Option<Box<PreeditCallbacks>>,
 }
 
 impl ImeContext {
@@ -44,11 +225,33 @@ impl ImeContext {
         im: ffi::XIM,
         window: ffi::Window,
         ic_spot: Option<ffi::XPoint>,
+        allowed: bool,
+        event_sender: ImeEventSender,
     ) -> Result<Self, ImeContextCreationError> {
-        let ic = if let Some(ic_spot) = ic_spot {
-            ImeContext::create_ic_with_spot(xconn, im, window, ic_spot)
+        let mut client_data = Box::new(ImeContextClientData {
+            window,
+            event_sender,
+            is_composing: false,
+            text: Vec::new(),
+            cursor_pos: 0,
+        });
+
+        // Over-the-spot pre-editing is used unless the application draws the composition itself.
+        let callbacks = if allowed && supports_preedit_callbacks(xconn, im) {
+            let client_data_ptr = &mut *client_data as *mut ImeContextClientData;
+            Some(Box::new(PreeditCallbacks::new(
+                client_data_ptr as ffi::XPointer,
+            )))
         } else {
-            ImeContext::create_ic(xconn, im, window)
+            None
+        };
+
+        let ic = match (&callbacks, ic_spot) {
+            (Some(callbacks), _) => {
+                ImeContext::create_preedit_ic(xconn, im, window, callbacks, ic_spot)
+            }
+            (None, Some(ic_spot)) => ImeContext::create_ic_with_spot(xconn, im, window, ic_spot),
+            (None, None) => ImeContext::create_ic(xconn, im, window),
         };
 
         let ic = ic.ok_or(ImeContextCreationError::Null)?;
@@ -56,9 +259,16 @@ impl ImeContext {
             .check_errors()
             .map_err(ImeContextCreationError::XError)?;
 
+        if callbacks.is_some() {
+            let _ = client_data.event_sender.send((window, ImeEvent::Enabled));
+        }
+
         Ok(ImeContext {
             ic,
             ic_spot: ic_spot.unwrap_or_else(|| ffi::XPoint { x: 0, y: 0 }),
+            allowed,
+            client_data,
+            callbacks,
         })
     }
 
@@ -82,6 +292,50 @@ impl ImeContext {
         }
     }
 
+    unsafe fn create_preedit_ic(
+        xconn: &Arc<XConnection>,
+        im: ffi::XIM,
+        window: ffi::Window,
+        callbacks: &PreeditCallbacks,
+        ic_spot: Option<ffi::XPoint>,
+    ) -> Option<ffi::XIC> {
+        let ic_spot = ic_spot.unwrap_or(ffi::XPoint { x: 0, y: 0 });
+        let pre_edit_attr = util::XSmartPointer::new(
+            xconn,
+            (xconn.xlib.XVaCreateNestedList)(
+                0,
+                ffi::XNSpotLocation_0.as_ptr() as *const _,
+                &ic_spot,
+                ffi::XNPreeditStartCallback_0.as_ptr() as *const _,
+                &callbacks.start_callback,
+                ffi::XNPreeditDoneCallback_0.as_ptr() as *const _,
+                &callbacks.done_callback,
+                ffi::XNPreeditDrawCallback_0.as_ptr() as *const _,
+                &callbacks.draw_callback,
+                ffi::XNPreeditCaretCallback_0.as_ptr() as *const _,
+                &callbacks.caret_callback,
+                ptr::null_mut::<()>(),
+            ),
+        )
+        .expect("XVaCreateNestedList returned NULL");
+
+        let ic = (xconn.xlib.XCreateIC)(
+            im,
+            ffi::XNInputStyle_0.as_ptr() as *const _,
+            PREEDIT_CALLBACKS_STYLE,
+            ffi::XNClientWindow_0.as_ptr() as *const _,
+            window,
+            ffi::XNPreeditAttributes_0.as_ptr() as *const _,
+            pre_edit_attr.ptr,
+            ptr::null_mut::<()>(),
+        );
+        if ic.is_null() {
+            None
+        } else {
+            Some(ic)
+        }
+    }
+
     unsafe fn create_ic_with_spot(
         xconn: &Arc<XConnection>,
         im: ffi::XIM,
@@ -120,6 +374,21 @@ impl ImeContext {
         xconn.check_errors()
     }
 
+    /// Whether an on-the-spot pre-edit is in progress.
+    pub fn is_composing(&self) -> bool {
+        self.client_data.is_composing
+    }
+
+    /// Notifies the application that the input method is going away for this context.
+    pub fn disable(&self) {
+        if self.callbacks.is_some() {
+            let client_data = &self.client_data;
+            let _ = client_data
+                .event_sender
+                .send((client_data.window, ImeEvent::Disabled));
+        }
+    }
+
     pub fn set_spot(&mut self, xconn: &Arc<XConnection>, x: c_short, y: c_short) {
         if self.ic_spot.x == x && self.ic_spot.y == y {
             return;
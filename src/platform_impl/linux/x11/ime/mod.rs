@@ -6,7 +6,7 @@ mod inner;
 mod input_method;
 
 use std::sync::{
-    mpsc::{Receiver, Sender},
+    mpsc::{self, Receiver, Sender},
     Arc,
 };
 
@@ -20,8 +20,32 @@ use self::{
     input_method::PotentialInputMethods,
 };
 
-pub type ImeReceiver = Receiver<(ffi::Window, i16, i16)>;
-pub type ImeSender = Sender<(ffi::Window, i16, i16)>;
+pub type ImeReceiver = Receiver<ImeRequest>;
+pub type ImeSender = Sender<ImeRequest>;
+pub type ImeEventSender = Sender<(ffi::Window, ImeEvent)>;
+
+/// Requests of windows, which may live on other threads, to the input method of the event loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImeRequest {
+    /// Moves the pre-edit spot of a window.
+    Position(ffi::Window, i16, i16),
+    /// Sets whether a window uses on-the-spot pre-editing, see `Window::set_ime_allowed`.
+    Allowed(ffi::Window, bool),
+    /// Destroys the context of a foreign window once we no longer use it, as it isn't destroyed
+    /// along with our `Window`.
+    DestroyContext(ffi::Window),
+}
+
+/// Pre-edit events reported by the XIM callbacks of a context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImeEvent {
+    Enabled,
+    Start,
+    /// The pre-edit text, and the cursor position in bytes.
+    Update(String, usize),
+    End,
+    Disabled,
+}
 
 #[derive(Debug)]
 pub enum ImeCreationError {
@@ -34,14 +58,17 @@ pub struct Ime {
     // The actual meat of this struct is boxed away, since it needs to have a fixed location in
     // memory so we can pass a pointer to it around.
     inner: Box<ImeInner>,
+    // Pre-edit events sent by the callbacks of the input contexts.
+    event_receiver: Receiver<(ffi::Window, ImeEvent)>,
 }
 
 impl Ime {
     pub fn new(xconn: Arc<XConnection>) -> Result<Self, ImeCreationError> {
         let potential_input_methods = PotentialInputMethods::new(&xconn);
+        let (event_sender, event_receiver) = mpsc::channel();
 
         let (mut inner, client_data) = {
-            let mut inner = Box::new(ImeInner::new(xconn, potential_input_methods));
+            let mut inner = Box::new(ImeInner::new(xconn, potential_input_methods, event_sender));
             let inner_ptr = Box::into_raw(inner);
             let client_data = inner_ptr as _;
             let destroy_callback = ffi::XIMCallback {
@@ -74,7 +101,11 @@ impl Ime {
                 }
                 result?;
             }
-            Ok(Ime { xconn, inner })
+            Ok(Ime {
+                xconn,
+                inner,
+                event_receiver,
+            })
         } else {
             Err(ImeCreationError::OpenFailure(inner.potential_input_methods))
         }
@@ -93,12 +124,57 @@ impl Ime {
             // Create empty entry in map, so that when IME is rebuilt, this window has a context.
             None
         } else {
-            Some(unsafe { ImeContext::new(&self.inner.xconn, self.inner.im, window, None) }?)
+            Some(unsafe {
+                ImeContext::new(
+                    &self.inner.xconn,
+                    self.inner.im,
+                    window,
+                    None,
+                    false,
+                    self.inner.event_sender.clone(),
+                )
+            }?)
         };
         self.inner.contexts.insert(window, context);
         Ok(!self.is_destroyed())
     }
 
+    /// Replaces the context of a window by one using on-the-spot pre-editing or not, which has
+    /// to be focused again if the window has the focus.
+    pub fn set_allowed(
+        &mut self,
+        window: ffi::Window,
+        allowed: bool,
+    ) -> Result<bool, ImeContextCreationError> {
+        if self.is_destroyed() {
+            return Ok(false);
+        }
+        let ic_spot = match self.inner.contexts.get(&window) {
+            Some(Some(context)) if context.allowed != allowed => context.ic_spot,
+            _ => return Ok(false),
+        };
+
+        let context = unsafe {
+            ImeContext::new(
+                &self.inner.xconn,
+                self.inner.im,
+                window,
+                Some(ic_spot),
+                allowed,
+                self.inner.event_sender.clone(),
+            )
+        }?;
+        if let Some(Some(old_context)) = self.inner.contexts.insert(window, Some(context)) {
+            old_context.disable();
+            unsafe {
+                self.inner
+                    .destroy_ic_if_necessary(old_context.ic)
+                    .map_err(ImeContextCreationError::XError)?;
+            }
+        }
+        Ok(true)
+    }
+
     pub fn get_context(&self, window: ffi::Window) -> Option<ffi::XIC> {
         if self.is_destroyed() {
             return None;
@@ -110,8 +186,24 @@ impl Ime {
         }
     }
 
+    pub fn is_composing(&self, window: ffi::Window) -> bool {
+        if self.is_destroyed() {
+            return false;
+        }
+        if let Some(Some(context)) = self.inner.contexts.get(&window) {
+            context.is_composing()
+        } else {
+            false
+        }
+    }
+
+    pub fn next_event(&self) -> Option<(ffi::Window, ImeEvent)> {
+        self.event_receiver.try_recv().ok()
+    }
+
     pub fn remove_context(&mut self, window: ffi::Window) -> Result<bool, XError> {
         if let Some(Some(context)) = self.inner.contexts.remove(&window) {
+            context.disable();
             unsafe {
                 self.inner.destroy_ic_if_necessary(context.ic)?;
             }
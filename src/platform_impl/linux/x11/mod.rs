@@ -6,7 +6,9 @@
     target_os = "openbsd"
 ))]
 
+mod clipboard;
 mod dnd;
+mod drag;
 mod event_processor;
 mod events;
 pub mod ffi;
@@ -15,11 +17,13 @@ mod monitor;
 pub mod util;
 mod window;
 mod xdisplay;
+mod xembed;
 
 pub use self::{
     monitor::{MonitorHandle, VideoMode},
+    util::CustomCursor,
     window::UnownedWindow,
-    xdisplay::{XConnection, XError, XNotSupported},
+    xdisplay::{previous_error_handler, XConnection, XError, XNotSupported},
 };
 
 use std::{
@@ -48,21 +52,33 @@ use mio_misc::{
 };
 
 use self::{
-    dnd::{Dnd, DndState},
+    clipboard::Clipboard,
+    dnd::Dnd,
+    drag::DragSource,
     event_processor::EventProcessor,
-    ime::{Ime, ImeCreationError, ImeReceiver, ImeSender},
+    ime::{Ime, ImeCreationError, ImeEvent, ImeReceiver, ImeRequest, ImeSender},
     util::modifiers::ModifierKeymap,
 };
+#[cfg(target_os = "linux")]
+use super::gamepad::{self, Gamepads};
+#[cfg(target_os = "linux")]
+use crate::gamepad::GamepadInfo;
 use crate::{
-    error::OsError as RootOsError,
-    event::{Event, StartCause},
+    clipboard::ClipboardKind,
+    error::{ExternalError, OsError as RootOsError},
+    event::{Event, StartCause, TabletTool},
     event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
-    platform_impl::{platform::sticky_exit_callback, PlatformSpecificWindowBuilderAttributes},
-    window::WindowAttributes,
+    platform_impl::{
+        platform::{sticky_exit_callback, OsError},
+        PlatformSpecificWindowBuilderAttributes,
+    },
+    window::{CursorGrabMode, CustomCursorSource, WindowAttributes},
 };
 
 const X_TOKEN: Token = Token(0);
 const USER_REDRAW_TOKEN: Token = Token(1);
+#[cfg(target_os = "linux")]
+const GAMEPAD_TOKEN: Token = Token(2);
 
 pub struct EventLoopWindowTarget<T> {
     xconn: Arc<XConnection>,
@@ -71,8 +87,14 @@ pub struct EventLoopWindowTarget<T> {
     ime_sender: ImeSender,
     root: ffi::Window,
     ime: RefCell<Ime>,
+    clipboard: Clipboard,
+    drag_source: Arc<DragSource>,
     windows: RefCell<HashMap<WindowId, Weak<UnownedWindow>>>,
     redraw_sender: Sender<WindowId>,
+    // Whether the server supports the touchpad gestures of XInput 2.4
+    xi2_gestures: bool,
+    #[cfg(target_os = "linux")]
+    gamepads: RefCell<Option<Gamepads>>,
     _marker: ::std::marker::PhantomData<T>,
 }
 
@@ -108,6 +130,13 @@ impl<T: 'static> EventLoop<T> {
         let dnd = Dnd::new(Arc::clone(&xconn))
             .expect("Failed to call XInternAtoms when initializing drag and drop");
 
+        let clipboard = Clipboard::new(Arc::clone(&xconn), root)
+            .expect("Failed to create the window owning the clipboard selections");
+
+        let drag_source = DragSource::new(Arc::clone(&xconn), root)
+            .map(Arc::new)
+            .expect("Failed to call XInternAtoms when initializing drag and drop");
+
         let (ime_sender, ime_receiver) = mpsc::channel();
         // Input methods will open successfully without setting the locale, but it won't be
         // possible to actually commit pre-edit sequences.
@@ -162,9 +191,10 @@ impl<T: 'static> EventLoop<T> {
             ext
         };
 
-        unsafe {
+        let xi2_gestures = unsafe {
+            // 2.4 adds touchpad gestures, and the server answers with the version it supports.
             let mut xinput_major_ver = ffi::XI_2_Major;
-            let mut xinput_minor_ver = ffi::XI_2_Minor;
+            let mut xinput_minor_ver = 4;
             if (xconn.xinput2.XIQueryVersion)(
                 xconn.display,
                 &mut xinput_major_ver,
@@ -176,7 +206,8 @@ impl<T: 'static> EventLoop<T> {
                     xinput_major_ver, xinput_minor_ver,
                 );
             }
-        }
+            xinput_major_ver > 2 || xinput_minor_ver >= 4
+        };
 
         xconn.update_cached_wm_info(root);
 
@@ -191,6 +222,23 @@ impl<T: 'static> EventLoop<T> {
             .register(&mut SourceFd(&xconn.x11_fd), X_TOKEN, Interest::READABLE)
             .unwrap();
 
+        #[cfg(target_os = "linux")]
+        let gamepads = {
+            // The devices are only opened once the application asks for them.
+            let gamepads = Gamepads::new().and_then(|gamepads| {
+                poll.registry().register(
+                    &mut SourceFd(&gamepads.fd()),
+                    GAMEPAD_TOKEN,
+                    Interest::READABLE,
+                )?;
+                Ok(gamepads)
+            });
+            if let Err(ref err) = gamepads {
+                warn!("Failed to create the gamepad poll: {}", err);
+            }
+            RefCell::new(gamepads.ok())
+        };
+
         let (user_sender, user_channel) = channel(queue.clone(), NotificationId::gen_next());
 
         let (redraw_sender, redraw_channel) = channel(queue, NotificationId::gen_next());
@@ -198,6 +246,8 @@ impl<T: 'static> EventLoop<T> {
         let target = Rc::new(RootELW {
             p: super::EventLoopWindowTarget::X(EventLoopWindowTarget {
                 ime,
+                clipboard,
+                drag_source,
                 root,
                 windows: Default::default(),
                 _marker: ::std::marker::PhantomData,
@@ -206,6 +256,9 @@ impl<T: 'static> EventLoop<T> {
                 wm_delete_window,
                 net_wm_ping,
                 redraw_sender,
+                xi2_gestures,
+                #[cfg(target_os = "linux")]
+                gamepads,
             }),
             _marker: ::std::marker::PhantomData,
         });
@@ -219,8 +272,12 @@ impl<T: 'static> EventLoop<T> {
             xi2ext,
             mod_keymap,
             device_mod_state: Default::default(),
+            pressed_keys: Default::default(),
             num_touch: 0,
             first_touch: None,
+            touch_forces: HashMap::new(),
+            pinch_scale: 1.0,
+            tablet_tools: HashMap::new(),
             active_window: None,
         };
 
@@ -228,7 +285,7 @@ impl<T: 'static> EventLoop<T> {
         // (The request buffer is flushed during `init_device`)
         get_xtarget(&target)
             .xconn
-            .select_xinput_events(root, ffi::XIAllDevices, ffi::XI_HierarchyChangedMask)
+            .select_xinput_events(root, ffi::XIAllDevices, ffi::XI_HierarchyChangedMask.into())
             .queue();
 
         event_processor.init_device(ffi::XIAllDevices);
@@ -354,9 +411,32 @@ impl<T: 'static> EventLoop<T> {
                 }
             }
 
-            // If the XConnection already contains buffered events, we don't
-            // need to wait for data on the socket.
-            if !self.event_processor.poll() {
+            // The windows dropped by the callback may have left requests.
+            self.event_processor.process_ime_requests();
+
+            // Wake up in time to cancel a drop the target doesn't answer.
+            let timeout = match get_xtarget(&self.target).drag_source.deadline() {
+                Some(drag_deadline) => {
+                    let drag_timeout = if drag_deadline > start {
+                        drag_deadline - start
+                    } else {
+                        Duration::from_millis(0)
+                    };
+                    Some(timeout.map_or(drag_timeout, |timeout| timeout.min(drag_timeout)))
+                }
+                None => timeout,
+            };
+
+            // If the XConnection already contains buffered events, or the gamepads opened by the
+            // callback were queued, we don't need to wait for data on the sockets.
+            #[cfg(target_os = "linux")]
+            let gamepads_pending = match *get_xtarget(&self.target).gamepads.borrow() {
+                Some(ref gamepads) => gamepads.has_events(),
+                None => false,
+            };
+            #[cfg(not(target_os = "linux"))]
+            let gamepads_pending = false;
+            if !self.event_processor.poll() && !gamepads_pending {
                 self.poll.poll(&mut events, timeout).unwrap();
                 events.clear();
             }
@@ -415,13 +495,37 @@ impl<T: 'static> EventLoop<T> {
                 );
             });
         }
+
+        if let Some((window, outcome)) = wt.drag_source.handle_timeout() {
+            let event = Event::WindowEvent {
+                window_id: mkwid(window),
+                event: crate::event::WindowEvent::DragEnded(outcome),
+            };
+            sticky_exit_callback(event, target, control_flow, callback);
+        }
+
+        // The callback may ask about the gamepads, so they're read beforehand.
+        #[cfg(target_os = "linux")]
+        {
+            let mut gamepad_events = Vec::new();
+            if let Some(ref mut gamepads) = *wt.gamepads.borrow_mut() {
+                gamepads.dispatch(|device_id, event| gamepad_events.push((device_id, event)));
+            }
+            for (device_id, event) in gamepad_events {
+                let event = Event::DeviceEvent {
+                    device_id: crate::event::DeviceId(super::DeviceId::Gamepad(device_id)),
+                    event,
+                };
+                sticky_exit_callback(event, target, control_flow, callback);
+            }
+        }
     }
 }
 
 pub(crate) fn get_xtarget<T>(target: &RootELW<T>) -> &EventLoopWindowTarget<T> {
     match target.p {
         super::EventLoopWindowTarget::X(ref target) => target,
-        #[cfg(feature = "wayland")]
+        #[cfg(any(feature = "wayland", feature = "mock"))]
         _ => unreachable!(),
     }
 }
@@ -432,6 +536,71 @@ impl<T> EventLoopWindowTarget<T> {
     pub fn x_connection(&self) -> &Arc<XConnection> {
         &self.xconn
     }
+
+    #[cfg(target_os = "linux")]
+    pub fn gamepad_info(&self, device_id: gamepad::DeviceId) -> Option<GamepadInfo> {
+        self.gamepads.borrow().as_ref()?.info(device_id)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn watch_gamepads(&self) {
+        if let Some(ref mut gamepads) = *self.gamepads.borrow_mut() {
+            gamepads.watch_devices();
+        }
+    }
+
+    pub fn clipboard_text(&self, kind: ClipboardKind) -> Result<Option<String>, ExternalError> {
+        self.clipboard
+            .text(kind)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+    }
+
+    pub fn set_clipboard_text(
+        &self,
+        kind: ClipboardKind,
+        text: String,
+    ) -> Result<(), ExternalError> {
+        self.clipboard
+            .set_text(kind, text)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+    }
+
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Result<Vec<String>, ExternalError> {
+        self.clipboard
+            .mime_types(kind)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+    }
+
+    pub fn clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.clipboard
+            .data(kind, mime_type)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+    }
+
+    pub fn set_clipboard_data(
+        &self,
+        kind: ClipboardKind,
+        data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        self.clipboard
+            .set_data(kind, data)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+    }
+
+    pub fn create_custom_cursor(
+        &self,
+        source: CustomCursorSource,
+    ) -> Result<CustomCursor, ExternalError> {
+        CustomCursor::new(&self.xconn, &source).ok_or_else(|| {
+            ExternalError::Os(os_error!(OsError::XMisc(
+                "Failed to create the custom cursor"
+            )))
+        })
+    }
 }
 
 impl<T: 'static> EventLoopProxy<T> {
@@ -527,17 +696,63 @@ impl Window {
             .insert(window.id(), Arc::downgrade(&window));
         Ok(Window(window))
     }
+
+    /// # Safety
+    ///
+    /// See `UnownedWindow::from_raw`.
+    pub unsafe fn from_raw<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        xwindow: ffi::Window,
+    ) -> Result<Self, RootOsError> {
+        let is_wrapped = event_loop
+            .windows
+            .borrow()
+            .get(&WindowId(xwindow))
+            .and_then(Weak::upgrade)
+            .is_some();
+        if is_wrapped {
+            return Err(os_error!(OsError::XMisc(
+                "the window is already a window of this event loop"
+            )));
+        }
+
+        let window = Arc::new(UnownedWindow::from_raw(event_loop, xwindow)?);
+        event_loop
+            .windows
+            .borrow_mut()
+            .insert(window.id(), Arc::downgrade(&window));
+        Ok(Window(window))
+    }
 }
 
 impl Drop for Window {
     fn drop(&mut self) {
         let window = self.deref();
         let xconn = &window.xconn;
-        unsafe {
-            (xconn.xlib.XDestroyWindow)(xconn.display, window.id().0);
-            // If the window was somehow already destroyed, we'll get a `BadWindow` error, which we don't care about.
-            let _ = xconn.check_errors();
+        // Windows are dropped outside of the calls which count our requests.
+        xconn.begin_own_requests();
+        // Pointer barriers outlive the window.
+        let _ = window.set_cursor_grab_region(None);
+
+        // Foreign windows are left to their creator, with the event masks it selected.
+        if let Some(event_masks) = window.foreign_event_masks() {
+            let _ = window.set_cursor_grab(CursorGrabMode::None);
+            window.destroy_ime_context();
+            unsafe {
+                (xconn.xlib.XSelectInput)(xconn.display, window.id().0, event_masks.core);
+            }
+            xconn
+                .select_xinput_events(window.id().0, ffi::XIAllMasterDevices, event_masks.xinput)
+                .queue();
+            let _ = xconn.flush_requests();
+        } else {
+            unsafe {
+                (xconn.xlib.XDestroyWindow)(xconn.display, window.id().0);
+                // If the window was somehow already destroyed, we'll get a `BadWindow` error, which we don't care about.
+                let _ = xconn.check_errors();
+            }
         }
+        xconn.end_own_requests();
     }
 }
 
@@ -590,6 +805,10 @@ fn mkdid(w: c_int) -> crate::event::DeviceId {
 struct Device {
     name: String,
     scroll_axes: Vec<(i32, ScrollAxis)>,
+    // The valuator reporting the pressure of touches, for touchscreens which have one.
+    pressure_axis: Option<ValuatorAxis>,
+    // The valuators of the tools of drawing tablets.
+    tablet_tool: Option<TabletToolDevice>,
     // For master devices, this is the paired device (pointer <-> keyboard).
     // For slave devices, this is the master.
     attachment: c_int,
@@ -608,10 +827,73 @@ enum ScrollOrientation {
     Horizontal,
 }
 
+#[derive(Debug, Copy, Clone)]
+struct ValuatorAxis {
+    number: i32,
+    min: f64,
+    max: f64,
+
+    /// Units per meter, or per radian for angles, 0 if unknown.
+    resolution: i32,
+}
+
+impl ValuatorAxis {
+    /// Maps the range of the valuator to 0.0..=1.0.
+    fn normalize(&self, value: f64) -> f64 {
+        if value <= self.min {
+            0.0
+        } else if value >= self.max {
+            1.0
+        } else {
+            (value - self.min) / (self.max - self.min)
+        }
+    }
+
+    /// Converts the value of an angle to degrees. The tablet drivers report tilt in degrees
+    /// already when they don't give a resolution.
+    fn degrees(&self, value: f64) -> f64 {
+        if self.resolution > 0 {
+            (value / self.resolution as f64).to_degrees()
+        } else {
+            value
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct TabletToolDevice {
+    tool: TabletTool,
+    pressure: Option<ValuatorAxis>,
+    tilt_x: Option<ValuatorAxis>,
+    tilt_y: Option<ValuatorAxis>,
+    distance: Option<ValuatorAxis>,
+    rotation: Option<ValuatorAxis>,
+}
+
+impl TabletToolDevice {
+    /// Guesses the kind of tool from the name of the device, as the drivers name each one after
+    /// the tool type it reports, e.g. "Wacom Intuos S Pen eraser".
+    fn tool_from_name(name: &str) -> TabletTool {
+        let name = name.to_lowercase();
+        if name.contains("eraser") {
+            TabletTool::Eraser
+        } else if name.contains("cursor") || name.contains("mouse") {
+            TabletTool::Mouse
+        } else if name.contains("lens") {
+            TabletTool::Lens
+        } else {
+            // Pens are by far the most common pressure-sensitive pointers.
+            TabletTool::Pen
+        }
+    }
+}
+
 impl Device {
     fn new<T: 'static>(el: &EventProcessor<T>, info: &ffi::XIDeviceInfo) -> Self {
         let name = unsafe { CStr::from_ptr(info.name).to_string_lossy() };
         let mut scroll_axes = Vec::new();
+        let mut pressure_axis = None;
+        let mut tablet_tool = None;
 
         let wt = get_xtarget(&el.target);
 
@@ -624,10 +906,29 @@ impl Device {
                 | ffi::XI_RawKeyReleaseMask;
             // The request buffer is flushed when we poll for events
             wt.xconn
-                .select_xinput_events(wt.root, info.deviceid, mask)
+                .select_xinput_events(wt.root, info.deviceid, mask.into())
                 .queue();
 
-            // Identify scroll axes
+            // The labels given to the valuators by the evdev, libinput and wacom drivers.
+            let label = |name: &[u8]| unsafe { wt.xconn.get_atom_unchecked(name) };
+            let (mt_pressure_label, pressure_label) =
+                (label(b"Abs MT Pressure\0"), label(b"Abs Pressure\0"));
+            let (tilt_x_label, tilt_y_label) = (label(b"Abs Tilt X\0"), label(b"Abs Tilt Y\0"));
+            let (distance_label, rotation_label) =
+                (label(b"Abs Distance\0"), label(b"Abs Rotary Z\0"));
+
+            let mut is_touchscreen = false;
+            let mut mt_pressure = None;
+            let mut tool = TabletToolDevice {
+                tool: TabletToolDevice::tool_from_name(&name),
+                pressure: None,
+                tilt_x: None,
+                tilt_y: None,
+                distance: None,
+                rotation: None,
+            };
+
+            // Identify scroll axes, and the valuators of touchscreens and tablets
             for class_ptr in Device::classes(info) {
                 let class = unsafe { &**class_ptr };
                 match class._type {
@@ -648,14 +949,47 @@ impl Device {
                             },
                         ));
                     }
+                    ffi::XIValuatorClass => {
+                        let info = unsafe {
+                            mem::transmute::<&ffi::XIAnyClassInfo, &ffi::XIValuatorClassInfo>(class)
+                        };
+                        if info.max <= info.min {
+                            continue;
+                        }
+                        let axis = Some(ValuatorAxis {
+                            number: info.number,
+                            min: info.min,
+                            max: info.max,
+                            resolution: info.resolution,
+                        });
+                        match info.label {
+                            label if label == mt_pressure_label => mt_pressure = axis,
+                            label if label == pressure_label => tool.pressure = axis,
+                            label if label == tilt_x_label => tool.tilt_x = axis,
+                            label if label == tilt_y_label => tool.tilt_y = axis,
+                            label if label == distance_label => tool.distance = axis,
+                            label if label == rotation_label => tool.rotation = axis,
+                            _ => (),
+                        }
+                    }
+                    ffi::XITouchClass => is_touchscreen = true,
                     _ => {}
                 }
             }
+
+            // The multitouch pressure is the one of each touch, which some touchscreens lack.
+            if is_touchscreen {
+                pressure_axis = mt_pressure.or(tool.pressure);
+            } else if tool.pressure.is_some() || tool.tilt_x.is_some() {
+                tablet_tool = Some(tool);
+            }
         }
 
         let mut device = Device {
             name: name.into_owned(),
             scroll_axes,
+            pressure_axis,
+            tablet_tool,
             attachment: info.attachment,
         };
         device.reset_scroll_position(info);
@@ -1,7 +1,72 @@
-use crate::window::CursorIcon;
+use std::{slice, sync::Arc, time::Duration};
+
+use crate::{
+    cursor::{CursorImage, CustomCursorSource},
+    platform_impl::platform::common::cursor as common_cursor,
+    window::CursorIcon,
+};
 
 use super::*;
 
+/// A custom cursor, with an X cursor for each of its images.
+#[derive(Debug, Clone)]
+pub struct CustomCursor {
+    inner: Arc<CustomCursorInner>,
+}
+
+#[derive(Debug)]
+struct CustomCursorInner {
+    xconn: Arc<XConnection>,
+    /// The cursors along with the scale factor of their image.
+    cursors: Vec<(u32, ffi::Cursor)>,
+}
+
+impl CustomCursor {
+    /// Creates a cursor for each scale factor of the images, or returns `None` if the server
+    /// couldn't create them.
+    ///
+    /// The cursors of animated sources are made of the frames' images of that scale factor.
+    pub fn new(xconn: &Arc<XConnection>, source: &CustomCursorSource) -> Option<Self> {
+        let scale_factors = common_cursor::scale_factors(source);
+
+        // Cursors created before a failure are freed when `inner` is dropped.
+        let mut inner = CustomCursorInner {
+            xconn: Arc::clone(xconn),
+            cursors: Vec::with_capacity(scale_factors.len()),
+        };
+        for scale_factor in scale_factors {
+            let cursor = unsafe { xconn.load_frames_cursor(source, scale_factor as f64) };
+            if cursor == 0 {
+                return None;
+            }
+            inner.cursors.push((scale_factor, cursor));
+        }
+
+        xconn.check_errors().ok()?;
+        Some(CustomCursor {
+            inner: Arc::new(inner),
+        })
+    }
+
+    fn cursor(&self, scale_factor: f64) -> ffi::Cursor {
+        *common_cursor::pick_variant(&self.inner.cursors, scale_factor)
+    }
+}
+
+impl PartialEq for CustomCursor {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Drop for CustomCursorInner {
+    fn drop(&mut self) {
+        for &(_, cursor) in &self.cursors {
+            unsafe { (self.xconn.xlib.XFreeCursor)(self.xconn.display, cursor) };
+        }
+    }
+}
+
 impl XConnection {
     pub fn set_cursor_icon(&self, window: ffi::Window, cursor: Option<CursorIcon>) {
         let cursor = *self
@@ -13,6 +78,63 @@ impl XConnection {
         self.update_cursor(window, cursor);
     }
 
+    /// Shows the variant of the custom cursor drawn for the given scale factor.
+    pub fn set_custom_cursor(&self, window: ffi::Window, cursor: &CustomCursor, scale_factor: f64) {
+        self.update_cursor(window, cursor.cursor(scale_factor));
+    }
+
+    /// Loads a cursor out of the images drawn for the given scale factor in each frame, which
+    /// the server animates if there are several.
+    unsafe fn load_frames_cursor(
+        &self,
+        source: &CustomCursorSource,
+        scale_factor: f64,
+    ) -> ffi::Cursor {
+        let xcursor_images = (self.xcursor.XcursorImagesCreate)(source.frames.len() as c_int);
+        if xcursor_images.is_null() {
+            return 0;
+        }
+
+        // The images are destroyed along with the set they're added to.
+        for frame in &source.frames {
+            let image = common_cursor::pick_image(frame, scale_factor);
+            let xcursor_image = self.create_xcursor_image(image, frame.duration);
+            if xcursor_image.is_null() {
+                (self.xcursor.XcursorImagesDestroy)(xcursor_images);
+                return 0;
+            }
+            let index = (*xcursor_images).nimage as usize;
+            *(*xcursor_images).images.add(index) = xcursor_image;
+            (*xcursor_images).nimage += 1;
+        }
+
+        let cursor = (self.xcursor.XcursorImagesLoadCursor)(self.display, xcursor_images);
+        (self.xcursor.XcursorImagesDestroy)(xcursor_images);
+        cursor
+    }
+
+    unsafe fn create_xcursor_image(
+        &self,
+        image: &CursorImage,
+        duration: Duration,
+    ) -> *mut ffi::XcursorImage {
+        let xcursor_image = (self.xcursor.XcursorImageCreate)(
+            image.rgba.width as c_int,
+            image.rgba.height as c_int,
+        );
+        if xcursor_image.is_null() {
+            return xcursor_image;
+        }
+
+        (*xcursor_image).xhot = image.hotspot_x;
+        (*xcursor_image).yhot = image.hotspot_y;
+        (*xcursor_image).delay = duration.as_millis().min(u32::MAX as u128) as u32;
+        let pixel_count = (image.rgba.width * image.rgba.height) as usize;
+        slice::from_raw_parts_mut((*xcursor_image).pixels, pixel_count)
+            .copy_from_slice(&common_cursor::argb_pixels(image));
+        xcursor_image
+    }
+
     fn create_empty_cursor(&self) -> ffi::Cursor {
         let data = 0;
         let pixmap = unsafe {
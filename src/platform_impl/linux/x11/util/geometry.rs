@@ -230,7 +230,7 @@ impl XConnection {
         client_list.map(|client_list| client_list.contains(&window))
     }
 
-    fn get_parent_window(&self, window: ffi::Window) -> Result<ffi::Window, XError> {
+    pub fn get_parent_window(&self, window: ffi::Window) -> Result<ffi::Window, XError> {
         let parent = unsafe {
             let mut root = 0;
             let mut parent = 0;
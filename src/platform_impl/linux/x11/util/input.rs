@@ -1,4 +1,4 @@
-use std::{slice, str};
+use std::{cmp, slice, str};
 
 use super::*;
 use crate::event::ModifiersState;
@@ -63,7 +63,7 @@ impl XConnection {
         &self,
         window: c_ulong,
         device_id: c_int,
-        mask: i32,
+        mask: i64,
     ) -> Flusher<'_> {
         let mut event_mask = ffi::XIEventMask {
             deviceid: device_id,
@@ -81,6 +81,35 @@ impl XConnection {
         Flusher::new(self)
     }
 
+    /// Returns the XInput2 events this client selected on `window` for `device_id`, as the mask
+    /// `select_xinput_events` takes.
+    pub fn get_selected_xinput_events(
+        &self,
+        window: c_ulong,
+        device_id: c_int,
+    ) -> Result<i64, XError> {
+        let mut num_masks = 0;
+        let masks =
+            unsafe { (self.xinput2.XIGetSelectedEvents)(self.display, window, &mut num_masks) };
+        self.check_errors()?;
+        if masks.is_null() {
+            return Ok(0);
+        }
+
+        let mut bytes = [0; mem::size_of::<i64>()];
+        unsafe {
+            let masks = slice::from_raw_parts(masks, num_masks as usize);
+            if let Some(mask) = masks.iter().find(|mask| mask.deviceid == device_id) {
+                let len = cmp::min(mask.mask_len as usize, bytes.len());
+                bytes[..len].copy_from_slice(slice::from_raw_parts(mask.mask, len));
+            }
+        }
+        unsafe {
+            (self.xlib.XFree)(masks as *mut c_void);
+        }
+        Ok(i64::from_ne_bytes(bytes))
+    }
+
     #[allow(dead_code)]
     pub fn select_xkb_events(&self, device_id: c_uint, mask: c_ulong) -> Option<Flusher<'_>> {
         let status = unsafe { (self.xlib.XkbSelectEvents)(self.display, device_id, mask, mask) };
@@ -17,7 +17,7 @@ mod window_property;
 mod wm;
 
 pub use self::{
-    atom::*, client_msg::*, format::*, geometry::*, hint::*, icon::*, input::*, memory::*,
+    client_msg::*, cursor::CustomCursor, format::*, geometry::*, hint::*, input::*, memory::*,
     randr::*, window_property::*, wm::*,
 };
 
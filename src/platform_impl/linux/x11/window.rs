@@ -6,7 +6,7 @@ use std::{
     os::raw::*,
     path::Path,
     ptr, slice,
-    sync::Arc,
+    sync::{Arc, Weak},
 };
 
 use libc;
@@ -14,18 +14,33 @@ use mio_misc::channel::Sender;
 use parking_lot::Mutex;
 
 use crate::{
+    dnd::DndActions,
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
     platform_impl::{
+        platform::popup,
         x11::{ime::ImeContextCreationError, MonitorHandle as X11MonitorHandle},
         MonitorHandle as PlatformMonitorHandle, OsError, PlatformSpecificWindowBuilderAttributes,
-        VideoMode as PlatformVideoMode,
+        VideoMode as PlatformVideoMode, WindowId as PlatformWindowId,
+    },
+    window::{
+        CursorGrabMode, CursorIcon, Fullscreen, Icon, PopupAttributes, UserAttentionType,
+        WindowAttributes, WindowId as RootWindowId,
     },
-    window::{CursorIcon, Fullscreen, Icon, UserAttentionType, WindowAttributes},
 };
 
-use super::{ffi, util, EventLoopWindowTarget, ImeSender, WindowId, XConnection, XError};
+use super::{
+    drag::DragSource, ffi, util, xembed::EmbedState, CustomCursor, EventLoopWindowTarget,
+    ImeRequest, ImeSender, WindowId, XConnection, XError,
+};
+
+/// The cursor shown over a window while it's visible.
+#[derive(Debug, Clone, PartialEq)]
+enum WindowCursor {
+    Icon(CursorIcon),
+    Custom(CustomCursor),
+}
 
 #[derive(Debug)]
 pub struct SharedState {
@@ -49,6 +64,12 @@ pub struct SharedState {
     pub resize_increments: Option<Size>,
     pub base_size: Option<Size>,
     pub visibility: Visibility,
+    // Position of a popup relative to the client area of its parent
+    pub popup_offset: Option<(i32, i32)>,
+    // Whether the popup grabs the pointer and the keyboard
+    pub popup_grab: bool,
+    // What the embedder of an embedded window told it
+    pub xembed: Option<EmbedState>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -70,6 +91,9 @@ impl SharedState {
         Mutex::new(SharedState {
             last_monitor,
             visibility,
+            popup_offset: None,
+            popup_grab: false,
+            xembed: None,
 
             cursor_pos: None,
             size: None,
@@ -93,17 +117,160 @@ impl SharedState {
 unsafe impl Send for UnownedWindow {}
 unsafe impl Sync for UnownedWindow {}
 
+/// The core events selected on our windows.
+const WINDOW_EVENT_MASK: c_long = ffi::ExposureMask
+    | ffi::StructureNotifyMask
+    | ffi::VisibilityChangeMask
+    | ffi::KeyPressMask
+    | ffi::KeyReleaseMask
+    | ffi::KeymapStateMask
+    | ffi::ButtonPressMask
+    | ffi::ButtonReleaseMask
+    | ffi::PointerMotionMask;
+
+/// The XInput2 events selected on our windows.
+fn xinput_event_mask<T>(event_loop: &EventLoopWindowTarget<T>) -> i64 {
+    let mask = ffi::XI_MotionMask
+        | ffi::XI_ButtonPressMask
+        | ffi::XI_ButtonReleaseMask
+        //| ffi::XI_KeyPressMask
+        //| ffi::XI_KeyReleaseMask
+        | ffi::XI_EnterMask
+        | ffi::XI_LeaveMask
+        | ffi::XI_FocusInMask
+        | ffi::XI_FocusOutMask
+        | ffi::XI_TouchBeginMask
+        | ffi::XI_TouchUpdateMask
+        | ffi::XI_TouchEndMask;
+    let mut mask = i64::from(mask);
+    if event_loop.xi2_gestures {
+        mask |= ffi::XI_GesturePinchBeginMask
+            | ffi::XI_GesturePinchUpdateMask
+            | ffi::XI_GesturePinchEndMask
+            | ffi::XI_GestureSwipeBeginMask
+            | ffi::XI_GestureSwipeUpdateMask
+            | ffi::XI_GestureSwipeEndMask;
+    }
+    mask
+}
+
+fn create_ime_context<T>(
+    event_loop: &EventLoopWindowTarget<T>,
+    xwindow: ffi::Window,
+) -> Result<(), RootOsError> {
+    let result = event_loop.ime.borrow_mut().create_context(xwindow);
+    result.map(|_| ()).map_err(|err| {
+        let e = match err {
+            ImeContextCreationError::XError(err) => OsError::XError(err),
+            ImeContextCreationError::Null => OsError::XMisc("IME Context creation failed"),
+        };
+        os_error!(e)
+    })
+}
+
+/// Returns the window `window_id` refers to, if it's a window of this event loop.
+fn find_window<T>(
+    event_loop: &EventLoopWindowTarget<T>,
+    window_id: RootWindowId,
+) -> Option<Arc<UnownedWindow>> {
+    match window_id.0 {
+        PlatformWindowId::X(window_id) => event_loop
+            .windows
+            .borrow()
+            .get(&window_id)
+            .and_then(Weak::upgrade),
+        #[cfg(feature = "wayland")]
+        PlatformWindowId::Wayland(_) => None,
+        #[cfg(feature = "mock")]
+        PlatformWindowId::Mock(_) => None,
+    }
+}
+
+/// Returns the parent of a popup, which has to be a window of this event loop.
+fn popup_parent<T>(
+    event_loop: &EventLoopWindowTarget<T>,
+    popup: &PopupAttributes,
+) -> Result<Arc<UnownedWindow>, RootOsError> {
+    find_window(event_loop, popup.parent).ok_or_else(|| {
+        os_error!(OsError::XMisc(
+            "the parent of a popup isn't a window of this event loop"
+        ))
+    })
+}
+
+/// Returns the position of a popup in root coordinates, on the monitor of its anchor rectangle.
+fn place_popup(
+    xconn: &XConnection,
+    popup: &PopupAttributes,
+    parent: &UnownedWindow,
+    scale_factor: f64,
+    size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    let (parent_x, parent_y) = parent.inner_position_physical();
+    let (anchor_position, anchor_size) = popup.anchor_rect;
+    let anchor_position = anchor_position.to_physical::<i32>(scale_factor);
+    let anchor_position =
+        PhysicalPosition::new(parent_x + anchor_position.x, parent_y + anchor_position.y);
+    let anchor_size = anchor_size.to_physical::<u32>(scale_factor);
+
+    // The anchor rectangle may be a line, which doesn't overlap with any monitor.
+    let monitor = xconn.get_monitor_for_window(Some(util::AaRect::new(
+        anchor_position.into(),
+        (
+            cmp::max(anchor_size.width, 1),
+            cmp::max(anchor_size.height, 1),
+        ),
+    )));
+    let bounds = if monitor.is_dummy() {
+        None
+    } else {
+        Some((monitor.position(), monitor.size()))
+    };
+
+    popup::place((anchor_position, anchor_size), popup.gravity, size, bounds)
+}
+
+/// How the window grabs the cursor.
+#[derive(Debug, Default)]
+struct CursorGrab {
+    mode: CursorGrabMode,
+
+    /// Where a locked cursor is held, in window coordinates.
+    locked_position: (f64, f64),
+
+    /// The part of the window a confined cursor is restricted to, if not the whole window.
+    region: Option<(Position, Size)>,
+
+    /// Pointer barriers around the region, in root coordinates.
+    barriers: Vec<ffi::PointerBarrier>,
+}
+
 pub struct UnownedWindow {
     pub xconn: Arc<XConnection>, // never changes
     xwindow: ffi::Window,        // never changes
     root: ffi::Window,           // never changes
     screen_id: i32,              // never changes
-    cursor: Mutex<CursorIcon>,
-    cursor_grabbed: Mutex<bool>,
+    cursor: Mutex<WindowCursor>,
+    cursor_grab: Mutex<CursorGrab>,
     cursor_visible: Mutex<bool>,
     ime_sender: Mutex<ImeSender>,
     pub shared_state: Mutex<SharedState>,
     redraw_sender: Sender<WindowId>,
+    drag_source: Arc<DragSource>,
+    popup_parent: Option<ffi::Window>,  // never changes
+    transient_for: Option<ffi::Window>, // never changes
+    modal: bool,                        // never changes
+    child_of: Option<ffi::Window>,      // never changes
+    // The event masks its creator selected on a foreign window
+    foreign_event_masks: Option<ForeignEventMasks>, // never changes
+}
+
+/// The events the creator of a foreign window selected on it, which are selected again when the
+/// window is dropped.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ForeignEventMasks {
+    pub core: c_long,
+    pub xinput: i64,
 }
 
 impl UnownedWindow {
@@ -115,8 +282,36 @@ impl UnownedWindow {
         let xconn = &event_loop.xconn;
         let root = event_loop.root;
 
+        let popup_parent = match window_attrs.popup {
+            Some(ref popup) => Some(popup_parent(event_loop, popup)?),
+            None => None,
+        };
+        let parent_window = match pl_attribs.parent_window {
+            Some(parent) => Some(find_window(event_loop, parent).ok_or_else(|| {
+                os_error!(OsError::XMisc(
+                    "the parent of a window isn't a window of this event loop"
+                ))
+            })?),
+            None => None,
+        };
+        let child_of = match pl_attribs.child_of {
+            Some(parent) => Some(find_window(event_loop, parent).ok_or_else(|| {
+                os_error!(OsError::XMisc(
+                    "the parent of a child window isn't a window of this event loop"
+                ))
+            })?),
+            None => None,
+        };
+        let transient_for = popup_parent
+            .as_ref()
+            .or(parent_window.as_ref())
+            .map(|parent| parent.xwindow);
+
         let mut monitors = xconn.available_monitors();
-        let guessed_monitor = if monitors.is_empty() {
+        let guessed_monitor = if let Some(parent) = popup_parent.as_ref().or(child_of.as_ref()) {
+            // Popups are shown next to their parent, and child windows inside of it.
+            parent.current_monitor()
+        } else if monitors.is_empty() {
             X11MonitorHandle::dummy()
         } else {
             xconn
@@ -146,10 +341,6 @@ impl UnownedWindow {
             .min_inner_size
             .map(|size| size.to_physical::<u32>(scale_factor).into());
 
-        let position = window_attrs
-            .position
-            .map(|position| position.to_physical::<i32>(scale_factor).into());
-
         let dimensions = {
             // x11 only applies constraints when the window is actively resized
             // by the user, so we have to manually apply the initial constraints
@@ -174,6 +365,22 @@ impl UnownedWindow {
             dimensions
         };
 
+        let position: Option<PhysicalPosition<i32>> = match (&window_attrs.popup, &popup_parent) {
+            (Some(popup), Some(parent)) => Some(place_popup(
+                xconn,
+                popup,
+                parent,
+                scale_factor,
+                dimensions.into(),
+            )),
+            _ => window_attrs
+                .position
+                .map(|position| position.to_physical::<i32>(scale_factor)),
+        };
+
+        // Popups aren't managed by the window manager, which would decorate and place them.
+        let override_redirect = pl_attribs.override_redirect || popup_parent.is_some();
+
         let screen_id = match pl_attribs.screen_id {
             Some(id) => id,
             None => unsafe { (xconn.xlib.XDefaultScreen)(xconn.display) },
@@ -190,31 +397,29 @@ impl UnownedWindow {
             } else {
                 0
             };
-            swa.event_mask = ffi::ExposureMask
-                | ffi::StructureNotifyMask
-                | ffi::VisibilityChangeMask
-                | ffi::KeyPressMask
-                | ffi::KeyReleaseMask
-                | ffi::KeymapStateMask
-                | ffi::ButtonPressMask
-                | ffi::ButtonReleaseMask
-                | ffi::PointerMotionMask;
+            swa.event_mask = WINDOW_EVENT_MASK;
             swa.border_pixel = 0;
-            swa.override_redirect = pl_attribs.override_redirect as c_int;
+            swa.override_redirect = override_redirect as c_int;
             swa
         };
 
         let mut window_attributes = ffi::CWBorderPixel | ffi::CWColormap | ffi::CWEventMask;
 
-        if pl_attribs.override_redirect {
+        if override_redirect {
             window_attributes |= ffi::CWOverrideRedirect;
         }
 
+        // Embedded windows are children of a window of another application.
+        let child_of = child_of
+            .as_ref()
+            .map(|parent| parent.xwindow)
+            .or(pl_attribs.embed_parent);
+
         // finally creating the window
         let xwindow = unsafe {
             (xconn.xlib.XCreateWindow)(
                 xconn.display,
-                root,
+                child_of.unwrap_or(root),
                 position.map_or(0, |p: PhysicalPosition<i32>| p.x as c_int),
                 position.map_or(0, |p: PhysicalPosition<i32>| p.y as c_int),
                 dimensions.0 as c_uint,
@@ -247,14 +452,32 @@ impl UnownedWindow {
             xwindow,
             root,
             screen_id,
-            cursor: Default::default(),
-            cursor_grabbed: Mutex::new(false),
+            cursor: Mutex::new(WindowCursor::Icon(CursorIcon::default())),
+            cursor_grab: Default::default(),
             cursor_visible: Mutex::new(true),
             ime_sender: Mutex::new(event_loop.ime_sender.clone()),
             shared_state: SharedState::new(guessed_monitor, window_attrs.visible),
             redraw_sender: event_loop.redraw_sender.clone(),
+            drag_source: Arc::clone(&event_loop.drag_source),
+            popup_parent: popup_parent.as_ref().map(|parent| parent.xwindow),
+            transient_for,
+            modal: pl_attribs.modal,
+            child_of,
+            foreign_event_masks: None,
         };
 
+        if let (Some(parent), Some(position)) = (&popup_parent, position) {
+            let (parent_x, parent_y) = parent.inner_position_physical();
+            window.shared_state.get_mut().popup_offset =
+                Some((position.x - parent_x, position.y - parent_y));
+        }
+
+        if let Some(parent) = transient_for {
+            unsafe {
+                (xconn.xlib.XSetTransientForHint)(xconn.display, window.xwindow, parent);
+            }
+        }
+
         // Title must be set before mapping. Some tiling window managers (i.e. i3) use the window
         // title to determine placement/etc., so doing this after mapping would cause the WM to
         // act on the wrong title state.
@@ -317,7 +540,31 @@ impl UnownedWindow {
 
             window.set_pid().map(|flusher| flusher.queue());
 
-            window.set_window_types(pl_attribs.x11_window_types).queue();
+            let window_types = if pl_attribs.x11_window_types != [util::WindowType::Normal] {
+                pl_attribs.x11_window_types
+            } else if popup_parent.is_some() {
+                vec![util::WindowType::PopupMenu]
+            } else if parent_window.is_some() {
+                vec![util::WindowType::Dialog]
+            } else {
+                pl_attribs.x11_window_types
+            };
+            window.set_window_types(window_types).queue();
+
+            // The initial state is read by the window manager when the window is mapped.
+            if pl_attribs.modal {
+                let state_atom = unsafe { xconn.get_atom_unchecked(b"_NET_WM_STATE\0") };
+                let modal_atom = unsafe { xconn.get_atom_unchecked(b"_NET_WM_STATE_MODAL\0") };
+                xconn
+                    .change_property(
+                        window.xwindow,
+                        state_atom,
+                        ffi::XA_ATOM,
+                        util::PropMode::Replace,
+                        &[modal_atom],
+                    )
+                    .queue();
+            }
 
             if let Some(variant) = pl_attribs.gtk_theme_variant {
                 window.set_gtk_theme_variant(variant).queue();
@@ -338,15 +585,15 @@ impl UnownedWindow {
                     } else {
                         max_inner_size = Some(dimensions.into());
                         min_inner_size = Some(dimensions.into());
-
-                        let mut shared_state = window.shared_state.get_mut();
-                        shared_state.min_inner_size = window_attrs.min_inner_size;
-                        shared_state.max_inner_size = window_attrs.max_inner_size;
-                        shared_state.resize_increments = pl_attribs.resize_increments;
-                        shared_state.base_size = pl_attribs.base_size;
                     }
                 }
 
+                let shared_state = window.shared_state.get_mut();
+                shared_state.min_inner_size = window_attrs.min_inner_size;
+                shared_state.max_inner_size = window_attrs.max_inner_size;
+                shared_state.resize_increments = pl_attribs.resize_increments;
+                shared_state.base_size = pl_attribs.base_size;
+
                 let mut normal_hints = util::NormalHints::new(xconn);
                 normal_hints.set_position(position.map(|PhysicalPosition { x, y }| (x, y)));
                 normal_hints.set_size(Some(dimensions));
@@ -381,8 +628,14 @@ impl UnownedWindow {
                 );
             } //.queue();
 
-            // Set visibility (map window)
-            if window_attrs.visible {
+            // The embedder maps the window itself, following `_XEMBED_INFO`.
+            if let Some(embed_parent) = pl_attribs.embed_parent {
+                xconn
+                    .set_xembed_info(window.xwindow, window_attrs.visible)
+                    .queue();
+                window.shared_state.get_mut().xembed = Some(EmbedState::new(embed_parent));
+            } else if window_attrs.visible {
+                // Set visibility (map window)
                 unsafe {
                     (xconn.xlib.XMapRaised)(xconn.display, window.xwindow);
                 } //.queue();
@@ -404,37 +657,15 @@ impl UnownedWindow {
             }
 
             // Select XInput2 events
-            let mask = {
-                let mask = ffi::XI_MotionMask
-                    | ffi::XI_ButtonPressMask
-                    | ffi::XI_ButtonReleaseMask
-                    //| ffi::XI_KeyPressMask
-                    //| ffi::XI_KeyReleaseMask
-                    | ffi::XI_EnterMask
-                    | ffi::XI_LeaveMask
-                    | ffi::XI_FocusInMask
-                    | ffi::XI_FocusOutMask
-                    | ffi::XI_TouchBeginMask
-                    | ffi::XI_TouchUpdateMask
-                    | ffi::XI_TouchEndMask;
-                mask
-            };
             xconn
-                .select_xinput_events(window.xwindow, ffi::XIAllMasterDevices, mask)
+                .select_xinput_events(
+                    window.xwindow,
+                    ffi::XIAllMasterDevices,
+                    xinput_event_mask(event_loop),
+                )
                 .queue();
 
-            {
-                let result = event_loop.ime.borrow_mut().create_context(window.xwindow);
-                if let Err(err) = result {
-                    let e = match err {
-                        ImeContextCreationError::XError(err) => OsError::XError(err),
-                        ImeContextCreationError::Null => {
-                            OsError::XMisc("IME Context creation failed")
-                        }
-                    };
-                    return Err(os_error!(e));
-                }
-            }
+            create_ime_context(event_loop, window.xwindow)?;
 
             // These properties must be set after mapping
             if window_attrs.maximized {
@@ -465,6 +696,109 @@ impl UnownedWindow {
             .map_err(|x_err| os_error!(OsError::XError(x_err)))
     }
 
+    /// Wraps `xwindow`, a window created by another client, without taking over its lifetime or
+    /// its decorations.
+    ///
+    /// # Safety
+    ///
+    /// `xwindow` must be a window of the server `event_loop` is connected to, which outlives the
+    /// returned window.
+    pub unsafe fn from_raw<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        xwindow: ffi::Window,
+    ) -> Result<UnownedWindow, RootOsError> {
+        let xconn = &event_loop.xconn;
+
+        let mut attributes: ffi::XWindowAttributes = mem::zeroed();
+        (xconn.xlib.XGetWindowAttributes)(xconn.display, xwindow, &mut attributes);
+        xconn
+            .check_errors()
+            .map_err(|err| os_error!(OsError::XError(err)))?;
+
+        // Toplevel windows are reparented into the frame of the window manager once they're
+        // mapped, the other ones are children of their parent.
+        let root = attributes.root;
+        let parent = xconn
+            .get_parent_window(xwindow)
+            .map_err(|err| os_error!(OsError::XError(err)))?;
+        let child_of = if parent == root || xconn.is_top_level(xwindow, root).unwrap_or(false) {
+            None
+        } else {
+            Some(parent)
+        };
+
+        let monitor = {
+            let position = xconn
+                .translate_coords(xwindow, root)
+                .map_err(|err| os_error!(OsError::XError(err)))?;
+            let size = (attributes.width as u32, attributes.height as u32);
+            xconn.get_monitor_for_window(Some(util::AaRect::new(
+                (position.x_rel_root, position.y_rel_root),
+                size,
+            )))
+        };
+        let is_visible = attributes.map_state == ffi::IsViewable;
+
+        // The XInput2 events are only shared with the creator if it uses the same connection.
+        let foreign_xinput_mask = xconn
+            .get_selected_xinput_events(xwindow, ffi::XIAllMasterDevices)
+            .map_err(|err| os_error!(OsError::XError(err)))?;
+
+        let mut window = UnownedWindow {
+            xconn: Arc::clone(xconn),
+            xwindow,
+            root,
+            screen_id: (xconn.xlib.XScreenNumberOfScreen)(attributes.screen),
+            cursor: Mutex::new(WindowCursor::Icon(CursorIcon::default())),
+            cursor_grab: Default::default(),
+            cursor_visible: Mutex::new(true),
+            ime_sender: Mutex::new(event_loop.ime_sender.clone()),
+            shared_state: SharedState::new(monitor, is_visible),
+            redraw_sender: event_loop.redraw_sender.clone(),
+            drag_source: Arc::clone(&event_loop.drag_source),
+            popup_parent: None,
+            transient_for: None,
+            modal: false,
+            child_of,
+            foreign_event_masks: Some(ForeignEventMasks {
+                core: attributes.your_event_mask,
+                xinput: foreign_xinput_mask,
+            }),
+        };
+
+        // No `VisibilityNotify` is coming for a window which is already visible.
+        if is_visible {
+            window.shared_state.get_mut().visibility = Visibility::Yes;
+        }
+
+        // Each client has its own event mask, which is shared with the creator of the window if
+        // it uses the same connection.
+        (xconn.xlib.XSelectInput)(
+            xconn.display,
+            xwindow,
+            attributes.your_event_mask | WINDOW_EVENT_MASK,
+        );
+        xconn
+            .select_xinput_events(
+                xwindow,
+                ffi::XIAllMasterDevices,
+                foreign_xinput_mask | xinput_event_mask(event_loop),
+            )
+            .queue();
+
+        create_ime_context(event_loop, xwindow)?;
+
+        xconn
+            .sync_with_server()
+            .map(|_| window)
+            .map_err(|x_err| os_error!(OsError::XError(x_err)))
+    }
+
+    /// The event masks to give back to the creator of a foreign window.
+    pub(crate) fn foreign_event_masks(&self) -> Option<ForeignEventMasks> {
+        self.foreign_event_masks
+    }
+
     fn set_pid(&self) -> Option<util::Flusher<'_>> {
         let pid_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_PID\0") };
         let client_machine_atom = unsafe { self.xconn.get_atom_unchecked(b"WM_CLIENT_MACHINE\0") };
@@ -647,7 +981,7 @@ impl UnownedWindow {
                         inner: PlatformMonitorHandle::X(monitor),
                     })) => (None, monitor),
                     Fullscreen::Borderless(None) => (None, self.current_monitor()),
-                    #[cfg(feature = "wayland")]
+                    #[cfg(any(feature = "wayland", feature = "mock"))]
                     _ => unreachable!(),
                 };
 
@@ -722,6 +1056,8 @@ impl UnownedWindow {
         let mut shared_state = self.shared_state.lock();
 
         match shared_state.visibility {
+            // Embedded windows are unmapped by their embedder.
+            Visibility::No if shared_state.xembed.is_some() => (),
             Visibility::No => unsafe {
                 (self.xconn.xlib.XUnmapWindow)(self.xconn.display, self.xwindow);
             },
@@ -934,6 +1270,20 @@ impl UnownedWindow {
             _ => (),
         }
 
+        // Embedded windows are mapped and unmapped by their embedder.
+        if shared_state.xembed.is_some() {
+            self.xconn
+                .set_xembed_info(self.xwindow, visible)
+                .flush()
+                .expect("Failed to set `_XEMBED_INFO`");
+            shared_state.visibility = if visible {
+                Visibility::YesWait
+            } else {
+                Visibility::No
+            };
+            return;
+        }
+
         if visible {
             unsafe {
                 (self.xconn.xlib.XMapRaised)(self.xconn.display, self.xwindow);
@@ -950,13 +1300,26 @@ impl UnownedWindow {
                 .flush_requests()
                 .expect("Failed to call XUnmapWindow");
             shared_state.visibility = Visibility::No;
+            // The server releases the grabs of windows which aren't viewable.
+            shared_state.popup_grab = false;
+        }
+    }
+
+    /// Returns the frame extents of the window, which child windows don't have.
+    pub(crate) fn frame_extents_heuristic(&self) -> util::FrameExtentsHeuristic {
+        if self.child_of.is_some() {
+            util::FrameExtentsHeuristic {
+                frame_extents: util::FrameExtents::from_border(0),
+                heuristic_path: util::FrameExtentsHeuristicPath::Supported,
+            }
+        } else {
+            self.xconn
+                .get_frame_extents_heuristic(self.xwindow, self.root)
         }
     }
 
     fn update_cached_frame_extents(&self) {
-        let extents = self
-            .xconn
-            .get_frame_extents_heuristic(self.xwindow, self.root);
+        let extents = self.frame_extents_heuristic();
         (*self.shared_state.lock()).frame_extents = Some(extents);
     }
 
@@ -979,7 +1342,7 @@ impl UnownedWindow {
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         let extents = (*self.shared_state.lock()).frame_extents.clone();
         if let Some(extents) = extents {
-            let (x, y) = self.inner_position_physical();
+            let (x, y) = self.inner_position_rel_parent();
             Ok(extents.inner_pos_to_outer(x, y).into())
         } else {
             self.update_cached_frame_extents();
@@ -996,15 +1359,56 @@ impl UnownedWindow {
             .unwrap()
     }
 
+    /// The position of the client area relative to the parent of child windows, and to the root
+    /// window otherwise.
+    fn inner_position_rel_parent(&self) -> (i32, i32) {
+        let parent = self.child_of.unwrap_or(self.root);
+        self.xconn
+            .translate_coords(self.xwindow, parent)
+            .map(|coords| (coords.x_rel_root, coords.y_rel_root))
+            .unwrap()
+    }
+
     #[inline]
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        Ok(self.inner_position_physical().into())
+        Ok(self.inner_position_rel_parent().into())
+    }
+
+    /// The parent of a child window.
+    pub(crate) fn child_of(&self) -> Option<ffi::Window> {
+        self.child_of
+    }
+
+    /// Updates the state of an embedded window from an `_XEMBED` message of its embedder,
+    /// returning whether the window gained or lost the keyboard focus.
+    pub(crate) fn handle_xembed_message(
+        &self,
+        client_msg: &ffi::XClientMessageEvent,
+    ) -> Option<bool> {
+        let mut shared_state = self.shared_state.lock();
+        shared_state.xembed.as_mut()?.handle_message(client_msg)
+    }
+
+    /// Asks the embedder of an embedded window to focus it, if it isn't focused yet.
+    pub(crate) fn request_embedder_focus(&self) {
+        let embedder = match self.shared_state.lock().xembed {
+            Some(ref xembed) if !xembed.has_focus() => Some(xembed.embedder()),
+            _ => None,
+        };
+        if let Some(embedder) = embedder {
+            if let Err(e) = self.xconn.request_xembed_focus(embedder).flush() {
+                log::error!(
+                    "`flush` returned an error when requesting the focus. Error was: {}",
+                    e
+                );
+            }
+        }
     }
 
     pub(crate) fn set_position_inner(&self, mut x: i32, mut y: i32) -> util::Flusher<'_> {
         // There are a few WMs that set client area position rather than window position, so
         // we'll translate for consistency.
-        if util::wm_name_is_one_of(&["Enlightenment", "FVWM"]) {
+        if self.child_of.is_none() && util::wm_name_is_one_of(&["Enlightenment", "FVWM"]) {
             let extents = (*self.shared_state.lock()).frame_extents.clone();
             if let Some(extents) = extents {
                 x += extents.frame_extents.left as i32;
@@ -1030,6 +1434,71 @@ impl UnownedWindow {
     pub fn set_outer_position(&self, position: Position) {
         let (x, y) = position.to_physical::<i32>(self.scale_factor()).into();
         self.set_position_physical(x, y);
+
+        // Popups keep their position relative to their parent when it moves.
+        if let Some(parent) = self.popup_parent {
+            if let Ok(coords) = self.xconn.translate_coords(parent, self.root) {
+                self.shared_state.lock().popup_offset =
+                    Some((x - coords.x_rel_root, y - coords.y_rel_root));
+            }
+        }
+    }
+
+    /// The window this popup belongs to, if it's a popup.
+    pub(crate) fn popup_parent(&self) -> Option<ffi::Window> {
+        self.popup_parent
+    }
+
+    /// Moves a popup along with its parent, whose client area is now at `parent_position`.
+    ///
+    /// Returns the new position of the popup.
+    pub(crate) fn follow_popup_parent(&self, parent_position: (i32, i32)) -> Option<(i32, i32)> {
+        let (offset_x, offset_y) = self.shared_state.lock().popup_offset?;
+        let (x, y) = (parent_position.0 + offset_x, parent_position.1 + offset_y);
+        unsafe {
+            (self.xconn.xlib.XMoveWindow)(self.xconn.display, self.xwindow, x, y);
+        }
+        self.xconn
+            .flush_requests()
+            .expect("Failed to call `XMoveWindow`");
+        Some((x, y))
+    }
+
+    /// Puts a popup back above its parent.
+    pub(crate) fn raise_popup(&self) {
+        unsafe {
+            (self.xconn.xlib.XRaiseWindow)(self.xconn.display, self.xwindow);
+        }
+        self.xconn
+            .flush_requests()
+            .expect("Failed to call `XRaiseWindow`");
+    }
+
+    /// Whether the input of `parent` is blocked by this window, which is the case of visible
+    /// modal windows.
+    pub(crate) fn blocks_input_of(&self, parent: ffi::Window) -> bool {
+        self.modal
+            && self.transient_for == Some(parent)
+            && self.shared_state.lock().visibility != Visibility::No
+    }
+
+    /// Releases the grab of a popup after a click outside of our windows.
+    ///
+    /// Returns whether the popup was grabbing the input.
+    pub(crate) fn release_popup_grab(&self) -> bool {
+        let mut shared_state = self.shared_state.lock();
+        if !shared_state.popup_grab {
+            return false;
+        }
+        unsafe {
+            (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
+            (self.xconn.xlib.XUngrabKeyboard)(self.xconn.display, ffi::CurrentTime);
+        }
+        self.xconn
+            .flush_requests()
+            .expect("Failed to release the grab of a popup");
+        shared_state.popup_grab = false;
+        true
     }
 
     pub(crate) fn inner_size_physical(&self) -> (u32, u32) {
@@ -1115,6 +1584,23 @@ impl UnownedWindow {
         self.set_max_inner_size_physical(physical_dimensions);
     }
 
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        self.shared_state.lock().resize_increments = increments;
+        let physical_increments =
+            increments.map(|increments| increments.to_physical::<u32>(self.scale_factor()).into());
+        self.update_normal_hints(|normal_hints| {
+            normal_hints.set_resize_increments(physical_increments)
+        })
+        .expect("Failed to call `XSetWMNormalHints`");
+    }
+
+    #[inline]
+    pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
+        let increments = self.shared_state.lock().resize_increments;
+        increments.map(|increments| increments.to_physical(self.scale_factor()))
+    }
+
     pub(crate) fn adjust_for_dpi(
         &self,
         old_scale_factor: f64,
@@ -1207,16 +1693,45 @@ impl UnownedWindow {
 
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-        let old_cursor = replace(&mut *self.cursor.lock(), cursor);
+        self.set_cursor(WindowCursor::Icon(cursor));
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) {
+        self.set_cursor(WindowCursor::Custom(cursor.clone()));
+    }
+
+    fn set_cursor(&self, cursor: WindowCursor) {
+        let old_cursor = replace(&mut *self.cursor.lock(), cursor.clone());
         if cursor != old_cursor && *self.cursor_visible.lock() {
-            self.xconn.set_cursor_icon(self.xwindow, Some(cursor));
+            self.show_cursor(&cursor);
+        }
+    }
+
+    fn show_cursor(&self, cursor: &WindowCursor) {
+        match cursor {
+            WindowCursor::Icon(icon) => self.xconn.set_cursor_icon(self.xwindow, Some(*icon)),
+            WindowCursor::Custom(cursor) => {
+                self.xconn
+                    .set_custom_cursor(self.xwindow, cursor, self.scale_factor())
+            }
+        }
+    }
+
+    /// Shows the variant of the custom cursor drawn for the current scale factor.
+    pub(crate) fn refresh_custom_cursor(&self) {
+        let cursor = self.cursor.lock().clone();
+        if let WindowCursor::Custom(_) = cursor {
+            if *self.cursor_visible.lock() {
+                self.show_cursor(&cursor);
+            }
         }
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        let mut grabbed_lock = self.cursor_grabbed.lock();
-        if grab == *grabbed_lock {
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        let mut grab_lock = self.cursor_grab.lock();
+        if mode == grab_lock.mode {
             return Ok(());
         }
         unsafe {
@@ -1224,7 +1739,9 @@ impl UnownedWindow {
             // Therefore, this is common to both codepaths.
             (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
         }
-        let result = if grab {
+        let result = if mode != CursorGrabMode::None {
+            // X11 can't lock the cursor, so a locked cursor is confined like any other and warped
+            // back whenever it moves.
             let result = unsafe {
                 (self.xconn.xlib.XGrabPointer)(
                     self.xconn.display,
@@ -1269,26 +1786,215 @@ impl UnownedWindow {
                 .flush_requests()
                 .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
         };
+        if result.is_ok() && mode == CursorGrabMode::Locked {
+            let pointer = self
+                .xconn
+                .query_pointer(self.xwindow, util::VIRTUAL_CORE_POINTER)
+                .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+            grab_lock.locked_position = (pointer.win_x, pointer.win_y);
+        }
         if result.is_ok() {
-            *grabbed_lock = grab;
+            grab_lock.mode = mode;
+            self.update_pointer_barriers(&mut grab_lock)?;
         }
         result
     }
 
+    #[inline]
+    pub fn set_cursor_grab_region(
+        &self,
+        region: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        if region.is_some() && !self.xconn.supports_pointer_barriers() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        let mut grab_lock = self.cursor_grab.lock();
+        grab_lock.region = region;
+        self.update_pointer_barriers(&mut grab_lock)
+    }
+
+    pub fn set_popup_grab(&self, grab: bool) -> Result<(), ExternalError> {
+        if self.popup_parent.is_none() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+        if !grab {
+            self.release_popup_grab();
+            return Ok(());
+        }
+
+        let mut shared_state = self.shared_state.lock();
+        if shared_state.popup_grab {
+            return Ok(());
+        }
+        let grab_error = |result| {
+            let err = match result {
+                ffi::AlreadyGrabbed => {
+                    "Popup could not grab the input: already grabbed by another client"
+                }
+                ffi::GrabInvalidTime => "Popup could not grab the input: invalid time",
+                ffi::GrabNotViewable => "Popup could not grab the input: popup not viewable",
+                ffi::GrabFrozen => "Popup could not grab the input: frozen by another client",
+                _ => unreachable!(),
+            };
+            ExternalError::Os(os_error!(OsError::XMisc(err)))
+        };
+        unsafe {
+            // Pointer events within our windows are reported as usual, and the other ones are
+            // reported to the popup, so that we know when it's clicked outside of.
+            let result = (self.xconn.xlib.XGrabPointer)(
+                self.xconn.display,
+                self.xwindow,
+                ffi::True,
+                (ffi::ButtonPressMask
+                    | ffi::ButtonReleaseMask
+                    | ffi::EnterWindowMask
+                    | ffi::LeaveWindowMask
+                    | ffi::PointerMotionMask) as c_uint,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                0,
+                0,
+                ffi::CurrentTime,
+            );
+            if result != ffi::GrabSuccess {
+                return Err(grab_error(result));
+            }
+            // Every key event is reported to the popup.
+            let result = (self.xconn.xlib.XGrabKeyboard)(
+                self.xconn.display,
+                self.xwindow,
+                ffi::False,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                ffi::CurrentTime,
+            );
+            if result != ffi::GrabSuccess {
+                (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
+                return Err(grab_error(result));
+            }
+        }
+        self.xconn
+            .flush_requests()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+        shared_state.popup_grab = true;
+        Ok(())
+    }
+
+    /// Moves the pointer barriers of the grab region after the window moved or its scale factor
+    /// changed.
+    pub(crate) fn refresh_cursor_grab_region(&self) {
+        let mut grab_lock = self.cursor_grab.lock();
+        if !grab_lock.barriers.is_empty() {
+            if let Err(err) = self.update_pointer_barriers(&mut grab_lock) {
+                warn!("Failed to move the cursor grab region: {}", err);
+            }
+        }
+    }
+
+    /// Replaces the pointer barriers, which only exist while the cursor is confined to a region.
+    fn update_pointer_barriers(&self, grab: &mut CursorGrab) -> Result<(), ExternalError> {
+        let xfixes = match self.xconn.xfixes.as_ref() {
+            Some(xfixes) => xfixes,
+            None => return Ok(()),
+        };
+
+        for barrier in grab.barriers.drain(..) {
+            unsafe { (xfixes.XFixesDestroyPointerBarrier)(self.xconn.display, barrier) };
+        }
+
+        match grab.region {
+            Some((position, size)) if grab.mode == CursorGrabMode::Confined => {
+                let scale_factor = self.scale_factor();
+                let position: PhysicalPosition<i32> = position.to_physical(scale_factor);
+                let size: PhysicalSize<i32> = size.to_physical(scale_factor);
+
+                let (window_x, window_y) = self.inner_position_physical();
+                let (left, top) = (window_x + position.x, window_y + position.y);
+                let (right, bottom) = (left + size.width.max(1), top + size.height.max(1));
+
+                // The barriers would keep the cursor out of the region if it isn't inside yet.
+                let pointer = self
+                    .xconn
+                    .query_pointer(self.root, util::VIRTUAL_CORE_POINTER)
+                    .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+                let (x, y) = (pointer.root_x as i32, pointer.root_y as i32);
+                let clamped = (x.max(left).min(right - 1), y.max(top).min(bottom - 1));
+                if (x, y) != clamped {
+                    unsafe {
+                        (self.xconn.xlib.XWarpPointer)(
+                            self.xconn.display,
+                            0,
+                            self.root,
+                            0,
+                            0,
+                            0,
+                            0,
+                            clamped.0,
+                            clamped.1,
+                        );
+                    }
+                }
+
+                for &(x1, y1, x2, y2) in &[
+                    (left, top, right, top),
+                    (left, bottom, right, bottom),
+                    (left, top, left, bottom),
+                    (right, top, right, bottom),
+                ] {
+                    let barrier = unsafe {
+                        (xfixes.XFixesCreatePointerBarrier)(
+                            self.xconn.display,
+                            self.root,
+                            x1,
+                            y1,
+                            x2,
+                            y2,
+                            0,
+                            0,
+                            ptr::null_mut(),
+                        )
+                    };
+                    grab.barriers.push(barrier);
+                }
+            }
+            _ => (),
+        }
+
+        self.xconn
+            .flush_requests()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    /// Warps a locked cursor back to where it was locked, if it moved to `position`.
+    ///
+    /// Returns whether the cursor is locked, in which case its motion isn't reported.
+    pub(crate) fn warp_locked_cursor(&self, position: (f64, f64)) -> bool {
+        let grab_lock = self.cursor_grab.lock();
+        if grab_lock.mode != CursorGrabMode::Locked {
+            return false;
+        }
+        let (x, y) = grab_lock.locked_position;
+        if position != (x, y) {
+            let _ = self.set_cursor_position_physical(x as i32, y as i32);
+        }
+        true
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         let mut visible_lock = self.cursor_visible.lock();
         if visible == *visible_lock {
             return;
         }
-        let cursor = if visible {
-            Some(*self.cursor.lock())
-        } else {
-            None
-        };
         *visible_lock = visible;
         drop(visible_lock);
-        self.xconn.set_cursor_icon(self.xwindow, cursor);
+        if visible {
+            let cursor = self.cursor.lock().clone();
+            self.show_cursor(&cursor);
+        } else {
+            self.xconn.set_cursor_icon(self.xwindow, None);
+        }
     }
 
     #[inline]
@@ -1321,16 +2027,17 @@ impl UnownedWindow {
 
         let message = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_MOVERESIZE\0") };
 
-        // we can't use `set_cursor_grab(false)` here because it doesn't run `XUngrabPointer`
+        // we can't use `set_cursor_grab(CursorGrabMode::None)` here because it doesn't run `XUngrabPointer`
         // if the cursor isn't currently grabbed
-        let mut grabbed_lock = self.cursor_grabbed.lock();
+        let mut grab_lock = self.cursor_grab.lock();
         unsafe {
             (self.xconn.xlib.XUngrabPointer)(self.xconn.display, ffi::CurrentTime);
         }
         self.xconn
             .flush_requests()
             .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
-        *grabbed_lock = false;
+        grab_lock.mode = CursorGrabMode::None;
+        self.update_pointer_barriers(&mut grab_lock)?;
 
         // we keep the lock until we are done
         self.xconn
@@ -1351,11 +2058,37 @@ impl UnownedWindow {
             .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
     }
 
+    pub fn start_drag(
+        &self,
+        data: Vec<(String, Vec<u8>)>,
+        allowed_actions: DndActions,
+    ) -> Result<(), ExternalError> {
+        self.drag_source
+            .start(self.xwindow, data, allowed_actions)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+    }
+
     pub(crate) fn set_ime_position_physical(&self, x: i32, y: i32) {
         let _ = self
             .ime_sender
             .lock()
-            .send((self.xwindow, x as i16, y as i16));
+            .send(ImeRequest::Position(self.xwindow, x as i16, y as i16));
+    }
+
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        let _ = self
+            .ime_sender
+            .lock()
+            .send(ImeRequest::Allowed(self.xwindow, allowed));
+    }
+
+    /// Destroys the input context, which is needed for foreign windows as we don't destroy them.
+    pub(crate) fn destroy_ime_context(&self) {
+        let _ = self
+            .ime_sender
+            .lock()
+            .send(ImeRequest::DestroyContext(self.xwindow));
     }
 
     #[inline]
@@ -1366,6 +2099,12 @@ impl UnownedWindow {
 
     #[inline]
     pub fn focus_window(&self) {
+        // Embedded windows get the focus from their embedder.
+        if self.shared_state.lock().xembed.is_some() {
+            self.request_embedder_focus();
+            return;
+        }
+
         let state_atom = unsafe { self.xconn.get_atom_unchecked(b"WM_STATE\0") };
         let state_type_atom = unsafe { self.xconn.get_atom_unchecked(b"CARD32\0") };
         let is_minimized = if let Ok(state) =
@@ -1,4 +1,10 @@
-use std::{collections::HashMap, error::Error, fmt, os::raw::c_int, ptr};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    os::raw::{c_int, c_ulong},
+    ptr,
+};
 
 use libc;
 use parking_lot::Mutex;
@@ -18,20 +24,68 @@ pub struct XConnection {
     pub xinput2: ffi::XInput2,
     pub xlib_xcb: ffi::Xlib_xcb,
     pub xrender: ffi::Xrender,
+    /// Used to be notified of clipboard changes, when available
+    pub xfixes: Option<ffi::XFixes>,
     pub display: *mut ffi::Display,
     pub x11_fd: c_int,
     pub latest_error: Mutex<Option<XError>>,
     pub cursor_cache: Mutex<HashMap<Option<CursorIcon>, ffi::Cursor>>,
+    /// Whether the display was opened by us, and is closed along with the connection
+    owns_display: bool,
+    /// On the display of the application, the requests we made, whose errors are ours
+    own_requests: Mutex<OwnRequests>,
+    /// Whether the server supports the pointer barriers of XFixes 5.0
+    pointer_barriers: bool,
 }
 
 unsafe impl Send for XConnection {}
 unsafe impl Sync for XConnection {}
 
+/// The serials of the requests we make on the display of the application.
+#[derive(Debug, Default)]
+struct OwnRequests {
+    /// How many of our calls are making requests.
+    depth: usize,
+    /// The ranges of serials of our requests whose errors may still come. The last one has no
+    /// end while we're making requests.
+    ranges: Vec<(c_ulong, Option<c_ulong>)>,
+}
+
 pub type XErrorHandler =
     Option<unsafe extern "C" fn(*mut ffi::Display, *mut ffi::XErrorEvent) -> libc::c_int>;
 
+lazy_static! {
+    /// The error handler set before ours, which gets the errors that aren't ours.
+    static ref PREVIOUS_ERROR_HANDLER: Mutex<XErrorHandler> = Mutex::new(None);
+}
+
+/// Returns the error handler which was set before ours.
+pub fn previous_error_handler() -> XErrorHandler {
+    *PREVIOUS_ERROR_HANDLER.lock()
+}
+
 impl XConnection {
     pub fn new(error_handler: XErrorHandler) -> Result<XConnection, XNotSupported> {
+        unsafe { Self::connect(error_handler, None) }
+    }
+
+    /// Uses the connection of `display`, which is left open when the `XConnection` is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `display` must be a valid `Display`, opened after `XInitThreads` was called, which outlives
+    /// the `XConnection`.
+    pub unsafe fn from_display(
+        display: *mut ffi::Display,
+        error_handler: XErrorHandler,
+    ) -> Result<XConnection, XNotSupported> {
+        Self::connect(error_handler, Some(display))
+    }
+
+    unsafe fn connect(
+        error_handler: XErrorHandler,
+        display: Option<*mut ffi::Display>,
+    ) -> Result<XConnection, XNotSupported> {
         // opening the libraries
         let xlib = ffi::Xlib::open()?;
         let xcursor = ffi::Xcursor::open()?;
@@ -40,21 +94,49 @@ impl XConnection {
         let xinput2 = ffi::XInput2::open()?;
         let xlib_xcb = ffi::Xlib_xcb::open()?;
         let xrender = ffi::Xrender::open()?;
+        let xfixes = ffi::XFixes::open().ok();
 
-        unsafe { (xlib.XInitThreads)() };
-        unsafe { (xlib.XSetErrorHandler)(error_handler) };
+        // Threads have to be initialized before the display is opened.
+        if display.is_none() {
+            (xlib.XInitThreads)();
+        }
+        // Our handler is set again for each connection, and mustn't pass the errors on to itself.
+        let previous_handler = (xlib.XSetErrorHandler)(error_handler);
+        if previous_handler.map(|handler| handler as usize)
+            != error_handler.map(|handler| handler as usize)
+        {
+            *PREVIOUS_ERROR_HANDLER.lock() = previous_handler;
+        }
 
         // calling XOpenDisplay
-        let display = unsafe {
-            let display = (xlib.XOpenDisplay)(ptr::null());
-            if display.is_null() {
-                return Err(XNotSupported::XOpenDisplayFailed);
+        let owns_display = display.is_none();
+        let display = match display {
+            Some(display) => display,
+            None => {
+                let display = (xlib.XOpenDisplay)(ptr::null());
+                if display.is_null() {
+                    return Err(XNotSupported::XOpenDisplayFailed);
+                }
+                display
             }
-            display
         };
 
         // Get X11 socket file descriptor
-        let fd = unsafe { (xlib.XConnectionNumber)(display) };
+        let fd = (xlib.XConnectionNumber)(display);
+
+        // The server answers with the highest version it supports, up to the one we ask for.
+        let pointer_barriers = match xfixes.as_ref() {
+            Some(xfixes) => {
+                let (mut major, mut minor) = (5, 0);
+                (xfixes.XFixesQueryVersion)(
+                    display,
+                    &mut major,
+                    &mut minor as *mut c_int as *const c_int,
+                ) != 0
+                    && major >= 5
+            }
+            None => false,
+        };
 
         Ok(XConnection {
             xlib,
@@ -64,13 +146,88 @@ impl XConnection {
             xinput2,
             xlib_xcb,
             xrender,
+            xfixes,
             display,
             x11_fd: fd,
             latest_error: Mutex::new(None),
             cursor_cache: Default::default(),
+            owns_display,
+            own_requests: Default::default(),
+            pointer_barriers,
         })
     }
 
+    /// Whether the display was opened by us, rather than by the application.
+    #[inline]
+    pub fn owns_display(&self) -> bool {
+        self.owns_display
+    }
+
+    /// Starts counting the requests made on the display of the application as ours, until the
+    /// matching call to `end_own_requests`, so that only their errors are reported to us.
+    pub fn begin_own_requests(&self) {
+        if self.owns_display {
+            return;
+        }
+        let next_request = unsafe { (self.xlib.XNextRequest)(self.display) };
+        let processed = unsafe { (self.xlib.XLastKnownRequestProcessed)(self.display) };
+
+        let mut own_requests = self.own_requests.lock();
+        own_requests.depth += 1;
+        if own_requests.depth == 1 {
+            // The errors of the requests the server processed already came.
+            own_requests.ranges.retain(|&(_, end)| match end {
+                Some(end) => end > processed + 1,
+                None => true,
+            });
+            own_requests.ranges.push((next_request, None));
+        }
+    }
+
+    /// Stops counting the requests made on the display of the application as ours, see
+    /// `begin_own_requests`.
+    pub fn end_own_requests(&self) {
+        if self.owns_display {
+            return;
+        }
+        let next_request = unsafe { (self.xlib.XNextRequest)(self.display) };
+
+        let mut own_requests = self.own_requests.lock();
+        if own_requests.depth == 0 {
+            return;
+        }
+        own_requests.depth -= 1;
+        if own_requests.depth == 0 {
+            match own_requests.ranges.pop() {
+                Some((start, _)) if start != next_request => {
+                    own_requests.ranges.push((start, Some(next_request)))
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Whether the request with this serial is one of ours.
+    pub fn is_own_request(&self, serial: c_ulong) -> bool {
+        if self.owns_display {
+            return true;
+        }
+        self.own_requests
+            .lock()
+            .ranges
+            .iter()
+            .any(|&(start, end)| match end {
+                Some(end) => (start..end).contains(&serial),
+                None => serial >= start,
+            })
+    }
+
+    /// Whether the server supports the pointer barriers of XFixes 5.0.
+    #[inline]
+    pub fn supports_pointer_barriers(&self) -> bool {
+        self.pointer_barriers
+    }
+
     /// Checks whether an error has been triggered by the previous function calls.
     #[inline]
     pub fn check_errors(&self) -> Result<(), XError> {
@@ -98,7 +255,9 @@ impl fmt::Debug for XConnection {
 impl Drop for XConnection {
     #[inline]
     fn drop(&mut self) {
-        unsafe { (self.xlib.XCloseDisplay)(self.display) };
+        if self.owns_display {
+            unsafe { (self.xlib.XCloseDisplay)(self.display) };
+        }
     }
 }
 
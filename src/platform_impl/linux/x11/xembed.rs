@@ -0,0 +1,113 @@
+//! The client side of the XEmbed protocol, with which other applications embed our windows into
+//! theirs.
+//!
+//! The embedder keeps the X input focus, and tells the embedded window when it's focused and when
+//! its toplevel window is active with `_XEMBED` client messages. The key events are forwarded to
+//! the embedded window with `XSendEvent`.
+
+use std::os::raw::*;
+
+use super::{ffi, util, XConnection};
+
+/// Version of the XEmbed protocol we speak.
+const XEMBED_VERSION: c_long = 0;
+
+/// Flag of `_XEMBED_INFO` asking the embedder to map the window.
+const XEMBED_MAPPED: c_long = 1 << 0;
+
+const XEMBED_EMBEDDED_NOTIFY: c_long = 0;
+const XEMBED_WINDOW_ACTIVATE: c_long = 1;
+const XEMBED_WINDOW_DEACTIVATE: c_long = 2;
+const XEMBED_REQUEST_FOCUS: c_long = 3;
+const XEMBED_FOCUS_IN: c_long = 4;
+const XEMBED_FOCUS_OUT: c_long = 5;
+
+/// The state of an embedded window, as told by its embedder.
+#[derive(Debug)]
+pub struct EmbedState {
+    /// The window the application asked to embed into.
+    parent: ffi::Window,
+
+    /// The window we're embedded into, once it notified us.
+    embedder: Option<ffi::Window>,
+
+    /// Whether the toplevel window of the embedder is active.
+    active: bool,
+
+    /// Whether the embedder gives us its focus.
+    focused: bool,
+}
+
+impl EmbedState {
+    pub fn new(parent: ffi::Window) -> Self {
+        EmbedState {
+            parent,
+            embedder: None,
+            active: false,
+            focused: false,
+        }
+    }
+
+    /// The window we're embedded into, which is the one we were created in until the embedder
+    /// notifies us.
+    #[inline]
+    pub fn embedder(&self) -> ffi::Window {
+        self.embedder.unwrap_or(self.parent)
+    }
+
+    /// Whether the window has the keyboard focus, which requires both.
+    #[inline]
+    pub fn has_focus(&self) -> bool {
+        self.active && self.focused
+    }
+
+    /// Updates the state from an `_XEMBED` client message, returning whether the window gained or
+    /// lost the keyboard focus.
+    pub fn handle_message(&mut self, client_msg: &ffi::XClientMessageEvent) -> Option<bool> {
+        let had_focus = self.has_focus();
+        match client_msg.data.get_long(1) {
+            XEMBED_EMBEDDED_NOTIFY => {
+                self.embedder = Some(client_msg.data.get_long(3) as ffi::Window);
+            }
+            XEMBED_WINDOW_ACTIVATE => self.active = true,
+            XEMBED_WINDOW_DEACTIVATE => self.active = false,
+            XEMBED_FOCUS_IN => self.focused = true,
+            XEMBED_FOCUS_OUT => self.focused = false,
+            _ => (),
+        }
+
+        let has_focus = self.has_focus();
+        if has_focus != had_focus {
+            Some(has_focus)
+        } else {
+            None
+        }
+    }
+}
+
+impl XConnection {
+    /// Sets the `_XEMBED_INFO` of `window`, which also tells the embedder whether to map it.
+    pub fn set_xembed_info(&self, window: ffi::Window, mapped: bool) -> util::Flusher<'_> {
+        let info_atom = unsafe { self.get_atom_unchecked(b"_XEMBED_INFO\0") };
+        let flags = if mapped { XEMBED_MAPPED } else { 0 };
+        self.change_property(
+            window,
+            info_atom,
+            info_atom,
+            util::PropMode::Replace,
+            &[XEMBED_VERSION as c_ulong, flags as c_ulong],
+        )
+    }
+
+    /// Asks `embedder` to give the focus to the window embedded into it.
+    pub fn request_xembed_focus(&self, embedder: ffi::Window) -> util::Flusher<'_> {
+        let xembed_atom = unsafe { self.get_atom_unchecked(b"_XEMBED\0") };
+        self.send_client_msg(
+            embedder,
+            embedder,
+            xembed_atom,
+            None,
+            [ffi::CurrentTime as c_long, XEMBED_REQUEST_FOCUS, 0, 0, 0],
+        )
+    }
+}
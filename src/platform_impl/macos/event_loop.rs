@@ -19,6 +19,8 @@ use cocoa::{
 use objc::rc::autoreleasepool;
 
 use crate::{
+    clipboard::ClipboardKind,
+    error::{ExternalError, NotSupportedError},
     event::Event,
     event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget},
     monitor::MonitorHandle as RootMonitorHandle,
@@ -29,7 +31,9 @@ use crate::{
         monitor::{self, MonitorHandle},
         observer::*,
         util::IdRef,
+        PlatformCustomCursor,
     },
+    window::CustomCursorSource,
 };
 
 #[derive(Default)]
@@ -83,6 +87,46 @@ impl<T: 'static> EventLoopWindowTarget<T> {
         let monitor = monitor::primary_monitor();
         Some(RootMonitorHandle { inner: monitor })
     }
+
+    pub fn clipboard_text(&self, _kind: ClipboardKind) -> Result<Option<String>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_clipboard_text(
+        &self,
+        _kind: ClipboardKind,
+        _text: String,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_mime_types(&self, _kind: ClipboardKind) -> Result<Vec<String>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_data(
+        &self,
+        _kind: ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_clipboard_data(
+        &self,
+        _kind: ClipboardKind,
+        _data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        _source: CustomCursorSource,
+    ) -> Result<PlatformCustomCursor, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
 }
 
 pub struct EventLoop<T: 'static> {
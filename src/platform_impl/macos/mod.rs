@@ -27,6 +27,7 @@ use crate::{
 };
 use objc::rc::autoreleasepool;
 
+pub(crate) use crate::cursor::NoCustomCursor as PlatformCustomCursor;
 pub(crate) use crate::icon::NoIcon as PlatformIcon;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -36,6 +37,12 @@ impl DeviceId {
     pub unsafe fn dummy() -> Self {
         DeviceId
     }
+
+    /// Devices can't be told apart on this platform.
+    #[cfg(feature = "serde")]
+    pub fn synthetic(_index: u32) -> Self {
+        DeviceId
+    }
 }
 
 // Constant device ID; to be removed when if backend is updated to report real device IDs.
@@ -10,6 +10,7 @@ use std::{
 };
 
 use crate::{
+    dnd::DndActions,
     dpi::{
         LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size, Size::Logical,
     },
@@ -26,10 +27,11 @@ use crate::{
         view::CursorState,
         view::{self, new_view},
         window_delegate::new_delegate,
-        OsError,
+        OsError, PlatformCustomCursor,
     },
     window::{
-        CursorIcon, Fullscreen, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+        CursorGrabMode, CursorIcon, Fullscreen, UserAttentionType, WindowAttributes,
+        WindowId as RootWindowId,
     },
 };
 use cocoa::{
@@ -525,6 +527,29 @@ impl UnownedWindow {
         }
     }
 
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        unsafe {
+            // The default increments of a window are 1x1.
+            let increments = increments.unwrap_or(Logical(LogicalSize {
+                width: 1.0,
+                height: 1.0,
+            }));
+            let LogicalSize { width, height } = increments.to_logical(self.scale_factor());
+            let size = NSSize::new(width.max(1.0) as CGFloat, height.max(1.0) as CGFloat);
+            self.ns_window.setResizeIncrements_(size);
+        }
+    }
+
+    pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
+        let increments: NSSize = unsafe { msg_send![*self.ns_window, resizeIncrements] };
+        if increments.width <= 1.0 && increments.height <= 1.0 {
+            None
+        } else {
+            let increments = LogicalSize::new(increments.width as f64, increments.height as f64);
+            Some(increments.to_physical(self.scale_factor()))
+        }
+    }
+
     #[inline]
     pub fn set_resizable(&self, resizable: bool) {
         let fullscreen = {
@@ -558,12 +583,36 @@ impl UnownedWindow {
     }
 
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+    pub fn set_custom_cursor(&self, _cursor: &PlatformCustomCursor) {}
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        let grab = match mode {
+            CursorGrabMode::None => false,
+            CursorGrabMode::Locked => true,
+            CursorGrabMode::Confined => {
+                return Err(ExternalError::NotSupported(NotSupportedError::new()))
+            }
+        };
+
         // TODO: Do this for real https://stackoverflow.com/a/40922095/5435443
         CGDisplay::associate_mouse_and_mouse_cursor_position(!grab)
             .map_err(|status| ExternalError::Os(os_error!(OsError::CGError(status))))
     }
 
+    #[inline]
+    pub fn set_cursor_grab_region(
+        &self,
+        _region: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_popup_grab(&self, _grab: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         if let Some(cursor_access) = self.cursor_state.upgrade() {
@@ -613,6 +662,14 @@ impl UnownedWindow {
         Ok(())
     }
 
+    pub fn start_drag(
+        &self,
+        _data: Vec<(String, Vec<u8>)>,
+        _allowed_actions: DndActions,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub(crate) fn is_zoomed(&self) -> bool {
         // because `isZoomed` doesn't work if the window's borderless,
         // we make it resizable temporalily.
@@ -961,6 +1018,9 @@ impl UnownedWindow {
         }
     }
 
+    #[inline]
+    pub fn set_ime_allowed(&self, _allowed: bool) {}
+
     #[inline]
     pub fn focus_window(&self) {
         let is_minimized: BOOL = unsafe { msg_send![*self.ns_window, isMiniaturized] };
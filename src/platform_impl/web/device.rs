@@ -5,4 +5,10 @@ impl Id {
     pub unsafe fn dummy() -> Self {
         Id(0)
     }
+
+    /// Pointer ids are never negative.
+    #[cfg(feature = "serde")]
+    pub fn synthetic(index: u32) -> Self {
+        Id(-1 - index as i32)
+    }
 }
@@ -1,11 +1,18 @@
-use super::{super::monitor, backend, device, proxy::Proxy, runner, window};
+use super::{
+    super::{monitor, PlatformCustomCursor},
+    backend, device,
+    proxy::Proxy,
+    runner, window,
+};
+use crate::clipboard::ClipboardKind;
 use crate::dpi::{PhysicalSize, Size};
+use crate::error::{ExternalError, NotSupportedError};
 use crate::event::{
     DeviceEvent, DeviceId, ElementState, Event, KeyboardInput, TouchPhase, WindowEvent,
 };
 use crate::event_loop::ControlFlow;
 use crate::monitor::MonitorHandle as RootMH;
-use crate::window::{Theme, WindowId};
+use crate::window::{CustomCursorSource, Theme, WindowId};
 use std::cell::RefCell;
 use std::clone::Clone;
 use std::collections::{vec_deque::IntoIter as VecDequeIter, VecDeque};
@@ -254,4 +261,44 @@ impl<T> WindowTarget<T> {
             inner: monitor::Handle,
         })
     }
+
+    pub fn clipboard_text(&self, _kind: ClipboardKind) -> Result<Option<String>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_clipboard_text(
+        &self,
+        _kind: ClipboardKind,
+        _text: String,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_mime_types(&self, _kind: ClipboardKind) -> Result<Vec<String>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard_data(
+        &self,
+        _kind: ClipboardKind,
+        _mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_clipboard_data(
+        &self,
+        _kind: ClipboardKind,
+        _data: Vec<(String, Vec<u8>)>,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn create_custom_cursor(
+        &self,
+        _source: CustomCursorSource,
+    ) -> Result<PlatformCustomCursor, ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
 }
@@ -10,6 +10,7 @@ pub use self::{
 };
 
 pub use self::icon::WinIcon as PlatformIcon;
+pub(crate) use crate::cursor::NoCustomCursor as PlatformCustomCursor;
 
 use crate::event::DeviceId as RootDeviceId;
 use crate::icon::Icon;
@@ -61,6 +62,12 @@ impl DeviceId {
     pub unsafe fn dummy() -> Self {
         DeviceId(0)
     }
+
+    /// Raw input device handles never get this high.
+    #[cfg(feature = "serde")]
+    pub fn synthetic(index: u32) -> Self {
+        DeviceId(!index)
+    }
 }
 
 impl DeviceId {
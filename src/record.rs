@@ -0,0 +1,498 @@
+#![cfg(feature = "serde")]
+
+//! Recording and replaying of event streams.
+//!
+//! An [`EventRecorder`] serializes the events delivered by an `EventLoop`, along with the time
+//! they were delivered at, relative to `StartCause::Init`. An [`EventReplayer`] later feeds these
+//! events to the event handler of a fresh `EventLoop`, which makes it possible to reproduce a bug
+//! from a recording made by a user.
+//!
+//! Any serde format can be used. Window and device ids can't be serialized as is, so they are
+//! stored as indices instead: windows are numbered in the order they're registered with
+//! [`EventRecorder::register_window`], or in the order they first show up in an event otherwise.
+//! When replaying, the windows of the application have to be registered in the same order with
+//! [`EventReplayer::register_window`]. Devices are replayed with synthetic ids, one per recorded
+//! device, which don't match any device of the event loop. Serializing or deserializing an event
+//! outside of the recorder or replayer fails.
+//!
+//! `WindowEvent::ScaleFactorChanged` and `WindowEvent::DragMoved` are never recorded, as they
+//! have no `'static` form (see [`Event::to_static`]).
+//!
+//! ```no_run
+//! use std::{fs::File, io::BufReader};
+//!
+//! use winit::{
+//!     event::{Event, WindowEvent},
+//!     event_loop::{ControlFlow, EventLoop},
+//!     record::{EventRecorder, EventReplayer, ReplayPacing},
+//!     window::Window,
+//! };
+//!
+//! fn run(replay: bool) {
+//!     let event_loop = EventLoop::<()>::new();
+//!     let window = Window::new(&event_loop).unwrap();
+//!
+//!     let mut event_handler = move |event: Event<'_, ()>, _: &_, control_flow: &mut _| {
+//!         *control_flow = ControlFlow::Wait;
+//!         if let Event::WindowEvent { event: WindowEvent::CloseRequested, .. } = event {
+//!             *control_flow = ControlFlow::Exit;
+//!         }
+//!     };
+//!
+//!     if replay {
+//!         let file = BufReader::new(File::open("events.json").unwrap());
+//!         let records = serde_json::Deserializer::from_reader(file).into_iter();
+//!         let mut replayer = EventReplayer::new(records, ReplayPacing::Original);
+//!         replayer.register_window(window.id());
+//!
+//!         event_loop.run(move |event, window_target, control_flow| {
+//!             replayer
+//!                 .handle_event(event, window_target, control_flow, &mut event_handler)
+//!                 .unwrap();
+//!         });
+//!     } else {
+//!         let mut file = File::create("events.json").unwrap();
+//!         let mut recorder = EventRecorder::new();
+//!         recorder.register_window(window.id());
+//!
+//!         event_loop.run(move |event, window_target, control_flow| {
+//!             recorder
+//!                 .record(&event, &mut serde_json::Serializer::new(&mut file))
+//!                 .unwrap();
+//!             event_handler(event, window_target, control_flow);
+//!         });
+//!     }
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::mem;
+use std::time::Duration;
+
+use instant::Instant;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    event::{DeviceId, Event, StartCause, WindowEvent},
+    event_loop::{ControlFlow, EventLoopWindowTarget},
+    platform_impl,
+    window::WindowId,
+};
+
+/// An event, along with the time it was delivered at.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord<T: 'static> {
+    /// Time elapsed between `StartCause::Init` and the delivery of the event.
+    pub time: Duration,
+    pub event: Event<'static, T>,
+}
+
+/// Borrowed version of `EventRecord`, to record events without taking ownership of them.
+#[derive(Serialize)]
+#[serde(rename = "EventRecord")]
+struct EventRecordRef<'a, 'b, T: 'static> {
+    time: Duration,
+    event: &'a Event<'b, T>,
+}
+
+/// Serializes the events delivered by an `EventLoop`.
+///
+/// See the [module-level docs](self) for more information.
+#[derive(Debug, Default)]
+pub struct EventRecorder {
+    context: Context,
+}
+
+impl EventRecorder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Assigns the next free index to `window_id`, and returns it.
+    ///
+    /// Windows should be registered as soon as they're created, in the same order as they will
+    /// be registered with the `EventReplayer`. Returns the existing index if the window was
+    /// already registered, or already showed up in a recorded event.
+    pub fn register_window(&mut self, window_id: WindowId) -> usize {
+        index_of(&mut self.context.windows, window_id)
+    }
+
+    /// Serializes `event` with `serializer`.
+    ///
+    /// Returns `Ok(None)` if the event can't be recorded, which is only the case for
+    /// `WindowEvent::ScaleFactorChanged` and `WindowEvent::DragMoved`.
+    pub fn record<T, S>(
+        &mut self,
+        event: &Event<'_, T>,
+        serializer: S,
+    ) -> Result<Option<S::Ok>, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        if let Event::WindowEvent {
+            event: WindowEvent::ScaleFactorChanged { .. },
+            ..
+        }
+        | Event::WindowEvent {
+            event: WindowEvent::DragMoved { .. },
+            ..
+        } = event
+        {
+            return Ok(None);
+        }
+
+        let now = Instant::now();
+        if let Event::NewEvents(StartCause::Init) = event {
+            self.context.origin = Some(now);
+        }
+        let origin = *self.context.origin.get_or_insert(now);
+
+        let record = EventRecordRef {
+            time: now - origin,
+            event,
+        };
+        with_context(&mut self.context, || record.serialize(serializer)).map(Some)
+    }
+}
+
+/// The speed at which an `EventReplayer` replays events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReplayPacing {
+    /// Events are replayed at the time they were recorded at, relative to `StartCause::Init`.
+    Original,
+
+    /// The events recorded during an iteration of the event loop are replayed during the next
+    /// iteration, and the event loop doesn't wait between iterations until the replay is over.
+    AsFastAsPossible,
+}
+
+/// Feeds recorded events to the event handler of an `EventLoop`.
+///
+/// `records` is an iterator which deserializes `EventRecord`s. As recorded windows are resolved
+/// while deserializing, records have to be deserialized lazily, e.g. with serde_json's
+/// `StreamDeserializer`, and windows have to be registered before the first record mentioning
+/// them is reached.
+///
+/// See the [module-level docs](self) for more information.
+pub struct EventReplayer<T: 'static, R> {
+    records: R,
+    pacing: ReplayPacing,
+    context: Context,
+
+    /// Record which was deserialized but not replayed yet.
+    next: Option<EventRecord<T>>,
+    finished: bool,
+}
+
+impl<T, R, E> EventReplayer<T, R>
+where
+    R: Iterator<Item = Result<EventRecord<T>, E>>,
+{
+    pub fn new(records: R, pacing: ReplayPacing) -> Self {
+        EventReplayer {
+            records,
+            pacing,
+            context: Default::default(),
+            next: None,
+            finished: false,
+        }
+    }
+
+    /// Assigns the next free index to `window_id`, and returns it.
+    ///
+    /// Windows have to be registered in the same order as they were during the recording.
+    pub fn register_window(&mut self, window_id: WindowId) -> usize {
+        index_of(&mut self.context.windows, window_id)
+    }
+
+    /// Returns `true` once every record has been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Handles an event delivered by the event loop, forwarding it and the recorded events to
+    /// `event_handler`.
+    ///
+    /// Window, device and user events come from the recording: the ones delivered by the event
+    /// loop are discarded, including after the replay is over. All the other events are
+    /// forwarded as is. Recorded events are replayed right before `MainEventsCleared`, and the
+    /// `ControlFlow` is adjusted after `RedrawEventsCleared` so that the event loop wakes up in
+    /// time for the next record.
+    ///
+    /// Recorded window events don't affect the actual windows: e.g. a `Resized` event doesn't
+    /// resize the window it targets.
+    pub fn handle_event<F>(
+        &mut self,
+        event: Event<'_, T>,
+        window_target: &EventLoopWindowTarget<T>,
+        control_flow: &mut ControlFlow,
+        mut event_handler: F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        match event {
+            Event::NewEvents(StartCause::Init) => {
+                self.context.origin = Some(Instant::now());
+                event_handler(event, window_target, control_flow);
+            }
+            Event::WindowEvent { .. } | Event::DeviceEvent { .. } | Event::UserEvent(_) => (),
+            Event::MainEventsCleared => {
+                self.replay(window_target, control_flow, &mut event_handler)?;
+                event_handler(event, window_target, control_flow);
+            }
+            Event::RedrawEventsCleared => {
+                event_handler(event, window_target, control_flow);
+                self.schedule(control_flow);
+            }
+            _ => event_handler(event, window_target, control_flow),
+        }
+
+        Ok(())
+    }
+
+    /// Replays the records which are due.
+    fn replay<F>(
+        &mut self,
+        window_target: &EventLoopWindowTarget<T>,
+        control_flow: &mut ControlFlow,
+        event_handler: &mut F,
+    ) -> Result<(), E>
+    where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let origin = *self.context.origin.get_or_insert_with(Instant::now);
+
+        loop {
+            if self.next.is_none() {
+                let records = &mut self.records;
+                self.next = with_context(&mut self.context, || records.next()).transpose()?;
+            }
+
+            let record = match self.next {
+                Some(ref record) => record,
+                None => {
+                    self.finished = true;
+                    return Ok(());
+                }
+            };
+
+            let end_of_iteration = match self.pacing {
+                ReplayPacing::Original if origin + record.time > Instant::now() => return Ok(()),
+                ReplayPacing::Original => false,
+                ReplayPacing::AsFastAsPossible => matches!(record.event, Event::MainEventsCleared),
+            };
+
+            match self.next.take().unwrap().event {
+                event @ Event::WindowEvent { .. }
+                | event @ Event::DeviceEvent { .. }
+                | event @ Event::UserEvent(_) => event_handler(event, window_target, control_flow),
+                // The other events are delivered by the event loop itself.
+                _ => (),
+            }
+
+            if end_of_iteration {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Makes sure the event loop wakes up in time for the next record.
+    fn schedule(&self, control_flow: &mut ControlFlow) {
+        if self.finished || *control_flow == ControlFlow::Exit {
+            return;
+        }
+
+        match self.pacing {
+            ReplayPacing::Original => {
+                let deadline = match (self.context.origin, self.next.as_ref()) {
+                    (Some(origin), Some(record)) => origin + record.time,
+                    _ => return,
+                };
+
+                match *control_flow {
+                    ControlFlow::Poll => (),
+                    ControlFlow::WaitUntil(wait_until) if wait_until <= deadline => (),
+                    _ => *control_flow = ControlFlow::WaitUntil(deadline),
+                }
+            }
+            ReplayPacing::AsFastAsPossible => *control_flow = ControlFlow::Poll,
+        }
+    }
+}
+
+/// State needed to serialize ids and instants, which is only available while recording or
+/// replaying.
+#[derive(Debug, Default)]
+struct Context {
+    /// Time of `StartCause::Init`.
+    origin: Option<Instant>,
+    windows: Vec<WindowId>,
+
+    /// The recorded devices while recording, or their synthetic ids while replaying.
+    devices: Vec<DeviceId>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+/// Makes `context` available to the (de)serialization happening in `f`.
+fn with_context<U, F: FnOnce() -> U>(context: &mut Context, f: F) -> U {
+    struct Guard<'a>(&'a mut Context);
+
+    impl Drop for Guard<'_> {
+        fn drop(&mut self) {
+            if let Some(context) = CONTEXT.with(|current| current.borrow_mut().take()) {
+                *self.0 = context;
+            }
+        }
+    }
+
+    CONTEXT.with(|current| *current.borrow_mut() = Some(mem::take(context)));
+    let _guard = Guard(context);
+    f()
+}
+
+fn current_context<U, F: FnOnce(&mut Context) -> U>(f: F) -> Option<U> {
+    CONTEXT.with(|current| current.borrow_mut().as_mut().map(f))
+}
+
+fn index_of<I: PartialEq>(ids: &mut Vec<I>, id: I) -> usize {
+    match ids.iter().position(|known| *known == id) {
+        Some(index) => index,
+        None => {
+            ids.push(id);
+            ids.len() - 1
+        }
+    }
+}
+
+/// (De)serializes a `WindowId` as the index of the window in the recording.
+pub(crate) mod serde_window_id {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        window_id: &WindowId,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match current_context(|context| index_of(&mut context.windows, *window_id)) {
+            Some(index) => serializer.serialize_u64(index as u64),
+            None => Err(ser::Error::custom(
+                "`WindowId` can only be serialized by an `EventRecorder`",
+            )),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<WindowId, D::Error> {
+        let index = u64::deserialize(deserializer)? as usize;
+        match current_context(|context| context.windows.get(index).cloned()) {
+            Some(Some(window_id)) => Ok(window_id),
+            Some(None) => Err(de::Error::custom(format!(
+                "window {} isn't registered with the `EventReplayer`",
+                index
+            ))),
+            None => Err(de::Error::custom(
+                "`WindowId` can only be deserialized by an `EventReplayer`",
+            )),
+        }
+    }
+}
+
+/// (De)serializes a `DeviceId` as the index of the device in the recording.
+///
+/// Devices can't be recreated, so each recorded device is replayed with a synthetic `DeviceId`,
+/// which is the same for all the events of that device.
+pub(crate) mod serde_device_id {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        device_id: &DeviceId,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match current_context(|context| index_of(&mut context.devices, *device_id)) {
+            Some(index) => serializer.serialize_u64(index as u64),
+            None => Err(ser::Error::custom(
+                "`DeviceId` can only be serialized by an `EventRecorder`",
+            )),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DeviceId, D::Error> {
+        let index = u64::deserialize(deserializer)?;
+        let index = u32::try_from(index)
+            .map_err(|_| de::Error::custom(format!("device {} is out of range", index)))?;
+        let device_id = current_context(|context| {
+            while context.devices.len() <= index as usize {
+                let next = context.devices.len() as u32;
+                context
+                    .devices
+                    .push(DeviceId(platform_impl::DeviceId::synthetic(next)));
+            }
+            context.devices[index as usize]
+        });
+        device_id.ok_or_else(|| {
+            de::Error::custom("`DeviceId` can only be deserialized by an `EventReplayer`")
+        })
+    }
+}
+
+fn context_origin() -> Option<Instant> {
+    current_context(|context| context.origin).flatten()
+}
+
+/// (De)serializes an `Instant` as the `Duration` elapsed since the start of the recording.
+pub(crate) mod serde_instant {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        let origin = context_origin().ok_or_else(|| {
+            ser::Error::custom("`Instant` can only be serialized by an `EventRecorder`")
+        })?;
+        let elapsed = if *instant > origin {
+            *instant - origin
+        } else {
+            Duration::from_secs(0)
+        };
+        elapsed.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        let elapsed = Duration::deserialize(deserializer)?;
+        let origin = context_origin().ok_or_else(|| {
+            de::Error::custom("`Instant` can only be deserialized by an `EventReplayer`")
+        })?;
+        Ok(origin + elapsed)
+    }
+}
+
+/// Same as `serde_instant`, for an `Option<Instant>`.
+pub(crate) mod serde_option_instant {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        instant: &Option<Instant>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        struct Wrapper<'a>(&'a Instant);
+
+        impl Serialize for Wrapper<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                super::serde_instant::serialize(self.0, serializer)
+            }
+        }
+
+        instant.as_ref().map(Wrapper).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Instant>, D::Error> {
+        #[derive(Deserialize)]
+        struct Wrapper(#[serde(with = "super::serde_instant")] Instant);
+
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(instant)| instant))
+    }
+}
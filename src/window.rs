@@ -2,6 +2,7 @@
 use std::fmt;
 
 use crate::{
+    dnd::DndActions,
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError},
     event_loop::EventLoopWindowTarget,
@@ -9,6 +10,7 @@ use crate::{
     platform_impl,
 };
 
+pub use crate::cursor::{CustomCursor, CustomCursorSource};
 pub use crate::icon::{BadIcon, Icon};
 
 /// Represents a window.
@@ -186,6 +188,13 @@ pub struct WindowAttributes {
     ///
     /// The default is `None`.
     pub window_icon: Option<Icon>,
+
+    /// Whether the window is a popup of another window, and where it's placed.
+    ///
+    /// The default is `None`.
+    ///
+    /// See [`WindowBuilder::with_popup`].
+    pub popup: Option<PopupAttributes>,
 }
 
 impl Default for WindowAttributes {
@@ -205,6 +214,7 @@ impl Default for WindowAttributes {
             decorations: true,
             always_on_top: false,
             window_icon: None,
+            popup: None,
         }
     }
 }
@@ -355,6 +365,44 @@ impl WindowBuilder {
         self
     }
 
+    /// Makes the window a popup of `parent`, such as a menu or a tooltip.
+    ///
+    /// The popup is placed next to `anchor_rect`, a rectangle of the parent's client area given
+    /// by the position of its top-left corner and its size. It's aligned with the side or corner
+    /// of the rectangle given by `gravity`, and extends away from the rectangle in that direction.
+    /// A menu opening below a button at `(x, y)` can for instance use the rectangle
+    /// `((x, y), (0, button_height))` with [`PopupGravity::BottomRight`]. A popup which doesn't
+    /// fit on the monitor is flipped to the other side of the rectangle, then slid back inside.
+    ///
+    /// Popups have no decorations, stay above their parent and move along with it. They receive
+    /// [`WindowEvent::PopupDismissed`] when they should be closed.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Popups are override-redirect windows, transient for their parent. They're
+    ///   dismissed when their parent is hidden.
+    /// - **Wayland:** Popups are `xdg_popup`s, placed by the compositor. They're dismissed when
+    ///   the compositor sends `popup_done`. Building the window fails if the parent isn't a
+    ///   toplevel or popup window, or if the compositor doesn't support `xdg_wm_base`.
+    /// - **Windows / macOS / iOS / Android / Web:** Ignored, the window is created as a regular
+    ///   window.
+    ///
+    /// [`WindowEvent::PopupDismissed`]: crate::event::WindowEvent::PopupDismissed
+    #[inline]
+    pub fn with_popup(
+        mut self,
+        parent: &Window,
+        anchor_rect: (Position, Size),
+        gravity: PopupGravity,
+    ) -> Self {
+        self.window.popup = Some(PopupAttributes {
+            parent: parent.id(),
+            anchor_rect,
+            gravity,
+        });
+        self
+    }
+
     /// Builds the window.
     ///
     /// Possible causes of error include denied permission, incompatible system, and lack of memory.
@@ -458,7 +506,9 @@ impl Window {
     ///   window's [safe area] in the screen space coordinate system.
     /// - **Web:** Returns the top-left coordinates relative to the viewport. _Note: this returns the
     ///    same value as `outer_position`._
-    /// - **Android / Wayland:** Always returns [`NotSupportedError`].
+    /// - **X11:** Child windows return coordinates relative to the client area of their parent.
+    /// - **Android / Wayland:** Always returns [`NotSupportedError`], except for child windows,
+    ///   which return coordinates relative to the client area of their parent.
     ///
     /// [safe area]: https://developer.apple.com/documentation/uikit/uiview/2891103-safeareainsets?language=objc
     #[inline]
@@ -481,7 +531,9 @@ impl Window {
     /// - **iOS:** Can only be called on the main thread. Returns the top left coordinates of the
     ///   window in the screen space coordinate system.
     /// - **Web:** Returns the top-left coordinates relative to the viewport.
-    /// - **Android / Wayland:** Always returns [`NotSupportedError`].
+    /// - **X11:** Child windows return coordinates relative to the client area of their parent.
+    /// - **Android / Wayland:** Always returns [`NotSupportedError`], except for child windows,
+    ///   which return coordinates relative to the client area of their parent.
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         self.window.outer_position()
@@ -497,7 +549,11 @@ impl Window {
     /// - **iOS:** Can only be called on the main thread. Sets the top left coordinates of the
     ///   window in the screen space coordinate system.
     /// - **Web:** Sets the top-left coordinates relative to the viewport.
-    /// - **Android / Wayland:** Unsupported.
+    /// - **X11 / Wayland:** Child windows are placed relative to the client area of their parent,
+    ///   see [`WindowBuilderExtUnix::with_child_of`].
+    /// - **Android / Wayland:** Unsupported, except for child windows.
+    ///
+    /// [`WindowBuilderExtUnix::with_child_of`]: crate::platform::unix::WindowBuilderExtUnix::with_child_of
     #[inline]
     pub fn set_outer_position<P: Into<Position>>(&self, position: P) {
         self.window.set_outer_position(position.into())
@@ -568,6 +624,37 @@ impl Window {
     pub fn set_max_inner_size<S: Into<Size>>(&self, max_size: Option<S>) {
         self.window.set_max_inner_size(max_size.map(|s| s.into()))
     }
+
+    /// Sets the steps the window's inner size snaps to when the user resizes it, or `None` to let
+    /// it be resized freely.
+    ///
+    /// The increments are kept when the scale factor changes, and `set_inner_size` isn't affected
+    /// by them.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The increments are a hint, which some window managers ignore. They're counted
+    ///   from the base size set with `WindowBuilderExtUnix::with_base_size`, or else from the
+    ///   minimum size.
+    /// - **Wayland:** The sizes the compositor configures the window with are snapped down to the
+    ///   increments, counted from the minimum size, unless the window is maximized, fullscreen or
+    ///   tiled.
+    /// - **Windows / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_resize_increments<S: Into<Size>>(&self, increments: Option<S>) {
+        self.window
+            .set_resize_increments(increments.map(|s| s.into()))
+    }
+
+    /// Returns the resize increments of the window, converted to the current scale factor.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / iOS / Android / Web:** Always returns `None`.
+    #[inline]
+    pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
+        self.window.resize_increments()
+    }
 }
 
 /// Misc. attribute functions.
@@ -731,6 +818,26 @@ impl Window {
         self.window.set_ime_position(position.into())
     }
 
+    /// Sets whether the window receives [`WindowEvent::Ime`] and draws the text being composed
+    /// itself.
+    ///
+    /// It's disallowed by default, in which case the input method draws the composition, and the
+    /// committed text is only sent as `ReceivedCharacter` events. Applications should only allow
+    /// it while they render the composition, e.g. while a text field has the focus.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Switches the input context to on-the-spot (`XIMPreeditCallbacks`) pre-editing,
+    ///   if the input method supports it, instead of the over-the-spot default.
+    /// - **Wayland:** The text input of the compositor is used either way.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    ///
+    /// [`WindowEvent::Ime`]: crate::event::WindowEvent::Ime
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.window.set_ime_allowed(allowed)
+    }
+
     /// Brings the window to the front and sets input focus. Has no effect if the window is
     /// already in focus, minimized, or not visible.
     ///
@@ -771,11 +878,25 @@ impl Window {
     /// ## Platform-specific
     ///
     /// - **iOS / Android:** Unsupported.
+    /// - **Wayland:** Animated theme cursors are animated by the event loop.
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
         self.window.set_cursor_icon(cursor);
     }
 
+    /// Shows a custom cursor image over the window, until another cursor is set.
+    ///
+    /// The image is picked among the variants of the cursor according to the scale factor of
+    /// the window. Animated cursors loop through their frames.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) {
+        self.window.set_custom_cursor(&cursor.inner);
+    }
+
     /// Changes the position of the cursor in window coordinates.
     ///
     /// ## Platform-specific
@@ -786,18 +907,78 @@ impl Window {
         self.window.set_cursor_position(position.into())
     }
 
-    /// Grabs the cursor, preventing it from leaving the window.
+    /// Grabs the cursor, either confining it to the window or locking it in place, or releases
+    /// it with [`CursorGrabMode::None`].
     ///
     /// There's no guarantee that the cursor will be hidden. You should
     /// hide it by yourself if you want so.
     ///
+    /// Returns an [`ExternalError::NotSupported`] if the platform can't provide the mode, in
+    /// which case the previous grab is kept.
+    ///
     /// ## Platform-specific
     ///
-    /// - **macOS:** This locks the cursor in a fixed location, which looks visually awkward.
+    /// - **Windows:** [`CursorGrabMode::Locked`] is unsupported.
+    /// - **macOS:** [`CursorGrabMode::Confined`] is unsupported.
+    /// - **X11:** [`CursorGrabMode::Locked`] is emulated by warping the cursor back whenever it
+    ///   moves, during which no `WindowEvent::CursorMoved` is reported.
+    /// - **Wayland:** Requires the compositor to support pointer constraints.
     /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
     #[inline]
-    pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-        self.window.set_cursor_grab(grab)
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        self.window.set_cursor_grab(mode)
+    }
+
+    /// Restricts [`CursorGrabMode::Confined`] to a region of the window, given by the position
+    /// of its top-left corner relative to the client area and its size, or extends it back to the
+    /// whole window with `None`.
+    ///
+    /// The region follows the window when it moves or when its scale factor changes, and applies
+    /// to later grabs as well.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires XFixes 5.0, which provides the pointer barriers the region is made of.
+    ///   A cursor outside of the region is moved inside once it's confined.
+    /// - **Wayland:** Requires the compositor to support pointer constraints. A cursor outside of
+    ///   the region is only confined once it enters the region.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_cursor_grab_region(
+        &self,
+        region: Option<(Position, Size)>,
+    ) -> Result<(), ExternalError> {
+        self.window.set_cursor_grab_region(region)
+    }
+
+    /// Grabs the pointer and the keyboard for a popup window, such as a menu, or releases them.
+    ///
+    /// While the grab is active, keyboard input goes to the popup, and pointer input outside of
+    /// the application's windows goes to the popup instead of the windows of other applications.
+    /// Clicking outside of the application's windows breaks the grab, as may the system, after
+    /// which the popup receives [`WindowEvent::PopupDismissed`]. The grab is also released when
+    /// the popup is hidden.
+    ///
+    /// Returns an [`ExternalError::NotSupported`] if the window isn't a popup, see
+    /// [`WindowBuilder::with_popup`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Grabs the pointer and the keyboard of the core devices, which fails if another
+    ///   client is grabbing them. The popup has to be visible.
+    /// - **Wayland:** Requests `xdg_popup.grab` with the last input event, which has to be the
+    ///   one that opened the popup. The grab has to be requested before the popup is drawn for
+    ///   the first time, and can't be released, so releasing it returns an
+    ///   [`ExternalError::NotSupported`]. When it's too late to grab, or the compositor denies
+    ///   the grab, the popup receives [`WindowEvent::PopupDismissed`].
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::PopupDismissed`]: crate::event::WindowEvent::PopupDismissed
+    #[inline]
+    pub fn set_popup_grab(&self, grab: bool) -> Result<(), ExternalError> {
+        self.window.set_popup_grab(grab)
     }
 
     /// Modifies the cursor's visibility.
@@ -832,6 +1013,44 @@ impl Window {
     pub fn drag_window(&self) -> Result<(), ExternalError> {
         self.window.drag_window()
     }
+
+    /// Starts dragging `data` out of the window, until the mouse button is released.
+    ///
+    /// `data` holds the representations of the dragged content as pairs of MIME types and bytes,
+    /// out of which the application it's dropped on picks the one it understands.
+    /// `allowed_actions` are the actions that application may perform with it. How the drag ended
+    /// is reported with a [`WindowEvent::DragEnded`].
+    ///
+    /// There's no guarantee that this will work unless a mouse button was pressed over the window
+    /// immediately before this function is called.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Fails while another drag started by the application is in progress. The drag is
+    ///   reported as cancelled if the application it's dropped on doesn't finish the drop within
+    ///   a few seconds.
+    /// - **Wayland:** Requires the cursor to be inside the window, otherwise the drag is reported
+    ///   as cancelled. [`DndActions::LINK`] isn't supported.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::DragEnded`]: crate::event::WindowEvent::DragEnded
+    #[inline]
+    pub fn start_drag<I, S>(
+        &self,
+        data: I,
+        allowed_actions: DndActions,
+    ) -> Result<(), ExternalError>
+    where
+        I: IntoIterator<Item = (S, Vec<u8>)>,
+        S: Into<String>,
+    {
+        let data = data
+            .into_iter()
+            .map(|(mime_type, data)| (mime_type.into(), data))
+            .collect();
+        self.window.start_drag(data, allowed_actions)
+    }
 }
 
 /// Monitor info functions.
@@ -956,6 +1175,53 @@ impl Default for CursorIcon {
     }
 }
 
+/// How the cursor is grabbed by a window, see [`Window::set_cursor_grab`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CursorGrabMode {
+    /// The cursor moves freely.
+    #[default]
+    None,
+
+    /// The cursor can move inside the window, but can't leave it.
+    Confined,
+
+    /// The cursor is held in place, and its movements are only reported through
+    /// [`DeviceEvent::MouseMotion`](crate::event::DeviceEvent::MouseMotion).
+    Locked,
+}
+
+/// Where a popup window is placed, see [`WindowBuilder::with_popup`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PopupAttributes {
+    /// The window the popup belongs to.
+    pub parent: WindowId,
+
+    /// The rectangle of the parent's client area the popup is placed next to, given by the
+    /// position of its top-left corner and its size.
+    pub anchor_rect: (Position, Size),
+
+    /// The direction the popup extends in from the anchor rectangle.
+    pub gravity: PopupGravity,
+}
+
+/// The side or corner of its anchor rectangle a popup window is placed at, which is also the
+/// direction it extends in. See [`WindowBuilder::with_popup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PopupGravity {
+    /// The popup is centered on the rectangle.
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
 /// Fullscreen modes.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Fullscreen {
@@ -966,6 +1232,7 @@ pub enum Fullscreen {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Theme {
     Light,
     Dark,
@@ -0,0 +1,148 @@
+#![cfg(all(target_os = "linux", feature = "mock", feature = "serde"))]
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{DeviceEvent, Event, WindowEvent},
+    event_loop::EventLoop,
+    platform::mock::{EventLoopExtMock, EventLoopWindowTargetExtMock, MockController},
+    record::{EventRecorder, EventReplayer, ReplayPacing},
+    window::WindowBuilder,
+};
+
+#[test]
+fn record_and_replay() {
+    let mut buffer = Vec::new();
+    {
+        let mut event_loop = EventLoop::<u32>::new_mock();
+        let controller = event_loop.mock_controller().unwrap();
+        let proxy = event_loop.create_proxy();
+        let window = WindowBuilder::new().build(&event_loop).unwrap();
+
+        let mut recorder = EventRecorder::new();
+        assert_eq!(recorder.register_window(window.id()), 0);
+
+        controller.send_window_event(window.id(), WindowEvent::Focused(true));
+        controller.send_window_event(window.id(), WindowEvent::Resized(PhysicalSize::new(10, 20)));
+        proxy.send_event(7).unwrap();
+        for _ in 0..2 {
+            event_loop.run_step(|event, _, _| {
+                recorder
+                    .record(&event, &mut serde_json::Serializer::new(&mut buffer))
+                    .unwrap();
+            });
+        }
+        controller.set_scale_factor(window.id(), 2.0);
+        controller.send_window_event(window.id(), WindowEvent::CloseRequested);
+        event_loop.run_step(|event, _, _| {
+            recorder
+                .record(&event, &mut serde_json::Serializer::new(&mut buffer))
+                .unwrap();
+        });
+    }
+
+    let mut event_loop = EventLoop::<u32>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+
+    let records = serde_json::Deserializer::from_slice(&buffer).into_iter();
+    let mut replayer = EventReplayer::new(records, ReplayPacing::AsFastAsPossible);
+    assert_eq!(replayer.register_window(window.id()), 0);
+
+    // Live input is replaced by the recording.
+    controller.send_window_event(window.id(), WindowEvent::Destroyed);
+
+    let mut replayed = Vec::new();
+    while !replayer.is_finished() {
+        event_loop.run_step(|event, window_target, control_flow| {
+            replayer
+                .handle_event(event, window_target, control_flow, |event, _, _| {
+                    replayed.push(event.to_static().unwrap())
+                })
+                .unwrap();
+        });
+    }
+
+    let window_id = window.id();
+    let input: Vec<_> = replayed
+        .into_iter()
+        .filter(|event| match event {
+            Event::WindowEvent { .. } | Event::UserEvent(_) => true,
+            _ => false,
+        })
+        .collect();
+    assert_eq!(
+        input,
+        vec![
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::Focused(true),
+            },
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::Resized(PhysicalSize::new(10, 20)),
+            },
+            Event::UserEvent(7),
+            // `ScaleFactorChanged` isn't recorded, but the `Resized` following it is.
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::Resized(PhysicalSize::new(20, 40)),
+            },
+            Event::WindowEvent {
+                window_id,
+                event: WindowEvent::CloseRequested,
+            },
+        ]
+    );
+}
+
+#[test]
+fn replayed_devices_stay_distinct() {
+    let mut buffer = Vec::new();
+    {
+        let mut event_loop = EventLoop::<()>::new_mock();
+        let controller = event_loop.mock_controller().unwrap();
+        let mut recorder = EventRecorder::new();
+
+        for &id in &[4, 9, 4] {
+            let event = DeviceEvent::MouseMotion { delta: (1.0, 0.0) };
+            controller.send_device_event(MockController::device_id(id), event);
+        }
+        event_loop.run_step(|event, _, _| {
+            recorder
+                .record(&event, &mut serde_json::Serializer::new(&mut buffer))
+                .unwrap();
+        });
+    }
+
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let records = serde_json::Deserializer::from_slice(&buffer).into_iter();
+    let mut replayer = EventReplayer::new(records, ReplayPacing::AsFastAsPossible);
+
+    let mut device_ids = Vec::new();
+    while !replayer.is_finished() {
+        event_loop.run_step(|event, window_target, control_flow| {
+            replayer
+                .handle_event(event, window_target, control_flow, |event, _, _| {
+                    if let Event::DeviceEvent { device_id, .. } = event {
+                        device_ids.push(device_id);
+                    }
+                })
+                .unwrap();
+        });
+    }
+
+    assert_eq!(device_ids.len(), 3);
+    assert_ne!(device_ids[0], device_ids[1]);
+    assert_eq!(device_ids[0], device_ids[2]);
+    assert_ne!(device_ids[0], MockController::device_id(4));
+}
+
+#[test]
+fn ids_need_a_recorder() {
+    let event_loop = EventLoop::<()>::new_mock();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+
+    let event: Event<'_, ()> = Event::RedrawRequested(window.id());
+    assert!(serde_json::to_string(&event).is_err());
+    assert!(serde_json::to_string(&Event::<()>::MainEventsCleared).is_ok());
+}
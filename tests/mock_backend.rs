@@ -0,0 +1,829 @@
+#![cfg(all(target_os = "linux", feature = "mock"))]
+
+use std::io::Write;
+use std::mem;
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use winit::{
+    clipboard::ClipboardKind,
+    dnd::{DndAction, DndActions, DragOutcome, DropData},
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
+    event::{
+        DeviceEvent, ElementState, Event, KeyboardInput, ModifiersState, VirtualKeyCode,
+        WindowEvent,
+    },
+    event_loop::{ControlFlow, EventLoop},
+    gamepad::{GamepadAxis, GamepadButton, GamepadInfo},
+    platform::{
+        mock::{
+            EventLoopExtMock, EventLoopWindowTargetExtMock, MockController, MockDrag, MockGamepad,
+            MockMonitor,
+        },
+        run_return::EventLoopExtRunReturn,
+        unix::{EventLoopWindowTargetExtUnix, WindowBuilderExtUnix},
+    },
+    window::{
+        BadIcon, CursorGrabMode, CursorIcon, CustomCursorSource, Fullscreen, PopupGravity,
+        WindowBuilder,
+    },
+};
+
+#[test]
+fn window_attributes() {
+    let event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let window = WindowBuilder::new()
+        .with_title("mock")
+        .with_inner_size(LogicalSize::new(320.0, 240.0))
+        .build(&event_loop)
+        .unwrap();
+
+    let state = controller.window_state(window.id()).unwrap();
+    assert_eq!(state.title, "mock");
+    assert_eq!(state.inner_size, PhysicalSize::new(320, 240));
+    assert!(!state.ime_allowed);
+
+    window.set_title("renamed");
+    window.set_inner_size(PhysicalSize::new(640, 480));
+    window.set_cursor_icon(CursorIcon::Hand);
+    window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+    window.set_ime_allowed(true);
+
+    let state = controller.window_state(window.id()).unwrap();
+    assert_eq!(state.title, "renamed");
+    assert!(state.ime_allowed);
+    assert_eq!(window.inner_size(), PhysicalSize::new(640, 480));
+    assert_eq!(state.cursor_icon, CursorIcon::Hand);
+    assert_eq!(
+        state.fullscreen,
+        Some(Fullscreen::Borderless(window.current_monitor()))
+    );
+
+    let window_id = window.id();
+    drop(window);
+    assert!(controller.window_state(window_id).is_none());
+}
+
+#[test]
+fn scripted_input_is_dispatched_in_order() {
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    let device_id = MockController::device_id(0);
+
+    #[allow(deprecated)]
+    let input = KeyboardInput {
+        scancode: 30,
+        state: ElementState::Pressed,
+        virtual_keycode: Some(VirtualKeyCode::A),
+        modifiers: ModifiersState::empty(),
+    };
+    controller.send_window_event(
+        window.id(),
+        WindowEvent::KeyboardInput {
+            device_id,
+            input,
+            is_synthetic: false,
+        },
+    );
+    #[allow(deprecated)]
+    controller.send_window_event(
+        window.id(),
+        WindowEvent::CursorMoved {
+            device_id,
+            position: PhysicalPosition::new(10.0, 20.0),
+            modifiers: ModifiersState::empty(),
+        },
+    );
+    controller.send_window_event(
+        window.id(),
+        WindowEvent::Resized(PhysicalSize::new(100, 50)),
+    );
+
+    let mut events = Vec::new();
+    event_loop.run_step(|event, _, _| {
+        if let Event::WindowEvent { event, .. } = event {
+            events.push(event.to_static().unwrap());
+        }
+    });
+
+    assert_eq!(events.len(), 3);
+    assert!(matches!(events[0], WindowEvent::KeyboardInput { input: i, .. } if i == input));
+    assert!(matches!(events[1], WindowEvent::CursorMoved { .. }));
+    assert_eq!(events[2], WindowEvent::Resized(PhysicalSize::new(100, 50)));
+    assert_eq!(window.inner_size(), PhysicalSize::new(100, 50));
+}
+
+#[test]
+fn scale_factor_from_monitor() {
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    controller.add_monitor(MockMonitor {
+        name: "hidpi".to_owned(),
+        position: PhysicalPosition::new(1920, 0),
+        scale_factor: 2.0,
+        ..MockMonitor::default()
+    });
+
+    let window = WindowBuilder::new()
+        .with_position(PhysicalPosition::new(2000, 0))
+        .with_inner_size(LogicalSize::new(100.0, 100.0))
+        .build(&event_loop)
+        .unwrap();
+    assert_eq!(window.scale_factor(), 2.0);
+    assert_eq!(window.inner_size(), PhysicalSize::new(200, 200));
+    assert_eq!(window.current_monitor().unwrap().name().unwrap(), "hidpi");
+
+    controller.set_scale_factor(window.id(), 1.5);
+    let mut events = Vec::new();
+    event_loop.run_step(|event, _, _| match event {
+        Event::WindowEvent {
+            event:
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size,
+                },
+            ..
+        } => events.push((scale_factor, *new_inner_size)),
+        Event::WindowEvent {
+            event: WindowEvent::Resized(size),
+            ..
+        } => events.push((0.0, size)),
+        _ => (),
+    });
+
+    assert_eq!(
+        events,
+        vec![
+            (1.5, PhysicalSize::new(150, 150)),
+            (0.0, PhysicalSize::new(150, 150))
+        ]
+    );
+    assert_eq!(window.scale_factor(), 1.5);
+}
+
+#[test]
+fn popups_are_placed_next_to_their_anchor() {
+    let event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let parent = WindowBuilder::new()
+        .with_position(PhysicalPosition::new(100, 100))
+        .with_inner_size(PhysicalSize::new(800, 600))
+        .build(&event_loop)
+        .unwrap();
+
+    let menu = WindowBuilder::new()
+        .with_inner_size(PhysicalSize::new(200, 300))
+        .with_popup(
+            &parent,
+            (
+                PhysicalPosition::new(10, 20).into(),
+                PhysicalSize::new(0, 30).into(),
+            ),
+            PopupGravity::BottomRight,
+        )
+        .build(&event_loop)
+        .unwrap();
+    assert_eq!(
+        menu.outer_position().unwrap(),
+        PhysicalPosition::new(110, 150)
+    );
+    let state = controller.window_state(menu.id()).unwrap();
+    assert_eq!(state.popup.unwrap().parent, parent.id());
+
+    // There's no room below the anchor, so the popup opens above it.
+    let flipped = WindowBuilder::new()
+        .with_inner_size(PhysicalSize::new(200, 500))
+        .with_popup(
+            &parent,
+            (
+                PhysicalPosition::new(10, 550).into(),
+                PhysicalSize::new(0, 30).into(),
+            ),
+            PopupGravity::BottomRight,
+        )
+        .build(&event_loop)
+        .unwrap();
+    assert_eq!(
+        flipped.outer_position().unwrap(),
+        PhysicalPosition::new(110, 150)
+    );
+
+    parent.set_outer_position(PhysicalPosition::new(200, 100));
+    assert_eq!(
+        menu.outer_position().unwrap(),
+        PhysicalPosition::new(210, 150)
+    );
+}
+
+#[test]
+fn popup_grabs_end_when_dismissed() {
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let parent = WindowBuilder::new().build(&event_loop).unwrap();
+    assert!(parent.set_popup_grab(true).is_err());
+
+    let menu = WindowBuilder::new()
+        .with_popup(
+            &parent,
+            (
+                PhysicalPosition::new(0, 0).into(),
+                PhysicalSize::new(10, 10).into(),
+            ),
+            PopupGravity::BottomRight,
+        )
+        .build(&event_loop)
+        .unwrap();
+    menu.set_popup_grab(true).unwrap();
+    assert!(controller.window_state(menu.id()).unwrap().popup_grab);
+
+    controller.send_window_event(menu.id(), WindowEvent::PopupDismissed);
+    let mut dismissed = false;
+    event_loop.run_step(|event, _, _| {
+        if let Event::WindowEvent {
+            event: WindowEvent::PopupDismissed,
+            window_id,
+        } = event
+        {
+            dismissed = window_id == menu.id();
+        }
+    });
+    assert!(dismissed);
+    assert!(!controller.window_state(menu.id()).unwrap().popup_grab);
+}
+
+#[test]
+fn dialogs_keep_their_parent() {
+    let event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let parent = WindowBuilder::new().build(&event_loop).unwrap();
+    let dialog = WindowBuilder::new()
+        .with_parent_window(&parent)
+        .with_modal(true)
+        .build(&event_loop)
+        .unwrap();
+
+    let state = controller.window_state(dialog.id()).unwrap();
+    assert_eq!(state.parent_window, Some(parent.id()));
+    assert!(state.modal);
+    let state = controller.window_state(parent.id()).unwrap();
+    assert_eq!(state.parent_window, None);
+    assert!(!state.modal);
+}
+
+#[test]
+fn child_windows_keep_their_parent() {
+    let event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let parent = WindowBuilder::new().build(&event_loop).unwrap();
+    let child = WindowBuilder::new()
+        .with_child_of(&parent)
+        .with_position(PhysicalPosition::new(10, 20))
+        .build(&event_loop)
+        .unwrap();
+
+    let state = controller.window_state(child.id()).unwrap();
+    assert_eq!(state.child_of, Some(parent.id()));
+    assert_eq!(
+        child.outer_position().unwrap(),
+        PhysicalPosition::new(10, 20)
+    );
+    let state = controller.window_state(parent.id()).unwrap();
+    assert_eq!(state.child_of, None);
+}
+
+#[test]
+#[cfg(feature = "x11")]
+fn foreign_windows_need_an_x11_event_loop() {
+    use winit::{platform::unix::WindowExtUnix, window::Window};
+
+    let event_loop = EventLoop::<()>::new_mock();
+    assert!(unsafe { Window::from_raw_x11(&event_loop, 0x0040_0001) }.is_err());
+}
+
+#[test]
+fn resize_increments_follow_scale_factor() {
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    assert_eq!(window.resize_increments(), None);
+
+    window.set_resize_increments(Some(LogicalSize::new(8.0, 16.0)));
+    assert_eq!(window.resize_increments(), Some(PhysicalSize::new(8, 16)));
+
+    controller.set_scale_factor(window.id(), 2.0);
+    event_loop.run_step(|_, _, _| ());
+    assert_eq!(window.resize_increments(), Some(PhysicalSize::new(16, 32)));
+
+    window.set_resize_increments(None::<LogicalSize<f64>>);
+    assert_eq!(window.resize_increments(), None);
+    assert_eq!(
+        controller
+            .window_state(window.id())
+            .unwrap()
+            .resize_increments,
+        None
+    );
+}
+
+#[test]
+fn custom_cursor_replaces_cursor_icon() {
+    let event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+
+    let source = CustomCursorSource::from_rgba(vec![255; 2 * 2 * 4], 2, 2, 1, 0)
+        .unwrap()
+        .with_hidpi_variant(2, vec![255; 4 * 4 * 4], 4, 4, 2, 0)
+        .unwrap();
+    let cursor = event_loop.create_custom_cursor(source.clone()).unwrap();
+    window.set_custom_cursor(&cursor);
+    assert_eq!(
+        controller.window_state(window.id()).unwrap().custom_cursor,
+        Some(source)
+    );
+
+    window.set_cursor_icon(CursorIcon::Text);
+    let state = controller.window_state(window.id()).unwrap();
+    assert_eq!(state.custom_cursor, None);
+    assert_eq!(state.cursor_icon, CursorIcon::Text);
+
+    assert!(matches!(
+        CustomCursorSource::from_rgba(vec![255; 2 * 2 * 4], 2, 2, 2, 0),
+        Err(BadIcon::HotspotOutOfBounds { hotspot_x: 2, .. })
+    ));
+    assert!(matches!(
+        CustomCursorSource::from_rgba(vec![255; 2 * 2 * 4], 2, 2, 1, 0)
+            .unwrap()
+            .with_hidpi_variant(0, vec![255; 4], 1, 1, 0, 0),
+        Err(BadIcon::ZeroScaleFactor)
+    ));
+}
+
+#[test]
+fn animated_custom_cursor() {
+    let event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+
+    let frame = |alpha| CustomCursorSource::from_rgba(vec![alpha; 2 * 2 * 4], 2, 2, 0, 0).unwrap();
+    assert!(!frame(255).is_animated());
+
+    let source = CustomCursorSource::from_animation(vec![
+        (frame(255), Duration::from_millis(100)),
+        (frame(128), Duration::from_millis(50)),
+    ])
+    .unwrap();
+    assert!(source.is_animated());
+    assert!(matches!(
+        CustomCursorSource::from_animation(vec![(frame(255), Duration::from_secs(0))]),
+        Err(BadIcon::ZeroFrameDuration)
+    ));
+    assert!(matches!(
+        CustomCursorSource::from_animation(Vec::new()),
+        Err(BadIcon::NoCursorFrames)
+    ));
+
+    let cursor = event_loop.create_custom_cursor(source.clone()).unwrap();
+    window.set_custom_cursor(&cursor);
+    assert_eq!(
+        controller.window_state(window.id()).unwrap().custom_cursor,
+        Some(source)
+    );
+}
+
+#[test]
+fn cursor_grab_modes_and_region() {
+    let event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    assert_eq!(
+        controller.window_state(window.id()).unwrap().cursor_grab,
+        CursorGrabMode::None
+    );
+
+    for &mode in &[
+        CursorGrabMode::Locked,
+        CursorGrabMode::Confined,
+        CursorGrabMode::None,
+    ] {
+        window.set_cursor_grab(mode).unwrap();
+        assert_eq!(
+            controller.window_state(window.id()).unwrap().cursor_grab,
+            mode
+        );
+    }
+
+    let region = (
+        LogicalPosition::new(10.0, 20.0).into(),
+        LogicalSize::new(100.0, 50.0).into(),
+    );
+    window.set_cursor_grab_region(Some(region)).unwrap();
+    assert_eq!(
+        controller
+            .window_state(window.id())
+            .unwrap()
+            .cursor_grab_region,
+        Some(region)
+    );
+}
+
+#[test]
+fn run_return_wakes_up_for_user_events() {
+    let mut event_loop = EventLoop::<u32>::new_mock();
+    let proxy = event_loop.create_proxy();
+
+    std::thread::spawn(move || proxy.send_event(42).unwrap());
+
+    let mut received = None;
+    event_loop.run_return(|event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        if let Event::UserEvent(event) = event {
+            received = Some(event);
+            *control_flow = ControlFlow::Exit;
+        }
+    });
+
+    assert_eq!(received, Some(42));
+}
+
+#[test]
+fn clipboards_are_independent() {
+    let event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    assert_eq!(
+        event_loop.clipboard_text(ClipboardKind::Clipboard).unwrap(),
+        None
+    );
+
+    event_loop
+        .set_clipboard_text(ClipboardKind::Clipboard, "copied")
+        .unwrap();
+    controller.set_clipboard_text(ClipboardKind::Primary, "selected");
+
+    assert_eq!(
+        controller
+            .clipboard_text(ClipboardKind::Clipboard)
+            .as_deref(),
+        Some("copied")
+    );
+    assert_eq!(
+        event_loop
+            .clipboard_text(ClipboardKind::Primary)
+            .unwrap()
+            .as_deref(),
+        Some("selected")
+    );
+}
+
+#[test]
+fn clipboard_changes_are_reported() {
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+
+    event_loop
+        .set_clipboard_data(
+            ClipboardKind::Clipboard,
+            vec![("text/html", b"<b>bold</b>".to_vec())],
+        )
+        .unwrap();
+    controller.set_clipboard_data(
+        ClipboardKind::Primary,
+        vec![("image/png".to_owned(), vec![0x89, b'P', b'N', b'G'])],
+    );
+
+    let mut changes = Vec::new();
+    event_loop.run_step(|event, _, _| {
+        if let Event::ClipboardChanged(kind) = event {
+            changes.push(kind);
+        }
+    });
+
+    assert_eq!(changes, vec![ClipboardKind::Primary]);
+    assert_eq!(
+        event_loop
+            .clipboard_mime_types(ClipboardKind::Primary)
+            .unwrap(),
+        vec!["image/png".to_owned()]
+    );
+    assert_eq!(
+        event_loop
+            .clipboard_data(ClipboardKind::Primary, "image/png")
+            .unwrap(),
+        Some(vec![0x89, b'P', b'N', b'G'])
+    );
+    assert_eq!(
+        controller.clipboard_data(ClipboardKind::Clipboard, "text/html"),
+        Some(b"<b>bold</b>".to_vec())
+    );
+}
+
+#[test]
+fn outgoing_drag_reports_its_outcome() {
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+
+    window
+        .start_drag(
+            vec![("text/uri-list", b"file:///tmp/a".to_vec())],
+            DndActions::COPY | DndActions::MOVE,
+        )
+        .unwrap();
+    assert_eq!(
+        controller.window_state(window.id()).unwrap().drag,
+        Some(MockDrag {
+            data: vec![("text/uri-list".to_owned(), b"file:///tmp/a".to_vec())],
+            allowed_actions: DndActions::COPY | DndActions::MOVE,
+        })
+    );
+
+    controller.end_drag(window.id(), DragOutcome::Dropped(DndAction::Move));
+    // Only the drag in progress ends.
+    controller.end_drag(window.id(), DragOutcome::Cancelled);
+
+    let mut outcomes = Vec::new();
+    event_loop.run_step(|event, _, _| {
+        if let Event::WindowEvent {
+            event: WindowEvent::DragEnded(outcome),
+            ..
+        } = event
+        {
+            outcomes.push(outcome);
+        }
+    });
+
+    assert_eq!(outcomes, vec![DragOutcome::Dropped(DndAction::Move)]);
+    assert!(controller.window_state(window.id()).unwrap().drag.is_none());
+}
+
+#[test]
+fn incoming_drag_is_accepted_per_position() {
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+
+    controller.send_window_event(
+        window.id(),
+        WindowEvent::DragEntered {
+            mime_types: vec!["text/plain;charset=utf-8".to_owned()],
+        },
+    );
+    controller.move_drag(
+        window.id(),
+        PhysicalPosition::new(5.0, 5.0),
+        DndAction::Copy,
+    );
+    controller.move_drag(
+        window.id(),
+        PhysicalPosition::new(50.0, 40.0),
+        DndAction::Copy,
+    );
+
+    let mut moves = Vec::new();
+    event_loop.run_step(|event, _, _| {
+        if let Event::WindowEvent {
+            event:
+                WindowEvent::DragMoved {
+                    position,
+                    action,
+                    accepted_action,
+                },
+            ..
+        } = event
+        {
+            // Text isn't accepted by default, only accept it away from the border.
+            moves.push((position, action, *accepted_action));
+            if position.x > 10.0 {
+                *accepted_action = Some(DndAction::Move);
+            }
+        }
+    });
+
+    assert_eq!(
+        moves,
+        vec![
+            (PhysicalPosition::new(5.0, 5.0), DndAction::Copy, None),
+            (PhysicalPosition::new(50.0, 40.0), DndAction::Copy, None),
+        ]
+    );
+    let incoming_drag = controller
+        .window_state(window.id())
+        .unwrap()
+        .incoming_drag
+        .unwrap();
+    assert_eq!(incoming_drag.accepted_action, Some(DndAction::Move));
+
+    controller.drop_drag(
+        window.id(),
+        PhysicalPosition::new(50.0, 40.0),
+        DropData::Text("dropped".to_owned()),
+    );
+
+    let mut drops = Vec::new();
+    event_loop.run_step(|event, _, _| {
+        if let Event::WindowEvent { event, .. } = event {
+            if let WindowEvent::DragDropped { .. } | WindowEvent::DragLeft = event {
+                drops.push(event.to_static().unwrap());
+            }
+        }
+    });
+
+    assert_eq!(
+        drops,
+        vec![WindowEvent::DragDropped {
+            position: PhysicalPosition::new(50.0, 40.0),
+            action: DndAction::Move,
+            data: DropData::Text("dropped".to_owned()),
+        }]
+    );
+    assert!(controller
+        .window_state(window.id())
+        .unwrap()
+        .incoming_drag
+        .is_none());
+}
+
+/// Writes a `struct input_event`, whose time is ignored.
+fn write_input_event(stream: &mut UnixStream, type_: u16, code: u16, value: i32) {
+    let mut event = vec![0; 2 * mem::size_of::<usize>()];
+    event.extend_from_slice(&type_.to_ne_bytes());
+    event.extend_from_slice(&code.to_ne_bytes());
+    event.extend_from_slice(&value.to_ne_bytes());
+    stream.write_all(&event).unwrap();
+}
+
+#[test]
+fn gamepad_input_is_mapped() {
+    const EV_SYN: u16 = 0x00;
+    const EV_KEY: u16 = 0x01;
+    const EV_ABS: u16 = 0x03;
+
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+
+    // The driver of the Xbox 360 controller swaps X and Y, which the built-in mapping undoes.
+    let (mut stream, events) = UnixStream::pair().unwrap();
+    let device_id = controller.connect_gamepad(
+        MockGamepad {
+            name: "Microsoft X-Box 360 pad".to_owned(),
+            bus_type: 0x03,
+            vendor_id: 0x045e,
+            product_id: 0x028e,
+            version: 0x0110,
+            buttons: vec![
+                0x130, 0x131, 0x133, 0x134, 0x136, 0x137, 0x13a, 0x13b, 0x13c, 0x13d, 0x13e,
+            ],
+            axes: vec![
+                (0x00, -32768, 32767),
+                (0x01, -32768, 32767),
+                (0x02, 0, 255),
+                (0x03, -32768, 32767),
+                (0x04, -32768, 32767),
+                (0x05, 0, 255),
+                (0x10, -1, 1),
+                (0x11, -1, 1),
+            ],
+        },
+        events,
+    );
+    assert_eq!(
+        event_loop.gamepad_info(device_id),
+        Some(GamepadInfo {
+            name: "Microsoft X-Box 360 pad".to_owned(),
+            vendor_id: 0x045e,
+            product_id: 0x028e,
+            is_mapped: true,
+        })
+    );
+
+    write_input_event(&mut stream, EV_KEY, 0x133, 1);
+    write_input_event(&mut stream, EV_ABS, 0x02, 255);
+    write_input_event(&mut stream, EV_ABS, 0x11, -1);
+    write_input_event(&mut stream, EV_SYN, 0, 0);
+    write_input_event(&mut stream, EV_KEY, 0x133, 0);
+    write_input_event(&mut stream, EV_SYN, 0, 0);
+    drop(stream);
+
+    let mut events = Vec::new();
+    event_loop.run_step(|event, _, _| {
+        if let Event::DeviceEvent {
+            device_id: event_device_id,
+            event,
+        } = event
+        {
+            assert_eq!(event_device_id, device_id);
+            events.push(event);
+        }
+    });
+
+    let button = |button: GamepadButton, state| DeviceEvent::Button {
+        button: button.button_id(),
+        state,
+    };
+    assert_eq!(
+        events,
+        vec![
+            DeviceEvent::Added,
+            button(GamepadButton::West, ElementState::Pressed),
+            DeviceEvent::Motion {
+                axis: GamepadAxis::LeftTrigger.axis_id(),
+                value: 1.0,
+            },
+            button(GamepadButton::LeftTrigger, ElementState::Pressed),
+            button(GamepadButton::DPadUp, ElementState::Pressed),
+            button(GamepadButton::West, ElementState::Released),
+            DeviceEvent::Removed,
+        ]
+    );
+    assert_eq!(event_loop.gamepad_info(device_id), None);
+}
+
+#[test]
+fn unmapped_joystick_reports_its_own_codes() {
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+
+    let (mut stream, events) = UnixStream::pair().unwrap();
+    let device_id = controller.connect_gamepad(
+        MockGamepad {
+            buttons: vec![0x120, 0x121],
+            axes: vec![(0x00, 0, 1023)],
+            ..Default::default()
+        },
+        events,
+    );
+    assert!(!event_loop.gamepad_info(device_id).unwrap().is_mapped);
+
+    write_input_event(&mut stream, 0x01, 0x121, 1);
+    write_input_event(&mut stream, 0x03, 0x00, 1023);
+
+    let mut events = Vec::new();
+    event_loop.run_step(|event, _, _| {
+        if let Event::DeviceEvent { event, .. } = event {
+            events.push(event);
+        }
+    });
+
+    assert_eq!(
+        events,
+        vec![
+            DeviceEvent::Added,
+            DeviceEvent::Button {
+                button: 0x121,
+                state: ElementState::Pressed,
+            },
+            DeviceEvent::Motion {
+                axis: 0x00,
+                value: 1.0,
+            },
+        ]
+    );
+}
+
+#[test]
+fn gamepad_mappings_of_other_platforms_are_skipped() {
+    let mut event_loop = EventLoop::<()>::new_mock();
+    let controller = event_loop.mock_controller().unwrap();
+
+    // The same joystick numbers its buttons differently on Windows.
+    controller.add_gamepad_mappings(
+        "03000000341200007856000001000000,Pad,a:b1,platform:Windows,\n\
+         03000000341200007856000001000000,Pad,a:b0,platform:Linux,",
+    );
+
+    let (mut stream, events) = UnixStream::pair().unwrap();
+    let device_id = controller.connect_gamepad(
+        MockGamepad {
+            bus_type: 0x03,
+            vendor_id: 0x1234,
+            product_id: 0x5678,
+            version: 0x0001,
+            buttons: vec![0x120, 0x121],
+            ..Default::default()
+        },
+        events,
+    );
+    assert!(event_loop.gamepad_info(device_id).unwrap().is_mapped);
+
+    write_input_event(&mut stream, 0x01, 0x120, 1);
+
+    let mut events = Vec::new();
+    event_loop.run_step(|event, _, _| {
+        if let Event::DeviceEvent { event, .. } = event {
+            events.push(event);
+        }
+    });
+
+    assert_eq!(
+        events,
+        vec![
+            DeviceEvent::Added,
+            DeviceEvent::Button {
+                button: GamepadButton::South.button_id(),
+                state: ElementState::Pressed,
+            },
+        ]
+    );
+}
@@ -25,3 +25,10 @@ fn ids_send() {
     needs_send::<winit::event::DeviceId>();
     needs_send::<winit::monitor::MonitorHandle>();
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn custom_cursor_send() {
+    // ensures that `winit::window::CustomCursor` implements `Send`
+    needs_send::<winit::window::CustomCursor>();
+}
@@ -4,10 +4,12 @@ use serde::{Deserialize, Serialize};
 use winit::{
     dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
     event::{
-        ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase,
-        VirtualKeyCode,
+        DeviceEvent, ElementState, Event, Ime, Key, KeyCode, KeyEvent, KeyLocation, KeyboardInput,
+        ModifiersState, MouseButton, MouseScrollDelta, StartCause, TabletTool, TabletToolAxes,
+        TabletToolEvent, Touch, TouchPhase, VirtualKeyCode, WindowEvent,
     },
-    window::CursorIcon,
+    gamepad::{GamepadAxis, GamepadButton},
+    window::{CursorGrabMode, CursorIcon, PopupGravity},
 };
 
 #[allow(dead_code)]
@@ -16,17 +18,38 @@ fn needs_serde<S: Serialize + Deserialize<'static>>() {}
 #[test]
 fn window_serde() {
     needs_serde::<CursorIcon>();
+    needs_serde::<CursorGrabMode>();
+    needs_serde::<PopupGravity>();
 }
 
 #[test]
 fn events_serde() {
     needs_serde::<KeyboardInput>();
+    needs_serde::<KeyEvent>();
+    needs_serde::<Key>();
+    needs_serde::<KeyCode>();
+    needs_serde::<KeyLocation>();
+    needs_serde::<Ime>();
     needs_serde::<TouchPhase>();
     needs_serde::<ElementState>();
     needs_serde::<MouseButton>();
     needs_serde::<MouseScrollDelta>();
     needs_serde::<VirtualKeyCode>();
     needs_serde::<ModifiersState>();
+    needs_serde::<Touch>();
+    needs_serde::<TabletToolEvent>();
+    needs_serde::<TabletTool>();
+    needs_serde::<TabletToolAxes>();
+    needs_serde::<StartCause>();
+    needs_serde::<WindowEvent<'static>>();
+    needs_serde::<DeviceEvent>();
+    needs_serde::<Event<'static, ()>>();
+}
+
+#[test]
+fn gamepad_serde() {
+    needs_serde::<GamepadButton>();
+    needs_serde::<GamepadAxis>();
 }
 
 #[test]